@@ -2,12 +2,16 @@ use std::borrow::Cow;
 use std::future::Future;
 use std::io::{IsTerminal, Write};
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use base64::prelude::{Engine as _, BASE64_STANDARD};
+use bytesize::ByteSize;
 use clap::{Args, Parser, Subcommand};
+use everscale_types::cell::HashBytes;
 use everscale_types::models::{BlockId, StdAddr};
 use serde::Serialize;
+use tycho_control::proto::{AccountStats, DbStats, ShardStateStats};
 use tycho_control::ControlClient;
 use tycho_util::cli::logger::init_logger_simple;
 use tycho_util::cli::signal;
@@ -22,6 +26,9 @@ pub enum CmdControl {
     Ping(CmdPing),
     GetAccount(CmdGetAccount),
     GetNeighbours(CmdGetNeighbours),
+    GetInboundPeers(CmdGetInboundPeers),
+    BanPeer(CmdBanPeer),
+    UnbanPeer(CmdUnbanPeer),
     FindArchive(CmdFindArchive),
     ListArchives(CmdListArchives),
     DumpArchive(CmdDumpArchive),
@@ -32,6 +39,11 @@ pub enum CmdControl {
     GcArchives(CmdGcArchives),
     GcBlocks(CmdGcBlocks),
     GcStates(CmdGcStates),
+    CreateCheckpoint(CmdCreateCheckpoint),
+    StateStats(CmdStateStats),
+    DbStats(CmdDbStats),
+    CompactDb(CmdCompactDb),
+    TxWatchList(CmdTxWatchList),
     #[clap(subcommand)]
     MemProfiler(CmdMemProfiler),
 }
@@ -43,6 +55,9 @@ impl CmdControl {
             Self::Ping(cmd) => cmd.run(args),
             Self::GetAccount(cmd) => cmd.run(args),
             Self::GetNeighbours(cmd) => cmd.run(args),
+            Self::GetInboundPeers(cmd) => cmd.run(args),
+            Self::BanPeer(cmd) => cmd.run(args),
+            Self::UnbanPeer(cmd) => cmd.run(args),
             Self::FindArchive(cmd) => cmd.run(args),
             Self::ListArchives(cmd) => cmd.run(args),
             Self::DumpArchive(cmd) => cmd.run(args),
@@ -53,6 +68,11 @@ impl CmdControl {
             Self::GcArchives(cmd) => cmd.run(args),
             Self::GcBlocks(cmd) => cmd.run(args),
             Self::GcStates(cmd) => cmd.run(args),
+            Self::CreateCheckpoint(cmd) => cmd.run(args),
+            Self::StateStats(cmd) => cmd.run(args),
+            Self::DbStats(cmd) => cmd.run(args),
+            Self::CompactDb(cmd) => cmd.run(args),
+            Self::TxWatchList(cmd) => cmd.run(args),
             Self::MemProfiler(cmd) => cmd.run(args),
         }
     }
@@ -184,7 +204,7 @@ impl CmdGetNeighbours {
         struct TableRow(tycho_control::proto::NeighbourInfo);
 
         impl tabled::Tabled for TableRow {
-            const LENGTH: usize = 5;
+            const LENGTH: usize = 6;
 
             fn fields(&self) -> Vec<Cow<'_, str>> {
                 vec![
@@ -193,6 +213,7 @@ impl CmdGetNeighbours {
                     Cow::from(self.0.failed_requests.to_string()),
                     Cow::from(self.0.total_requests.to_string()),
                     Cow::from(self.0.roundtrip_ms.to_string()),
+                    Cow::from(format!("{:.2}", self.0.reputation_penalty)),
                 ]
             }
 
@@ -203,6 +224,7 @@ impl CmdGetNeighbours {
                     Cow::from("failed_requests"),
                     Cow::from("total_requests"),
                     Cow::from("roundtrip_ms"),
+                    Cow::from("penalty"),
                 ]
             }
         }
@@ -222,6 +244,117 @@ impl CmdGetNeighbours {
     }
 }
 
+/// Get inbound request counters of the connected peers.
+#[derive(Parser)]
+#[clap(disable_help_flag = true)]
+pub struct CmdGetInboundPeers {
+    #[clap(flatten)]
+    args: ControlArgs,
+
+    /// Show only the top N peers by the number of rejected requests.
+    #[clap(short, long)]
+    limit: Option<usize>,
+
+    #[clap(short, long)]
+    human_readable: bool,
+
+    #[clap(long, action = clap::ArgAction::HelpLong)]
+    help: Option<bool>,
+}
+
+impl CmdGetInboundPeers {
+    pub fn run(self, args: BaseArgs) -> Result<()> {
+        struct TableRow(tycho_control::proto::InboundPeerInfo);
+
+        impl tabled::Tabled for TableRow {
+            const LENGTH: usize = 4;
+
+            fn fields(&self) -> Vec<Cow<'_, str>> {
+                vec![
+                    Cow::from(self.0.id.to_string()),
+                    Cow::from(self.0.rejected_requests.to_string()),
+                    Cow::from(ByteSize(self.0.total_bytes).to_string()),
+                    Cow::from(format!("{:.2}", self.0.reputation_penalty)),
+                ]
+            }
+
+            fn headers() -> Vec<Cow<'static, str>> {
+                vec![
+                    Cow::from("peer_id"),
+                    Cow::from("rejected_requests"),
+                    Cow::from("total_bytes"),
+                    Cow::from("penalty"),
+                ]
+            }
+        }
+
+        self.args.rt(args, move |client| async move {
+            let mut res = client.get_inbound_peers_info().await?;
+            if let Some(limit) = self.limit {
+                res.peers.truncate(limit);
+            }
+
+            if self.human_readable {
+                let mut table = tabled::Table::new(res.peers.into_iter().map(TableRow));
+                table.with(tabled::settings::Style::psql());
+                println!("{table}");
+                Ok(())
+            } else {
+                print_json(res)
+            }
+        })
+    }
+}
+
+/// Temporarily ban a peer.
+///
+/// Banned peers are not selected as public overlay neighbours.
+#[derive(Parser)]
+pub struct CmdBanPeer {
+    #[clap(flatten)]
+    args: ControlArgs,
+
+    /// Peer id (hex).
+    #[clap()]
+    peer_id: HashBytes,
+
+    /// Ban duration.
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "30m")]
+    duration: Duration,
+}
+
+impl CmdBanPeer {
+    pub fn run(self, args: BaseArgs) -> Result<()> {
+        let duration_sec = u32::try_from(self.duration.as_secs()).unwrap_or(u32::MAX);
+        self.args.rt(args, move |client| async move {
+            client.ban_peer(self.peer_id, duration_sec).await?;
+            print_json(Empty {})
+        })
+    }
+}
+
+/// Remove a peer ban.
+#[derive(Parser)]
+pub struct CmdUnbanPeer {
+    #[clap(flatten)]
+    args: ControlArgs,
+
+    /// Peer id (hex).
+    #[clap()]
+    peer_id: HashBytes,
+}
+
+impl CmdUnbanPeer {
+    pub fn run(self, args: BaseArgs) -> Result<()> {
+        self.args.rt(args, |client| async move {
+            let was_banned = client.unban_peer(self.peer_id).await?;
+            print_json(serde_json::json!({
+                "was_banned": was_banned,
+            }))
+        })
+    }
+}
+
 /// Trigger a garbage collection of archives.
 #[derive(Parser)]
 pub struct CmdGcArchives {
@@ -279,6 +412,286 @@ impl CmdGcStates {
     }
 }
 
+/// Create a consistent checkpoint of the node storage.
+#[derive(Parser)]
+pub struct CmdCreateCheckpoint {
+    #[clap(flatten)]
+    args: ControlArgs,
+
+    /// Path to a new checkpoint directory.
+    #[clap()]
+    path: PathBuf,
+}
+
+impl CmdCreateCheckpoint {
+    pub fn run(self, args: BaseArgs) -> Result<()> {
+        let path = std::path::absolute(&self.path)?;
+        self.args.rt(args, |client| async move {
+            let res = client.create_checkpoint(path).await?;
+            print_json(serde_json::json!({
+                "path": res.path,
+                "last_mc_block_id": res.last_mc_block_id.map(|id| id.to_string()),
+                "init_mc_block_id": res.init_mc_block_id.map(|id| id.to_string()),
+                "has_rpc": res.has_rpc,
+            }))
+        })
+    }
+}
+
+/// Compute the space usage of the stored shard states.
+#[derive(Parser)]
+#[clap(disable_help_flag = true)]
+pub struct CmdStateStats {
+    #[clap(flatten)]
+    args: ControlArgs,
+
+    /// Full block ID of the state. Latest states are used by default.
+    #[clap(short, long, allow_hyphen_values(true))]
+    block_id: Option<BlockId>,
+
+    /// Number of the heaviest accounts to show.
+    #[clap(short, long, default_value_t = 10)]
+    top: usize,
+
+    #[clap(short, long)]
+    human_readable: bool,
+
+    #[clap(long, action = clap::ArgAction::HelpLong)]
+    help: Option<bool>,
+}
+
+impl CmdStateStats {
+    pub fn run(self, args: BaseArgs) -> Result<()> {
+        self.args.rt(args, move |client| async move {
+            let res = client.get_state_stats(self.block_id, self.top).await?;
+            print_state_stats(res.states, self.human_readable)
+        })
+    }
+}
+
+/// Get column family stats of all databases.
+#[derive(Parser)]
+#[clap(disable_help_flag = true)]
+pub struct CmdDbStats {
+    #[clap(flatten)]
+    args: ControlArgs,
+
+    #[clap(short, long)]
+    human_readable: bool,
+
+    #[clap(long, action = clap::ArgAction::HelpLong)]
+    help: Option<bool>,
+}
+
+impl CmdDbStats {
+    pub fn run(self, args: BaseArgs) -> Result<()> {
+        self.args.rt(args, move |client| async move {
+            let databases = client.get_db_stats().await?;
+            print_db_stats(databases, self.human_readable)
+        })
+    }
+}
+
+fn print_db_stats(databases: Vec<DbStats>, human_readable: bool) -> Result<()> {
+    struct CfRow<'a>(&'a str, &'a tycho_control::proto::ColumnFamilyStats);
+
+    impl tabled::Tabled for CfRow<'_> {
+        const LENGTH: usize = 8;
+
+        fn fields(&self) -> Vec<Cow<'_, str>> {
+            let stats = self.1;
+            vec![
+                Cow::from(self.0),
+                Cow::from(stats.name.as_str()),
+                Cow::from(ByteSize(stats.estimated_live_data_size).to_string()),
+                Cow::from(ByteSize(stats.total_sst_files_size).to_string()),
+                Cow::from(stats.sst_files.to_string()),
+                Cow::from(stats.estimated_keys.to_string()),
+                Cow::from(ByteSize(stats.pending_compaction_bytes).to_string()),
+                Cow::from(ByteSize(stats.memtables_size).to_string()),
+            ]
+        }
+
+        fn headers() -> Vec<Cow<'static, str>> {
+            vec![
+                Cow::from("db"),
+                Cow::from("cf"),
+                Cow::from("live_data"),
+                Cow::from("sst_size"),
+                Cow::from("sst_files"),
+                Cow::from("keys"),
+                Cow::from("pending_compaction"),
+                Cow::from("memtables"),
+            ]
+        }
+    }
+
+    if !human_readable {
+        return print_json(databases);
+    }
+
+    let rows = databases.iter().flat_map(|db| {
+        db.column_families
+            .iter()
+            .map(|cf| CfRow(db.name.as_str(), cf))
+    });
+
+    let mut table = tabled::Table::new(rows);
+    table.with(tabled::settings::Style::psql());
+    println!("{table}\n");
+
+    if let Some(db) = databases.first() {
+        println!(
+            "block cache usage: {}, pinned: {}",
+            ByteSize(db.block_cache_usage),
+            ByteSize(db.block_cache_pinned_usage)
+        );
+    }
+
+    Ok(())
+}
+
+/// Start a manual compaction of the database in background.
+#[derive(Parser)]
+pub struct CmdCompactDb {
+    #[clap(flatten)]
+    args: ControlArgs,
+
+    /// Database name (`base`, `rpc`, `mempool` or `cold`).
+    #[clap()]
+    db: String,
+
+    /// Column family name. All column families are compacted by default.
+    #[clap(long)]
+    cf: Option<String>,
+}
+
+impl CmdCompactDb {
+    pub fn run(self, args: BaseArgs) -> Result<()> {
+        self.args.rt(args, |client| async move {
+            let column_families = client.compact_db(self.db, self.cf).await?;
+            print_json(serde_json::json!({
+                "column_families": column_families,
+            }))
+        })
+    }
+}
+
+/// Get or update accounts which always keep the full transactions history.
+#[derive(Parser)]
+pub struct CmdTxWatchList {
+    #[clap(flatten)]
+    args: ControlArgs,
+
+    /// Account address to add to the watch list.
+    #[clap(long, allow_hyphen_values(true))]
+    add: Vec<StdAddr>,
+
+    /// Account address to remove from the watch list.
+    #[clap(long, allow_hyphen_values(true))]
+    remove: Vec<StdAddr>,
+}
+
+impl CmdTxWatchList {
+    pub fn run(self, args: BaseArgs) -> Result<()> {
+        self.args.rt(args, |client| async move {
+            let addresses = if self.add.is_empty() && self.remove.is_empty() {
+                client.get_tx_watch_list().await?
+            } else {
+                client.update_tx_watch_list(self.add, self.remove).await?
+            };
+
+            print_json(serde_json::json!({
+                "addresses": addresses.iter().map(|addr| addr.to_string()).collect::<Vec<_>>(),
+            }))
+        })
+    }
+}
+
+pub(crate) fn print_state_stats(states: Vec<ShardStateStats>, human_readable: bool) -> Result<()> {
+    struct ShardRow(ShardStateStats);
+
+    impl tabled::Tabled for ShardRow {
+        const LENGTH: usize = 8;
+
+        fn fields(&self) -> Vec<Cow<'_, str>> {
+            let stats = &self.0;
+            let other_cells = stats.total.cells - stats.accounts.cells - stats.shared.cells;
+            let other_bytes = stats.total.bytes - stats.accounts.bytes - stats.shared.bytes;
+            vec![
+                Cow::from(stats.block_id.as_short_id().to_string()),
+                Cow::from(stats.account_count.to_string()),
+                Cow::from(stats.total.cells.to_string()),
+                Cow::from(ByteSize(stats.total.bytes).to_string()),
+                Cow::from(ByteSize(stats.accounts.bytes).to_string()),
+                Cow::from(ByteSize(stats.shared.bytes).to_string()),
+                Cow::from(other_cells.to_string()),
+                Cow::from(ByteSize(other_bytes).to_string()),
+            ]
+        }
+
+        fn headers() -> Vec<Cow<'static, str>> {
+            vec![
+                Cow::from("block"),
+                Cow::from("accounts"),
+                Cow::from("cells"),
+                Cow::from("bytes"),
+                Cow::from("unique_bytes"),
+                Cow::from("shared_bytes"),
+                Cow::from("other_cells"),
+                Cow::from("other_bytes"),
+            ]
+        }
+    }
+
+    struct AccountRow(AccountStats);
+
+    impl tabled::Tabled for AccountRow {
+        const LENGTH: usize = 5;
+
+        fn fields(&self) -> Vec<Cow<'_, str>> {
+            let stats = &self.0;
+            vec![
+                Cow::from(stats.address.to_string()),
+                Cow::from(stats.total.cells.to_string()),
+                Cow::from(ByteSize(stats.total.bytes).to_string()),
+                Cow::from(stats.unique.cells.to_string()),
+                Cow::from(ByteSize(stats.unique.bytes).to_string()),
+            ]
+        }
+
+        fn headers() -> Vec<Cow<'static, str>> {
+            vec![
+                Cow::from("address"),
+                Cow::from("cells"),
+                Cow::from("bytes"),
+                Cow::from("unique_cells"),
+                Cow::from("unique_bytes"),
+            ]
+        }
+    }
+
+    if !human_readable {
+        return print_json(states);
+    }
+
+    let mut accounts = states
+        .iter()
+        .flat_map(|stats| stats.top_accounts.iter().cloned())
+        .collect::<Vec<_>>();
+    accounts.sort_unstable_by_key(|item| std::cmp::Reverse(item.unique.bytes));
+
+    let mut table = tabled::Table::new(states.into_iter().map(ShardRow));
+    table.with(tabled::settings::Style::psql());
+    println!("{table}\n");
+
+    let mut table = tabled::Table::new(accounts.into_iter().map(AccountRow));
+    table.with(tabled::settings::Style::psql());
+    println!("{table}");
+
+    Ok(())
+}
+
 /// Manage memory profiler.
 #[derive(Subcommand)]
 pub enum CmdMemProfiler {
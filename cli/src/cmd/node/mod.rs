@@ -8,6 +8,7 @@ use tycho_util::cli::logger::{init_logger, set_abort_with_tracing};
 use tycho_util::cli::metrics::init_metrics;
 use tycho_util::cli::{resolve_public_ip, signal};
 
+pub(crate) use self::control::print_state_stats;
 pub use self::control::CmdControl;
 use crate::node::{Node, NodeConfig, NodeKeys};
 use crate::BaseArgs;
@@ -63,6 +64,11 @@ struct CmdRun {
     /// List of zerostate files to import.
     #[clap(long)]
     import_zerostate: Option<Vec<PathBuf>>,
+
+    /// Path to the persistent states bundle to boot from.
+    /// Used only when the node has no synced blocks yet.
+    #[clap(long)]
+    state_bundle: Option<PathBuf>,
 }
 
 impl CmdRun {
@@ -145,7 +151,7 @@ impl CmdRun {
         node.wait_for_neighbours().await;
 
         let init_block_id = node
-            .boot(self.import_zerostate)
+            .boot(self.import_zerostate, self.state_bundle)
             .await
             .context("failed to init node")?;
 
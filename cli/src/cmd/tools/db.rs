@@ -0,0 +1,272 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use everscale_types::models::BlockId;
+use tycho_control::proto::ShardStateStats;
+use tycho_storage::{
+    restore_checkpoint, CheckOptions, KeyBlocksDirection, PersistentStateKind, Storage,
+    StorageChecker,
+};
+use tycho_util::cli::logger::init_logger_simple;
+
+use crate::cmd::node::print_state_stats;
+use crate::node::NodeConfig;
+use crate::util::print_json;
+use crate::BaseArgs;
+
+/// Work with the node database.
+#[derive(clap::Parser)]
+pub struct Cmd {
+    #[clap(subcommand)]
+    cmd: SubCmd,
+}
+
+impl Cmd {
+    pub fn run(self, args: BaseArgs) -> Result<()> {
+        match self.cmd {
+            SubCmd::Check(cmd) => cmd.run(args),
+            SubCmd::Restore(cmd) => cmd.run(args),
+            SubCmd::StateStats(cmd) => cmd.run(args),
+            SubCmd::ExportState(cmd) => cmd.run(args),
+        }
+    }
+}
+
+#[derive(clap::Subcommand)]
+enum SubCmd {
+    Check(CheckCmd),
+    Restore(RestoreCmd),
+    StateStats(StateStatsCmd),
+    ExportState(ExportStateCmd),
+}
+
+/// Check the integrity of the stopped node database
+#[derive(clap::Parser)]
+struct CheckCmd {
+    /// Path to the node config. Default: `$TYCHO_HOME/config.json`
+    #[clap(long)]
+    config: Option<PathBuf>,
+
+    /// Skip block handles and block parts check.
+    #[clap(long)]
+    skip_blocks: bool,
+
+    /// Skip archives check.
+    #[clap(long)]
+    skip_archives: bool,
+
+    /// Skip shard states and cells check.
+    #[clap(long)]
+    skip_states: bool,
+
+    /// Fix invalid cell refcounts and remove orphaned cells.
+    #[clap(long, conflicts_with = "skip_states")]
+    repair: bool,
+}
+
+impl CheckCmd {
+    fn run(self, args: BaseArgs) -> Result<()> {
+        let node_config = NodeConfig::from_file(args.node_config_path(self.config.as_ref()))
+            .context("failed to load node config")?
+            .with_relative_paths(&args.home);
+
+        // NOTE: Storage is opened for writing only to repair refcounts
+        let checker = StorageChecker::open(&node_config.storage, !self.repair)
+            .context("failed to open storage")?;
+
+        let report = checker.check(&CheckOptions {
+            blocks: !self.skip_blocks,
+            archives: !self.skip_archives,
+            states: !self.skip_states,
+            repair_refcounts: self.repair,
+        })?;
+
+        let is_ok = report.is_ok();
+        print_json(report)?;
+
+        anyhow::ensure!(is_ok, "storage check failed");
+        Ok(())
+    }
+}
+
+/// Restore the stopped node database from a checkpoint
+#[derive(clap::Parser)]
+struct RestoreCmd {
+    /// Path to the node config. Default: `$TYCHO_HOME/config.json`
+    #[clap(long)]
+    config: Option<PathBuf>,
+
+    /// Path to the checkpoint directory.
+    #[clap()]
+    checkpoint: PathBuf,
+
+    /// Remove the existing node database.
+    #[clap(long)]
+    force: bool,
+}
+
+impl RestoreCmd {
+    fn run(self, args: BaseArgs) -> Result<()> {
+        let node_config = NodeConfig::from_file(args.node_config_path(self.config.as_ref()))
+            .context("failed to load node config")?
+            .with_relative_paths(&args.home);
+
+        let info = restore_checkpoint(&self.checkpoint, &node_config.storage, self.force)
+            .context("failed to restore storage")?;
+
+        print_json(serde_json::json!({
+            "last_mc_block_id": info.last_mc_block_id.map(|id| id.to_string()),
+            "init_mc_block_id": info.init_mc_block_id.map(|id| id.to_string()),
+            "has_rpc": info.has_rpc,
+        }))
+    }
+}
+
+/// Compute the space usage of the stored shard states of the stopped node
+#[derive(clap::Parser)]
+#[clap(disable_help_flag = true)]
+struct StateStatsCmd {
+    /// Path to the node config. Default: `$TYCHO_HOME/config.json`
+    #[clap(long)]
+    config: Option<PathBuf>,
+
+    /// Full block ID of the state. Latest states are used by default.
+    #[clap(short, long, allow_hyphen_values(true))]
+    block_id: Option<BlockId>,
+
+    /// Number of the heaviest accounts to show.
+    #[clap(short, long, default_value_t = 10)]
+    top: usize,
+
+    #[clap(short, long)]
+    human_readable: bool,
+
+    #[clap(long, action = clap::ArgAction::HelpLong)]
+    help: Option<bool>,
+}
+
+impl StateStatsCmd {
+    fn run(self, args: BaseArgs) -> Result<()> {
+        init_logger_simple("info");
+
+        let node_config = NodeConfig::from_file(args.node_config_path(self.config.as_ref()))
+            .context("failed to load node config")?
+            .with_relative_paths(&args.home);
+
+        anyhow::ensure!(
+            node_config.storage.root_dir.exists(),
+            "storage not found at {}",
+            node_config.storage.root_dir.display()
+        );
+
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?
+            .block_on(async move {
+                let storage = Storage::builder()
+                    .with_config(node_config.storage)
+                    .build()
+                    .await
+                    .context("failed to open storage")?;
+
+                let shard_states = storage.shard_state_storage();
+
+                let block_ids = match self.block_id {
+                    Some(block_id) => vec![block_id],
+                    None => {
+                        let mc_block_id = storage
+                            .node_state()
+                            .load_last_mc_block_id()
+                            .context("no applied blocks found")?;
+                        let mc_state = shard_states.load_state(&mc_block_id).await?;
+
+                        let mut block_ids = vec![mc_block_id];
+                        for block_id in mc_state.shards()?.latest_blocks() {
+                            block_ids.push(block_id?);
+                        }
+                        block_ids
+                    }
+                };
+
+                let mut states = Vec::<ShardStateStats>::with_capacity(block_ids.len());
+                for block_id in block_ids {
+                    let stats = shard_states
+                        .compute_state_stats(&block_id, self.top)
+                        .await?;
+
+                    states.push(stats.into());
+                }
+
+                print_state_stats(states, self.human_readable)
+            })
+    }
+}
+
+/// Export persistent states of the stopped node into a bundle
+/// which can be used to boot another node
+#[derive(clap::Parser)]
+#[clap(disable_help_flag = true)]
+struct ExportStateCmd {
+    /// Path to the node config. Default: `$TYCHO_HOME/config.json`
+    #[clap(long)]
+    config: Option<PathBuf>,
+
+    /// Full key block ID of the persistent state.
+    /// The latest persistent state is used by default.
+    #[clap(short, long, allow_hyphen_values(true))]
+    block_id: Option<BlockId>,
+
+    /// Path to the bundle directory. The directory must not exist.
+    #[clap()]
+    output: PathBuf,
+
+    #[clap(long, action = clap::ArgAction::HelpLong)]
+    help: Option<bool>,
+}
+
+impl ExportStateCmd {
+    fn run(self, args: BaseArgs) -> Result<()> {
+        init_logger_simple("info");
+
+        let node_config = NodeConfig::from_file(args.node_config_path(self.config.as_ref()))
+            .context("failed to load node config")?
+            .with_relative_paths(&args.home);
+
+        anyhow::ensure!(
+            node_config.storage.root_dir.exists(),
+            "storage not found at {}",
+            node_config.storage.root_dir.display()
+        );
+
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?
+            .block_on(async move {
+                let storage = Storage::builder()
+                    .with_config(node_config.storage)
+                    .build()
+                    .await
+                    .context("failed to open storage")?;
+
+                let persistent_states = storage.persistent_state_storage();
+
+                let mc_block_id = match self.block_id {
+                    Some(block_id) => block_id,
+                    None => storage
+                        .block_handle_storage()
+                        .key_blocks_iterator(KeyBlocksDirection::Backward)
+                        .find(|block_id| {
+                            persistent_states.state_exists(block_id, PersistentStateKind::Shard)
+                        })
+                        .context("no persistent states found")?,
+                };
+
+                let manifest = persistent_states
+                    .export_bundle(&mc_block_id, &self.output)
+                    .await
+                    .context("failed to export state bundle")?;
+
+                print_json(manifest)
+            })
+    }
+}
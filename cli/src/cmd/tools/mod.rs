@@ -1,7 +1,10 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 
+use crate::BaseArgs;
+
 mod bc;
+mod db;
 mod gen_account;
 mod gen_dht;
 mod gen_key;
@@ -15,13 +18,14 @@ pub struct Cmd {
 }
 
 impl Cmd {
-    pub fn run(self) -> Result<()> {
+    pub fn run(self, args: BaseArgs) -> Result<()> {
         match self.cmd {
             SubCmd::GenDht(cmd) => cmd.run(),
             SubCmd::GenKey(cmd) => cmd.run(),
             SubCmd::GenZerostate(cmd) => cmd.run(),
             SubCmd::GenAccount(cmd) => cmd.run(),
             SubCmd::Bc(cmd) => cmd.run(),
+            SubCmd::Db(cmd) => cmd.run(args),
         }
     }
 }
@@ -33,4 +37,5 @@ enum SubCmd {
     GenZerostate(gen_zerostate::Cmd),
     GenAccount(gen_account::Cmd),
     Bc(bc::Cmd),
+    Db(db::Cmd),
 }
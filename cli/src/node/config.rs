@@ -13,9 +13,10 @@ use tycho_collator::validator::ValidatorStdImplConfig;
 use tycho_consensus::prelude::MempoolNodeConfig;
 use tycho_control::ControlServerConfig;
 use tycho_core::block_strider::{
-    ArchiveBlockProviderConfig, BlockchainBlockProviderConfig, StarterConfig,
+    ArchiveBlockProviderConfig, ArchiveUploaderConfig, BlockchainBlockProviderConfig, StarterConfig,
 };
 use tycho_core::blockchain_rpc::{BlockchainRpcClientConfig, BlockchainRpcServiceConfig};
+use tycho_core::object_storage::ObjectStorageConfig;
 use tycho_core::overlay_client::PublicOverlayClientConfig;
 use tycho_network::{DhtConfig, NetworkConfig, OverlayConfig, PeerResolverConfig};
 use tycho_rpc::RpcConfig;
@@ -283,6 +284,18 @@ pub struct NodeConfig {
 
     pub archive_block_provider: ArchiveBlockProviderConfig,
 
+    /// Download archives from the external mirror before asking peers.
+    ///
+    /// NOTE: The `http` mirror must support WebDAV (`PROPFIND`) listing.
+    ///
+    /// Default: disabled.
+    pub archive_mirror: Option<ObjectStorageConfig>,
+
+    /// Upload committed archives to the external object storage.
+    ///
+    /// Default: disabled.
+    pub archive_uploader: Option<ArchiveUploaderConfig>,
+
     pub collator: CollatorConfig,
 
     pub mempool: MempoolNodeConfig,
@@ -321,6 +334,8 @@ impl Default for NodeConfig {
             blockchain_rpc_service: BlockchainRpcServiceConfig::default(),
             blockchain_block_provider: BlockchainBlockProviderConfig::default(),
             archive_block_provider: ArchiveBlockProviderConfig::default(),
+            archive_mirror: None,
+            archive_uploader: None,
             collator: CollatorConfig::default(),
             mempool: MempoolNodeConfig::default(),
             validator: ValidatorStdImplConfig::default(),
@@ -337,7 +352,24 @@ impl Default for NodeConfig {
 
 impl NodeConfig {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        tycho_util::serde_helpers::load_json_from_file(path)
+        let config: Self = tycho_util::serde_helpers::load_json_from_file(path)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let keep_until_uploaded = self
+            .storage
+            .archives_gc
+            .is_some_and(|config| config.keep_until_uploaded);
+
+        // NOTE: Archives GC would be blocked forever otherwise.
+        anyhow::ensure!(
+            !keep_until_uploaded || self.archive_uploader.is_some(),
+            "`storage.archives_gc.keep_until_uploaded` requires `archive_uploader` to be configured"
+        );
+
+        Ok(())
     }
 
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
@@ -365,3 +397,22 @@ impl NodeConfig {
 pub struct MemoryProfilingConfig {
     pub profiling_dir: PathBuf,
 }
+
+#[cfg(test)]
+mod tests {
+    use tycho_storage::ArchivesGcConfig;
+
+    use super::*;
+
+    #[test]
+    fn keep_until_uploaded_requires_uploader() {
+        let mut config = NodeConfig::default();
+        config.validate().unwrap();
+
+        config.storage.archives_gc = Some(ArchivesGcConfig {
+            keep_until_uploaded: true,
+            ..Default::default()
+        });
+        assert!(config.validate().is_err());
+    }
+}
@@ -23,9 +23,10 @@ use tycho_collator::validator::{
 };
 use tycho_control::{ControlEndpoint, ControlServer, ControlServerConfig, ControlServerVersion};
 use tycho_core::block_strider::{
-    ArchiveBlockProvider, ArchiveBlockProviderConfig, BlockProvider, BlockProviderExt,
-    BlockStrider, BlockSubscriberExt, BlockchainBlockProvider, BlockchainBlockProviderConfig,
-    ColdBootType, FileZerostateProvider, GcSubscriber, MetricsSubscriber, OptionalBlockStuff,
+    ArchiveBlockProvider, ArchiveBlockProviderConfig, ArchiveHandler, ArchiveMirror,
+    ArchiveUploader, ArchiveUploaderConfig, BlockProvider, BlockProviderExt, BlockStrider,
+    BlockSubscriberExt, BlockchainBlockProvider, BlockchainBlockProviderConfig, ColdBootType,
+    FileZerostateProvider, GcSubscriber, MetricsSubscriber, OptionalBlockStuff,
     PersistentBlockStriderState, PsSubscriber, ShardStateApplier, Starter, StarterConfig,
     StateSubscriber, StateSubscriberContext, StorageBlockProvider,
 };
@@ -33,6 +34,7 @@ use tycho_core::blockchain_rpc::{
     BlockchainRpcClient, BlockchainRpcService, BroadcastListener, SelfBroadcastListener,
 };
 use tycho_core::global_config::{GlobalConfig, MempoolGlobalConfig, ZerostateId};
+use tycho_core::object_storage::ObjectStorageConfig;
 use tycho_core::overlay_client::PublicOverlayClient;
 use tycho_network::{
     DhtClient, DhtService, InboundRequestMeta, Network, OverlayService, PeerResolver,
@@ -67,6 +69,8 @@ pub struct Node {
     control_socket: PathBuf,
     blockchain_block_provider_config: BlockchainBlockProviderConfig,
     archive_block_provider_config: ArchiveBlockProviderConfig,
+    archive_mirror_config: Option<ObjectStorageConfig>,
+    archive_uploader_config: Option<ArchiveUploaderConfig>,
 
     collator_config: CollatorConfig,
     validator_config: ValidatorStdImplConfig,
@@ -206,6 +210,8 @@ impl Node {
             control_socket,
             blockchain_block_provider_config: node_config.blockchain_block_provider,
             archive_block_provider_config: node_config.archive_block_provider,
+            archive_mirror_config: node_config.archive_mirror,
+            archive_uploader_config: node_config.archive_uploader,
             collator_config: node_config.collator,
             validator_config: node_config.validator,
             internal_queue_config: node_config.internal_queue,
@@ -225,7 +231,11 @@ impl Node {
     }
 
     /// Initialize the node and return the init block id.
-    pub async fn boot(&self, zerostates: Option<Vec<PathBuf>>) -> Result<BlockId> {
+    pub async fn boot(
+        &self,
+        zerostates: Option<Vec<PathBuf>>,
+        state_bundle: Option<PathBuf>,
+    ) -> Result<BlockId> {
         let node_state = self.storage.node_state();
         let last_mc_block_id = match node_state.load_last_mc_block_id() {
             Some(block_id) => block_id,
@@ -237,7 +247,10 @@ impl Node {
                     self.starter_config.clone(),
                 )
                 .cold_boot(
-                    ColdBootType::LatestPersistent,
+                    match state_bundle {
+                        Some(path) => ColdBootType::StateBundle(path),
+                        None => ColdBootType::LatestPersistent,
+                    },
                     zerostates.map(FileZerostateProvider),
                 )
                 .await?
@@ -369,6 +382,15 @@ impl Node {
         let gc_subscriber = GcSubscriber::new(self.storage.clone());
         let ps_subscriber = PsSubscriber::new(self.storage.clone());
 
+        let archive_handler = match &self.archive_uploader_config {
+            Some(config) => {
+                let uploader = ArchiveUploader::new(self.storage.clone(), config.clone())
+                    .context("failed to create archive uploader")?;
+                Some(ArchiveHandler::new(self.storage.clone(), uploader)?)
+            }
+            None => None,
+        };
+
         // Create control server
         let control_server = {
             let mut builder = ControlServer::builder()
@@ -423,6 +445,15 @@ impl Node {
             self.archive_block_provider_config.clone(),
         );
 
+        let mirror_block_provider = match &self.archive_mirror_config {
+            Some(config) => Some(ArchiveBlockProvider::with_mirror(
+                ArchiveMirror::new(config)?,
+                self.storage.clone(),
+                self.archive_block_provider_config.clone(),
+            )),
+            None => None,
+        };
+
         let blockchain_block_provider = BlockchainBlockProvider::new(
             self.blockchain_rpc_client.clone(),
             self.storage.clone(),
@@ -446,6 +477,7 @@ impl Node {
             .with_provider(
                 collator
                     .new_sync_point(CollatorSyncContext::Historical)
+                    .chain(mirror_block_provider)
                     .chain(archive_block_provider)
                     .chain(collator.new_sync_point(CollatorSyncContext::Recent))
                     .chain((
@@ -470,7 +502,7 @@ impl Node {
                     validator_subscriber,
                     MetricsSubscriber,
                 )
-                    .chain(gc_subscriber),
+                    .chain((archive_handler, gc_subscriber)),
             )
             .build();
 
@@ -2,7 +2,8 @@ use anyhow::Result;
 use futures_util::future::BoxFuture;
 use tl_proto::TlError;
 use tycho_network::{
-    try_handle_prefix, try_handle_prefix_with_offset, Network, PeerId, PrivateOverlay, Request,
+    try_handle_prefix, try_handle_prefix_with_offset, Network, PeerId, PeerReputation,
+    PrivateOverlay, Request,
 };
 use tycho_util::metrics::HistogramGuard;
 
@@ -36,6 +37,9 @@ impl Dispatcher {
             network: network.clone(),
         }
     }
+    pub fn peer_reputation(&self) -> &PeerReputation {
+        self.network.peer_reputation()
+    }
     pub fn query_broadcast(
         &self,
         peer_id: &PeerId,
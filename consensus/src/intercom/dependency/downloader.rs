@@ -11,7 +11,7 @@ use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio::time::{Interval, MissedTickBehavior};
 use tracing::Instrument;
-use tycho_network::{PeerId, Request};
+use tycho_network::{PeerId, PeerPenalty, Request};
 use tycho_util::metrics::HistogramGuard;
 use tycho_util::FastHashMap;
 
@@ -378,6 +378,7 @@ impl<T: DownloadType> DownloadTask<T> {
                 // it's a ban
                 self.not_found = self.not_found.saturating_add(1);
                 DownloadCtx::meter_unreliable();
+                self.penalize(peer_id);
                 tracing::error!(
                     peer_id = display(peer_id.alt()),
                     author = display(point.data().author.alt()),
@@ -394,6 +395,7 @@ impl<T: DownloadType> DownloadTask<T> {
                         // reliable peer won't return unverifiable point
                         self.not_found = self.not_found.saturating_add(1);
                         DownloadCtx::meter_unreliable();
+                        self.penalize(peer_id);
                         tracing::error!(
                             result = display(error),
                             peer = display(peer_id.alt()),
@@ -429,6 +431,12 @@ impl<T: DownloadType> DownloadTask<T> {
         }
     }
 
+    fn penalize(&self, peer_id: &PeerId) {
+        (self.parent.inner.dispatcher)
+            .peer_reputation()
+            .penalize(peer_id, PeerPenalty::Malicious);
+    }
+
     fn match_peer_updates(
         &mut self,
         result: Result<(PeerId, PeerState), RecvError>,
@@ -1,9 +1,9 @@
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use bytes::Bytes;
 use everscale_types::boc::{Boc, BocRepr};
-use everscale_types::cell::DynCell;
+use everscale_types::cell::{DynCell, HashBytes};
 use everscale_types::models::{BlockId, BlockIdShort, OwnedMessage, StdAddr};
 use futures_util::StreamExt;
 use tarpc::tokio_serde::formats::Bincode;
@@ -84,6 +84,30 @@ impl ControlClient {
             .map_err(Into::into)
     }
 
+    pub async fn ban_peer(&self, peer_id: HashBytes, duration_sec: u32) -> ClientResult<()> {
+        self.inner
+            .ban_peer(current_context(), BanPeerRequest {
+                peer_id,
+                duration_sec,
+            })
+            .await?
+            .map_err(Into::into)
+    }
+
+    pub async fn unban_peer(&self, peer_id: HashBytes) -> ClientResult<bool> {
+        self.inner
+            .unban_peer(current_context(), peer_id)
+            .await?
+            .map_err(Into::into)
+    }
+
+    pub async fn get_inbound_peers_info(&self) -> ClientResult<InboundPeersInfoResponse> {
+        self.inner
+            .get_inbound_peers_info(current_context())
+            .await?
+            .map_err(Into::into)
+    }
+
     pub async fn broadcast_external_message(&self, message: OwnedMessage) -> ClientResult<()> {
         if !message.info.is_external_in() {
             return Err(ClientError::ClientFailed(anyhow::anyhow!(
@@ -263,6 +287,60 @@ impl ControlClient {
             .await?
             .map_err(Into::into)
     }
+
+    pub async fn create_checkpoint(&self, path: PathBuf) -> ClientResult<CreateCheckpointResponse> {
+        self.inner
+            .create_checkpoint(current_context(), CreateCheckpointRequest { path })
+            .await?
+            .map_err(Into::into)
+    }
+
+    pub async fn get_state_stats(
+        &self,
+        block_id: Option<BlockId>,
+        top_n: usize,
+    ) -> ClientResult<StateStatsResponse> {
+        self.inner
+            .get_state_stats(current_context(), StateStatsRequest { block_id, top_n })
+            .await?
+            .map_err(Into::into)
+    }
+
+    pub async fn get_db_stats(&self) -> ClientResult<Vec<DbStats>> {
+        self.inner
+            .get_db_stats(current_context())
+            .await?
+            .map(|res| res.databases)
+            .map_err(Into::into)
+    }
+
+    pub async fn compact_db(&self, db: String, cf: Option<String>) -> ClientResult<Vec<String>> {
+        self.inner
+            .compact_db(current_context(), CompactDbRequest { db, cf })
+            .await?
+            .map(|res| res.column_families)
+            .map_err(Into::into)
+    }
+
+    pub async fn get_tx_watch_list(&self) -> ClientResult<Vec<StdAddr>> {
+        self.inner
+            .get_tx_watch_list(current_context())
+            .await?
+            .map(|res| res.addresses)
+            .map_err(Into::into)
+    }
+
+    pub async fn update_tx_watch_list(
+        &self,
+        add: Vec<StdAddr>,
+        remove: Vec<StdAddr>,
+    ) -> ClientResult<Vec<StdAddr>> {
+        self.inner
+            .update_tx_watch_list(current_context(), UpdateTxWatchListRequest { add, remove })
+            .await?
+            .map(|res| res.addresses)
+            .map_err(Into::into)
+    }
 }
 
 // sets a 10-minute deadline on the context instead of default 10 seconds
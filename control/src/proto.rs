@@ -1,5 +1,6 @@
 use std::net::SocketAddr;
 use std::num::{NonZeroU32, NonZeroU64};
+use std::path::PathBuf;
 
 use bytes::Bytes;
 use everscale_types::models::{
@@ -37,6 +38,15 @@ pub trait ControlServer {
     /// Get node neighbours info
     async fn get_neighbours_info() -> ServerResult<NeighboursInfoResponse>;
 
+    /// Temporarily bans the peer in the shared peer reputation.
+    async fn ban_peer(req: BanPeerRequest) -> ServerResult<()>;
+
+    /// Removes the peer ban. Returns whether the peer was banned.
+    async fn unban_peer(peer_id: HashBytes) -> ServerResult<bool>;
+
+    /// Get inbound request counters of the connected peers.
+    async fn get_inbound_peers_info() -> ServerResult<InboundPeersInfoResponse>;
+
     /// Broadcast a message to validators.
     async fn broadcast_external_message(req: BroadcastExtMsgRequest) -> ServerResult<()>;
 
@@ -67,6 +77,28 @@ pub trait ControlServer {
     /// Returns list of all block ids.
     async fn get_block_ids(req: BlockListRequest) -> ServerResult<BlockListResponse>;
 
+    /// Creates a consistent checkpoint of the node storage.
+    async fn create_checkpoint(
+        req: CreateCheckpointRequest,
+    ) -> ServerResult<CreateCheckpointResponse>;
+
+    /// Computes the space usage of the stored shard states.
+    async fn get_state_stats(req: StateStatsRequest) -> ServerResult<StateStatsResponse>;
+
+    /// Returns column family stats of all key-value databases.
+    async fn get_db_stats() -> ServerResult<DbStatsResponse>;
+
+    /// Starts a manual compaction of the database in background.
+    async fn compact_db(req: CompactDbRequest) -> ServerResult<CompactDbResponse>;
+
+    /// Returns accounts which always keep the full transactions history.
+    async fn get_tx_watch_list() -> ServerResult<TxWatchListResponse>;
+
+    /// Updates the transactions watch list. Returns the updated list.
+    async fn update_tx_watch_list(
+        req: UpdateTxWatchListRequest,
+    ) -> ServerResult<TxWatchListResponse>;
+
     /// Signs an elections payload.
     async fn sign_elections_payload(
         req: ElectionsPayloadRequest,
@@ -248,6 +280,165 @@ pub struct BlockListResponse {
     pub continuation: Option<BlockIdShort>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCheckpointRequest {
+    /// Absolute path to a new checkpoint directory.
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCheckpointResponse {
+    pub path: PathBuf,
+    pub last_mc_block_id: Option<BlockId>,
+    pub init_mc_block_id: Option<BlockId>,
+    pub has_rpc: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateStatsRequest {
+    /// Block id of the state. Latest masterchain and
+    /// shard states are used if not specified.
+    pub block_id: Option<BlockId>,
+    /// Number of the heaviest accounts to return.
+    pub top_n: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateStatsResponse {
+    pub states: Vec<ShardStateStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbStatsResponse {
+    pub databases: Vec<DbStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbStats {
+    pub name: String,
+    pub block_cache_usage: u64,
+    pub block_cache_pinned_usage: u64,
+    pub column_families: Vec<ColumnFamilyStats>,
+}
+
+impl From<tycho_storage::DbStats> for DbStats {
+    fn from(stats: tycho_storage::DbStats) -> Self {
+        Self {
+            name: stats.name,
+            block_cache_usage: stats.block_cache_usage,
+            block_cache_pinned_usage: stats.block_cache_pinned_usage,
+            column_families: stats
+                .column_families
+                .into_iter()
+                .map(|cf| ColumnFamilyStats {
+                    name: cf.name,
+                    estimated_live_data_size: cf.estimated_live_data_size,
+                    total_sst_files_size: cf.total_sst_files_size,
+                    sst_files: cf.sst_files,
+                    estimated_keys: cf.estimated_keys,
+                    pending_compaction_bytes: cf.pending_compaction_bytes,
+                    memtables_size: cf.memtables_size,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnFamilyStats {
+    pub name: String,
+    pub estimated_live_data_size: u64,
+    pub total_sst_files_size: u64,
+    pub sst_files: u64,
+    pub estimated_keys: u64,
+    pub pending_compaction_bytes: u64,
+    pub memtables_size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactDbRequest {
+    /// Database name (`base`, `rpc`, `mempool` or `cold`).
+    pub db: String,
+    /// Column family name. All column families are compacted if not specified.
+    pub cf: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactDbResponse {
+    /// Column families which will be compacted.
+    pub column_families: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateTxWatchListRequest {
+    pub add: Vec<StdAddr>,
+    pub remove: Vec<StdAddr>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxWatchListResponse {
+    pub addresses: Vec<StdAddr>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardStateStats {
+    pub block_id: BlockId,
+    /// All unique cells of the state.
+    pub total: CellsUsage,
+    /// Cells which are reachable only from a single account.
+    pub accounts: CellsUsage,
+    /// Cells which are reachable from more than one account.
+    pub shared: CellsUsage,
+    pub account_count: u64,
+    pub top_accounts: Vec<AccountStats>,
+}
+
+#[cfg(feature = "server")]
+impl From<tycho_storage::ShardStateStats> for ShardStateStats {
+    fn from(stats: tycho_storage::ShardStateStats) -> Self {
+        let workchain = stats.block_id.shard.workchain() as i8;
+        Self {
+            block_id: stats.block_id,
+            total: stats.total.into(),
+            accounts: stats.accounts.into(),
+            shared: stats.shared.into(),
+            account_count: stats.account_count,
+            top_accounts: stats
+                .top_accounts
+                .into_iter()
+                .map(|item| AccountStats {
+                    address: StdAddr::new(workchain, item.address),
+                    total: item.total.into(),
+                    unique: item.unique.into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountStats {
+    pub address: StdAddr,
+    pub total: CellsUsage,
+    pub unique: CellsUsage,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CellsUsage {
+    pub cells: u64,
+    pub bytes: u64,
+}
+
+#[cfg(feature = "server")]
+impl From<tycho_storage::CellsUsage> for CellsUsage {
+    fn from(usage: tycho_storage::CellsUsage) -> Self {
+        Self {
+            cells: usage.cells,
+            bytes: usage.bytes,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ElectionsPayloadRequest {
     pub election_id: u32,
@@ -280,4 +471,34 @@ pub struct NeighbourInfo {
     pub failed_requests: u64,
     pub total_requests: u64,
     pub roundtrip_ms: u64,
+    /// Accumulated penalty in the shared peer reputation.
+    pub reputation_penalty: f64,
+    /// Unix timestamp until which the peer is banned.
+    pub banned_until: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InboundPeersInfoResponse {
+    /// Peers sorted by the number of rejected requests.
+    pub peers: Vec<InboundPeerInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InboundPeerInfo {
+    pub id: HashBytes,
+    /// Number of inbound requests rejected due to quotas.
+    pub rejected_requests: u64,
+    /// Total size of the accepted inbound requests and their responses.
+    pub total_bytes: u64,
+    /// Accumulated penalty in the shared peer reputation.
+    pub reputation_penalty: f64,
+    /// Unix timestamp until which the peer is banned.
+    pub banned_until: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BanPeerRequest {
+    pub peer_id: HashBytes,
+    /// Ban duration in seconds.
+    pub duration_sec: u32,
 }
@@ -1,6 +1,7 @@
 use std::num::NonZeroU64;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
 use anyhow::{Context as _, Result};
 use arc_swap::ArcSwapOption;
@@ -25,7 +26,7 @@ use tycho_core::block_strider::{
     GcSubscriber, ManualGcTrigger, StateSubscriber, StateSubscriberContext,
 };
 use tycho_core::blockchain_rpc::BlockchainRpcClient;
-use tycho_network::Network;
+use tycho_network::{Network, PeerId};
 use tycho_storage::{ArchiveId, BlockHandle, Storage};
 use tycho_util::FastHashMap;
 
@@ -450,15 +451,16 @@ impl proto::ControlServer for ControlServer {
     }
 
     async fn get_neighbours_info(self, _: Context) -> ServerResult<proto::NeighboursInfoResponse> {
-        let neighbours = self
-            .inner
-            .blockchain_rpc_client
-            .overlay_client()
+        let overlay_client = self.inner.blockchain_rpc_client.overlay_client();
+        let reputation = overlay_client.network().peer_reputation();
+
+        let neighbours = overlay_client
             .neighbours()
             .get_active_neighbours()
             .iter()
             .map(|x| {
                 let stats = x.get_stats();
+                let reputation = reputation.get(x.peer_id());
                 proto::NeighbourInfo {
                     id: HashBytes(x.peer_id().to_bytes()),
                     expires_at: x.expires_at_secs(),
@@ -466,6 +468,8 @@ impl proto::ControlServer for ControlServer {
                     failed_requests: stats.failed_requests,
                     total_requests: stats.total_requests,
                     roundtrip_ms: stats.avg_roundtrip.unwrap_or_default().as_millis() as u64,
+                    reputation_penalty: reputation.map(|r| r.penalty).unwrap_or_default(),
+                    banned_until: reputation.and_then(|r| r.banned_until),
                 }
             })
             .collect::<_>();
@@ -473,6 +477,49 @@ impl proto::ControlServer for ControlServer {
         Ok(proto::NeighboursInfoResponse { neighbours })
     }
 
+    async fn ban_peer(self, _: Context, req: proto::BanPeerRequest) -> ServerResult<()> {
+        let network = self.inner.blockchain_rpc_client.overlay_client().network();
+        network.peer_reputation().ban(
+            &PeerId(req.peer_id.0),
+            Duration::from_secs(req.duration_sec as u64),
+        );
+        Ok(())
+    }
+
+    async fn unban_peer(self, _: Context, peer_id: HashBytes) -> ServerResult<bool> {
+        let network = self.inner.blockchain_rpc_client.overlay_client().network();
+        Ok(network.peer_reputation().unban(&PeerId(peer_id.0)))
+    }
+
+    async fn get_inbound_peers_info(
+        self,
+        _: Context,
+    ) -> ServerResult<proto::InboundPeersInfoResponse> {
+        let network = self.inner.blockchain_rpc_client.overlay_client().network();
+        let reputation = network.peer_reputation();
+
+        let mut peers = network
+            .inbound_quotas()
+            .peers()
+            .into_iter()
+            .map(|(peer_id, info)| {
+                let reputation = reputation.get(&peer_id);
+                proto::InboundPeerInfo {
+                    id: HashBytes(peer_id.to_bytes()),
+                    rejected_requests: info.rejected_requests,
+                    total_bytes: info.total_bytes,
+                    reputation_penalty: reputation.map(|r| r.penalty).unwrap_or_default(),
+                    banned_until: reputation.and_then(|r| r.banned_until),
+                }
+            })
+            .collect::<Vec<_>>();
+        peers.sort_unstable_by(|a, b| {
+            (b.rejected_requests, b.total_bytes).cmp(&(a.rejected_requests, a.total_bytes))
+        });
+
+        Ok(proto::InboundPeersInfoResponse { peers })
+    }
+
     async fn broadcast_external_message(
         self,
         _: Context,
@@ -701,6 +748,105 @@ impl proto::ControlServer for ControlServer {
         })
     }
 
+    async fn create_checkpoint(
+        self,
+        _: Context,
+        req: proto::CreateCheckpointRequest,
+    ) -> ServerResult<proto::CreateCheckpointResponse> {
+        if !req.path.is_absolute() {
+            return Err(ServerError::new("checkpoint path must be absolute"));
+        }
+
+        let info = self.inner.storage.create_checkpoint(&req.path).await?;
+
+        Ok(proto::CreateCheckpointResponse {
+            path: req.path,
+            last_mc_block_id: info.last_mc_block_id,
+            init_mc_block_id: info.init_mc_block_id,
+            has_rpc: info.has_rpc,
+        })
+    }
+
+    async fn get_state_stats(
+        self,
+        _: Context,
+        req: proto::StateStatsRequest,
+    ) -> ServerResult<proto::StateStatsResponse> {
+        let block_ids = match req.block_id {
+            Some(block_id) => vec![block_id],
+            None => {
+                let mut block_ids = Vec::new();
+                if let Some(cached) = &*self.inner.mc_accounts.read() {
+                    block_ids.push(*cached.block_handle.id());
+                }
+                for cached in self.inner.sc_accounts.read().values() {
+                    block_ids.push(*cached.block_handle.id());
+                }
+                block_ids
+            }
+        };
+
+        if block_ids.is_empty() {
+            return Err(ServerError::new("no states available"));
+        }
+
+        let shard_states = self.inner.storage.shard_state_storage();
+
+        let mut states = Vec::<proto::ShardStateStats>::with_capacity(block_ids.len());
+        for block_id in block_ids {
+            let stats = shard_states
+                .compute_state_stats(&block_id, req.top_n)
+                .await?;
+
+            states.push(stats.into());
+        }
+
+        Ok(proto::StateStatsResponse { states })
+    }
+
+    async fn get_db_stats(self, _: Context) -> ServerResult<proto::DbStatsResponse> {
+        let stats = self.inner.storage.kv_db_stats().await?;
+        Ok(proto::DbStatsResponse {
+            databases: stats.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    async fn compact_db(
+        self,
+        _: Context,
+        req: proto::CompactDbRequest,
+    ) -> ServerResult<proto::CompactDbResponse> {
+        let column_families = self
+            .inner
+            .storage
+            .start_kv_db_compaction(&req.db, req.cf.as_deref())
+            .await?;
+        Ok(proto::CompactDbResponse { column_families })
+    }
+
+    async fn get_tx_watch_list(self, _: Context) -> ServerResult<proto::TxWatchListResponse> {
+        let Some(rpc_storage) = self.inner.storage.rpc_storage() else {
+            return Err(ServerError::new("rpc storage is not enabled"));
+        };
+
+        Ok(proto::TxWatchListResponse {
+            addresses: rpc_storage.tx_watch_list(),
+        })
+    }
+
+    async fn update_tx_watch_list(
+        self,
+        _: Context,
+        req: proto::UpdateTxWatchListRequest,
+    ) -> ServerResult<proto::TxWatchListResponse> {
+        let Some(rpc_storage) = self.inner.storage.rpc_storage() else {
+            return Err(ServerError::new("rpc storage is not enabled"));
+        };
+
+        let addresses = rpc_storage.update_tx_watch_list(&req.add, &req.remove)?;
+        Ok(proto::TxWatchListResponse { addresses })
+    }
+
     async fn sign_elections_payload(
         self,
         _: tarpc::context::Context,
@@ -20,6 +20,8 @@ pub use self::provider::{
     BlockchainBlockProvider, BlockchainBlockProviderConfig, ChainBlockProvider, CheckProof,
     EmptyBlockProvider, OptionalBlockStuff, ProofChecker, RetryConfig, StorageBlockProvider,
 };
+#[cfg(feature = "object-store")]
+pub use self::provider::{ArchiveMirror, MirrorArchive};
 pub use self::starter::{
     ColdBootType, FileZerostateProvider, Starter, StarterConfig, ZerostateProvider,
 };
@@ -33,6 +35,8 @@ pub use self::subscriber::{
     MetricsSubscriber, NoopSubscriber, PsSubscriber, StateSubscriber, StateSubscriberContext,
     StateSubscriberExt,
 };
+#[cfg(feature = "object-store")]
+pub use self::subscriber::{ArchiveUploader, ArchiveUploaderConfig};
 
 mod archive_handler;
 mod provider;
@@ -172,6 +176,8 @@ where
         let mc_block_id = *block.id();
         tracing::debug!(%mc_block_id, "processing masterchain block");
 
+        let _checkpoint_guard = self.state.begin_master().await;
+
         let started_at = Instant::now();
 
         let custom = block.load_custom()?;
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use futures_util::StreamExt;
+use object_store::ObjectStore;
+use parking_lot::Mutex;
+use tokio::sync::mpsc;
+use tycho_block_util::archive::ArchiveVerifier;
+use tycho_storage::ARCHIVE_PACKAGE_SIZE;
+use tycho_util::compression::ZstdDecompressStream;
+use tycho_util::metrics::HistogramGuard;
+
+use crate::object_storage::{archive_object_path, ObjectStorageConfig};
+
+/// A read-only client for the archives mirror
+/// (e.g. a bucket filled by [`ArchiveUploader`]).
+///
+/// [`ArchiveUploader`]: crate::block_strider::ArchiveUploader
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct ArchiveMirror {
+    inner: Arc<Inner>,
+}
+
+impl ArchiveMirror {
+    pub fn new(config: &ObjectStorageConfig) -> Result<Self> {
+        let store = config
+            .build()
+            .context("failed to create archive mirror client")?;
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                store,
+                listing_ttl: LISTING_TTL,
+                known_archives: Default::default(),
+            }),
+        })
+    }
+
+    /// Finds an archive which might contain the specified masterchain block.
+    ///
+    /// NOTE: The last archive in the mirror is returned for all newer blocks
+    /// which can still be in it.
+    pub async fn find_archive(&self, mc_seqno: u32) -> Result<Option<MirrorArchive>> {
+        // Refresh the list of archives if the requested one might be too new.
+        let is_known = matches!(self.inner.find_known_archive(mc_seqno), Some((_, false)));
+        if !is_known && self.inner.is_listing_outdated() {
+            self.inner.refresh_known_archives().await?;
+        }
+
+        let Some((archive, _)) = self.inner.find_known_archive(mc_seqno) else {
+            return Ok(None);
+        };
+
+        // NOTE: Archive can't contain the block if it is too far from its start
+        if mc_seqno >= archive.id.saturating_add(ARCHIVE_PACKAGE_SIZE) {
+            return Ok(None);
+        }
+
+        Ok(Some(archive))
+    }
+
+    /// Downloads and decompresses the archive into the `output`.
+    pub async fn download_archive<W>(&self, archive_id: u32, output: W) -> Result<W>
+    where
+        W: Write + Send + 'static,
+    {
+        const DECOMPRESS_BUFFER_SIZE: usize = 1 << 20; // 1 MB
+        const MAX_PENDING_CHUNKS: usize = 10;
+
+        let _histogram = HistogramGuard::begin("tycho_core_archive_mirror_download_time");
+
+        let path = archive_object_path(archive_id);
+        let mut stream = self.inner.store.get(&path).await?.into_stream();
+
+        let (chunks_tx, mut chunks_rx) = mpsc::channel::<Bytes>(MAX_PENDING_CHUNKS);
+
+        let span = tracing::Span::current();
+        let processing_task = tokio::task::spawn_blocking(move || {
+            let _span = span.enter();
+
+            let mut output = output;
+            let mut verifier = ArchiveVerifier::default();
+            let mut zstd_decoder = ZstdDecompressStream::new(DECOMPRESS_BUFFER_SIZE)?;
+
+            // Reuse buffer for decompressed data
+            let mut decompressed_chunk = Vec::new();
+
+            while let Some(chunk) = chunks_rx.blocking_recv() {
+                decompressed_chunk.clear();
+                zstd_decoder.write(chunk.as_ref(), &mut decompressed_chunk)?;
+
+                verifier.write_verify(&decompressed_chunk)?;
+                output.write_all(&decompressed_chunk)?;
+            }
+
+            verifier.final_check()?;
+            output.flush()?;
+            Ok::<_, anyhow::Error>(output)
+        });
+
+        while let Some(chunk) = stream.next().await.transpose()? {
+            metrics::counter!("tycho_core_archive_mirror_downloaded_bytes_total")
+                .increment(chunk.len() as u64);
+
+            if chunks_tx.send(chunk).await.is_err() {
+                break;
+            }
+        }
+        drop(chunks_tx);
+
+        processing_task.await?
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MirrorArchive {
+    pub id: u32,
+    /// Compressed archive size.
+    pub size: u64,
+}
+
+/// Min interval between archive listings.
+const LISTING_TTL: Duration = Duration::from_secs(10);
+
+struct Inner {
+    store: Arc<dyn ObjectStore>,
+    listing_ttl: Duration,
+    known_archives: Mutex<KnownArchives>,
+}
+
+impl Inner {
+    /// Returns the closest archive and whether it is the last known one.
+    fn find_known_archive(&self, mc_seqno: u32) -> Option<(MirrorArchive, bool)> {
+        let known_archives = self.known_archives.lock();
+        let items = &known_archives.items;
+
+        let (&id, &size) = items.range(..=mc_seqno).next_back()?;
+        let is_last = items.range(mc_seqno.saturating_add(1)..).next().is_none();
+
+        Some((MirrorArchive { id, size }, is_last))
+    }
+
+    fn is_listing_outdated(&self) -> bool {
+        match self.known_archives.lock().refreshed_at {
+            Some(refreshed_at) => refreshed_at.elapsed() >= self.listing_ttl,
+            None => true,
+        }
+    }
+
+    async fn refresh_known_archives(&self) -> Result<()> {
+        let mut archives = BTreeMap::new();
+
+        let mut stream = self.store.list(None);
+        while let Some(meta) = stream
+            .next()
+            .await
+            .transpose()
+            .context("failed to list archives")?
+        {
+            let Some(archive_id) = meta
+                .location
+                .filename()
+                .and_then(|name| name.strip_prefix("archive_"))
+                .and_then(|id| id.parse::<u32>().ok())
+            else {
+                continue;
+            };
+
+            archives.insert(archive_id, meta.size as u64);
+        }
+
+        tracing::debug!(count = archives.len(), "refreshed archives mirror");
+
+        *self.known_archives.lock() = KnownArchives {
+            items: archives,
+            refreshed_at: Some(Instant::now()),
+        };
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct KnownArchives {
+    /// Archive id to compressed size.
+    items: BTreeMap<u32, u64>,
+    refreshed_at: Option<Instant>,
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+    use object_store::path::Path;
+    use object_store::PutPayload;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn find_archive_in_mirror() -> Result<()> {
+        let store = Arc::new(InMemory::new());
+        for id in [1, 101, 150] {
+            let data = vec![0u8; id as usize];
+            store
+                .put(&archive_object_path(id), PutPayload::from(data))
+                .await?;
+        }
+        store
+            .put(&Path::from("unrelated"), PutPayload::from_static(b"hello"))
+            .await?;
+
+        let mirror = ArchiveMirror {
+            inner: Arc::new(Inner {
+                store: store.clone(),
+                listing_ttl: Duration::ZERO,
+                known_archives: Default::default(),
+            }),
+        };
+
+        assert!(mirror.find_archive(0).await?.is_none());
+
+        let archive = mirror.find_archive(100).await?.unwrap();
+        assert_eq!((archive.id, archive.size), (1, 1));
+
+        let archive = mirror.find_archive(149).await?.unwrap();
+        assert_eq!((archive.id, archive.size), (101, 101));
+
+        // The last archive is returned for all newer blocks which can be in it
+        let archive = mirror.find_archive(249).await?.unwrap();
+        assert_eq!(archive.id, 150);
+        assert!(mirror.find_archive(250).await?.is_none());
+
+        // Newly uploaded archives are found after refresh
+        store
+            .put(&archive_object_path(250), PutPayload::from_static(b"new"))
+            .await?;
+        let archive = mirror.find_archive(260).await?.unwrap();
+        assert_eq!(archive.id, 250);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn archives_listing_is_cached() -> Result<()> {
+        let store = Arc::new(InMemory::new());
+        store
+            .put(&archive_object_path(1), PutPayload::from_static(b"old"))
+            .await?;
+
+        let mirror = ArchiveMirror {
+            inner: Arc::new(Inner {
+                store: store.clone(),
+                listing_ttl: Duration::from_secs(600),
+                known_archives: Default::default(),
+            }),
+        };
+
+        assert_eq!(mirror.find_archive(50).await?.unwrap().id, 1);
+
+        // New archives are not listed until the cached listing expires
+        store
+            .put(&archive_object_path(60), PutPayload::from_static(b"new"))
+            .await?;
+        assert_eq!(mirror.find_archive(70).await?.unwrap().id, 1);
+
+        mirror.inner.known_archives.lock().refreshed_at = None;
+        assert_eq!(mirror.find_archive(70).await?.unwrap().id, 60);
+
+        Ok(())
+    }
+}
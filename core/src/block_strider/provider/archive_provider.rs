@@ -15,8 +15,10 @@ use tycho_block_util::archive::Archive;
 use tycho_block_util::block::{BlockIdRelation, BlockStuffAug};
 use tycho_storage::{MappedFile, Storage};
 
+#[cfg(feature = "object-store")]
+use crate::block_strider::provider::ArchiveMirror;
 use crate::block_strider::provider::{BlockProvider, CheckProof, OptionalBlockStuff, ProofChecker};
-use crate::blockchain_rpc::{BlockchainRpcClient, PendingArchive, PendingArchiveResponse};
+use crate::blockchain_rpc::{BlockchainRpcClient, PendingArchiveResponse};
 use crate::overlay_client::{Neighbour, PunishReason};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +46,25 @@ impl ArchiveBlockProvider {
         client: BlockchainRpcClient,
         storage: Storage,
         config: ArchiveBlockProviderConfig,
+    ) -> Self {
+        Self::with_client(ArchiveClient::Blockchain(client), storage, config)
+    }
+
+    /// Creates a provider which downloads archives from the external mirror
+    /// instead of the peers.
+    #[cfg(feature = "object-store")]
+    pub fn with_mirror(
+        mirror: ArchiveMirror,
+        storage: Storage,
+        config: ArchiveBlockProviderConfig,
+    ) -> Self {
+        Self::with_client(ArchiveClient::Mirror(mirror), storage, config)
+    }
+
+    fn with_client(
+        client: ArchiveClient,
+        storage: Storage,
+        config: ArchiveBlockProviderConfig,
     ) -> Self {
         let proof_checker = ProofChecker::new(storage.clone());
 
@@ -152,7 +173,7 @@ impl ArchiveBlockProvider {
 struct Inner {
     storage: Storage,
 
-    client: BlockchainRpcClient,
+    client: ArchiveClient,
     proof_checker: ProofChecker,
 
     known_archives: parking_lot::Mutex<ArchivesMap>,
@@ -301,14 +322,39 @@ enum ArchiveSlot {
     Pending(ArchiveTask),
 }
 
+#[derive(Clone)]
+enum ArchiveClient {
+    Blockchain(BlockchainRpcClient),
+    #[cfg(feature = "object-store")]
+    Mirror(ArchiveMirror),
+}
+
 #[derive(Clone)]
 struct ArchiveInfo {
-    from: Neighbour,
+    from: ArchiveSource,
     archive: Arc<Archive>,
 }
 
+#[derive(Clone)]
+enum ArchiveSource {
+    Neighbour(Neighbour),
+    #[cfg(feature = "object-store")]
+    Mirror,
+}
+
+impl ArchiveSource {
+    fn punish(&self, reason: PunishReason) {
+        match self {
+            Self::Neighbour(neighbour) => neighbour.punish(reason),
+            // NOTE: Mirror is trusted, so there is no one to punish.
+            #[cfg(feature = "object-store")]
+            Self::Mirror => {}
+        }
+    }
+}
+
 struct ArchiveDownloader {
-    client: BlockchainRpcClient,
+    client: ArchiveClient,
     storage: Storage,
     memory_threshold: ByteSize,
 }
@@ -359,19 +405,34 @@ impl ArchiveDownloader {
     }
 
     async fn try_download(&self, seqno: u32) -> Result<Option<ArchiveInfo>> {
-        let response = self.client.find_archive(seqno).await?;
-        let pending = match response {
-            PendingArchiveResponse::Found(pending) => pending,
-            PendingArchiveResponse::TooNew => return Ok(None),
+        let (writer, from) = match &self.client {
+            ArchiveClient::Blockchain(client) => {
+                let response = client.find_archive(seqno).await?;
+                let pending = match response {
+                    PendingArchiveResponse::Found(pending) => pending,
+                    PendingArchiveResponse::TooNew => return Ok(None),
+                };
+
+                let neighbour = pending.neighbour.clone();
+
+                let writer = self.get_archive_writer(pending.size.get())?;
+                let writer = client.download_archive(pending, writer).await?;
+                (writer, ArchiveSource::Neighbour(neighbour))
+            }
+            #[cfg(feature = "object-store")]
+            ArchiveClient::Mirror(mirror) => {
+                let Some(found) = mirror.find_archive(seqno).await? else {
+                    return Ok(None);
+                };
+
+                let writer = self.get_archive_writer(found.size)?;
+                let writer = mirror.download_archive(found.id, writer).await?;
+                (writer, ArchiveSource::Mirror)
+            }
         };
 
-        let neighbour = pending.neighbour.clone();
-
-        let writer = self.get_archive_writer(&pending)?;
-        let writer = self.client.download_archive(pending, writer).await?;
-
         let span = tracing::Span::current();
-        tokio::task::spawn_blocking(move || {
+        let info = tokio::task::spawn_blocking(move || {
             let _span = span.enter();
 
             let bytes = writer.try_freeze()?;
@@ -379,28 +440,38 @@ impl ArchiveDownloader {
             let archive = match Archive::new(bytes) {
                 Ok(array) => array,
                 Err(e) => {
-                    neighbour.punish(PunishReason::Malicious);
+                    from.punish(PunishReason::Malicious);
                     return Err(e);
                 }
             };
 
             if let Err(e) = archive.check_mc_blocks_range() {
                 // TODO: Punish a bit less for missing mc blocks?
-                neighbour.punish(PunishReason::Malicious);
+                from.punish(PunishReason::Malicious);
                 return Err(e);
             }
 
             Ok(ArchiveInfo {
                 archive: Arc::new(archive),
-                from: neighbour,
+                from,
             })
         })
-        .await?
-        .map(Some)
+        .await??;
+
+        // NOTE: Mirror returns the latest uploaded archive for all newer
+        // seqno, so it might not contain the requested block yet.
+        #[cfg(feature = "object-store")]
+        if matches!(info.from, ArchiveSource::Mirror)
+            && !info.archive.mc_block_ids.contains_key(&seqno)
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(info))
     }
 
-    fn get_archive_writer(&self, pending: &PendingArchive) -> Result<ArchiveWriter> {
-        Ok(if pending.size.get() > self.memory_threshold.as_u64() {
+    fn get_archive_writer(&self, size: u64) -> Result<ArchiveWriter> {
+        Ok(if size > self.memory_threshold.as_u64() {
             let file = self.storage.temp_file_storage().unnamed_file().open()?;
             ArchiveWriter::File(std::io::BufWriter::new(file))
         } else {
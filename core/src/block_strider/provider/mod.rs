@@ -19,12 +19,16 @@ use tycho_storage::{MaybeExistingHandle, NewBlockMeta, Storage};
 use tycho_util::metrics::HistogramGuard;
 use tycho_util::serde_helpers;
 
+#[cfg(feature = "object-store")]
+pub use self::archive_mirror::{ArchiveMirror, MirrorArchive};
 pub use self::archive_provider::{ArchiveBlockProvider, ArchiveBlockProviderConfig};
 pub use self::blockchain_provider::{BlockchainBlockProvider, BlockchainBlockProviderConfig};
 pub use self::box_provider::BoxBlockProvider;
 use self::futures::SelectNonEmptyFut;
 pub use self::storage_provider::StorageBlockProvider;
 
+#[cfg(feature = "object-store")]
+mod archive_mirror;
 mod archive_provider;
 mod blockchain_provider;
 mod box_provider;
@@ -83,6 +87,34 @@ impl<T: BlockProvider> BlockProvider for Arc<T> {
     }
 }
 
+impl<T: BlockProvider> BlockProvider for Option<T> {
+    type GetNextBlockFut<'a> =
+        future::Either<T::GetNextBlockFut<'a>, future::Ready<OptionalBlockStuff>>;
+    type GetBlockFut<'a> = future::Either<T::GetBlockFut<'a>, future::Ready<OptionalBlockStuff>>;
+    type CleanupFut<'a> = future::Either<T::CleanupFut<'a>, future::Ready<Result<()>>>;
+
+    fn get_next_block<'a>(&'a self, prev_block_id: &'a BlockId) -> Self::GetNextBlockFut<'a> {
+        match self {
+            Some(provider) => future::Either::Left(provider.get_next_block(prev_block_id)),
+            None => future::Either::Right(future::ready(None)),
+        }
+    }
+
+    fn get_block<'a>(&'a self, block_id_relation: &'a BlockIdRelation) -> Self::GetBlockFut<'a> {
+        match self {
+            Some(provider) => future::Either::Left(provider.get_block(block_id_relation)),
+            None => future::Either::Right(future::ready(None)),
+        }
+    }
+
+    fn cleanup_until(&self, mc_seqno: u32) -> Self::CleanupFut<'_> {
+        match self {
+            Some(provider) => future::Either::Left(provider.cleanup_until(mc_seqno)),
+            None => future::Either::Right(future::ready(Ok(()))),
+        }
+    }
+}
+
 pub trait BlockProviderExt: Sized {
     fn boxed(self) -> BoxBlockProvider;
 
@@ -1,4 +1,5 @@
 use std::fs::File;
+use std::io::BufWriter;
 use std::pin::pin;
 use std::sync::Arc;
 use std::time::Duration;
@@ -14,7 +15,7 @@ use tycho_block_util::queue::QueueDiffStuff;
 use tycho_block_util::state::{MinRefMcStateTracker, ShardStateStuff};
 use tycho_storage::{
     BlockHandle, FileBuilder, KeyBlocksDirection, MaybeExistingHandle, NewBlockMeta,
-    PersistentStateKind, Storage,
+    PersistentStateKind, StateBundle, Storage,
 };
 use tycho_util::futures::JoinTask;
 use tycho_util::sync::rayon_run;
@@ -45,25 +46,15 @@ impl StarterInner {
                 let (genersis_handle, _) = self.import_zerostates(zerostates).await?;
                 *genersis_handle.id()
             }
-            ColdBootType::LatestPersistent => {
-                // Find the last known key block (or zerostate)
-                // from which we can start downloading other key blocks
-                let init_block = self.prepare_init_block(zerostates).await?;
-
-                // Ensure that all key blocks until now (with some offset) are downloaded
-                self.download_key_blocks(init_block).await?;
-
-                // Choose the latest key block with persistent state
-                let last_key_block = self.choose_key_block()?;
-
-                if last_key_block.id().seqno != 0 {
-                    // If the last suitable key block is not zerostate, we must download all blocks
-                    // with their states from shards for that
-                    self.download_start_blocks_and_states(last_key_block.id())
-                        .await?;
-                }
-
-                *last_key_block.id()
+            ColdBootType::LatestPersistent => self.boot_from_persistent(zerostates, None).await?,
+            ColdBootType::StateBundle(path) => {
+                let bundle = StateBundle::open(&path).context("failed to open state bundle")?;
+                tracing::info!(
+                    path = %path.display(),
+                    mc_block_id = %bundle.mc_block_id(),
+                    "using local state bundle"
+                );
+                self.boot_from_persistent(zerostates, Some(&bundle)).await?
             }
         };
 
@@ -75,6 +66,42 @@ impl StarterInner {
         Ok(last_mc_block_id)
     }
 
+    /// Returns the key block from which the node will start syncing.
+    async fn boot_from_persistent<P>(
+        &self,
+        zerostates: Option<P>,
+        bundle: Option<&StateBundle>,
+    ) -> Result<BlockId>
+    where
+        P: ZerostateProvider,
+    {
+        // Find the last known key block (or zerostate)
+        // from which we can start downloading other key blocks
+        let init_block = self.prepare_init_block(zerostates).await?;
+
+        // Ensure that all key blocks until now (with some offset) are downloaded.
+        // NOTE: The bundle key block must be downloaded even if it is too new.
+        let until_seqno = bundle.map(|bundle| bundle.mc_block_id().seqno);
+        self.download_key_blocks(init_block, until_seqno.unwrap_or_default())
+            .await?;
+
+        let last_key_block = match bundle {
+            // Use the key block of the bundle
+            Some(bundle) => self.find_bundle_key_block(bundle)?,
+            // Choose the latest key block with persistent state
+            None => self.choose_key_block()?,
+        };
+
+        if last_key_block.id().seqno != 0 {
+            // If the last suitable key block is not zerostate, we must download all blocks
+            // with their states from shards for that
+            self.download_start_blocks_and_states(last_key_block.id(), bundle)
+                .await?;
+        }
+
+        Ok(*last_key_block.id())
+    }
+
     // === Sync steps ===
 
     /// Prepare the initial block to start syncing.
@@ -128,7 +155,14 @@ impl StarterInner {
     }
 
     /// Download all key blocks since the initial block.
-    async fn download_key_blocks(&self, mut prev_key_block: InitBlock) -> Result<()> {
+    ///
+    /// Key blocks with seqno not greater than `until_seqno` are downloaded
+    /// regardless of the boot offset.
+    async fn download_key_blocks(
+        &self,
+        mut prev_key_block: InitBlock,
+        until_seqno: u32,
+    ) -> Result<()> {
         const BLOCKS_PER_BATCH: u32 = 10;
         const PARALLEL_REQUESTS: usize = 10;
 
@@ -204,7 +238,10 @@ impl StarterInner {
             for (index, proof) in proofs.into_iter().enumerate() {
                 // Verify block proof
                 match prev_key_block.check_next_proof(&proof.data) {
-                    Ok(meta) if satisfies_offset(meta.gen_utime, now_utime) => {
+                    Ok(meta)
+                        if proof.data.id().seqno <= until_seqno
+                            || satisfies_offset(meta.gen_utime, now_utime) =>
+                    {
                         // Save block proof
                         let handle = self
                             .storage
@@ -318,10 +355,32 @@ impl StarterInner {
         anyhow::bail!("no suitable key block found")
     }
 
-    async fn download_start_blocks_and_states(&self, mc_block_id: &BlockId) -> Result<()> {
+    /// Check that the bundle key block is a known persistent key block.
+    fn find_bundle_key_block(&self, bundle: &StateBundle) -> Result<BlockHandle> {
+        let block_id = bundle.mc_block_id();
+
+        let handle = self
+            .storage
+            .block_handle_storage()
+            .load_handle(block_id)
+            .with_context(|| format!("bundle key block not found: {block_id}"))?;
+        anyhow::ensure!(
+            handle.is_key_block(),
+            "bundle block is not a key block: {block_id}"
+        );
+
+        tracing::info!(%block_id, "found bundle key block handle");
+        Ok(handle)
+    }
+
+    async fn download_start_blocks_and_states(
+        &self,
+        mc_block_id: &BlockId,
+        bundle: Option<&StateBundle>,
+    ) -> Result<()> {
         // Download and save masterchain block and state
         let (_, init_mc_block) = self
-            .download_block_with_states(mc_block_id, mc_block_id)
+            .download_block_with_states(mc_block_id, mc_block_id, bundle)
             .await?;
 
         tracing::info!(
@@ -332,7 +391,7 @@ impl StarterInner {
         // Download and save blocks and states from other shards
         for (_, block_id) in init_mc_block.shard_blocks()? {
             let (handle, _) = self
-                .download_block_with_states(mc_block_id, &block_id)
+                .download_block_with_states(mc_block_id, &block_id, bundle)
                 .await?;
 
             self.storage
@@ -460,12 +519,14 @@ impl StarterInner {
         tracing::info!(zerostate_id = %zerostate_id, "download zerostates");
 
         let (handle, state) = self
-            .download_shard_state(&zerostate_id, &zerostate_id)
+            .download_shard_state(&zerostate_id, &zerostate_id, None)
             .await?;
 
         for item in state.shards()?.latest_blocks() {
             let block_id = item?;
-            let _state = self.download_shard_state(&zerostate_id, &block_id).await?;
+            let _state = self
+                .download_shard_state(&zerostate_id, &block_id, None)
+                .await?;
         }
 
         Ok((handle, state))
@@ -475,6 +536,7 @@ impl StarterInner {
         &self,
         mc_block_id: &BlockId,
         block_id: &BlockId,
+        bundle: Option<&StateBundle>,
     ) -> Result<(BlockHandle, BlockStuff)> {
         // First download the block itself, with all its parts (proof and queue diff).
         let (handle, block) = self.download_block_data(mc_block_id, block_id).await?;
@@ -486,7 +548,9 @@ impl StarterInner {
         {
             let state_update = block.as_ref().load_state_update()?;
 
-            let (_, shard_state) = self.download_shard_state(mc_block_id, block_id).await?;
+            let (_, shard_state) = self
+                .download_shard_state(mc_block_id, block_id, bundle)
+                .await?;
             let state_hash = *shard_state.root_cell().repr_hash();
             anyhow::ensure!(
                 state_update.new_hash == state_hash,
@@ -499,7 +563,8 @@ impl StarterInner {
         //       where there were no blocks in the shard.
         if block_id.seqno != 0 {
             let top_update = &block.as_ref().out_msg_queue_updates;
-            self.download_queue_state(&handle, top_update).await?;
+            self.download_queue_state(&handle, top_update, bundle)
+                .await?;
         }
 
         Ok((handle, block))
@@ -620,6 +685,7 @@ impl StarterInner {
         &self,
         mc_block_id: &BlockId,
         block_id: &BlockId,
+        bundle: Option<&StateBundle>,
     ) -> Result<(BlockHandle, ShardStateStuff)> {
         use tycho_storage::FileBuilder;
 
@@ -699,6 +765,12 @@ impl StarterInner {
             }
         }
 
+        // Use the state from the local bundle if possible
+        if let Some(bundle) = bundle {
+            self.unpack_bundle_state(bundle, block_id, PersistentStateKind::Shard, &state_file)
+                .await?;
+        }
+
         // Try download the state
         for attempt in 0..MAX_PERSISTENT_STATE_RETRIES {
             let file = match self
@@ -748,6 +820,7 @@ impl StarterInner {
         &self,
         block_handle: &BlockHandle,
         top_update: &OutMsgQueueUpdates,
+        bundle: Option<&StateBundle>,
     ) -> Result<()> {
         let block_id = block_handle.id();
 
@@ -767,6 +840,12 @@ impl StarterInner {
             }
         };
 
+        // Use the state from the local bundle if possible
+        if let Some(bundle) = bundle {
+            self.unpack_bundle_state(bundle, block_id, PersistentStateKind::Queue, &state_file)
+                .await?;
+        }
+
         for attempt in 0..MAX_PERSISTENT_STATE_RETRIES {
             let file = match self
                 .download_persistent_state_file(block_id, PersistentStateKind::Queue, &state_file)
@@ -792,6 +871,43 @@ impl StarterInner {
         anyhow::bail!("ran out of attempts")
     }
 
+    /// Unpacks the state from the bundle into the `state_file`,
+    /// so it will be used instead of downloading.
+    async fn unpack_bundle_state(
+        &self,
+        bundle: &StateBundle,
+        block_id: &BlockId,
+        kind: PersistentStateKind,
+        state_file: &FileBuilder,
+    ) -> Result<()> {
+        if state_file.exists() || !bundle.contains(block_id, kind) {
+            return Ok(());
+        }
+
+        let bundle = bundle.clone();
+        let block_id = *block_id;
+        let state_file = state_file.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut temp_file = state_file.with_extension("temp");
+            let temp_file_path = temp_file.path().to_owned();
+            scopeguard::defer! {
+                std::fs::remove_file(&temp_file_path).ok();
+            };
+
+            let output = temp_file.write(true).create(true).truncate(true).open()?;
+            bundle
+                .unpack(&block_id, kind, BufWriter::new(output))
+                .with_context(|| format!("failed to unpack {kind:?} state from bundle"))?;
+
+            std::fs::rename(temp_file.path(), state_file.path())?;
+            Ok::<_, anyhow::Error>(())
+        })
+        .await??;
+
+        tracing::info!(?kind, "unpacked state from local bundle");
+        Ok(())
+    }
+
     async fn download_persistent_state_file(
         &self,
         block_id: &BlockId,
@@ -72,6 +72,10 @@ impl Starter {
 pub enum ColdBootType {
     Genesis,
     LatestPersistent,
+    /// Use persistent states from the local bundle directory
+    /// instead of downloading them. Key blocks and block data
+    /// are still downloaded.
+    StateBundle(PathBuf),
 }
 
 struct StarterInner {
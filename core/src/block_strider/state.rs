@@ -1,8 +1,9 @@
+use std::future::Future;
 use std::sync::Mutex;
 
 use everscale_types::models::BlockId;
 use tycho_block_util::block::ShardHeights;
-use tycho_storage::Storage;
+use tycho_storage::{CheckpointPauseGuard, Storage};
 
 pub trait BlockStriderState: Send + Sync + 'static {
     fn load_last_mc_block_id(&self) -> BlockId;
@@ -11,6 +12,12 @@ pub trait BlockStriderState: Send + Sync + 'static {
 
     fn commit_master(&self, block_id: &BlockId, shard_heights: &ShardHeights);
     fn commit_shard(&self, block_id: &BlockId);
+
+    /// Returns a guard which is held while the masterchain block
+    /// and its shard blocks are processed.
+    fn begin_master(&self) -> impl Future<Output = Option<CheckpointPauseGuard>> + Send {
+        futures_util::future::ready(None)
+    }
 }
 
 pub struct PersistentBlockStriderState {
@@ -50,6 +57,11 @@ impl BlockStriderState for PersistentBlockStriderState {
     fn commit_shard(&self, block_id: &BlockId) {
         assert!(!block_id.is_masterchain());
     }
+
+    async fn begin_master(&self) -> Option<CheckpointPauseGuard> {
+        // NOTE: Checkpoints must not contain partially processed blocks
+        Some(self.storage.pause_checkpoints().await)
+    }
 }
 
 pub struct TempBlockStriderState {
@@ -0,0 +1,229 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use object_store::{ObjectStore, WriteMultipart};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+use tycho_storage::Storage;
+use tycho_util::metrics::HistogramGuard;
+use tycho_util::serde_helpers;
+
+use crate::block_strider::{ArchiveSubscriber, ArchiveSubscriberContext};
+use crate::object_storage::{archive_object_path, ObjectStorageConfig};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveUploaderConfig {
+    /// Storage to upload archives to.
+    pub storage: ObjectStorageConfig,
+
+    /// Interval between attempts to upload an archive.
+    ///
+    /// Default: 10s.
+    #[serde(default = "default_retry_interval", with = "serde_helpers::humantime")]
+    pub retry_interval: Duration,
+
+    /// Max number of archive parts which are uploaded concurrently.
+    ///
+    /// Default: 4.
+    #[serde(default = "default_max_concurrent_parts")]
+    pub max_concurrent_parts: usize,
+}
+
+/// Uploads committed archives to the external object storage.
+///
+/// Archives are uploaded in the background strictly in order, so the
+/// block strider is not blocked by the object storage availability.
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct ArchiveUploader {
+    inner: Arc<Inner>,
+}
+
+impl ArchiveUploader {
+    pub fn new(storage: Storage, config: ArchiveUploaderConfig) -> Result<Self> {
+        let store = config
+            .storage
+            .build()
+            .context("failed to create object storage client")?;
+
+        let (archive_ids_tx, archive_ids_rx) = mpsc::unbounded_channel();
+
+        let upload_task = tokio::spawn(Self::upload_archives(
+            storage,
+            store,
+            config,
+            archive_ids_rx,
+        ));
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                archive_ids_tx,
+                upload_handle: upload_task.abort_handle(),
+            }),
+        })
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn upload_archives(
+        storage: Storage,
+        store: Arc<dyn ObjectStore>,
+        config: ArchiveUploaderConfig,
+        mut archive_ids_rx: mpsc::UnboundedReceiver<u32>,
+    ) {
+        tracing::info!("started");
+        scopeguard::defer! {
+            tracing::info!("finished");
+        }
+
+        let block_storage = storage.block_storage();
+
+        let mut last_uploaded = match block_storage.load_last_uploaded_archive_id() {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::error!("failed to load the last uploaded archive id: {e:?}");
+                return;
+            }
+        };
+        if let Some(id) = last_uploaded {
+            metrics::gauge!("tycho_core_last_uploaded_archive_id").set(id as f64);
+        }
+
+        // NOTE: Archives which were committed while the uploader was not running
+        // are uploaded first. The last archive might not be committed yet, it will
+        // be received from the channel after the commit.
+        let mut pending = block_storage.list_archive_ids();
+        pending.retain(|id| !matches!(last_uploaded, Some(last) if *id <= last));
+
+        let mut pending = pending.into_iter();
+        loop {
+            let archive_id = match pending.next() {
+                Some(id) => id,
+                None => match archive_ids_rx.recv().await {
+                    Some(id) => id,
+                    None => break,
+                },
+            };
+
+            if matches!(last_uploaded, Some(last) if archive_id <= last) {
+                continue;
+            }
+
+            let uploaded = loop {
+                match Self::upload_archive(&storage, store.as_ref(), &config, archive_id).await {
+                    Ok(uploaded) => break uploaded,
+                    Err(e) => {
+                        metrics::counter!("tycho_core_archive_upload_errors_total").increment(1);
+                        tracing::warn!(archive_id, "failed to upload archive: {e:?}");
+                        tokio::time::sleep(config.retry_interval).await;
+                    }
+                }
+            };
+
+            if !uploaded {
+                tracing::debug!(archive_id, "skipping uncommitted archive");
+                continue;
+            }
+
+            if let Err(e) = block_storage.store_last_uploaded_archive_id(archive_id) {
+                tracing::error!(
+                    archive_id,
+                    "failed to store the last uploaded archive id: {e:?}"
+                );
+                continue;
+            }
+            last_uploaded = Some(archive_id);
+            metrics::gauge!("tycho_core_last_uploaded_archive_id").set(archive_id as f64);
+        }
+    }
+
+    /// Returns `false` if the archive is not committed yet.
+    async fn upload_archive(
+        storage: &Storage,
+        store: &dyn ObjectStore,
+        config: &ArchiveUploaderConfig,
+        archive_id: u32,
+    ) -> Result<bool> {
+        let block_storage = storage.block_storage();
+
+        let Some(size) = block_storage.get_archive_size(archive_id)? else {
+            return Ok(false);
+        };
+
+        let _histogram = HistogramGuard::begin("tycho_core_archive_upload_time");
+        tracing::info!(archive_id, size, "uploading archive");
+
+        let path = archive_object_path(archive_id);
+        let mut upload = WriteMultipart::new(store.put_multipart(&path).await?);
+
+        let chunk_size = block_storage.archive_chunk_size().get() as u64;
+        let res = async {
+            let mut offset = 0;
+            while offset < size as u64 {
+                upload
+                    .wait_for_capacity(config.max_concurrent_parts)
+                    .await?;
+
+                let chunk = block_storage.get_archive_chunk(archive_id, offset).await?;
+                upload.write(chunk.as_ref());
+                offset += chunk_size;
+            }
+
+            // Wait for all parts in flight to handle their errors here
+            upload.wait_for_capacity(0).await?;
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+
+        // NOTE: Uploaded parts are kept by the store until the upload is aborted
+        if let Err(e) = res {
+            if let Err(e) = upload.abort().await {
+                tracing::warn!(archive_id, "failed to abort archive upload: {e:?}");
+            }
+            return Err(e);
+        }
+
+        // NOTE: `finish` also aborts the upload if it fails to complete
+        upload.finish().await?;
+
+        metrics::counter!("tycho_core_archive_uploaded_bytes_total").increment(size as u64);
+        tracing::info!(archive_id, "uploaded archive");
+        Ok(true)
+    }
+}
+
+impl ArchiveSubscriber for ArchiveUploader {
+    type HandleArchiveFut<'a> = futures_util::future::Ready<Result<()>>;
+
+    fn handle_archive<'a>(
+        &'a self,
+        cx: &'a ArchiveSubscriberContext<'_>,
+    ) -> Self::HandleArchiveFut<'a> {
+        let res = self
+            .inner
+            .archive_ids_tx
+            .send(cx.archive_id)
+            .map_err(|_e| anyhow::anyhow!("archive uploader is not running"));
+        futures_util::future::ready(res)
+    }
+}
+
+struct Inner {
+    archive_ids_tx: mpsc::UnboundedSender<u32>,
+    upload_handle: AbortHandle,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        self.upload_handle.abort();
+    }
+}
+
+fn default_retry_interval() -> Duration {
+    Duration::from_secs(10)
+}
+
+fn default_max_concurrent_parts() -> usize {
+    4
+}
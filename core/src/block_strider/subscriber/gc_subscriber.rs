@@ -119,6 +119,10 @@ impl GcSubscriber {
 
     #[tracing::instrument(skip_all)]
     async fn archives_gc(mut tick_rx: TickRx, mut manual_rx: ManualTriggerRx, storage: Storage) {
+        if storage.config().archive_node {
+            tracing::info!("manager disabled in archive node mode");
+            return;
+        }
         let Some(config) = storage.config().archives_gc else {
             tracing::warn!("manager disabled");
             return;
@@ -200,6 +204,10 @@ impl GcSubscriber {
 
     #[tracing::instrument(skip_all)]
     async fn blocks_gc(mut tick_rx: TickRx, mut manual_rx: ManualTriggerRx, storage: Storage) {
+        if storage.config().archive_node {
+            tracing::info!("manager disabled in archive node mode");
+            return;
+        }
         let Some(config) = storage.config().blocks_gc else {
             tracing::warn!("manager disabled");
             return;
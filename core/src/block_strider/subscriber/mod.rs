@@ -9,11 +9,15 @@ use tycho_block_util::block::BlockStuff;
 use tycho_block_util::state::ShardStateStuff;
 use tycho_storage::{BlockHandle, Storage};
 
+#[cfg(feature = "object-store")]
+pub use self::archive_uploader::{ArchiveUploader, ArchiveUploaderConfig};
 pub use self::futures::{OptionHandleFut, OptionPrepareFut};
 pub use self::gc_subscriber::{GcSubscriber, ManualGcTrigger};
 pub use self::metrics_subscriber::MetricsSubscriber;
 pub use self::ps_subscriber::PsSubscriber;
 
+#[cfg(feature = "object-store")]
+mod archive_uploader;
 mod futures;
 mod gc_subscriber;
 mod metrics_subscriber;
@@ -1,5 +1,7 @@
 pub mod block_strider;
 pub mod blockchain_rpc;
 pub mod global_config;
+#[cfg(feature = "object-store")]
+pub mod object_storage;
 pub mod overlay_client;
 pub mod proto;
@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use object_store::aws::AmazonS3Builder;
+use object_store::http::HttpBuilder;
+use object_store::path::Path;
+use object_store::prefix::PrefixStore;
+use object_store::{ClientOptions, ObjectStore};
+use serde::{Deserialize, Serialize};
+
+/// External object storage for archives.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ObjectStorageConfig {
+    /// S3-compatible bucket (AWS, MinIO, etc.).
+    S3(S3BucketConfig),
+    /// Read-only HTTP mirror.
+    ///
+    /// NOTE: Listing requires WebDAV (`PROPFIND`) support on the server.
+    Http(HttpMirrorConfig),
+}
+
+impl ObjectStorageConfig {
+    pub fn build(&self) -> Result<Arc<dyn ObjectStore>> {
+        match self {
+            Self::S3(config) => config.build(),
+            Self::Http(config) => config.build(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3BucketConfig {
+    /// Bucket name.
+    pub bucket: String,
+
+    /// Custom endpoint URL (e.g. `http://127.0.0.1:9000` for MinIO).
+    ///
+    /// Default: AWS endpoint for the region.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+
+    /// Default: `us-east-1`.
+    #[serde(default = "default_region")]
+    pub region: String,
+
+    /// Prefix for all object keys.
+    ///
+    /// Default: empty.
+    #[serde(default)]
+    pub prefix: String,
+
+    /// Default: taken from the `AWS_ACCESS_KEY_ID` env.
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+
+    /// Default: taken from the `AWS_SECRET_ACCESS_KEY` env.
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+
+    /// Allow plain HTTP endpoints.
+    ///
+    /// Default: false.
+    #[serde(default)]
+    pub allow_http: bool,
+
+    /// Use virtual-hosted-style requests instead of path-style ones.
+    ///
+    /// Default: false.
+    #[serde(default)]
+    pub virtual_hosted_style_request: bool,
+}
+
+impl S3BucketConfig {
+    pub fn build(&self) -> Result<Arc<dyn ObjectStore>> {
+        let mut builder = AmazonS3Builder::from_env()
+            .with_bucket_name(&self.bucket)
+            .with_region(&self.region)
+            .with_allow_http(self.allow_http)
+            .with_virtual_hosted_style_request(self.virtual_hosted_style_request);
+
+        if let Some(endpoint) = &self.endpoint {
+            builder = builder.with_endpoint(endpoint);
+        }
+        if let Some(access_key_id) = &self.access_key_id {
+            builder = builder.with_access_key_id(access_key_id);
+        }
+        if let Some(secret_access_key) = &self.secret_access_key {
+            builder = builder.with_secret_access_key(secret_access_key);
+        }
+
+        let store = builder.build()?;
+        Ok(if self.prefix.is_empty() {
+            Arc::new(store)
+        } else {
+            Arc::new(PrefixStore::new(store, self.prefix.as_str()))
+        })
+    }
+}
+
+/// Read-only HTTP mirror config.
+///
+/// The server must support WebDAV `PROPFIND` requests to list archives
+/// (e.g. nginx with `nginx-dav-ext-module`).
+/// A plain static file server can't be used as a mirror.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpMirrorConfig {
+    /// Base URL of the mirror (e.g. `https://archives.example.com/mainnet/`).
+    pub url: String,
+}
+
+impl HttpMirrorConfig {
+    pub fn build(&self) -> Result<Arc<dyn ObjectStore>> {
+        let allow_http = self.url.starts_with("http://");
+
+        let store = HttpBuilder::new()
+            .with_url(&self.url)
+            .with_client_options(ClientOptions::new().with_allow_http(allow_http))
+            .build()?;
+
+        Ok(Arc::new(store))
+    }
+}
+
+/// Returns the object path of the archive with the specified id.
+pub fn archive_object_path(archive_id: u32) -> Path {
+    Path::from(format!("archive_{archive_id}"))
+}
+
+fn default_region() -> String {
+    "us-east-1".to_owned()
+}
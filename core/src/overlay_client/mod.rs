@@ -4,7 +4,9 @@ use std::time::{Duration, Instant};
 use anyhow::Result;
 use bytes::Bytes;
 use tokio::task::AbortHandle;
-use tycho_network::{ConnectionError, Network, PublicOverlay, Request, UnknownPeerError};
+use tycho_network::{
+    ConnectionError, Network, PublicOverlay, QueryRejectedError, Request, UnknownPeerError,
+};
 
 pub use self::config::{NeighborsConfig, PublicOverlayClientConfig, ValidatorsConfig};
 pub use self::neighbour::{Neighbour, NeighbourStats, PunishReason};
@@ -32,15 +34,18 @@ impl PublicOverlayClient {
         let ttl = overlay.entry_ttl_sec();
 
         let neighbors_config = &config.neighbors;
+        let reputation = network.peer_reputation();
 
         let entries = overlay
             .read_entries()
             .choose_multiple(&mut rand::thread_rng(), neighbors_config.keep)
+            .filter(|entry_data| !reputation.is_banned(&entry_data.entry.peer_id))
             .map(|entry_data| {
-                Neighbour::new(
+                Neighbour::with_reputation(
                     entry_data.entry.peer_id,
                     entry_data.expires_at(ttl),
                     &neighbors_config.default_roundtrip,
+                    Some(reputation.clone()),
                 )
             })
             .collect::<Vec<_>>();
@@ -242,6 +247,7 @@ impl Inner {
         let ttl = self.overlay.entry_ttl_sec();
         let max_neighbours = self.config.neighbors.keep;
         let default_roundtrip = self.config.neighbors.default_roundtrip;
+        let reputation = self.network.peer_reputation();
 
         let mut overlay_peers_added = self.overlay.entires_added().notified();
         let mut overlay_peer_count = self.overlay.read_entries().len();
@@ -267,7 +273,15 @@ impl Inner {
                 self.overlay
                     .read_entries()
                     .choose_multiple(&mut rand::thread_rng(), neighbours_to_get)
-                    .map(|x| Neighbour::new(x.entry.peer_id, x.expires_at(ttl), &default_roundtrip))
+                    .filter(|x| !reputation.is_banned(&x.entry.peer_id))
+                    .map(|x| {
+                        Neighbour::with_reputation(
+                            x.entry.peer_id,
+                            x.expires_at(ttl),
+                            &default_roundtrip,
+                            Some(reputation.clone()),
+                        )
+                    })
                     .collect::<Vec<_>>()
             };
             self.neighbours.update(neighbours);
@@ -514,6 +528,9 @@ fn apply_network_error(error: &anyhow::Error, neighbour: &Neighbour) {
     let Some(error) = (*error).downcast_ref() else {
         if let Some(UnknownPeerError { .. }) = (*error).downcast_ref() {
             neighbour.punish(PunishReason::Malicious);
+        } else if (*error).downcast_ref::<QueryRejectedError>().is_some() {
+            // NOTE: Peer is overloaded, so prefer other neighbours for a while
+            neighbour.punish(PunishReason::Dumb);
         }
 
         // TODO: Handle other errors as well
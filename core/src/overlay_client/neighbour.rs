@@ -2,7 +2,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use parking_lot::RwLock;
-use tycho_network::PeerId;
+use tycho_network::{PeerId, PeerPenalty, PeerReputation};
 use tycho_util::time::now_sec;
 
 #[derive(Clone)]
@@ -13,11 +13,23 @@ pub struct Neighbour {
 
 impl Neighbour {
     pub fn new(peer_id: PeerId, expires_at: u32, default_roundtrip: &Duration) -> Self {
+        Self::with_reputation(peer_id, expires_at, default_roundtrip, None)
+    }
+
+    /// Creates a neighbour which also reports punishments
+    /// to the shared peer reputation.
+    pub fn with_reputation(
+        peer_id: PeerId,
+        expires_at: u32,
+        default_roundtrip: &Duration,
+        reputation: Option<PeerReputation>,
+    ) -> Self {
         Self {
             inner: Arc::new(Inner {
                 peer_id,
                 expires_at,
                 stats: RwLock::new(TrackedStats::new(truncate_time(default_roundtrip))),
+                reputation,
             }),
         }
     }
@@ -72,6 +84,10 @@ impl Neighbour {
 
     pub fn punish(&self, reason: PunishReason) {
         self.inner.stats.write().punish(reason);
+
+        if let (Some(reputation), Some(penalty)) = (&self.inner.reputation, reason.as_penalty()) {
+            reputation.penalize(&self.inner.peer_id, penalty);
+        }
     }
 }
 
@@ -90,6 +106,15 @@ impl PunishReason {
             Self::Malicious => 128,
         }
     }
+
+    /// Penalty for the shared peer reputation.
+    fn as_penalty(self) -> Option<PeerPenalty> {
+        match self {
+            Self::Dumb => None,
+            Self::Slow => Some(PeerPenalty::Unresponsive),
+            Self::Malicious => Some(PeerPenalty::Malicious),
+        }
+    }
 }
 
 /// Neighbour request statistics.
@@ -112,6 +137,7 @@ struct Inner {
     peer_id: PeerId,
     expires_at: u32,
     stats: parking_lot::RwLock<TrackedStats>,
+    reputation: Option<PeerReputation>,
 }
 
 struct TrackedStats {
@@ -235,6 +235,9 @@ async fn archives() -> Result<()> {
         states_gc: None,
         blocks_gc: None,
         blocks_cache: Default::default(),
+        archive_node: false,
+        cold_storage: None,
+        compaction_window: None,
     };
 
     let zerostate_data = utils::read_file("zerostate.boc")?;
@@ -343,6 +346,9 @@ async fn heavy_archives() -> Result<()> {
         states_gc: None,
         blocks_gc: None,
         blocks_cache: Default::default(),
+        archive_node: false,
+        cold_storage: None,
+        compaction_window: None,
     };
 
     let zerostate_path = integration_test_path.join("zerostate.boc");
@@ -1,4 +1,5 @@
 use std::collections::hash_map;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -8,7 +9,7 @@ use tokio::sync::{broadcast, Semaphore};
 use tokio::task::JoinHandle;
 use tycho_util::time::{now_sec, shifted_interval};
 
-use crate::dht::{random_key_at_distance, DhtInner, DhtQueryMode, Query};
+use crate::dht::{peer_store, random_key_at_distance, DhtInner, DhtQueryMode, Query};
 use crate::network::{Network, WeakNetwork};
 use crate::proto::dht::{PeerValueKeyName, ValueRef};
 use crate::types::PeerInfo;
@@ -19,6 +20,7 @@ impl DhtInner {
             RefreshLocalPeerInfo,
             AnnounceLocalPeerInfo,
             RefreshRoutingTable,
+            SavePeers,
             AddPeer(Arc<PeerInfo>),
         }
 
@@ -33,18 +35,32 @@ impl DhtInner {
             self.config.routing_table_refresh_period_max_jitter,
         );
 
+        let peer_store_path = self.config.peer_store_path.clone();
+        let mut save_peers_interval = tokio::time::interval_at(
+            tokio::time::Instant::now() + self.config.peer_store_save_period,
+            self.config.peer_store_save_period,
+        );
+
         let mut announced_peers = self.announced_peers.subscribe();
 
         let this = Arc::downgrade(self);
         tokio::spawn(async move {
             tracing::debug!("background DHT loop started");
 
+            if let Some(path) = &peer_store_path {
+                let (Some(this), Some(network)) = (this.upgrade(), network.upgrade()) else {
+                    return;
+                };
+                this.load_saved_peers(&network, path.clone()).await;
+            }
+
             let mut prev_refresh_routing_table_fut = None::<JoinHandle<()>>;
             loop {
                 let action = tokio::select! {
                     _ = refresh_peer_info_interval.tick() => Action::RefreshLocalPeerInfo,
                     _ = announce_peer_info_interval.tick() => Action::AnnounceLocalPeerInfo,
                     _ = refresh_routing_table_interval.tick() => Action::RefreshRoutingTable,
+                    _ = save_peers_interval.tick(), if peer_store_path.is_some() => Action::SavePeers,
                     peer = announced_peers.recv() => match peer {
                         Ok(peer) => Action::AddPeer(peer),
                         Err(broadcast::error::RecvError::Closed) => return,
@@ -84,6 +100,11 @@ impl DhtInner {
                             this.refresh_routing_table(&network).await;
                         }));
                     }
+                    Action::SavePeers => {
+                        if let Some(path) = &peer_store_path {
+                            this.save_peers(path.clone()).await;
+                        }
+                    }
                     Action::AddPeer(peer_info) => {
                         let peer_id = peer_info.id;
                         let mut signature_checked = false;
@@ -108,6 +129,49 @@ impl DhtInner {
         });
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(local_id = %self.local_id))]
+    async fn load_saved_peers(&self, network: &Network, path: PathBuf) {
+        if !path.exists() {
+            return;
+        }
+
+        let peers = tokio::task::spawn_blocking(move || peer_store::load_peers(&path, now_sec()))
+            .await
+            .unwrap();
+
+        match peers {
+            Ok(peers) => {
+                let mut count = 0usize;
+                for peer_info in peers {
+                    count += self.add_peer_info(network, peer_info) as usize;
+                }
+                tracing::info!(count, "loaded saved DHT peers");
+            }
+            Err(e) => tracing::warn!("failed to load saved DHT peers: {e:?}"),
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(local_id = %self.local_id))]
+    async fn save_peers(&self, path: PathBuf) {
+        let peers = self.routing_table.lock().unwrap().peers();
+
+        // NOTE: Keep the previous peers when the node is isolated
+        if peers.is_empty() {
+            return;
+        }
+
+        let count = peers.len();
+        let res =
+            tokio::task::spawn_blocking(move || peer_store::save_peers(&path, peers, now_sec()))
+                .await
+                .unwrap();
+
+        match res {
+            Ok(()) => tracing::debug!(count, "saved DHT peers"),
+            Err(e) => tracing::warn!("failed to save DHT peers: {e:?}"),
+        }
+    }
+
     fn refresh_local_peer_info(&self, network: &Network) {
         let peer_info = self.make_local_peer_info(network, now_sec());
         *self.local_peer_info.lock().unwrap() = Some(peer_info);
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
 use bytesize::ByteSize;
@@ -70,6 +71,19 @@ pub struct DhtConfig {
     ///
     /// Default: 10.
     pub announced_peers_channel_capacity: usize,
+
+    /// Path to the file where peers of the routing table are saved.
+    /// Saved peers are loaded on startup (if they are still valid)
+    /// in addition to the bootstrap peers.
+    ///
+    /// Default: `None` (peers are not saved).
+    pub peer_store_path: Option<PathBuf>,
+
+    /// A period of saving peers of the routing table.
+    ///
+    /// Default: 5 minutes.
+    #[serde(with = "serde_helpers::humantime")]
+    pub peer_store_save_period: Duration,
 }
 
 impl Default for DhtConfig {
@@ -86,6 +100,8 @@ impl Default for DhtConfig {
             routing_table_refresh_period: Duration::from_secs(600),
             routing_table_refresh_period_max_jitter: Duration::from_secs(60),
             announced_peers_channel_capacity: 10,
+            peer_store_path: None,
+            peer_store_save_period: Duration::from_secs(300),
         }
     }
 }
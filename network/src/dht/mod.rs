@@ -28,6 +28,7 @@ use crate::util::{NetworkExt, Routable};
 mod background_tasks;
 mod config;
 mod peer_resolver;
+mod peer_store;
 mod query;
 mod routing;
 mod storage;
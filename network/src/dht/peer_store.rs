@@ -0,0 +1,82 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use crate::proto::dht::PeerStore;
+use crate::types::PeerInfo;
+
+/// Loads saved peers and returns only the valid ones.
+///
+/// NOTE: Might be expensive since it verifies signatures of all peers.
+pub(crate) fn load_peers(path: &Path, now: u32) -> Result<Vec<Arc<PeerInfo>>> {
+    let data = std::fs::read(path)
+        .with_context(|| format!("failed to read peer store {}", path.display()))?;
+    let store = tl_proto::deserialize::<PeerStore>(&data).context("invalid peer store")?;
+
+    let mut peers = store.nodes;
+    peers.retain(|peer| peer.verify(now));
+    Ok(peers)
+}
+
+/// Atomically replaces the saved peers.
+pub(crate) fn save_peers(path: &Path, nodes: Vec<Arc<PeerInfo>>, now: u32) -> Result<()> {
+    let data = tl_proto::serialize(PeerStore {
+        saved_at: now,
+        nodes,
+    });
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let temp_path = path.with_extension("temp");
+    std::fs::write(&temp_path, data)
+        .with_context(|| format!("failed to write peer store {}", temp_path.display()))?;
+    std::fs::rename(&temp_path, path)
+        .with_context(|| format!("failed to replace peer store {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use everscale_crypto::ed25519;
+
+    use super::*;
+    use crate::types::{Address, PeerId};
+
+    fn make_signed_peer_info(now: u32, ttl: u32) -> Arc<PeerInfo> {
+        let keypair = ed25519::KeyPair::generate(&mut rand::thread_rng());
+        let mut peer_info = PeerInfo {
+            id: PeerId::from(keypair.public_key),
+            address_list: vec![Address::from(SocketAddr::from(([127, 0, 0, 1], 30000)))]
+                .into_boxed_slice(),
+            created_at: now,
+            expires_at: now + ttl,
+            signature: Box::new([0; 64]),
+        };
+        *peer_info.signature = keypair.sign(&peer_info);
+        Arc::new(peer_info)
+    }
+
+    #[test]
+    fn peer_store_keeps_only_valid_peers() -> Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let path = tmp_dir.path().join("peers.bin");
+
+        let now = 1000;
+        let valid = make_signed_peer_info(now, 3600);
+        let expired = make_signed_peer_info(now - 100, 10);
+        let mut invalid = (*make_signed_peer_info(now, 3600)).clone();
+        invalid.signature = Box::new([0; 64]);
+
+        save_peers(&path, vec![valid.clone(), expired, Arc::new(invalid)], now)?;
+
+        let peers = load_peers(&path, now)?;
+        assert_eq!(peers, vec![valid]);
+
+        Ok(())
+    }
+}
@@ -58,6 +58,15 @@ impl<T: AsPeerInfo> RoutingTable<T> {
             .insert(peer, max_k, node_ttl, f)
     }
 
+    /// Returns all peers ordered by buckets.
+    pub fn peers(&self) -> Vec<Arc<PeerInfo>> {
+        self.buckets
+            .values()
+            .flat_map(|bucket| bucket.nodes.iter())
+            .map(|node| node.data.load_peer_info())
+            .collect()
+    }
+
     pub fn closest(&self, key: &[u8; 32], count: usize) -> Vec<Arc<PeerInfo>> {
         if count == 0 {
             return Vec::new();
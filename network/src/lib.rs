@@ -5,16 +5,19 @@ pub use dht::{
     StorageError,
 };
 pub use network::{
-    Connection, ConnectionError, KnownPeerHandle, KnownPeers, KnownPeersError, Network,
-    NetworkBuilder, NetworkConfig, Peer, PeerBannedError, QuicConfig, RecvStream, SendStream,
-    ToSocket, WeakKnownPeerHandle, WeakNetwork,
+    CompressionConfig, Connection, ConnectionError, InboundQuotaConfig, InboundQuotaInfo,
+    InboundQuotas, KnownPeerHandle, KnownPeers, KnownPeersError, Network, NetworkBuilder,
+    NetworkConfig, Peer, PeerBannedError, PeerPenalty, PeerReputation, PeerReputationConfig,
+    PeerReputationInfo, QueryRejectedError, QuicConfig, RecvStream, SendStream, ToSocket,
+    WeakKnownPeerHandle, WeakNetwork,
 };
 pub use quinn;
 pub use types::{
     service_datagram_fn, service_message_fn, service_query_fn, Address, BoxCloneService,
-    BoxService, Direction, DisconnectReason, InboundRequestMeta, PeerAffinity, PeerEvent,
-    PeerEventData, PeerId, PeerInfo, Request, Response, RpcQuery, Service, ServiceDatagramFn,
-    ServiceExt, ServiceMessageFn, ServiceQueryFn, ServiceRequest, Version,
+    BoxService, Direction, DisconnectReason, Features, InboundRequestMeta, PeerAffinity,
+    PeerCapabilities, PeerEvent, PeerEventData, PeerId, PeerInfo, Request, Response, RpcQuery,
+    Service, ServiceDatagramFn, ServiceExt, ServiceMessageFn, ServiceQueryFn, ServiceRequest,
+    Version,
 };
 
 pub use self::overlay::{
@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use tycho_util::compression::{ZstdCompressStream, ZstdDecompressStream, ZstdError};
+
+use crate::network::config::CompressionConfig;
+
+// Counters
+const METRIC_COMPRESSION_RAW_BYTES_TOTAL: &str = "tycho_net_compression_raw_bytes_total";
+const METRIC_COMPRESSION_COMPRESSED_BYTES_TOTAL: &str =
+    "tycho_net_compression_compressed_bytes_total";
+
+/// Output buffer growth step.
+const RESIZE_BY: usize = 128 * 1024;
+
+/// Payload compression context of connections which negotiated
+/// the [`Features::ZSTD`] feature.
+///
+/// [`Features::ZSTD`]: crate::types::Features::ZSTD
+#[derive(Clone)]
+#[repr(transparent)]
+pub(crate) struct WireCompression(Arc<Inner>);
+
+impl WireCompression {
+    /// Returns `None` if the compression is disabled.
+    pub fn from_config(config: &CompressionConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let dictionary = match &config.dictionary_path {
+            Some(path) => {
+                let dictionary = std::fs::read(path).with_context(|| {
+                    format!("failed to read zstd dictionary {}", path.display())
+                })?;
+                Some(dictionary.into_boxed_slice())
+            }
+            None => None,
+        };
+
+        let compression = Self(Arc::new(Inner {
+            level: config.level,
+            threshold: config.threshold.as_u64() as usize,
+            dictionary,
+        }));
+
+        // Check that the level and the dictionary are valid
+        compression
+            .make_compressor()
+            .context("invalid zstd compression config")?;
+
+        Ok(Some(compression))
+    }
+
+    /// Returns `None` if the payload is too small or is not compressible.
+    pub fn compress(&self, data: &[u8]) -> std::io::Result<Option<Bytes>> {
+        if data.len() < self.0.threshold {
+            return Ok(None);
+        }
+
+        let mut compressor = self.make_compressor()?;
+        let mut output = Vec::with_capacity(std::cmp::min(data.len(), RESIZE_BY));
+        compressor.write(data, &mut output)?;
+        compressor.finish(&mut output)?;
+
+        if output.len() >= data.len() {
+            return Ok(None);
+        }
+
+        metrics::counter!(METRIC_COMPRESSION_RAW_BYTES_TOTAL).increment(data.len() as u64);
+        metrics::counter!(METRIC_COMPRESSION_COMPRESSED_BYTES_TOTAL).increment(output.len() as u64);
+
+        Ok(Some(Bytes::from(output)))
+    }
+
+    /// Decompresses a single zstd frame which must not exceed `max_len`
+    /// after decompression.
+    pub fn decompress(&self, data: &[u8], max_len: usize) -> std::io::Result<Bytes> {
+        let mut decompressor = ZstdDecompressStream::new(RESIZE_BY)?;
+        if let Some(dictionary) = &self.0.dictionary {
+            decompressor.load_dictionary(dictionary)?;
+        }
+
+        // NOTE: The output size is checked after each decoded part so
+        // a small payload can't be expanded much beyond the limit.
+        let mut output = Vec::new();
+        decompressor
+            .write_bounded(data, &mut output, max_len)
+            .map_err(|e| match e {
+                ZstdError::OutputTooLarge { .. } => {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, CompressionError::TooLarge)
+                }
+                e => std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+            })?;
+
+        if !decompressor.is_finished() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                CompressionError::Truncated,
+            ));
+        }
+
+        Ok(Bytes::from(output))
+    }
+
+    fn make_compressor(&self) -> std::io::Result<ZstdCompressStream<'static>> {
+        let mut compressor = ZstdCompressStream::new(self.0.level, RESIZE_BY)?;
+        if let Some(dictionary) = &self.0.dictionary {
+            compressor.load_dictionary(dictionary)?;
+        }
+        Ok(compressor)
+    }
+}
+
+struct Inner {
+    level: i32,
+    threshold: usize,
+    dictionary: Option<Box<[u8]>>,
+}
+
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+enum CompressionError {
+    #[error("decompressed payload is too large")]
+    TooLarge,
+    #[error("truncated compressed payload")]
+    Truncated,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_compression(dictionary: Option<&[u8]>) -> WireCompression {
+        WireCompression(Arc::new(Inner {
+            level: 3,
+            threshold: 128,
+            dictionary: dictionary.map(Box::from),
+        }))
+    }
+
+    #[test]
+    fn compression_roundtrip() {
+        let data = b"some repetitive payload ".repeat(1000);
+
+        for dictionary in [None, Some(&b"some repetitive dictionary"[..])] {
+            let compression = make_compression(dictionary);
+
+            // Small payloads are sent as is
+            assert!(compression.compress(&data[..100]).unwrap().is_none());
+
+            let compressed = compression.compress(&data).unwrap().unwrap();
+            assert!(compressed.len() < data.len());
+
+            let decompressed = compression.decompress(&compressed, data.len()).unwrap();
+            assert_eq!(decompressed, data);
+
+            // Decompressed size is limited
+            assert!(compression.decompress(&compressed, data.len() - 1).is_err());
+            assert!(compression.decompress(&compressed, 0).is_err());
+
+            // Truncated payloads are rejected
+            assert!(compression
+                .decompress(&compressed[..compressed.len() - 1], data.len())
+                .is_err());
+        }
+
+        // Incompressible payloads are sent as is
+        let random = (0..4096).map(|_| rand::random::<u8>()).collect::<Vec<_>>();
+        assert!(make_compression(None).compress(&random).unwrap().is_none());
+    }
+}
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -64,6 +65,12 @@ pub struct NetworkConfig {
 
     /// Default: no.
     pub enable_0rtt: bool,
+
+    pub peer_reputation: PeerReputationConfig,
+
+    pub inbound_quota: InboundQuotaConfig,
+
+    pub compression: CompressionConfig,
 }
 
 impl Default for NetworkConfig {
@@ -82,6 +89,101 @@ impl Default for NetworkConfig {
             active_peers_event_channel_capacity: 128,
             shutdown_idle_timeout: Duration::from_secs(60),
             enable_0rtt: false,
+            peer_reputation: PeerReputationConfig::default(),
+            inbound_quota: InboundQuotaConfig::default(),
+            compression: CompressionConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PeerReputationConfig {
+    /// Time after which the accumulated penalty of a peer is halved.
+    ///
+    /// Default: 10 minutes.
+    #[serde(with = "serde_helpers::humantime")]
+    pub penalty_half_life: Duration,
+
+    /// Accumulated penalty after which the peer is temporarily banned.
+    ///
+    /// Default: 100.
+    pub ban_threshold: f64,
+
+    /// For how long the peer is banned after reaching the threshold.
+    ///
+    /// Default: 30 minutes.
+    #[serde(with = "serde_helpers::humantime")]
+    pub ban_duration: Duration,
+}
+
+impl Default for PeerReputationConfig {
+    fn default() -> Self {
+        Self {
+            penalty_half_life: Duration::from_secs(600),
+            ban_threshold: 100.0,
+            ban_duration: Duration::from_secs(1800),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InboundQuotaConfig {
+    /// Max number of concurrently processed queries and messages
+    /// from a single peer.
+    ///
+    /// Default: unlimited.
+    pub max_concurrent_queries_per_peer: Option<usize>,
+
+    /// Max number of query and message bytes (requests and responses)
+    /// per second for a single peer.
+    ///
+    /// NOTE: The budget is kept for some time after the peer disconnects,
+    /// so it can't be reset by reconnecting.
+    ///
+    /// Default: unlimited.
+    pub max_bytes_per_sec_per_peer: Option<bytesize::ByteSize>,
+
+    /// Max number of concurrently processed queries and messages from all peers.
+    ///
+    /// Default: unlimited.
+    pub max_concurrent_queries: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// Whether to compress payloads for peers which also support it.
+    ///
+    /// Default: no.
+    pub enabled: bool,
+
+    /// Zstd compression level.
+    ///
+    /// Default: 3.
+    pub level: i32,
+
+    /// Payloads smaller than this are always sent uncompressed.
+    ///
+    /// Default: 4 KiB.
+    pub threshold: bytesize::ByteSize,
+
+    /// Path to the zstd dictionary.
+    ///
+    /// NOTE: All nodes which enable compression must use the same dictionary.
+    ///
+    /// Default: none.
+    pub dictionary_path: Option<PathBuf>,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            level: 3,
+            threshold: bytesize::ByteSize::kib(4),
+            dictionary_path: None,
         }
     }
 }
@@ -7,13 +7,15 @@ use bytes::Bytes;
 use quinn::{ConnectionError, SendDatagramError};
 use webpki::types::CertificateDer;
 
+use crate::network::compression::WireCompression;
 use crate::network::crypto::peer_id_from_certificate;
-use crate::types::{Direction, InboundRequestMeta, PeerId};
+use crate::types::{Direction, Features, InboundRequestMeta, PeerCapabilities, PeerId};
 
 #[derive(Clone)]
 pub struct Connection {
     inner: quinn::Connection,
     request_meta: Arc<InboundRequestMeta>,
+    compression: Option<WireCompression>,
 }
 
 impl Connection {
@@ -23,7 +25,9 @@ impl Connection {
                 peer_id,
                 origin,
                 remote_address: inner.remote_address(),
+                capabilities: PeerCapabilities::LEGACY,
             }),
+            compression: None,
             inner,
         }
     }
@@ -48,6 +52,38 @@ impl Connection {
         self.request_meta.remote_address
     }
 
+    /// Capabilities negotiated with the peer during the handshake.
+    pub fn capabilities(&self) -> &PeerCapabilities {
+        &self.request_meta.capabilities
+    }
+
+    pub(crate) fn with_capabilities(mut self, capabilities: PeerCapabilities) -> Self {
+        Arc::make_mut(&mut self.request_meta).capabilities = capabilities;
+        self
+    }
+
+    /// Payload compression context (if it was negotiated).
+    pub(crate) fn compression(&self) -> Option<&WireCompression> {
+        self.compression.as_ref()
+    }
+
+    /// Enables the payload compression if both sides support it.
+    ///
+    /// NOTE: Must be called after [`Connection::with_capabilities`].
+    pub(crate) fn with_compression(mut self, compression: Option<WireCompression>) -> Self {
+        self.compression = compression.filter(|_| self.capabilities().supports(Features::ZSTD));
+        self
+    }
+
+    /// Server name which was requested by the client.
+    pub(crate) fn server_name(&self) -> Option<String> {
+        let data = self.inner.handshake_data()?;
+        let data = data
+            .downcast::<quinn::crypto::rustls::HandshakeData>()
+            .ok()?;
+        data.server_name
+    }
+
     pub fn close(&self) {
         self.inner.close(0u8.into(), b"connection closed");
     }
@@ -90,6 +126,7 @@ impl std::fmt::Debug for Connection {
             .field("id", &self.stable_id())
             .field("remote_address", &self.remote_address())
             .field("peer_id", &self.request_meta.peer_id)
+            .field("capabilities", &self.request_meta.capabilities)
             .finish_non_exhaustive()
     }
 }
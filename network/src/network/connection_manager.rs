@@ -11,15 +11,17 @@ use tokio::task::{AbortHandle, JoinSet};
 use tokio_util::time::{delay_queue, DelayQueue};
 use tycho_util::{FastDashMap, FastHashMap};
 
+use crate::network::compression::WireCompression;
 use crate::network::config::NetworkConfig;
 use crate::network::connection::Connection;
 use crate::network::endpoint::{Connecting, ConnectionInitError, Endpoint, Into0RttResult};
-use crate::network::request_handler::InboundRequestHandler;
+use crate::network::peer_reputation::PeerReputation;
+use crate::network::request_handler::{InboundQuotas, InboundRequestHandler};
 use crate::network::wire::{handshake, HandshakeError};
 use crate::network::ConnectionError;
 use crate::types::{
-    Address, BoxCloneService, Direction, DisconnectReason, PeerAffinity, PeerEvent, PeerId,
-    PeerInfo, Response, ServiceRequest,
+    Address, BoxCloneService, Direction, DisconnectReason, Features, PeerAffinity,
+    PeerCapabilities, PeerEvent, PeerId, PeerInfo, Response, ServiceRequest,
 };
 
 // Histograms
@@ -64,6 +66,10 @@ pub(crate) struct ConnectionManager {
 
     active_peers: ActivePeers,
     known_peers: KnownPeers,
+    peer_reputation: PeerReputation,
+    inbound_quotas: InboundQuotas,
+    local_capabilities: PeerCapabilities,
+    compression: Option<WireCompression>,
 
     service: BoxCloneService<ServiceRequest, Response>,
 }
@@ -79,14 +85,23 @@ impl Drop for ConnectionManager {
 }
 
 impl ConnectionManager {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         config: Arc<NetworkConfig>,
         endpoint: Arc<Endpoint>,
         active_peers: ActivePeers,
         known_peers: KnownPeers,
+        peer_reputation: PeerReputation,
+        inbound_quotas: InboundQuotas,
+        compression: Option<WireCompression>,
         service: BoxCloneService<ServiceRequest, Response>,
     ) -> (Self, mpsc::Sender<ConnectionManagerRequest>) {
         let (mailbox_tx, mailbox) = mpsc::channel(config.connection_manager_channel_capacity);
+        let local_features = match &compression {
+            Some(_) => Features::ZSTD,
+            None => Features::empty(),
+        };
+
         let connection_manager = Self {
             config,
             endpoint,
@@ -100,6 +115,10 @@ impl ConnectionManager {
             dial_backoff_states: Default::default(),
             active_peers,
             known_peers,
+            peer_reputation,
+            inbound_quotas,
+            local_capabilities: PeerCapabilities::local(local_features),
+            compression,
             service,
         };
         (connection_manager, mailbox_tx)
@@ -205,6 +224,9 @@ impl ConnectionManager {
     fn handle_connectivity_check(&mut self, now: Instant) {
         use std::collections::hash_map::Entry;
 
+        self.peer_reputation.cleanup();
+        self.inbound_quotas.cleanup();
+
         self.pending_dials
             .retain(|peer_id, oneshot| match oneshot.try_recv() {
                 Ok(Ok(returned_peer_id)) => {
@@ -348,6 +370,7 @@ impl ConnectionManager {
             connection: ConnectionClosedOnDrop,
             accepted: Option<quinn::ZeroRttAccepted>,
             timeout_at: Instant,
+            local_capabilities: PeerCapabilities,
         ) -> ConnectingOutput {
             let target_peer_id = *connection.peer_id();
             let target_address = connection.remote_address().into();
@@ -356,13 +379,13 @@ impl ConnectionManager {
                     // NOTE: `bool` output of this future is meaningless for servers.
                     accepted.await;
                 }
-                handshake(&connection).await
+                handshake(&connection, &local_capabilities).await
             };
 
             let started_at = Instant::now();
 
             let connecting_result = match tokio::time::timeout_at(timeout_at.into(), fut).await {
-                Ok(Ok(())) => Ok(connection.disarm()),
+                Ok(Ok(capabilities)) => Ok(connection.disarm().with_capabilities(capabilities)),
                 Ok(Err(e)) => Err(FullConnectionError::HandshakeFailed(e)),
                 Err(_) => Err(FullConnectionError::Timeout),
             };
@@ -461,6 +484,7 @@ impl ConnectionManager {
                 ConnectionClosedOnDrop::new(connection),
                 accepted,
                 timeout_at,
+                self.local_capabilities,
             )));
             metrics::gauge!(METRIC_CONNECTIONS_PENDING).increment(1);
         }
@@ -545,6 +569,7 @@ impl ConnectionManager {
     }
 
     fn add_peer(&mut self, connection: Connection) -> Connection {
+        let connection = connection.with_compression(self.compression.clone());
         match self.active_peers.add(self.endpoint.peer_id(), connection) {
             AddedPeer::New(connection) => {
                 let origin = connection.origin();
@@ -554,6 +579,7 @@ impl ConnectionManager {
                     connection.clone(),
                     self.service.clone(),
                     self.active_peers.clone(),
+                    &self.inbound_quotas,
                 );
 
                 metrics::counter!(match origin {
@@ -587,6 +613,7 @@ impl ConnectionManager {
             address: Address,
             peer_id: PeerId,
             config: Arc<NetworkConfig>,
+            local_capabilities: PeerCapabilities,
         ) -> ConnectingOutput {
             let fut = async {
                 let address = address
@@ -599,8 +626,8 @@ impl ConnectionManager {
                     .map_err(|e| FullConnectionError::InvalidAddress(std::io::Error::other(e)))?;
 
                 let connection = ConnectionClosedOnDrop::new(connecting.await?);
-                match handshake(&connection).await {
-                    Ok(()) => Ok(connection),
+                match handshake(&connection, &local_capabilities).await {
+                    Ok(capabilities) => Ok((connection, capabilities)),
                     Err(e) => Err(FullConnectionError::HandshakeFailed(e)),
                 }
             };
@@ -608,7 +635,9 @@ impl ConnectionManager {
             let started_at = Instant::now();
 
             let connecting_result = match tokio::time::timeout(config.connect_timeout, fut).await {
-                Ok(res) => res.map(ConnectionClosedOnDrop::disarm),
+                Ok(res) => res.map(|(connection, capabilities)| {
+                    connection.disarm().with_capabilities(capabilities)
+                }),
                 Err(_) => Err(FullConnectionError::Timeout),
             };
 
@@ -678,6 +707,7 @@ impl ConnectionManager {
                 address.clone(),
                 *peer_id,
                 self.config.clone(),
+                self.local_capabilities,
             )));
             metrics::gauge!(METRIC_CONNECTIONS_PENDING).increment(1);
         }
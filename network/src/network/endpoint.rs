@@ -9,6 +9,7 @@ use anyhow::Result;
 
 use crate::network::config::EndpointConfig;
 use crate::network::connection::{extract_peer_id, parse_peer_identity, Connection};
+use crate::network::wire::EXTENDED_HANDSHAKE_SERVER_NAME;
 use crate::types::{Direction, PeerId};
 
 pub(crate) struct Endpoint {
@@ -89,7 +90,7 @@ impl Endpoint {
         address: &SocketAddr,
     ) -> Result<Connecting, quinn::ConnectError> {
         self.inner
-            .connect_with(config, *address, "tycho")
+            .connect_with(config, *address, EXTENDED_HANDSHAKE_SERVER_NAME)
             .map(Connecting::new_outbound)
     }
 
@@ -7,8 +7,11 @@ use anyhow::Result;
 use everscale_crypto::ed25519;
 use tokio::sync::{broadcast, mpsc, oneshot};
 
+use self::compression::WireCompression;
 use self::config::EndpointConfig;
-pub use self::config::{NetworkConfig, QuicConfig};
+pub use self::config::{
+    CompressionConfig, InboundQuotaConfig, NetworkConfig, PeerReputationConfig, QuicConfig,
+};
 pub use self::connection::{Connection, RecvStream, SendStream};
 use self::connection_manager::{ActivePeers, ConnectionManager, ConnectionManagerRequest};
 pub use self::connection_manager::{
@@ -16,17 +19,22 @@ pub use self::connection_manager::{
 };
 use self::endpoint::Endpoint;
 pub use self::peer::Peer;
+pub use self::peer_reputation::{PeerPenalty, PeerReputation, PeerReputationInfo};
+pub use self::request_handler::{InboundQuotaInfo, InboundQuotas};
+pub use self::wire::QueryRejectedError;
 use crate::types::{
     Address, DisconnectReason, PeerEvent, PeerId, PeerInfo, Response, Service, ServiceExt,
     ServiceRequest,
 };
 
+mod compression;
 mod config;
 mod connection;
 mod connection_manager;
 mod crypto;
 mod endpoint;
 mod peer;
+mod peer_reputation;
 mod request_handler;
 mod wire;
 
@@ -104,10 +112,14 @@ impl NetworkBuilder {
             "recv",
         );
 
+        let compression = WireCompression::from_config(&config.compression)?;
+
         let config = Arc::new(config);
         let endpoint = Arc::new(Endpoint::new(endpoint_config, socket.into())?);
         let active_peers = ActivePeers::new(config.active_peers_event_channel_capacity);
         let known_peers = KnownPeers::new();
+        let peer_reputation = PeerReputation::new(config.peer_reputation.clone());
+        let inbound_quotas = InboundQuotas::new(&config.inbound_quota);
 
         let remote_addr = self.optional_fields.remote_addr.unwrap_or_else(|| {
             let addr = endpoint.local_addr();
@@ -122,6 +134,9 @@ impl NetworkBuilder {
             endpoint.clone(),
             active_peers.clone(),
             known_peers.clone(),
+            peer_reputation.clone(),
+            inbound_quotas.clone(),
+            compression,
             service,
         );
 
@@ -133,6 +148,8 @@ impl NetworkBuilder {
             endpoint,
             active_peers,
             known_peers,
+            peer_reputation,
+            inbound_quotas,
             connection_manager_handle,
             keypair,
         })))
@@ -217,6 +234,16 @@ impl Network {
         &self.0.known_peers
     }
 
+    /// Shared peer scoring.
+    pub fn peer_reputation(&self) -> &PeerReputation {
+        &self.0.peer_reputation
+    }
+
+    /// Limits and counters of inbound requests.
+    pub fn inbound_quotas(&self) -> &InboundQuotas {
+        &self.0.inbound_quotas
+    }
+
     /// Subscribe to active peer changes.
     pub fn subscribe(&self) -> broadcast::Receiver<PeerEvent> {
         self.0.active_peers.subscribe()
@@ -278,6 +305,8 @@ struct NetworkInner {
     endpoint: Arc<Endpoint>,
     active_peers: ActivePeers,
     known_peers: KnownPeers,
+    peer_reputation: PeerReputation,
+    inbound_quotas: InboundQuotas,
     connection_manager_handle: mpsc::Sender<ConnectionManagerRequest>,
     keypair: ed25519::KeyPair,
 }
@@ -460,7 +489,10 @@ mod tests {
     use futures_util::StreamExt;
 
     use super::*;
-    use crate::types::{service_message_fn, service_query_fn, BoxCloneService, PeerInfo, Request};
+    use crate::types::{
+        service_message_fn, service_query_fn, BoxCloneService, Features, PeerCapabilities,
+        PeerInfo, Request,
+    };
     use crate::util::{NetworkExt, UnknownPeerError};
 
     fn echo_service() -> BoxCloneService<ServiceRequest, Response> {
@@ -514,6 +546,71 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn handshake_exchanges_capabilities() -> Result<()> {
+        tycho_util::test::init_logger("handshake_exchanges_capabilities", "debug");
+
+        let peer1 = make_network()?;
+        let peer2 = make_network()?;
+
+        let peer = peer1.connect(peer2.local_addr(), peer2.peer_id()).await?;
+        let expected = PeerCapabilities::local(Features::empty());
+        assert_eq!(peer.capabilities(), &expected);
+
+        let peer = peer2.connect(peer1.local_addr(), peer1.peer_id()).await?;
+        assert_eq!(peer.capabilities(), &expected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn compression_is_negotiated() -> Result<()> {
+        tycho_util::test::init_logger("compression_is_negotiated", "debug");
+
+        let make_compressed_network = || {
+            Network::builder()
+                .with_config(NetworkConfig {
+                    compression: CompressionConfig {
+                        enabled: true,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_random_private_key()
+                .build("127.0.0.1:0", echo_service())
+        };
+
+        let peer1 = make_compressed_network()?;
+        let peer2 = make_compressed_network()?;
+        let legacy = make_network()?;
+
+        let body = bytes::Bytes::from(b"compressible payload ".repeat(10000));
+
+        let peer = peer1.connect(peer2.local_addr(), peer2.peer_id()).await?;
+        assert!(peer.capabilities().supports(Features::ZSTD));
+
+        let response = peer
+            .rpc(Request {
+                version: Default::default(),
+                body: body.clone(),
+            })
+            .await?;
+        assert_eq!(response.body, body);
+
+        let peer = peer1.connect(legacy.local_addr(), legacy.peer_id()).await?;
+        assert!(!peer.capabilities().supports(Features::ZSTD));
+
+        let response = peer
+            .rpc(Request {
+                version: Default::default(),
+                body: body.clone(),
+            })
+            .await?;
+        assert_eq!(response.body, body);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn invalid_peer_id_detectable() -> Result<()> {
         tycho_util::test::init_logger("invalid_peer_id_detectable", "debug");
@@ -611,6 +708,79 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn inbound_quota_rejects_queries() -> Result<()> {
+        tycho_util::test::init_logger("inbound_quota_rejects_queries", "debug");
+
+        let started = Arc::new(tokio::sync::Notify::new());
+        let release = Arc::new(tokio::sync::Notify::new());
+
+        let service = service_query_fn({
+            let started = started.clone();
+            let release = release.clone();
+            move |request: ServiceRequest| {
+                let started = started.clone();
+                let release = release.clone();
+                async move {
+                    started.notify_one();
+                    release.notified().await;
+                    Some(Response {
+                        version: Default::default(),
+                        body: request.body,
+                    })
+                }
+            }
+        });
+
+        let server = Network::builder()
+            .with_config(NetworkConfig {
+                inbound_quota: InboundQuotaConfig {
+                    max_concurrent_queries_per_peer: Some(1),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_random_private_key()
+            .build("127.0.0.1:0", service.boxed_clone())?;
+        let client = make_network()?;
+
+        let _handle = client
+            .known_peers()
+            .insert(make_peer_info(&server), false)?;
+
+        let req = Request {
+            version: Default::default(),
+            body: "hello".into(),
+        };
+
+        let first = tokio::spawn({
+            let client = client.clone();
+            let server_id = *server.peer_id();
+            let req = req.clone();
+            async move { client.query(&server_id, req).await }
+        });
+        started.notified().await;
+
+        // The second query exceeds the per-peer limit
+        let err = client
+            .query(server.peer_id(), req.clone())
+            .await
+            .map(|_| ())
+            .unwrap_err();
+        let err = (*err).downcast_ref::<QueryRejectedError>().unwrap();
+        assert_eq!(*err, QueryRejectedError::TooManyQueries);
+
+        // The first one is still processed
+        release.notify_one();
+        assert_eq!(first.await??.body, req.body);
+
+        let info = server.inbound_quotas().get(client.peer_id()).unwrap();
+        assert_eq!(info.rejected_requests, 1);
+        assert_eq!(info.total_bytes, 2 * req.body.len() as u64);
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn uni_message_handler() -> Result<()> {
         tycho_util::test::init_logger("uni_message_handler", "debug");
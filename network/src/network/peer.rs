@@ -7,8 +7,8 @@ use tycho_util::metrics::{GaugeGuard, HistogramGuard};
 
 use crate::network::config::NetworkConfig;
 use crate::network::connection::Connection;
-use crate::network::wire::{make_codec, recv_response, send_request};
-use crate::types::{PeerId, Request, Response};
+use crate::network::wire::{make_codec, recv_response, send_request, QueryRejectedError};
+use crate::types::{PeerCapabilities, PeerId, Request, Response};
 
 // Histograms
 const METRIC_OUT_QUERIES_TIME: &str = "tycho_net_out_queries_time";
@@ -37,6 +37,11 @@ impl Peer {
         self.connection.peer_id()
     }
 
+    /// Capabilities negotiated with the peer during the handshake.
+    pub fn capabilities(&self) -> &PeerCapabilities {
+        self.connection.capabilities()
+    }
+
     pub async fn rpc(&self, request: Request) -> Result<Response> {
         metrics::counter!(METRIC_OUT_QUERIES_TOTAL).increment(1);
         let _gauge = GaugeGuard::increment(METRIC_OUT_QUERIES, 1);
@@ -46,10 +51,15 @@ impl Peer {
         let mut send_stream = FramedWrite::new(send_stream, make_codec(&self.config));
         let mut recv_stream = FramedRead::new(recv_stream, make_codec(&self.config));
 
-        send_request(&mut send_stream, request).await?;
+        let compression = self.connection.compression();
+        send_request(&mut send_stream, request, compression)
+            .await
+            .map_err(map_query_error)?;
         send_stream.get_mut().finish()?;
 
-        recv_response(&mut recv_stream).await.map_err(Into::into)
+        recv_response(&mut recv_stream, compression)
+            .await
+            .map_err(map_query_error)
     }
 
     pub async fn send_message(&self, request: Request) -> Result<()> {
@@ -60,9 +70,18 @@ impl Peer {
         let send_stream = self.connection.open_uni().await?;
         let mut send_stream = FramedWrite::new(send_stream, make_codec(&self.config));
 
-        send_request(&mut send_stream, request).await?;
+        send_request(&mut send_stream, request, self.connection.compression())
+            .await
+            .map_err(map_query_error)?;
         send_stream.get_mut().finish()?;
-        _ = send_stream.get_mut().stopped().await;
+
+        // NOTE: Rejection might not be observed if the message
+        // was fully acknowledged before the stream was stopped.
+        if let Ok(Some(code)) = send_stream.get_mut().stopped().await {
+            if let Some(rejected) = QueryRejectedError::from_code(code) {
+                return Err(rejected.into());
+            }
+        }
 
         Ok(())
     }
@@ -73,6 +92,13 @@ impl Peer {
     }
 }
 
+fn map_query_error(error: std::io::Error) -> anyhow::Error {
+    match QueryRejectedError::from_io_error(&error) {
+        Some(rejected) => rejected.into(),
+        None => error.into(),
+    }
+}
+
 impl std::fmt::Debug for Peer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_tuple("Peer")
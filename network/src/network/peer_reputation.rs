@@ -0,0 +1,305 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tycho_util::time::now_sec;
+use tycho_util::FastDashMap;
+
+use crate::network::config::PeerReputationConfig;
+use crate::types::PeerId;
+
+// Counters
+const METRIC_PEER_PENALTIES_TOTAL: &str = "tycho_net_peer_penalties_total";
+const METRIC_PEER_BANS_TOTAL: &str = "tycho_net_peer_bans_total";
+
+// Gauges
+const METRIC_BANNED_PEERS: &str = "tycho_net_banned_peers";
+
+/// Penalty below which the reputation entry is considered clean.
+const MIN_PENALTY: f64 = 0.01;
+
+/// Shared peer scoring.
+///
+/// Components report misbehaving peers via [`PeerReputation::penalize`].
+/// Penalties decay exponentially over time, and a peer which accumulated
+/// too many of them is temporarily banned until the ban expires.
+///
+/// NOTE: Bans don't affect connections. They are only taken into account
+/// by the public overlay clients when selecting neighbours, so private
+/// overlays (e.g. the validator set) keep working with banned peers.
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct PeerReputation(Arc<Inner>);
+
+impl PeerReputation {
+    pub(crate) fn new(config: PeerReputationConfig) -> Self {
+        Self(Arc::new(Inner {
+            config,
+            entries: Default::default(),
+        }))
+    }
+
+    /// Adds a penalty to the peer score.
+    ///
+    /// Returns `true` if the peer has just been banned.
+    pub fn penalize(&self, peer_id: &PeerId, penalty: PeerPenalty) -> bool {
+        let banned = self.penalize_at(peer_id, penalty, Instant::now());
+        if banned {
+            tracing::warn!(%peer_id, ?penalty, "peer banned due to low reputation");
+        }
+        banned
+    }
+
+    /// Bans the peer for the specified duration.
+    ///
+    /// NOTE: Extends the existing ban if it expires earlier.
+    pub fn ban(&self, peer_id: &PeerId, duration: Duration) {
+        let now = Instant::now();
+        let until = now + duration;
+
+        let mut entry = self
+            .0
+            .entries
+            .entry(*peer_id)
+            .or_insert_with(|| Entry::new(now));
+        entry.decay(now, self.0.config.penalty_half_life);
+        if entry.banned_until.map_or(true, |prev| prev < until) {
+            entry.banned_until = Some(until);
+        }
+        drop(entry);
+
+        metrics::counter!(METRIC_PEER_BANS_TOTAL).increment(1);
+        tracing::info!(%peer_id, ?duration, "peer banned manually");
+    }
+
+    /// Removes the ban and resets the accumulated penalty.
+    ///
+    /// Returns `true` if the peer was banned.
+    pub fn unban(&self, peer_id: &PeerId) -> bool {
+        let now = Instant::now();
+        match self.0.entries.remove(peer_id) {
+            Some((_, entry)) => entry.is_banned(now),
+            None => false,
+        }
+    }
+
+    pub fn is_banned(&self, peer_id: &PeerId) -> bool {
+        let now = Instant::now();
+        self.0
+            .entries
+            .get(peer_id)
+            .map(|entry| entry.is_banned(now))
+            .unwrap_or_default()
+    }
+
+    /// Returns the current reputation of the peer (if it was ever penalized).
+    pub fn get(&self, peer_id: &PeerId) -> Option<PeerReputationInfo> {
+        self.get_at(peer_id, Instant::now())
+    }
+
+    /// Removes expired bans and fully decayed penalties.
+    pub(crate) fn cleanup(&self) {
+        let now = Instant::now();
+        let half_life = self.0.config.penalty_half_life;
+
+        let mut banned = 0usize;
+        self.0.entries.retain(|_, entry| {
+            entry.decay(now, half_life);
+            if entry.is_banned(now) {
+                banned += 1;
+                true
+            } else {
+                entry.penalty >= MIN_PENALTY
+            }
+        });
+
+        metrics::gauge!(METRIC_BANNED_PEERS).set(banned as f64);
+    }
+
+    fn penalize_at(&self, peer_id: &PeerId, penalty: PeerPenalty, now: Instant) -> bool {
+        let config = &self.0.config;
+
+        metrics::counter!(METRIC_PEER_PENALTIES_TOTAL, "kind" => penalty.as_str()).increment(1);
+
+        let mut entry = self
+            .0
+            .entries
+            .entry(*peer_id)
+            .or_insert_with(|| Entry::new(now));
+        entry.decay(now, config.penalty_half_life);
+        entry.penalty += penalty.points();
+
+        if entry.banned_until.is_none() && entry.penalty >= config.ban_threshold {
+            entry.banned_until = Some(now + config.ban_duration);
+            metrics::counter!(METRIC_PEER_BANS_TOTAL).increment(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn get_at(&self, peer_id: &PeerId, now: Instant) -> Option<PeerReputationInfo> {
+        let mut entry = *self.0.entries.get(peer_id)?;
+        entry.decay(now, self.0.config.penalty_half_life);
+
+        Some(PeerReputationInfo {
+            penalty: entry.penalty,
+            banned_until: entry
+                .banned_until
+                .map(|until| now_sec().saturating_add(until.duration_since(now).as_secs() as u32)),
+        })
+    }
+}
+
+/// A reason to lower the peer reputation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerPenalty {
+    /// Peer didn't respond in time or the request has failed.
+    Unresponsive,
+    /// Peer has returned a malformed or an unexpected response.
+    InvalidResponse,
+    /// Peer has returned a provably invalid data (e.g. bad proof or signature).
+    Malicious,
+}
+
+impl PeerPenalty {
+    pub fn points(self) -> f64 {
+        match self {
+            Self::Unresponsive => 1.0,
+            Self::InvalidResponse => 5.0,
+            Self::Malicious => 20.0,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Unresponsive => "unresponsive",
+            Self::InvalidResponse => "invalid_response",
+            Self::Malicious => "malicious",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PeerReputationInfo {
+    /// Accumulated penalty with decay applied.
+    pub penalty: f64,
+    /// Unix timestamp in seconds until which the peer is banned.
+    pub banned_until: Option<u32>,
+}
+
+struct Inner {
+    config: PeerReputationConfig,
+    entries: FastDashMap<PeerId, Entry>,
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    penalty: f64,
+    updated_at: Instant,
+    banned_until: Option<Instant>,
+}
+
+impl Entry {
+    fn new(now: Instant) -> Self {
+        Self {
+            penalty: 0.0,
+            updated_at: now,
+            banned_until: None,
+        }
+    }
+
+    fn is_banned(&self, now: Instant) -> bool {
+        matches!(self.banned_until, Some(until) if now < until)
+    }
+
+    fn decay(&mut self, now: Instant, half_life: Duration) {
+        let elapsed = now.saturating_duration_since(self.updated_at);
+        if half_life.is_zero() {
+            self.penalty = 0.0;
+        } else {
+            self.penalty *= 0.5f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64());
+        }
+        self.updated_at = std::cmp::max(self.updated_at, now);
+
+        if !self.is_banned(now) {
+            self.banned_until = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_reputation() -> PeerReputation {
+        let config = PeerReputationConfig {
+            penalty_half_life: Duration::from_secs(60),
+            ban_threshold: 100.0,
+            ban_duration: Duration::from_secs(300),
+        };
+        PeerReputation::new(config)
+    }
+
+    #[test]
+    fn penalties_decay_and_bans_expire() {
+        let reputation = make_reputation();
+        let peer_id = PeerId([1; 32]);
+        let now = Instant::now();
+
+        // Penalties accumulate
+        assert!(!reputation.penalize_at(&peer_id, PeerPenalty::Malicious, now));
+        let info = reputation.get_at(&peer_id, now).unwrap();
+        assert_eq!(info.penalty, 20.0);
+        assert!(info.banned_until.is_none());
+
+        // And are halved after the half-life
+        let now = now + Duration::from_secs(60);
+        let info = reputation.get_at(&peer_id, now).unwrap();
+        assert!((info.penalty - 10.0).abs() < 1e-6);
+
+        // A single malicious response is not enough to ban the peer
+        for _ in 0..4 {
+            assert!(!reputation.penalize_at(&peer_id, PeerPenalty::Malicious, now));
+        }
+        assert!(!reputation.is_banned(&peer_id));
+
+        // Reaching the threshold bans the peer only once
+        assert!(reputation.penalize_at(&peer_id, PeerPenalty::Malicious, now));
+        assert!(!reputation.penalize_at(&peer_id, PeerPenalty::Malicious, now));
+        assert!(reputation.is_banned(&peer_id));
+        assert!(reputation
+            .get_at(&peer_id, now)
+            .unwrap()
+            .banned_until
+            .is_some());
+
+        // Ban expires
+        let now = now + Duration::from_secs(301);
+        let info = reputation.get_at(&peer_id, now).unwrap();
+        assert!(info.banned_until.is_none());
+        assert!(info.penalty < 100.0);
+
+        reputation.unban(&peer_id);
+        assert!(!reputation.is_banned(&peer_id));
+        assert!(reputation.get(&peer_id).is_none());
+    }
+
+    #[test]
+    fn manual_ban_and_unban() {
+        let reputation = make_reputation();
+        let peer_id = PeerId([2; 32]);
+
+        assert!(!reputation.unban(&peer_id));
+
+        reputation.ban(&peer_id, Duration::from_secs(60));
+        assert!(reputation.is_banned(&peer_id));
+
+        // Shorter bans don't override the existing one
+        reputation.ban(&peer_id, Duration::ZERO);
+        assert!(reputation.is_banned(&peer_id));
+
+        assert!(reputation.unban(&peer_id));
+        assert!(!reputation.is_banned(&peer_id));
+        assert!(!reputation.unban(&peer_id));
+    }
+}
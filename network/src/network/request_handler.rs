@@ -1,16 +1,23 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::Result;
+use parking_lot::Mutex;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio::task::JoinSet;
 use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
 use tycho_util::metrics::HistogramGuard;
+use tycho_util::FastDashMap;
 
-use crate::network::config::NetworkConfig;
+use crate::network::compression::WireCompression;
+use crate::network::config::{InboundQuotaConfig, NetworkConfig};
 use crate::network::connection::{Connection, RecvStream, SendStream};
 use crate::network::connection_manager::ActivePeers;
-use crate::network::wire::{make_codec, recv_request, send_response};
+use crate::network::wire::{make_codec, recv_request, send_response, QueryRejectedError};
 use crate::types::{
-    BoxCloneService, DisconnectReason, InboundRequestMeta, Response, Service, ServiceRequest,
+    BoxCloneService, DisconnectReason, InboundRequestMeta, PeerId, Response, Service,
+    ServiceRequest,
 };
 
 // Histograms
@@ -21,6 +28,8 @@ const METRIC_IN_MESSAGES_TIME: &str = "tycho_net_in_messages_time";
 const METRIC_IN_QUERIES_TOTAL: &str = "tycho_net_in_queries_total";
 const METRIC_IN_MESSAGES_TOTAL: &str = "tycho_net_in_messages_total";
 const METRIC_IN_DATAGRAMS_TOTAL: &str = "tycho_net_in_datagrams_total";
+const METRIC_IN_REQUESTS_REJECTED_TOTAL: &str = "tycho_net_in_requests_rejected_total";
+const METRIC_IN_REQUEST_BYTES_TOTAL: &str = "tycho_net_in_request_bytes_total";
 
 // Gauges
 const METRIC_REQ_HANDLERS: &str = "tycho_net_req_handlers";
@@ -30,6 +39,7 @@ pub(crate) struct InboundRequestHandler {
     connection: Connection,
     service: BoxCloneService<ServiceRequest, Response>,
     active_peers: ActivePeers,
+    quota: InboundQuota,
 }
 
 impl InboundRequestHandler {
@@ -38,12 +48,15 @@ impl InboundRequestHandler {
         connection: Connection,
         service: BoxCloneService<ServiceRequest, Response>,
         active_peers: ActivePeers,
+        quotas: &InboundQuotas,
     ) -> Self {
+        let quota = quotas.for_peer(connection.peer_id());
         Self {
             config,
             connection,
             service,
             active_peers,
+            quota,
         }
     }
 
@@ -77,15 +90,30 @@ impl InboundRequestHandler {
                 uni = self.connection.accept_uni() => match uni {
                     Ok(stream) => {
                         tracing::trace!(id = %stream.id(), "incoming uni stream");
-                        let handler = UniStreamRequestHandler::new(
-                            &self.config,
-                            self.connection.request_meta().clone(),
-                            self.service.clone(),
-                            stream,
-                        );
-                        inflight_requests.spawn(handler.handle());
                         metrics::counter!(METRIC_IN_MESSAGES_TOTAL).increment(1);
-                        metrics::gauge!(METRIC_REQ_HANDLERS).increment(1);
+
+                        match self.quota.try_acquire() {
+                            Ok(permit) => {
+                                let handler = UniStreamRequestHandler::new(
+                                    &self.config,
+                                    self.connection.request_meta().clone(),
+                                    self.connection.compression().cloned(),
+                                    self.service.clone(),
+                                    stream,
+                                    permit,
+                                );
+                                inflight_requests.spawn(handler.handle());
+                                metrics::gauge!(METRIC_REQ_HANDLERS).increment(1);
+                            }
+                            Err(reason) => {
+                                tracing::debug!(
+                                    peer_id = %self.connection.peer_id(),
+                                    %reason,
+                                    "rejected an incoming message",
+                                );
+                                self.quota.reject(None, stream, reason);
+                            }
+                        }
                     },
                     Err(e) => {
                         tracing::trace!("failed to accept an incoming uni stream: {e:?}");
@@ -95,16 +123,31 @@ impl InboundRequestHandler {
                 bi = self.connection.accept_bi() => match bi {
                     Ok((tx, rx)) => {
                         tracing::trace!(id = %tx.id(), "incoming bi stream");
-                        let handler = BiStreamRequestHandler::new(
-                            &self.config,
-                            self.connection.request_meta().clone(),
-                            self.service.clone(),
-                            tx,
-                            rx,
-                        );
-                        inflight_requests.spawn(handler.handle());
                         metrics::counter!(METRIC_IN_QUERIES_TOTAL).increment(1);
-                        metrics::gauge!(METRIC_REQ_HANDLERS).increment(1);
+
+                        match self.quota.try_acquire() {
+                            Ok(permit) => {
+                                let handler = BiStreamRequestHandler::new(
+                                    &self.config,
+                                    self.connection.request_meta().clone(),
+                                    self.connection.compression().cloned(),
+                                    self.service.clone(),
+                                    tx,
+                                    rx,
+                                    permit,
+                                );
+                                inflight_requests.spawn(handler.handle());
+                                metrics::gauge!(METRIC_REQ_HANDLERS).increment(1);
+                            }
+                            Err(reason) => {
+                                tracing::debug!(
+                                    peer_id = %self.connection.peer_id(),
+                                    %reason,
+                                    "rejected an incoming query",
+                                );
+                                self.quota.reject(Some(tx), rx, reason);
+                            }
+                        }
                     }
                     Err(e) => {
                         tracing::trace!("failed to accept an incoming bi stream: {e:?}");
@@ -158,21 +201,27 @@ impl InboundRequestHandler {
 
 struct UniStreamRequestHandler {
     meta: Arc<InboundRequestMeta>,
+    compression: Option<WireCompression>,
     service: BoxCloneService<ServiceRequest, Response>,
     recv_stream: FramedRead<RecvStream, LengthDelimitedCodec>,
+    permit: RequestPermit,
 }
 
 impl UniStreamRequestHandler {
     fn new(
         config: &NetworkConfig,
         meta: Arc<InboundRequestMeta>,
+        compression: Option<WireCompression>,
         service: BoxCloneService<ServiceRequest, Response>,
         recv_stream: RecvStream,
+        permit: RequestPermit,
     ) -> Self {
         Self {
             meta,
+            compression,
             service,
             recv_stream: FramedRead::new(recv_stream, make_codec(config)),
+            permit,
         }
     }
 
@@ -185,7 +234,9 @@ impl UniStreamRequestHandler {
     }
 
     async fn do_handle(mut self) -> Result<()> {
-        let req = recv_request(&mut self.recv_stream).await?;
+        let req = recv_request(&mut self.recv_stream, self.compression.as_ref()).await?;
+        self.permit.consume_bytes(req.body.len());
+
         self.service
             .on_message(ServiceRequest {
                 metadata: self.meta,
@@ -198,24 +249,30 @@ impl UniStreamRequestHandler {
 
 struct BiStreamRequestHandler {
     meta: Arc<InboundRequestMeta>,
+    compression: Option<WireCompression>,
     service: BoxCloneService<ServiceRequest, Response>,
     send_stream: FramedWrite<SendStream, LengthDelimitedCodec>,
     recv_stream: FramedRead<RecvStream, LengthDelimitedCodec>,
+    permit: RequestPermit,
 }
 
 impl BiStreamRequestHandler {
     fn new(
         config: &NetworkConfig,
         meta: Arc<InboundRequestMeta>,
+        compression: Option<WireCompression>,
         service: BoxCloneService<ServiceRequest, Response>,
         send_stream: SendStream,
         recv_stream: RecvStream,
+        permit: RequestPermit,
     ) -> Self {
         Self {
             meta,
+            compression,
             service,
             send_stream: FramedWrite::new(send_stream, make_codec(config)),
             recv_stream: FramedRead::new(recv_stream, make_codec(config)),
+            permit,
         }
     }
 
@@ -228,7 +285,9 @@ impl BiStreamRequestHandler {
     }
 
     async fn do_handle(mut self) -> Result<()> {
-        let req = recv_request(&mut self.recv_stream).await?;
+        let req = recv_request(&mut self.recv_stream, self.compression.as_ref()).await?;
+        self.permit.consume_bytes(req.body.len());
+
         let handler = self.service.on_query(ServiceRequest {
             metadata: self.meta,
             body: req.body,
@@ -238,7 +297,8 @@ impl BiStreamRequestHandler {
         tokio::select! {
             res = handler => {
                 if let Some(res) = res {
-                    send_response(&mut self.send_stream, res).await?;
+                    self.permit.consume_bytes(res.body.len());
+                    send_response(&mut self.send_stream, res, self.compression.as_ref()).await?;
                 }
                 self.send_stream.get_mut().finish().expect("must not be closed twise");
                 _ = self.send_stream.get_mut().stopped().await;
@@ -248,3 +308,285 @@ impl BiStreamRequestHandler {
         }
     }
 }
+
+/// Limits for inbound requests shared between all connections.
+///
+/// Per-peer quotas are keyed by the peer id, so they are not
+/// reset when the peer reconnects.
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct InboundQuotas(Arc<InboundQuotasInner>);
+
+impl InboundQuotas {
+    pub(crate) fn new(config: &InboundQuotaConfig) -> Self {
+        Self(Arc::new(InboundQuotasInner {
+            config: config.clone(),
+            global_requests: config
+                .max_concurrent_queries
+                .map(|limit| Arc::new(Semaphore::new(limit))),
+            peers: Default::default(),
+        }))
+    }
+
+    fn for_peer(&self, peer_id: &PeerId) -> InboundQuota {
+        let config = &self.0.config;
+        let peer = self
+            .0
+            .peers
+            .entry(*peer_id)
+            .or_insert_with(|| {
+                Arc::new(PeerQuota {
+                    requests: config
+                        .max_concurrent_queries_per_peer
+                        .map(|limit| Arc::new(Semaphore::new(limit))),
+                    bandwidth: config.max_bytes_per_sec_per_peer.map(|rate| {
+                        Mutex::new(BandwidthBucket::new(rate.as_u64() as f64, Instant::now()))
+                    }),
+                    rejected_requests: AtomicU64::new(0),
+                    total_bytes: AtomicU64::new(0),
+                })
+            })
+            .clone();
+
+        InboundQuota {
+            peer,
+            global_requests: self.0.global_requests.clone(),
+        }
+    }
+
+    /// Returns inbound request counters of the peer.
+    ///
+    /// NOTE: Counters are reset when the quota of a disconnected peer is removed.
+    pub fn get(&self, peer_id: &PeerId) -> Option<InboundQuotaInfo> {
+        self.0.peers.get(peer_id).map(|peer| peer.info())
+    }
+
+    /// Returns inbound request counters of all tracked peers.
+    pub fn peers(&self) -> Vec<(PeerId, InboundQuotaInfo)> {
+        self.0
+            .peers
+            .iter()
+            .map(|item| (*item.key(), item.value().info()))
+            .collect()
+    }
+
+    /// Removes quotas of disconnected peers which have no bandwidth debt.
+    pub(crate) fn cleanup(&self) {
+        let now = Instant::now();
+        self.0
+            .peers
+            .retain(|_, peer| Arc::strong_count(peer) > 1 || peer.is_in_debt(now));
+    }
+}
+
+struct InboundQuotasInner {
+    config: InboundQuotaConfig,
+    global_requests: Option<Arc<Semaphore>>,
+    peers: FastDashMap<PeerId, Arc<PeerQuota>>,
+}
+
+/// Limits for inbound requests of a single connection.
+struct InboundQuota {
+    peer: Arc<PeerQuota>,
+    global_requests: Option<Arc<Semaphore>>,
+}
+
+impl InboundQuota {
+    fn try_acquire(&self) -> Result<RequestPermit, QueryRejectedError> {
+        if let Some(bandwidth) = &self.peer.bandwidth {
+            if !bandwidth.lock().has_tokens(Instant::now()) {
+                return Err(QueryRejectedError::BandwidthExceeded);
+            }
+        }
+
+        let peer_permit = match &self.peer.requests {
+            Some(semaphore) => match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => return Err(QueryRejectedError::TooManyQueries),
+            },
+            None => None,
+        };
+
+        let global_permit = match &self.global_requests {
+            Some(semaphore) => match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => return Err(QueryRejectedError::Overloaded),
+            },
+            None => None,
+        };
+
+        Ok(RequestPermit {
+            peer: self.peer.clone(),
+            _peer_permit: peer_permit,
+            _global_permit: global_permit,
+        })
+    }
+
+    fn reject(
+        &self,
+        send_stream: Option<SendStream>,
+        mut recv_stream: RecvStream,
+        reason: QueryRejectedError,
+    ) {
+        self.peer.rejected_requests.fetch_add(1, Ordering::Relaxed);
+
+        let code = reason.to_code();
+        _ = recv_stream.stop(code);
+        if let Some(mut send_stream) = send_stream {
+            _ = send_stream.reset(code);
+        }
+
+        metrics::counter!(METRIC_IN_REQUESTS_REJECTED_TOTAL, "reason" => reason.as_str())
+            .increment(1);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InboundQuotaInfo {
+    /// Number of requests rejected due to quotas.
+    pub rejected_requests: u64,
+    /// Total size of the accepted requests and their responses in bytes.
+    pub total_bytes: u64,
+}
+
+struct PeerQuota {
+    requests: Option<Arc<Semaphore>>,
+    bandwidth: Option<Mutex<BandwidthBucket>>,
+    rejected_requests: AtomicU64,
+    total_bytes: AtomicU64,
+}
+
+impl PeerQuota {
+    fn info(&self) -> InboundQuotaInfo {
+        InboundQuotaInfo {
+            rejected_requests: self.rejected_requests.load(Ordering::Relaxed),
+            total_bytes: self.total_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    fn is_in_debt(&self, now: Instant) -> bool {
+        match &self.bandwidth {
+            Some(bandwidth) => !bandwidth.lock().is_full(now),
+            None => false,
+        }
+    }
+}
+
+struct RequestPermit {
+    peer: Arc<PeerQuota>,
+    _peer_permit: Option<OwnedSemaphorePermit>,
+    _global_permit: Option<OwnedSemaphorePermit>,
+}
+
+impl RequestPermit {
+    fn consume_bytes(&self, bytes: usize) {
+        metrics::counter!(METRIC_IN_REQUEST_BYTES_TOTAL).increment(bytes as u64);
+        self.peer
+            .total_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        if let Some(bandwidth) = &self.peer.bandwidth {
+            bandwidth.lock().consume(bytes, Instant::now());
+        }
+    }
+}
+
+/// Byte budget which is allowed to go into debt.
+///
+/// Queries are rejected until the debt is repaid.
+struct BandwidthBucket {
+    rate: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl BandwidthBucket {
+    fn new(rate: f64, now: Instant) -> Self {
+        Self {
+            rate,
+            tokens: rate,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.updated_at = now;
+    }
+
+    fn has_tokens(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens > 0.0
+    }
+
+    fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= self.rate
+    }
+
+    fn consume(&mut self, bytes: usize, now: Instant) {
+        self.refill(now);
+        self.tokens -= bytes as f64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn bandwidth_bucket_allows_debt() {
+        let now = Instant::now();
+        let mut bucket = BandwidthBucket::new(1000.0, now);
+        assert!(bucket.has_tokens(now));
+
+        // A single large response is allowed, but the next one must wait
+        bucket.consume(3000, now);
+        assert!(!bucket.has_tokens(now));
+        assert!(!bucket.has_tokens(now + Duration::from_secs(1)));
+        assert!(bucket.has_tokens(now + Duration::from_millis(2100)));
+
+        // Refill is capped by the rate
+        assert!(!bucket.is_full(now + Duration::from_secs(3)));
+        assert!(bucket.is_full(now + Duration::from_secs(100)));
+        assert_eq!(bucket.tokens, 1000.0);
+    }
+
+    #[test]
+    fn peer_quota_survives_reconnect() {
+        let quotas = InboundQuotas::new(&InboundQuotaConfig {
+            max_concurrent_queries_per_peer: Some(1),
+            max_bytes_per_sec_per_peer: Some(bytesize::ByteSize::b(1000)),
+            max_concurrent_queries: None,
+        });
+        let peer_id = PeerId([1; 32]);
+
+        let quota = quotas.for_peer(&peer_id);
+        let permit = quota.try_acquire().unwrap();
+        permit.consume_bytes(5000);
+        drop(quota);
+
+        // New connection of the same peer shares the quota
+        let quota = quotas.for_peer(&peer_id);
+        assert_eq!(
+            quota.try_acquire().err(),
+            Some(QueryRejectedError::TooManyQueries)
+        );
+        drop(permit);
+        assert_eq!(
+            quota.try_acquire().err(),
+            Some(QueryRejectedError::BandwidthExceeded)
+        );
+        drop(quota);
+
+        // Quota is kept while the peer is in debt
+        quotas.cleanup();
+        assert!(quotas.0.peers.contains_key(&peer_id));
+
+        // Other peers are not affected
+        let other = quotas.for_peer(&PeerId([2; 32]));
+        assert!(other.try_acquire().is_ok());
+    }
+}
@@ -1,12 +1,14 @@
 use anyhow::Result;
+use bytes::Bytes;
 use futures_util::sink::SinkExt;
 use futures_util::StreamExt;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
 
+use crate::network::compression::WireCompression;
 use crate::network::config::NetworkConfig;
 use crate::network::connection::Connection;
-use crate::types::{Direction, Request, Response, Version};
+use crate::types::{Direction, Features, PeerCapabilities, Request, Response, Version};
 
 pub(crate) fn make_codec(config: &NetworkConfig) -> LengthDelimitedCodec {
     let mut builder = LengthDelimitedCodec::builder();
@@ -16,31 +18,65 @@ pub(crate) fn make_codec(config: &NetworkConfig) -> LengthDelimitedCodec {
     builder.length_field_length(4).big_endian().new_codec()
 }
 
-pub(crate) async fn handshake(connection: &Connection) -> Result<(), HandshakeError> {
-    match connection.origin() {
+/// Server name which is used by clients supporting the capabilities exchange.
+///
+/// NOTE: Legacy nodes ignore the server name, so it is used as a signal
+/// for the server to wait for the client capabilities.
+pub(crate) const EXTENDED_HANDSHAKE_SERVER_NAME: &str = "ext.tycho";
+
+/// Exchanges capabilities with the peer and returns the common ones.
+///
+/// Inbound side always sends its capabilities right after the version header
+/// (legacy peers just ignore the rest of the stream). Outbound side responds
+/// with its own capabilities only when the inbound side has sent them.
+pub(crate) async fn handshake(
+    connection: &Connection,
+    local: &PeerCapabilities,
+) -> Result<PeerCapabilities, HandshakeError> {
+    let remote = match connection.origin() {
         Direction::Inbound => {
             let mut send_stream = connection
                 .open_uni()
                 .await
                 .map_err(HandshakeError::ConnectionFailed)?;
 
-            send_version(&mut send_stream, Version::V1)
+            send_capabilities(&mut send_stream, local)
                 .await
                 .map_err(HandshakeError::WireError)?;
 
             // Finish the stream (ignore double-finish error)
             _ = send_stream.finish();
 
+            let remote =
+                if connection.server_name().as_deref() == Some(EXTENDED_HANDSHAKE_SERVER_NAME) {
+                    let mut recv_stream = connection
+                        .accept_uni()
+                        .await
+                        .map_err(HandshakeError::ConnectionFailed)?;
+
+                    recv_capabilities(&mut recv_stream)
+                        .await
+                        .map_err(HandshakeError::WireError)?
+                        .ok_or_else(|| {
+                            HandshakeError::WireError(std::io::Error::new(
+                                std::io::ErrorKind::UnexpectedEof,
+                                WireError::UnexpectedEof,
+                            ))
+                        })?
+                } else {
+                    PeerCapabilities::LEGACY
+                };
+
             match send_stream.stopped().await {
-                Ok(_) => Ok(()),
+                Ok(_) => remote,
                 Err(quinn::StoppedError::ConnectionLost(e)) => {
-                    Err(HandshakeError::ConnectionFailed(e))
+                    return Err(HandshakeError::ConnectionFailed(e));
                 }
                 Err(quinn::StoppedError::ZeroRttRejected) => {
-                    Err(HandshakeError::WireError(std::io::Error::new(
+                    return Err(HandshakeError::WireError(std::io::Error::new(
                         std::io::ErrorKind::InvalidData,
                         WireError::ZeroRttRejected,
-                    )))
+                    )));
                 }
             }
         }
@@ -50,12 +86,38 @@ pub(crate) async fn handshake(connection: &Connection) -> Result<(), HandshakeEr
                 .await
                 .map_err(HandshakeError::ConnectionFailed)?;
 
-            match recv_version(&mut recv_stream).await {
-                Ok(_) => Ok(()),
-                Err(e) => Err(HandshakeError::WireError(e)),
+            match recv_capabilities(&mut recv_stream)
+                .await
+                .map_err(HandshakeError::WireError)?
+            {
+                Some(remote) => {
+                    let mut send_stream = connection
+                        .open_uni()
+                        .await
+                        .map_err(HandshakeError::ConnectionFailed)?;
+
+                    send_capabilities(&mut send_stream, local)
+                        .await
+                        .map_err(HandshakeError::WireError)?;
+
+                    // Finish the stream (ignore double-finish error)
+                    _ = send_stream.finish();
+
+                    if let Err(quinn::StoppedError::ConnectionLost(e)) = send_stream.stopped().await
+                    {
+                        return Err(HandshakeError::ConnectionFailed(e));
+                    }
+
+                    remote
+                }
+                None => PeerCapabilities::LEGACY,
             }
         }
-    }
+    };
+
+    local
+        .intersect(&remote)
+        .ok_or(HandshakeError::IncompatibleVersion(remote))
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -64,71 +126,184 @@ pub(crate) enum HandshakeError {
     ConnectionFailed(quinn::ConnectionError),
     #[error("wire error")]
     WireError(#[source] std::io::Error),
+    #[error("no common protocol versions with the peer: {0:?}")]
+    IncompatibleVersion(PeerCapabilities),
 }
 
 pub(crate) async fn send_request<T: AsyncWrite + Unpin>(
     send_stream: &mut FramedWrite<T, LengthDelimitedCodec>,
     request: Request,
+    compression: Option<&WireCompression>,
 ) -> std::io::Result<()> {
-    send_version(send_stream.get_mut(), request.version).await?;
-    send_stream.send(request.body).await
+    let (flags, body) = encode_body(request.body, compression)?;
+    send_header(send_stream.get_mut(), request.version, flags).await?;
+    send_stream.send(body).await
 }
 
 pub(crate) async fn recv_request<T: AsyncRead + Unpin>(
     recv_stream: &mut FramedRead<T, LengthDelimitedCodec>,
+    compression: Option<&WireCompression>,
 ) -> std::io::Result<Request> {
-    let version = recv_version(recv_stream.get_mut()).await?;
-    match recv_stream.next().await {
-        Some(body) => Ok(Request {
-            version,
-            body: body?.freeze(),
-        }),
-        None => Err(std::io::Error::new(
-            std::io::ErrorKind::UnexpectedEof,
-            WireError::UnexpectedEof,
-        )),
-    }
+    let (version, flags) = recv_header(recv_stream.get_mut()).await?;
+    let body = recv_body(recv_stream, flags, compression).await?;
+    Ok(Request { version, body })
 }
 
 pub(crate) async fn send_response<T: AsyncWrite + Unpin>(
     send_stream: &mut FramedWrite<T, LengthDelimitedCodec>,
     response: Response,
+    compression: Option<&WireCompression>,
 ) -> std::io::Result<()> {
-    send_version(send_stream.get_mut(), response.version).await?;
-    send_stream.send(response.body).await
+    let (flags, body) = encode_body(response.body, compression)?;
+    send_header(send_stream.get_mut(), response.version, flags).await?;
+    send_stream.send(body).await
 }
 
 pub(crate) async fn recv_response<T: AsyncRead + Unpin>(
     recv_stream: &mut FramedRead<T, LengthDelimitedCodec>,
+    compression: Option<&WireCompression>,
 ) -> std::io::Result<Response> {
-    let version = recv_version(recv_stream.get_mut()).await?;
-    match recv_stream.next().await {
-        Some(body) => Ok(Response {
-            version,
-            body: body?.freeze(),
-        }),
-        None => Err(std::io::Error::new(
+    let (version, flags) = recv_header(recv_stream.get_mut()).await?;
+    let body = recv_body(recv_stream, flags, compression).await?;
+    Ok(Response { version, body })
+}
+
+/// Compresses the body if the compression was negotiated and is worth it.
+fn encode_body(body: Bytes, compression: Option<&WireCompression>) -> std::io::Result<(u8, Bytes)> {
+    if let Some(compression) = compression {
+        if let Some(compressed) = compression.compress(&body)? {
+            return Ok((FLAG_COMPRESSED, compressed));
+        }
+    }
+    Ok((0, body))
+}
+
+async fn recv_body<T: AsyncRead + Unpin>(
+    recv_stream: &mut FramedRead<T, LengthDelimitedCodec>,
+    flags: u8,
+    compression: Option<&WireCompression>,
+) -> std::io::Result<Bytes> {
+    let body = match recv_stream.next().await {
+        Some(body) => body?.freeze(),
+        None => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                WireError::UnexpectedEof,
+            ))
+        }
+    };
+
+    match (flags, compression) {
+        (0, _) => Ok(body),
+        // NOTE: Compressed frames are only allowed if the compression was negotiated.
+        (FLAG_COMPRESSED, Some(compression)) => {
+            let max_len = recv_stream.decoder().max_frame_length();
+            compression.decompress(&body, max_len)
+        }
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            WireError::InvalidHeader,
+        )),
+    }
+}
+
+async fn send_capabilities<T: AsyncWrite + Unpin>(
+    send_stream: &mut T,
+    capabilities: &PeerCapabilities,
+) -> std::io::Result<()> {
+    // NOTE: Legacy peers only understand the first version in the header.
+    send_version(send_stream, Version::V1).await?;
+
+    let mut buffer = [0; CAPABILITIES_LEN];
+    buffer[0..2].copy_from_slice(&capabilities.min_version.to_u16().to_be_bytes());
+    buffer[2..4].copy_from_slice(&capabilities.max_version.to_u16().to_be_bytes());
+    buffer[4..12].copy_from_slice(&capabilities.features.bits().to_be_bytes());
+    send_stream.write_all(&buffer).await
+}
+
+/// Returns `None` if the peer doesn't support the capabilities exchange.
+async fn recv_capabilities<T: AsyncRead + Unpin>(
+    recv_stream: &mut T,
+) -> std::io::Result<Option<PeerCapabilities>> {
+    recv_version(recv_stream).await?;
+
+    // NOTE: Newer peers might send some extra data which is ignored.
+    let mut buffer = Vec::with_capacity(CAPABILITIES_LEN);
+    recv_stream
+        .take(MAX_CAPABILITIES_LEN)
+        .read_to_end(&mut buffer)
+        .await?;
+
+    if buffer.is_empty() {
+        return Ok(None);
+    } else if buffer.len() < CAPABILITIES_LEN {
+        return Err(std::io::Error::new(
             std::io::ErrorKind::UnexpectedEof,
             WireError::UnexpectedEof,
-        )),
+        ));
     }
+
+    // NOTE: Versions newer than the latest known one are clamped
+    // to still be able to find the common ones.
+    let min_version = match u16::from_be_bytes([buffer[0], buffer[1]]) {
+        0 => Some(Version::V1),
+        version => Version::try_from_u16(version),
+    };
+    let max_version = match u16::from_be_bytes([buffer[2], buffer[3]]) {
+        version if version > Version::LATEST.to_u16() => Some(Version::LATEST),
+        version => Version::try_from_u16(version),
+    };
+    let (Some(min_version), Some(max_version)) = (min_version, max_version) else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            WireError::InvalidVersion,
+        ));
+    };
+
+    let features = u64::from_be_bytes(buffer[4..12].try_into().unwrap());
+
+    Ok(Some(PeerCapabilities {
+        min_version,
+        max_version,
+        features: Features::from_bits(features),
+    }))
 }
 
 async fn send_version<T: AsyncWrite + Unpin>(
     send_stream: &mut T,
     version: Version,
+) -> std::io::Result<()> {
+    send_header(send_stream, version, 0).await
+}
+
+async fn recv_version<T: AsyncRead + Unpin>(recv_stream: &mut T) -> std::io::Result<Version> {
+    match recv_header(recv_stream).await? {
+        (version, 0) => Ok(version),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            WireError::InvalidHeader,
+        )),
+    }
+}
+
+async fn send_header<T: AsyncWrite + Unpin>(
+    send_stream: &mut T,
+    version: Version,
+    flags: u8,
 ) -> std::io::Result<()> {
     let mut buffer: [u8; 8] = [0; 8];
     buffer[0..=4].copy_from_slice(MAGIC);
     buffer[5..=6].copy_from_slice(&version.to_u16().to_be_bytes());
+    buffer[7] = flags;
     send_stream.write_all(&buffer).await
 }
 
-async fn recv_version<T: AsyncRead + Unpin>(recv_stream: &mut T) -> std::io::Result<Version> {
+/// Reads the header and returns the version and the frame flags.
+async fn recv_header<T: AsyncRead + Unpin>(recv_stream: &mut T) -> std::io::Result<(Version, u8)> {
     let mut buffer: [u8; 8] = [0; 8];
     recv_stream.read_exact(&mut buffer).await?;
 
-    if &buffer[0..=4] != MAGIC || buffer[7] != 0 {
+    if &buffer[0..=4] != MAGIC || buffer[7] & !KNOWN_FLAGS != 0 {
         return Err(std::io::Error::new(
             std::io::ErrorKind::InvalidData,
             WireError::InvalidHeader,
@@ -136,7 +311,7 @@ async fn recv_version<T: AsyncRead + Unpin>(recv_stream: &mut T) -> std::io::Res
     }
 
     match Version::try_from_u16(u16::from_be_bytes([buffer[5], buffer[6]])) {
-        Some(version) => Ok(version),
+        Some(version) => Ok((version, buffer[7])),
         None => Err(std::io::Error::new(
             std::io::ErrorKind::InvalidData,
             WireError::InvalidVersion,
@@ -146,6 +321,68 @@ async fn recv_version<T: AsyncRead + Unpin>(recv_stream: &mut T) -> std::io::Res
 
 const MAGIC: &[u8; 5] = b"tycho";
 
+/// The frame body is compressed with zstd.
+///
+/// NOTE: Legacy peers require the flags byte to be zero, so flags
+/// are only set when the corresponding feature was negotiated.
+const FLAG_COMPRESSED: u8 = 0b1;
+const KNOWN_FLAGS: u8 = FLAG_COMPRESSED;
+
+/// Serialized capabilities size: min and max versions and a features bitset.
+const CAPABILITIES_LEN: usize = 2 + 2 + 8;
+const MAX_CAPABILITIES_LEN: u64 = 1024;
+
+/// Query or message was rejected by the remote peer due to its inbound quotas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum QueryRejectedError {
+    #[error("too many concurrent queries from this peer")]
+    TooManyQueries,
+    #[error("bandwidth limit for this peer exceeded")]
+    BandwidthExceeded,
+    #[error("remote peer is overloaded")]
+    Overloaded,
+}
+
+impl QueryRejectedError {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::TooManyQueries => "too_many_queries",
+            Self::BandwidthExceeded => "bandwidth_exceeded",
+            Self::Overloaded => "overloaded",
+        }
+    }
+
+    pub(crate) fn to_code(self) -> quinn::VarInt {
+        quinn::VarInt::from_u32(match self {
+            Self::TooManyQueries => 0x101,
+            Self::BandwidthExceeded => 0x102,
+            Self::Overloaded => 0x103,
+        })
+    }
+
+    pub(crate) fn from_code(code: quinn::VarInt) -> Option<Self> {
+        match code.into_inner() {
+            0x101 => Some(Self::TooManyQueries),
+            0x102 => Some(Self::BandwidthExceeded),
+            0x103 => Some(Self::Overloaded),
+            _ => None,
+        }
+    }
+
+    /// Extracts the rejection reason from a stream error.
+    pub(crate) fn from_io_error(error: &std::io::Error) -> Option<Self> {
+        let error = error.get_ref()?;
+        let code = if let Some(quinn::ReadError::Reset(code)) = error.downcast_ref() {
+            *code
+        } else if let Some(quinn::WriteError::Stopped(code)) = error.downcast_ref() {
+            *code
+        } else {
+            return None;
+        };
+        Self::from_code(code)
+    }
+}
+
 #[derive(Clone, Copy, Debug, thiserror::Error)]
 enum WireError {
     #[error("invalid header")]
@@ -305,6 +305,16 @@ impl<'a> TlRead<'a> for ValueRef<'a> {
     }
 }
 
+/// A snapshot of the routing table saved on disk.
+#[derive(Debug, Clone, TlRead, TlWrite)]
+#[tl(boxed, id = "dht.peerStore", scheme = "proto.tl")]
+pub struct PeerStore {
+    /// Unix timestamp when the snapshot was made.
+    pub saved_at: u32,
+    /// DHT nodes ordered by buckets.
+    pub nodes: Vec<Arc<PeerInfo>>,
+}
+
 /// A response for the [`rpc::FindNode`] query.
 #[derive(Debug, Clone, TlRead, TlWrite)]
 #[tl(boxed, id = "dht.nodesFound", scheme = "proto.tl")]
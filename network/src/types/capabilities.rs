@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::Version;
+
+/// Wire protocol capabilities of a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerCapabilities {
+    /// The oldest supported protocol version.
+    pub min_version: Version,
+    /// The newest supported protocol version.
+    pub max_version: Version,
+    /// Supported optional features.
+    pub features: Features,
+}
+
+impl PeerCapabilities {
+    /// Capabilities of peers which don't support the capabilities exchange.
+    pub const LEGACY: Self = Self {
+        min_version: Version::V1,
+        max_version: Version::V1,
+        features: Features::empty(),
+    };
+
+    /// Capabilities of this node with the specified features enabled.
+    pub const fn local(features: Features) -> Self {
+        Self {
+            min_version: Version::V1,
+            max_version: Version::LATEST,
+            features,
+        }
+    }
+
+    /// Returns `true` if all of the specified features are supported.
+    pub const fn supports(&self, features: Features) -> bool {
+        self.features.contains(features)
+    }
+
+    /// Returns capabilities supported by both sides,
+    /// or `None` if there are no common protocol versions.
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let min_version = std::cmp::max(self.min_version, other.min_version);
+        let max_version = std::cmp::min(self.max_version, other.max_version);
+        (min_version <= max_version).then_some(Self {
+            min_version,
+            max_version,
+            features: self.features.intersection(other.features),
+        })
+    }
+}
+
+impl Default for PeerCapabilities {
+    #[inline]
+    fn default() -> Self {
+        Self::LEGACY
+    }
+}
+
+/// A set of optional wire protocol features.
+///
+/// NOTE: Unknown bits are preserved so that newer peers can
+/// advertise features which this node doesn't know about yet.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct Features(u64);
+
+impl Features {
+    /// Payloads above the size threshold can be compressed with zstd.
+    pub const ZSTD: Self = Self(1 << 0);
+
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    #[inline]
+    pub const fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    #[inline]
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline]
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[inline]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    #[inline]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+}
+
+impl std::fmt::Debug for Features {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Features({:#x})", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities_intersection() {
+        let local = PeerCapabilities::local(Features::from_bits(0b011));
+        let remote = PeerCapabilities {
+            min_version: Version::V1,
+            max_version: Version::V1,
+            features: Features::from_bits(0b110),
+        };
+
+        let common = local.intersect(&remote).unwrap();
+        assert_eq!(common.max_version, Version::V1);
+        assert_eq!(common.features, Features::from_bits(0b010));
+        assert!(common.supports(Features::from_bits(0b010)));
+        assert!(!common.supports(Features::from_bits(0b001)));
+
+        assert_eq!(
+            PeerCapabilities::LEGACY.intersect(&local).unwrap().features,
+            Features::empty()
+        );
+    }
+}
@@ -1,4 +1,5 @@
 pub use self::address::Address;
+pub use self::capabilities::{Features, PeerCapabilities};
 pub use self::peer_event::{DisconnectReason, PeerEvent, PeerEventData};
 pub use self::peer_id::PeerId;
 pub use self::peer_info::{PeerAffinity, PeerInfo};
@@ -12,6 +13,7 @@ pub use self::service::{
 };
 
 mod address;
+mod capabilities;
 mod peer_event;
 mod peer_id;
 mod peer_info;
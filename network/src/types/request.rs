@@ -4,7 +4,7 @@ use std::sync::Arc;
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 
-use crate::types::PeerId;
+use crate::types::{PeerCapabilities, PeerId};
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u16)]
@@ -14,6 +14,9 @@ pub enum Version {
 }
 
 impl Version {
+    /// The newest protocol version supported by this node.
+    pub const LATEST: Self = Self::V1;
+
     pub fn try_from_u16(value: u16) -> Option<Self> {
         match value {
             1 => Some(Self::V1),
@@ -144,6 +147,9 @@ pub struct InboundRequestMeta {
     pub origin: Direction,
     #[serde(with = "tycho_util::serde_helpers::socket_addr")]
     pub remote_address: SocketAddr,
+    /// Capabilities negotiated with the peer during the handshake.
+    #[serde(default)]
+    pub capabilities: PeerCapabilities,
 }
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
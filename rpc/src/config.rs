@@ -1,6 +1,8 @@
-use std::net::{Ipv4Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::time::Duration;
 
+use everscale_types::cell::HashBytes;
+use everscale_types::models::StdAddr;
 use serde::{Deserialize, Serialize};
 use tycho_util::serde_helpers;
 
@@ -22,7 +24,24 @@ pub struct RpcConfig {
     /// Default: `4` (= 16 virtual shards).
     pub shard_split_depth: u8,
 
+    /// Max number of requests in a single JSON-RPC batch.
+    ///
+    /// Default: `100`.
+    pub max_batch_size: usize,
+
     pub storage: RpcStorage,
+
+    /// WebSocket subscriptions config.
+    pub subscriptions: SubscriptionsConfig,
+
+    /// Get-methods execution config.
+    pub run_get_method: RunGetMethodConfig,
+
+    /// Sent messages tracking config.
+    pub pending_messages: PendingMessagesConfig,
+
+    /// Per-client rate limits.
+    pub rate_limits: RateLimitsConfig,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -70,14 +89,224 @@ impl Default for RpcConfig {
             listen_addr: (Ipv4Addr::UNSPECIFIED, 8000).into(),
             generate_stub_keyblock: false,
             shard_split_depth: 4,
+            max_batch_size: 100,
             storage: RpcStorage::Full {
                 gc: Some(Default::default()),
                 force_reindex: false,
             },
+            subscriptions: SubscriptionsConfig::default(),
+            run_get_method: RunGetMethodConfig::default(),
+            pending_messages: PendingMessagesConfig::default(),
+            rate_limits: RateLimitsConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubscriptionsConfig {
+    /// Max number of simultaneous WebSocket connections.
+    ///
+    /// Default: `1000`.
+    pub max_connections: usize,
+
+    /// Max number of active subscriptions per connection.
+    ///
+    /// Default: `16`.
+    pub max_subscriptions_per_connection: usize,
+
+    /// Max number of addresses in a single subscription.
+    ///
+    /// Default: `100`.
+    pub max_addresses_per_subscription: usize,
+
+    /// Number of notifications which can be queued for a connection.
+    /// The connection is closed when the client is not able to keep up.
+    ///
+    /// Default: `1024`.
+    pub queue_capacity: usize,
+}
+
+impl Default for SubscriptionsConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 1000,
+            max_subscriptions_per_connection: 16,
+            max_addresses_per_subscription: 100,
+            queue_capacity: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunGetMethodConfig {
+    /// Max amount of gas which can be spent by a single get-method call.
+    ///
+    /// Default: `1000000`.
+    pub gas_limit: u64,
+
+    /// Max time to wait for a get-method execution.
+    ///
+    /// Default: `1s`.
+    #[serde(with = "serde_helpers::humantime")]
+    pub time_limit: Duration,
+
+    /// Max number of get-methods executed at the same time.
+    /// New calls are rejected when the limit is reached.
+    ///
+    /// NOTE: A timed out execution still occupies a slot until
+    /// it runs out of gas.
+    ///
+    /// Default: `16`.
+    pub max_concurrent: usize,
+}
+
+impl Default for RunGetMethodConfig {
+    fn default() -> Self {
+        Self {
+            gas_limit: 1_000_000,
+            time_limit: Duration::from_secs(1),
+            max_concurrent: 16,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PendingMessagesConfig {
+    /// Max number of tracked messages.
+    ///
+    /// Default: `10000`.
+    pub max_pending: usize,
+
+    /// Message TTL used when no explicit expiration time is specified.
+    ///
+    /// Default: `60s`.
+    #[serde(with = "serde_helpers::humantime")]
+    pub default_ttl: Duration,
+
+    /// Max time to wait for the message delivery in a single request.
+    /// Must be less than the request timeout.
+    ///
+    /// Default: `20s`.
+    #[serde(with = "serde_helpers::humantime")]
+    pub max_wait: Duration,
+}
+
+impl Default for PendingMessagesConfig {
+    fn default() -> Self {
+        Self {
+            max_pending: 10000,
+            default_ttl: Duration::from_secs(60),
+            max_wait: Duration::from_secs(20),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitsConfig {
+    /// Whether to limit incoming requests.
+    ///
+    /// Default: `false`.
+    pub enabled: bool,
+
+    /// HTTP header with an API key.
+    ///
+    /// Default: `x-api-key`.
+    pub api_key_header: String,
+
+    /// Limits for clients without a known API key (tracked by IP).
+    ///
+    /// Default: `20` requests per second and `2` heavy requests per second.
+    pub per_ip: ClientLimits,
+
+    /// Known API keys with their own limits.
+    ///
+    /// Requests with an unknown API key are limited by IP.
+    ///
+    /// Default: empty.
+    pub api_keys: Vec<ApiKeyConfig>,
+
+    /// Max number of tracked IP addresses.
+    ///
+    /// Default: `100000`.
+    pub max_tracked_ips: u64,
+
+    /// Addresses of reverse proxies which are allowed to specify
+    /// the client IP in the `X-Forwarded-For` header.
+    ///
+    /// NOTE: The header is ignored for requests from other addresses.
+    ///
+    /// Default: empty.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for RateLimitsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_key_header: "x-api-key".to_owned(),
+            per_ip: ClientLimits::default(),
+            api_keys: Vec::new(),
+            max_tracked_ips: 100000,
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    /// Key name used in metrics.
+    pub name: String,
+
+    /// API key value.
+    pub key: String,
+
+    /// Limits for this key.
+    #[serde(default)]
+    pub limits: ClientLimits,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientLimits {
+    /// Budget for all requests.
+    ///
+    /// Default: `20` requests per second with a burst of `40`.
+    pub requests: TokenBucketConfig,
+
+    /// Separate budget for heavy methods (listing transactions or accounts,
+    /// sending or emulating messages, running get-methods, building proofs,
+    /// loading blocks or historical states).
+    /// Consumed in addition to `requests`.
+    ///
+    /// Default: `2` requests per second with a burst of `5`.
+    pub heavy_requests: TokenBucketConfig,
+}
+
+impl Default for ClientLimits {
+    fn default() -> Self {
+        Self {
+            requests: TokenBucketConfig {
+                rate: 20,
+                burst: 40,
+            },
+            heavy_requests: TokenBucketConfig { rate: 2, burst: 5 },
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TokenBucketConfig {
+    /// Number of requests per second. `0` to disable the limit.
+    pub rate: u32,
+
+    /// Max number of requests which can be made at once.
+    pub burst: u32,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TransactionsGcConfig {
     /// Minimum transaction TTL.
@@ -85,12 +314,45 @@ pub struct TransactionsGcConfig {
     /// Default: `1 week`.
     #[serde(with = "serde_helpers::humantime")]
     pub tx_ttl: Duration,
+
+    /// Custom retention rules for specific accounts.
+    ///
+    /// Rules can only extend the retention, so an account
+    /// keeps transactions for the longest matching TTL.
+    ///
+    /// NOTE: Accounts from the watch list (updated through the control socket)
+    /// always keep the full history.
+    ///
+    /// Default: empty.
+    #[serde(default)]
+    pub retention: Vec<TransactionsRetentionRule>,
 }
 
 impl Default for TransactionsGcConfig {
     fn default() -> Self {
         Self {
             tx_ttl: Duration::from_secs(60 * 60 * 24 * 7),
+            retention: Vec::new(),
         }
     }
 }
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionsRetentionRule {
+    /// Account addresses.
+    #[serde(default)]
+    pub addresses: Vec<StdAddr>,
+
+    /// Code hashes of accounts.
+    ///
+    /// NOTE: Only the current code hash of an account is checked.
+    #[serde(default)]
+    pub code_hashes: Vec<HashBytes>,
+
+    /// Minimum transaction TTL for matching accounts.
+    ///
+    /// Default: keep the full history.
+    #[serde(default, with = "serde_helpers::humantime")]
+    pub tx_ttl: Option<Duration>,
+}
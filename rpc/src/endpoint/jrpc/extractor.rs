@@ -78,15 +78,12 @@ pub struct Jrpc<T: ParseParams> {
     pub method: &'static str,
 }
 
-#[async_trait]
-impl<S, T> FromRequest<S> for Jrpc<T>
+impl<T> Jrpc<T>
 where
     T: ParseParams + for<'de> Deserialize<'de>,
-    S: Send + Sync,
 {
-    type Rejection = JrpcErrorResponse;
-
-    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+    /// Parses a single JSON-RPC request object.
+    pub fn parse(bytes: &[u8]) -> Result<Self, JrpcErrorResponse> {
         #[derive(Deserialize)]
         enum Unknown {
             #[serde(other)]
@@ -109,18 +106,7 @@ where
             params: &'a RawValue,
         }
 
-        let bytes = match Bytes::from_request(req, state).await {
-            Ok(bytes) => bytes,
-            Err(e) => {
-                return Err(JrpcErrorResponse {
-                    id: None,
-                    code: PARSE_ERROR_CODE,
-                    message: e.to_string().into(),
-                });
-            }
-        };
-
-        let (id, code, message) = match serde_json::from_slice::<Request<'_, T>>(&bytes) {
+        let (id, code, message) = match serde_json::from_slice::<Request<'_, T>>(bytes) {
             Ok(req) if req.jsonrpc == JSONRPC_VERSION => match req.method {
                 ParsedMethod::Known(known) => {
                     let method = known.method_name();
@@ -164,6 +150,93 @@ where
     }
 }
 
+#[async_trait]
+impl<S, T> FromRequest<S> for Jrpc<T>
+where
+    T: ParseParams + for<'de> Deserialize<'de>,
+    S: Send + Sync,
+{
+    type Rejection = JrpcErrorResponse;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let bytes = read_body(req, state).await?;
+        Self::parse(&bytes)
+    }
+}
+
+/// Either a single JSON-RPC request or a batch of requests.
+pub enum JrpcBatch<T: ParseParams> {
+    Single(Jrpc<T>),
+    /// Parsed batch items in the request order.
+    Batch(Vec<Result<Jrpc<T>, JrpcErrorResponse>>),
+}
+
+impl<T> JrpcBatch<T>
+where
+    T: ParseParams + for<'de> Deserialize<'de>,
+{
+    /// Parses a single JSON-RPC request object or an array of them.
+    pub fn parse(bytes: &[u8]) -> Result<Self, JrpcErrorResponse> {
+        let is_batch = bytes.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[');
+        if !is_batch {
+            return Jrpc::parse(bytes).map(Self::Single);
+        }
+
+        let items = match serde_json::from_slice::<Vec<&RawValue>>(bytes) {
+            Ok(items) => items,
+            Err(e) => {
+                return Err(JrpcErrorResponse {
+                    id: None,
+                    code: PARSE_ERROR_CODE,
+                    message: e.to_string().into(),
+                })
+            }
+        };
+
+        if items.is_empty() {
+            return Err(JrpcErrorResponse {
+                id: None,
+                code: INVALID_REQUEST_CODE,
+                message: "empty batch".into(),
+            });
+        }
+
+        Ok(Self::Batch(
+            items
+                .into_iter()
+                .map(|item| Jrpc::parse(item.get().as_bytes()))
+                .collect(),
+        ))
+    }
+}
+
+#[async_trait]
+impl<S, T> FromRequest<S> for JrpcBatch<T>
+where
+    T: ParseParams + for<'de> Deserialize<'de>,
+    S: Send + Sync,
+{
+    type Rejection = JrpcErrorResponse;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let bytes = read_body(req, state).await?;
+        Self::parse(&bytes)
+    }
+}
+
+async fn read_body<S>(req: Request, state: &S) -> Result<Bytes, JrpcErrorResponse>
+where
+    S: Send + Sync,
+{
+    Bytes::from_request(req, state)
+        .await
+        .map_err(|e| JrpcErrorResponse {
+            id: None,
+            code: PARSE_ERROR_CODE,
+            message: e.to_string().into(),
+        })
+}
+
 pub struct JrpcOkResponse<T> {
     pub id: i64,
     pub result: T,
@@ -236,3 +309,48 @@ impl IntoResponse for JrpcErrorResponse {
 
 const JSONRPC_FIELD: &str = "jsonrpc";
 const JSONRPC_VERSION: &str = "2.0";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    declare_jrpc_method! {
+        pub enum TestMethodParams: TestMethod {
+            GetStatus(()),
+        }
+    }
+
+    #[test]
+    fn parse_batch() {
+        let req = JrpcBatch::<TestMethod>::parse(
+            br#" [
+                {"jsonrpc":"2.0","id":1,"method":"getStatus","params":null},
+                {"jsonrpc":"2.0","id":2,"method":"unknown","params":{}},
+                {"jsonrpc":"2.0","id":3,"method":"getStatus","params":null}
+            ]"#,
+        );
+        let Ok(JrpcBatch::Batch(items)) = req else {
+            panic!("expected batch");
+        };
+
+        assert_eq!(items.len(), 3);
+        assert!(matches!(
+            &items[0],
+            Ok(Jrpc {
+                id: 1,
+                params: TestMethodParams::GetStatus(()),
+                ..
+            })
+        ));
+        assert!(matches!(&items[1], Err(e) if e.id == Some(2) && e.code == METHOD_NOT_FOUND_CODE));
+        assert!(matches!(&items[2], Ok(item) if item.id == 3));
+
+        let req = JrpcBatch::<TestMethod>::parse(
+            br#"{"jsonrpc":"2.0","id":1,"method":"getStatus","params":null}"#,
+        );
+        assert!(matches!(req, Ok(JrpcBatch::Single(item)) if item.id == 1));
+
+        let req = JrpcBatch::<TestMethod>::parse(b"[]");
+        assert!(matches!(req, Err(e) if e.code == INVALID_REQUEST_CODE));
+    }
+}
@@ -9,20 +9,28 @@ use everscale_types::models::*;
 use everscale_types::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::value::RawValue;
-use tycho_storage::{CodeHashesIter, TransactionsIterBuilder};
+use tycho_storage::{
+    AccountsByBalanceIter, ActiveAccountsIter, CodeHashesIter, McBlockIndexEntry,
+    TransactionsIterBuilder,
+};
 use tycho_util::metrics::HistogramGuard;
 use tycho_util::{bc, serde_helpers};
 
 pub use self::cache::JrpcEndpointCache;
 use self::extractor::{declare_jrpc_method, Jrpc, JrpcErrorResponse, JrpcOkResponse};
 use crate::endpoint::{
-    INTERNAL_ERROR_CODE, INVALID_BOC_CODE, NOT_READY_CODE, NOT_SUPPORTED_CODE, TOO_LARGE_LIMIT_CODE,
+    ClientBuckets, INTERNAL_ERROR_CODE, INVALID_BOC_CODE, INVALID_PARAMS_CODE,
+    INVALID_REQUEST_CODE, NOT_READY_CODE, NOT_SUPPORTED_CODE, STATE_PRUNED_CODE, TIMEOUT_CODE,
+    TOO_LARGE_LIMIT_CODE, TOO_MANY_REQUESTS_CODE,
 };
 use crate::models::{GenTimings, LastTransactionId};
-use crate::state::{LoadedAccountState, RpcState, RpcStateError};
+use crate::state::{
+    method_id_from_name, BlockProofChain, EmulationOutput, LoadedAccountState, MessageDelivery,
+    RpcState, RpcStateError, RunGetMethodResult, StateAt,
+};
 
 mod cache;
-mod extractor;
+pub(super) mod extractor;
 
 declare_jrpc_method! {
     pub enum MethodParams: Method {
@@ -38,10 +46,33 @@ declare_jrpc_method! {
         GetTransactionsList(GetTransactionsListRequest),
         GetTransaction(GetTransactionRequest),
         GetDstTransaction(GetDstTransactionRequest),
+        GetBlock(GetBlockRequest),
+        GetBlockHeader(GetBlockRequest),
+        GetBlockProof(GetBlockRequest),
+        GetShardBlocksForMc(GetShardBlocksForMcRequest),
+        GetLatestMcBlockId(EmptyParams),
+        RunGetMethod(RunGetMethodRequest),
+        EmulateMessage(SendMessageRequest),
+        SendMessageAndWait(SendMessageAndWaitRequest),
+        GetContractStateAt(GetContractStateAtRequest),
+        GetAccountsByBalance(GetAccountsByBalanceRequest),
+        GetActiveAccountsByCodeHash(GetActiveAccountsByCodeHashRequest),
+        GetMcBlockByUtime(GetMcBlockByUtimeRequest),
+        GetMcBlockByLt(GetMcBlockByLtRequest),
     }
 }
 
-pub async fn route(State(state): State<RpcState>, req: Jrpc<Method>) -> Response {
+pub async fn route(
+    State(state): State<RpcState>,
+    req: Jrpc<Method>,
+    client: Option<&ClientBuckets>,
+) -> Response {
+    if let Some(client) = client {
+        if !client.try_acquire(is_heavy(&req.params)) {
+            return too_many_requests_response(req.id);
+        }
+    }
+
     let label = [("method", req.method)];
     let _hist = HistogramGuard::begin_with_labels("tycho_jrpc_request_time", &label);
     match req.params {
@@ -91,6 +122,9 @@ pub async fn route(State(state): State<RpcState>, req: Jrpc<Method>) -> Response
             };
             ok_to_response(req.id, GetLibraryCellResponse { cell: library_boc })
         }
+        MethodParams::GetContractState(p) if p.with_proof => {
+            get_contract_state_with_proof(req.id, &state, p).await
+        }
         MethodParams::GetContractState(p) => {
             let item = match state.get_account_state(&p.address) {
                 Ok(item) => item,
@@ -99,9 +133,10 @@ pub async fn route(State(state): State<RpcState>, req: Jrpc<Method>) -> Response
 
             let account;
             ok_to_response(req.id, match &item {
-                &LoadedAccountState::NotFound { timings } => {
-                    GetContractStateResponse::NotExists { timings }
-                }
+                &LoadedAccountState::NotFound { timings } => GetContractStateResponse::NotExists {
+                    timings,
+                    proof: None,
+                },
                 LoadedAccountState::Found {
                     state, gen_utime, ..
                 } if Some(state.last_trans_lt) <= p.last_transaction_lt => {
@@ -110,6 +145,7 @@ pub async fn route(State(state): State<RpcState>, req: Jrpc<Method>) -> Response
                             gen_lt: state.last_trans_lt,
                             gen_utime: *gen_utime,
                         },
+                        proof: None,
                     }
                 }
                 LoadedAccountState::Found {
@@ -129,9 +165,13 @@ pub async fn route(State(state): State<RpcState>, req: Jrpc<Method>) -> Response
                                     hash: state.last_trans_hash,
                                     lt: state.last_trans_lt,
                                 },
+                                proof: None,
                             }
                         }
-                        Ok(None) => GetContractStateResponse::NotExists { timings },
+                        Ok(None) => GetContractStateResponse::NotExists {
+                            timings,
+                            proof: None,
+                        },
                         Err(e) => {
                             return error_to_response(req.id, RpcStateError::Internal(e.into()))
                         }
@@ -153,6 +193,42 @@ pub async fn route(State(state): State<RpcState>, req: Jrpc<Method>) -> Response
                 Err(e) => error_to_response(req.id, e),
             }
         }
+        MethodParams::GetAccountsByBalance(p) => {
+            if p.limit == 0 {
+                return JrpcOkResponse::new(req.id, [(); 0]).into_response();
+            } else if p.limit > GetAccountsByBalanceResponse::MAX_LIMIT {
+                return too_large_limit_response(req.id);
+            }
+            let continuation = p
+                .continuation
+                .as_ref()
+                .map(|item| (&item.address, Tokens::new(item.balance)));
+            match state.get_accounts_by_balance(p.workchain, continuation) {
+                Ok(list) => ok_to_response(req.id, GetAccountsByBalanceResponse {
+                    list: RefCell::new(Some(list)),
+                    limit: p.limit,
+                }),
+                Err(e) => error_to_response(req.id, e),
+            }
+        }
+        MethodParams::GetActiveAccountsByCodeHash(p) => {
+            if p.limit == 0 {
+                return JrpcOkResponse::new(req.id, [(); 0]).into_response();
+            } else if p.limit > GetActiveAccountsByCodeHashResponse::MAX_LIMIT {
+                return too_large_limit_response(req.id);
+            }
+            let continuation = p
+                .continuation
+                .as_ref()
+                .map(|item| (&item.address, item.last_transaction_lt));
+            match state.get_active_accounts_by_code_hash(&p.code_hash, p.since_lt, continuation) {
+                Ok(list) => ok_to_response(req.id, GetActiveAccountsByCodeHashResponse {
+                    list: RefCell::new(Some(list)),
+                    limit: p.limit,
+                }),
+                Err(e) => error_to_response(req.id, e),
+            }
+        }
         MethodParams::GetTransactionsList(p) => {
             if p.limit == 0 {
                 return JrpcOkResponse::new(req.id, [(); 0]).into_response();
@@ -167,6 +243,25 @@ pub async fn route(State(state): State<RpcState>, req: Jrpc<Method>) -> Response
                 Err(e) => error_to_response(req.id, e),
             }
         }
+        MethodParams::GetTransaction(p) if p.with_proof => {
+            let transaction = match state.get_transaction(&p.id) {
+                Ok(Some(value)) => encode_base64(value),
+                Ok(None) => return ok_to_response(req.id, ()),
+                Err(e) => return error_to_response(req.id, e),
+            };
+
+            match state
+                .get_transaction_proof(&p.id, p.known_key_block_seqno)
+                .await
+            {
+                Ok(Some(tx_proof)) => ok_to_response(req.id, GetTransactionResponse {
+                    transaction,
+                    proof: ProofResponse::new(&tx_proof.proof, tx_proof.block_id, &tx_proof.chain),
+                }),
+                Ok(None) => ok_to_response(req.id, ()),
+                Err(e) => error_to_response(req.id, e),
+            }
+        }
         MethodParams::GetTransaction(p) => match state.get_transaction(&p.id) {
             Ok(value) => ok_to_response(req.id, value.map(encode_base64)),
             Err(e) => error_to_response(req.id, e),
@@ -175,6 +270,161 @@ pub async fn route(State(state): State<RpcState>, req: Jrpc<Method>) -> Response
             Ok(value) => ok_to_response(req.id, value.map(encode_base64)),
             Err(e) => error_to_response(req.id, e),
         },
+        MethodParams::GetBlock(p) => match state.get_block_data(&p.block_id).await {
+            Ok(value) => ok_to_response(req.id, GetBlockResponse {
+                block: value.map(encode_base64),
+            }),
+            Err(e) => error_to_response(req.id, e),
+        },
+        MethodParams::GetBlockHeader(p) => match state.get_block_header(&p.block_id).await {
+            Ok(value) => ok_to_response(req.id, GetBlockHeaderResponse {
+                header: value.map(Boc::encode_base64),
+            }),
+            Err(e) => error_to_response(req.id, e),
+        },
+        MethodParams::GetBlockProof(p) => match state.get_block_proof(&p.block_id).await {
+            Ok(value) => ok_to_response(req.id, GetBlockProofResponse {
+                proof: value.map(encode_base64),
+            }),
+            Err(e) => error_to_response(req.id, e),
+        },
+        MethodParams::GetShardBlocksForMc(p) => match state.get_shard_blocks_for_mc(p.mc_seqno) {
+            Ok(value) => ok_to_response(req.id, GetShardBlocksForMcResponse {
+                shard_blocks: value,
+            }),
+            Err(e) => error_to_response(req.id, e),
+        },
+        MethodParams::GetLatestMcBlockId(_) => match state.get_latest_mc_block_id() {
+            Ok(block_id) => ok_to_response(req.id, GetLatestMcBlockIdResponse { block_id }),
+            Err(e) => error_to_response(req.id, e),
+        },
+        MethodParams::GetMcBlockByUtime(p) => match state.find_mc_block_by_utime(p.utime) {
+            Ok(value) => ok_to_response(req.id, GetMcBlockResponse {
+                block: value.map(McBlockInfo::from),
+            }),
+            Err(e) => error_to_response(req.id, e),
+        },
+        MethodParams::GetMcBlockByLt(p) => match state.find_mc_block_by_lt(p.lt) {
+            Ok(value) => ok_to_response(req.id, GetMcBlockResponse {
+                block: value.map(McBlockInfo::from),
+            }),
+            Err(e) => error_to_response(req.id, e),
+        },
+        MethodParams::RunGetMethod(p) => {
+            let args = match p.stack.as_deref().map(Boc::decode_base64).transpose() {
+                Ok(args) => args,
+                Err(_) => {
+                    return JrpcErrorResponse {
+                        id: Some(req.id),
+                        code: INVALID_BOC_CODE,
+                        message: Cow::Borrowed("invalid stack BOC"),
+                    }
+                    .into_response()
+                }
+            };
+
+            let method_id = match &p.method {
+                GetMethodId::Name(name) => method_id_from_name(name),
+                GetMethodId::Id(id) => *id,
+            };
+
+            match state.run_get_method(&p.address, method_id, args).await {
+                Ok(result) => ok_to_response(req.id, match result {
+                    RunGetMethodResult::NotExists { timings } => {
+                        RunGetMethodResponse::NotExists { timings }
+                    }
+                    RunGetMethodResult::NotActive { timings } => {
+                        RunGetMethodResponse::NotActive { timings }
+                    }
+                    RunGetMethodResult::Executed { output, timings } => {
+                        RunGetMethodResponse::Executed {
+                            exit_code: output.exit_code,
+                            gas_used: output.gas_used,
+                            stack: Boc::encode_base64(output.stack),
+                            timings,
+                        }
+                    }
+                }),
+                Err(e) => error_to_response(req.id, e),
+            }
+        }
+        MethodParams::EmulateMessage(p) => match state.emulate_message(&p.message).await {
+            Ok(EmulationOutput::Rejected { error }) => {
+                ok_to_response(req.id, EmulateMessageResponse::Rejected { error })
+            }
+            Ok(EmulationOutput::Executed(tx)) => {
+                ok_to_response(req.id, EmulateMessageResponse::Executed {
+                    transaction: Boc::encode_base64(&tx.transaction),
+                    out_messages: tx.out_msgs.iter().map(Boc::encode_base64).collect(),
+                    total_fees: tx.total_fees.tokens.into_inner(),
+                    exit_code: tx.exit_code,
+                    aborted: tx.aborted,
+                })
+            }
+            Err(e) => error_to_response(req.id, e),
+        },
+        MethodParams::SendMessageAndWait(p) => {
+            let Ok(message) = CellBuilder::build_from(p.message) else {
+                return JrpcErrorResponse {
+                    id: Some(req.id),
+                    code: INVALID_BOC_CODE,
+                    message: Cow::Borrowed("invalid message BOC"),
+                }
+                .into_response();
+            };
+            match state.send_message_and_wait(message, p.expire_at).await {
+                Ok(Some(MessageDelivery::Delivered { transaction })) => {
+                    ok_to_response(req.id, SendMessageAndWaitResponse::Delivered {
+                        transaction: Boc::encode_base64(transaction),
+                    })
+                }
+                Ok(Some(MessageDelivery::Expired)) => {
+                    ok_to_response(req.id, SendMessageAndWaitResponse::Expired)
+                }
+                Ok(None) => ok_to_response(req.id, SendMessageAndWaitResponse::Pending),
+                Err(e) => error_to_response(req.id, e),
+            }
+        }
+        MethodParams::GetContractStateAt(p) => {
+            let at = match (p.mc_seqno, p.utime) {
+                (Some(mc_seqno), None) => StateAt::McSeqno(mc_seqno),
+                (None, Some(utime)) => StateAt::Utime(utime),
+                _ => {
+                    return JrpcErrorResponse {
+                        id: Some(req.id),
+                        code: INVALID_PARAMS_CODE,
+                        message: Cow::Borrowed("exactly one of `mcSeqno` or `utime` is required"),
+                    }
+                    .into_response()
+                }
+            };
+
+            let item = match state.get_account_state_at(&p.address, at).await {
+                Ok(item) => item,
+                Err(e) => return error_to_response(req.id, e),
+            };
+
+            let proof = Boc::encode_base64(&item.proof);
+            ok_to_response(req.id, match &item.account {
+                Some(account) => GetContractStateAtResponse::Exists {
+                    account: Boc::encode_base64(account.account.inner()),
+                    last_transaction_id: LastTransactionId {
+                        hash: account.last_trans_hash,
+                        lt: account.last_trans_lt,
+                    },
+                    mc_block_id: item.mc_block_id,
+                    block_id: item.block_id,
+                    timings: item.timings,
+                    proof,
+                },
+                None => GetContractStateAtResponse::NotExists {
+                    mc_block_id: item.mc_block_id,
+                    block_id: item.block_id,
+                    timings: item.timings,
+                    proof,
+                },
+            })
+        }
     }
 }
 
@@ -202,12 +452,37 @@ pub struct SendMessageRequest {
     pub message: Box<OwnedMessage>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendMessageAndWaitRequest {
+    #[serde(with = "bc::ExtMsgRepr")]
+    pub message: Box<OwnedMessage>,
+    /// Unix timestamp after which the message is considered expired.
+    #[serde(default)]
+    pub expire_at: Option<u32>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetContractStateRequest {
     pub address: StdAddr,
     #[serde(default, with = "serde_helpers::option_string")]
     pub last_transaction_lt: Option<u64>,
+    #[serde(default)]
+    pub with_proof: bool,
+    /// Seqno of the latest key block trusted by the client.
+    #[serde(default)]
+    pub known_key_block_seqno: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetContractStateAtRequest {
+    pub address: StdAddr,
+    #[serde(default)]
+    pub mc_seqno: Option<u32>,
+    #[serde(default)]
+    pub utime: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -225,6 +500,44 @@ pub struct GetAccountsByCodeHashRequest {
     pub limit: u8,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAccountsByBalanceRequest {
+    pub workchain: i8,
+    #[serde(default)]
+    pub continuation: Option<AccountBalanceItem>,
+    pub limit: u8,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetActiveAccountsByCodeHashRequest {
+    pub code_hash: HashBytes,
+    #[serde(default, with = "serde_helpers::string")]
+    pub since_lt: u64,
+    #[serde(default)]
+    pub continuation: Option<ActiveAccountItem>,
+    pub limit: u8,
+}
+
+/// Response item and continuation for [`GetAccountsByBalanceRequest`].
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBalanceItem {
+    pub address: StdAddr,
+    #[serde(with = "serde_helpers::string")]
+    pub balance: u128,
+}
+
+/// Response item and continuation for [`GetActiveAccountsByCodeHashRequest`].
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveAccountItem {
+    pub address: StdAddr,
+    #[serde(with = "serde_helpers::string")]
+    pub last_transaction_lt: u64,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetTransactionsListRequest {
@@ -235,8 +548,14 @@ pub struct GetTransactionsListRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GetTransactionRequest {
     pub id: HashBytes,
+    #[serde(default)]
+    pub with_proof: bool,
+    /// Seqno of the latest key block trusted by the client.
+    #[serde(default)]
+    pub known_key_block_seqno: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -245,6 +564,48 @@ pub struct GetDstTransactionRequest {
     pub message_hash: HashBytes,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBlockRequest {
+    pub block_id: BlockId,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetShardBlocksForMcRequest {
+    pub mc_seqno: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMcBlockByUtimeRequest {
+    pub utime: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMcBlockByLtRequest {
+    #[serde(with = "serde_helpers::string")]
+    pub lt: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunGetMethodRequest {
+    pub address: StdAddr,
+    pub method: GetMethodId,
+    /// Base64 encoded BOC with the method arguments (`VmStack`).
+    #[serde(default)]
+    pub stack: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum GetMethodId {
+    Name(String),
+    Id(u32),
+}
+
 // === Responses ===
 
 // NOTE: `RpcState` full/not-full state is determined only once at startup,
@@ -260,6 +621,15 @@ fn get_capabilities(state: &RpcState) -> &'static RawValue {
             "getTimings",
             "getContractState",
             "sendMessage",
+            "getBlock",
+            "getBlockHeader",
+            "getBlockProof",
+            "getShardBlocksForMc",
+            "getLatestMcBlockId",
+            "runGetMethod",
+            "emulateMessage",
+            "sendMessageAndWait",
+            "getContractStateAt",
         ];
 
         if state.is_full() {
@@ -268,9 +638,15 @@ fn get_capabilities(state: &RpcState) -> &'static RawValue {
                 "getTransaction",
                 "getDstTransaction",
                 "getAccountsByCodeHash",
+                "getAccountsByBalance",
+                "getActiveAccountsByCodeHash",
             ]);
         }
 
+        if state.is_archive_node() {
+            capabilities.extend(["getMcBlockByUtime", "getMcBlockByLt"]);
+        }
+
         serde_json::value::to_raw_value(&capabilities).unwrap()
     })
 }
@@ -286,11 +662,115 @@ struct GetLibraryCellResponse {
     pub cell: Option<String>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetBlockResponse {
+    block: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetBlockHeaderResponse {
+    header: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetBlockProofResponse {
+    proof: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetShardBlocksForMcResponse {
+    shard_blocks: Option<Vec<BlockId>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetLatestMcBlockIdResponse {
+    block_id: BlockId,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetMcBlockResponse {
+    block: Option<McBlockInfo>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct McBlockInfo {
+    block_id: BlockId,
+    gen_utime: u32,
+    #[serde(with = "serde_helpers::string")]
+    start_lt: u64,
+    #[serde(with = "serde_helpers::string")]
+    end_lt: u64,
+}
+
+impl From<McBlockIndexEntry> for McBlockInfo {
+    fn from(value: McBlockIndexEntry) -> Self {
+        Self {
+            block_id: value.block_id,
+            gen_utime: value.gen_utime,
+            start_lt: value.start_lt,
+            end_lt: value.end_lt,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+enum RunGetMethodResponse {
+    NotExists {
+        timings: GenTimings,
+    },
+    NotActive {
+        timings: GenTimings,
+    },
+    #[serde(rename_all = "camelCase")]
+    Executed {
+        exit_code: i32,
+        #[serde(with = "serde_helpers::string")]
+        gas_used: u64,
+        stack: String,
+        timings: GenTimings,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+enum EmulateMessageResponse {
+    Rejected {
+        error: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Executed {
+        transaction: String,
+        out_messages: Vec<String>,
+        #[serde(with = "serde_helpers::string")]
+        total_fees: u128,
+        exit_code: Option<i32>,
+        aborted: bool,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+enum SendMessageAndWaitResponse {
+    Delivered { transaction: String },
+    Expired,
+    Pending,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase", tag = "type")]
 enum GetContractStateResponse<'a> {
     NotExists {
         timings: GenTimings,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        proof: Option<ProofResponse>,
     },
     #[serde(rename_all = "camelCase")]
     Exists {
@@ -298,9 +778,75 @@ enum GetContractStateResponse<'a> {
         account: &'a Account,
         timings: GenTimings,
         last_transaction_id: LastTransactionId,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        proof: Option<ProofResponse>,
     },
     Unchanged {
         timings: GenTimings,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        proof: Option<ProofResponse>,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetTransactionResponse {
+    transaction: String,
+    proof: ProofResponse,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProofResponse {
+    /// Merkle proof of the item against the block or shard state root.
+    item: String,
+    block_id: BlockId,
+    mc_block_id: BlockId,
+    mc_block_header: String,
+    mc_block_proof: String,
+    shard_block_headers: Vec<String>,
+    key_block_proofs: Vec<String>,
+    has_more_key_blocks: bool,
+}
+
+impl ProofResponse {
+    fn new(item: &Cell, block_id: BlockId, chain: &BlockProofChain) -> Self {
+        Self {
+            item: Boc::encode_base64(item),
+            block_id,
+            mc_block_id: chain.mc_block_id,
+            mc_block_header: Boc::encode_base64(&chain.mc_block_header),
+            mc_block_proof: encode_base64(&chain.mc_block_proof),
+            shard_block_headers: chain
+                .shard_block_headers
+                .iter()
+                .map(Boc::encode_base64)
+                .collect(),
+            key_block_proofs: chain.key_block_proofs.iter().map(encode_base64).collect(),
+            has_more_key_blocks: chain.has_more_key_blocks,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+enum GetContractStateAtResponse {
+    #[serde(rename_all = "camelCase")]
+    NotExists {
+        mc_block_id: BlockId,
+        block_id: BlockId,
+        timings: GenTimings,
+        proof: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Exists {
+        /// Raw account cell BOC.
+        account: String,
+        last_transaction_id: LastTransactionId,
+        mc_block_id: BlockId,
+        block_id: BlockId,
+        timings: GenTimings,
+        proof: String,
     },
 }
 
@@ -342,6 +888,64 @@ impl Serialize for GetAccountsByCodeHashResponse<'_> {
     }
 }
 
+struct GetAccountsByBalanceResponse<'a> {
+    list: RefCell<Option<AccountsByBalanceIter<'a>>>,
+    limit: u8,
+}
+
+impl GetAccountsByBalanceResponse<'_> {
+    const MAX_LIMIT: u8 = 100;
+}
+
+impl Serialize for GetAccountsByBalanceResponse<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeSeq;
+
+        let list = self.list.borrow_mut().take().unwrap();
+
+        let mut seq = serializer.serialize_seq(None)?;
+        for (address, balance) in list.take(self.limit as usize) {
+            seq.serialize_element(&AccountBalanceItem {
+                address,
+                balance: balance.into_inner(),
+            })?;
+        }
+        seq.end()
+    }
+}
+
+struct GetActiveAccountsByCodeHashResponse<'a> {
+    list: RefCell<Option<ActiveAccountsIter<'a>>>,
+    limit: u8,
+}
+
+impl GetActiveAccountsByCodeHashResponse<'_> {
+    const MAX_LIMIT: u8 = 100;
+}
+
+impl Serialize for GetActiveAccountsByCodeHashResponse<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeSeq;
+
+        let list = self.list.borrow_mut().take().unwrap();
+
+        let mut seq = serializer.serialize_seq(None)?;
+        for (address, last_transaction_lt) in list.take(self.limit as usize) {
+            seq.serialize_element(&ActiveAccountItem {
+                address,
+                last_transaction_lt,
+            })?;
+        }
+        seq.end()
+    }
+}
+
 struct GetTransactionsListResponse<'a> {
     list: RefCell<Option<TransactionsIterBuilder<'a>>>,
     limit: u8,
@@ -387,10 +991,85 @@ fn ok_to_response<T: Serialize>(id: i64, result: T) -> Response {
     JrpcOkResponse::new(id, result).into_response()
 }
 
+async fn get_contract_state_with_proof(
+    id: i64,
+    state: &RpcState,
+    p: GetContractStateRequest,
+) -> Response {
+    let latest_seqno = match state.get_latest_mc_block_id() {
+        Ok(block_id) => block_id.seqno,
+        Err(e) => return error_to_response(id, e),
+    };
+
+    let item = match state
+        .get_account_state_at(&p.address, StateAt::McSeqno(latest_seqno))
+        .await
+    {
+        Ok(item) => item,
+        Err(e) => return error_to_response(id, e),
+    };
+
+    let chain = match state
+        .get_block_proof_chain(
+            &item.block_id,
+            item.mc_block_id.seqno,
+            p.known_key_block_seqno,
+        )
+        .await
+    {
+        Ok(chain) => chain,
+        Err(e) => return error_to_response(id, e),
+    };
+    let proof = Some(ProofResponse::new(&item.proof, item.block_id, &chain));
+
+    let account;
+    ok_to_response(id, match &item.account {
+        None => GetContractStateResponse::NotExists {
+            timings: item.timings,
+            proof,
+        },
+        Some(state) if Some(state.last_trans_lt) <= p.last_transaction_lt => {
+            GetContractStateResponse::Unchanged {
+                timings: GenTimings {
+                    gen_lt: state.last_trans_lt,
+                    gen_utime: item.timings.gen_utime,
+                },
+                proof,
+            }
+        }
+        Some(state) => match state.load_account() {
+            Ok(Some(loaded)) => {
+                account = loaded;
+                GetContractStateResponse::Exists {
+                    account: &account,
+                    timings: GenTimings {
+                        gen_lt: state.last_trans_lt,
+                        gen_utime: item.timings.gen_utime,
+                    },
+                    last_transaction_id: LastTransactionId {
+                        hash: state.last_trans_hash,
+                        lt: state.last_trans_lt,
+                    },
+                    proof,
+                }
+            }
+            Ok(None) => GetContractStateResponse::NotExists {
+                timings: item.timings,
+                proof,
+            },
+            Err(e) => return error_to_response(id, RpcStateError::Internal(e.into())),
+        },
+    })
+}
+
 fn error_to_response(id: i64, e: RpcStateError) -> Response {
     let (code, message) = match e {
         RpcStateError::NotReady => (NOT_READY_CODE, Cow::Borrowed("not ready")),
         RpcStateError::NotSupported => (NOT_SUPPORTED_CODE, Cow::Borrowed("method not supported")),
+        RpcStateError::StatePruned => (STATE_PRUNED_CODE, Cow::Borrowed("state pruned")),
+        RpcStateError::InvalidParams(e) => (INVALID_PARAMS_CODE, Cow::Borrowed(e)),
+        RpcStateError::LimitExceeded(e) => (TOO_MANY_REQUESTS_CODE, Cow::Borrowed(e)),
+        RpcStateError::Timeout => (TIMEOUT_CODE, Cow::Borrowed("timeout")),
         RpcStateError::Internal(e) => (INTERNAL_ERROR_CODE, e.to_string().into()),
     };
 
@@ -402,6 +1081,96 @@ fn error_to_response(id: i64, e: RpcStateError) -> Response {
     .into_response()
 }
 
+/// Executes all batch items concurrently and returns responses in the request order.
+pub async fn route_batch(
+    State(state): State<RpcState>,
+    items: Vec<Result<Jrpc<Method>, JrpcErrorResponse>>,
+    client: Option<&ClientBuckets>,
+) -> Response {
+    if items.len() > state.config().max_batch_size {
+        return JrpcErrorResponse {
+            id: None,
+            code: INVALID_REQUEST_CODE,
+            message: Cow::Borrowed("too many requests in batch"),
+        }
+        .into_response();
+    }
+
+    let responses = futures_util::future::join_all(items.into_iter().map(|item| {
+        let state = state.clone();
+        async move {
+            let response = match item {
+                Ok(req) => route(State(state), req, client).await,
+                Err(e) => e.into_response(),
+            };
+            axum::body::to_bytes(response.into_body(), usize::MAX).await
+        }
+    }))
+    .await;
+
+    let mut body = Vec::new();
+    body.push(b'[');
+    for (i, response) in responses.into_iter().enumerate() {
+        if i > 0 {
+            body.push(b',');
+        }
+        match response {
+            Ok(bytes) => body.extend_from_slice(&bytes),
+            Err(e) => {
+                let error = JrpcErrorResponse {
+                    id: None,
+                    code: INTERNAL_ERROR_CODE,
+                    message: e.to_string().into(),
+                };
+                serde_json::to_writer(&mut body, &error).expect("shouldn't fail");
+            }
+        }
+    }
+    body.push(b']');
+
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            axum::http::HeaderValue::from_static("application/json"),
+        )],
+        body,
+    )
+        .into_response()
+}
+
+fn is_heavy(params: &MethodParams) -> bool {
+    match params {
+        MethodParams::GetContractState(p) => return p.with_proof,
+        MethodParams::GetTransaction(p) => return p.with_proof,
+        _ => {}
+    }
+
+    matches!(
+        params,
+        MethodParams::SendMessage(_)
+            | MethodParams::SendMessageAndWait(_)
+            | MethodParams::GetTransactionsList(_)
+            | MethodParams::GetAccountsByCodeHash(_)
+            | MethodParams::GetAccountsByBalance(_)
+            | MethodParams::GetActiveAccountsByCodeHash(_)
+            | MethodParams::RunGetMethod(_)
+            | MethodParams::EmulateMessage(_)
+            | MethodParams::GetContractStateAt(_)
+            | MethodParams::GetBlock(_)
+            | MethodParams::GetBlockHeader(_)
+            | MethodParams::GetShardBlocksForMc(_)
+    )
+}
+
+fn too_many_requests_response(id: i64) -> Response {
+    JrpcErrorResponse {
+        id: Some(id),
+        code: TOO_MANY_REQUESTS_CODE,
+        message: Cow::Borrowed("too many requests"),
+    }
+    .into_response()
+}
+
 fn too_large_limit_response(id: i64) -> Response {
     JrpcErrorResponse {
         id: Some(id),
@@ -1,3 +1,4 @@
+use std::net::SocketAddr;
 use std::time::Duration;
 
 use anyhow::Result;
@@ -7,12 +8,16 @@ use axum::routing::{get, post};
 use axum::RequestExt;
 use tokio::net::TcpListener;
 
+use self::jrpc::extractor::JrpcBatch;
 pub use self::jrpc::JrpcEndpointCache;
 pub use self::proto::ProtoEndpointCache;
+pub use self::rate_limiter::{ClientBuckets, RateLimiter};
 use crate::state::RpcState;
 
 mod jrpc;
 mod proto;
+mod rate_limiter;
+mod ws;
 
 pub struct RpcEndpoint {
     listener: TcpListener,
@@ -45,11 +50,16 @@ impl RpcEndpoint {
             .route("/", post(common_route))
             .route("/rpc", post(common_route))
             .route("/proto", post(common_route))
+            .route("/ws", get(ws::route))
             .layer(service)
             .with_state(self.state);
 
         // Start server
-        axum::serve(self.listener, router).await
+        axum::serve(
+            self.listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
     }
 }
 
@@ -66,13 +76,16 @@ fn health_check() -> futures_util::future::Ready<impl IntoResponse> {
 async fn common_route(state: State<RpcState>, req: Request) -> Response {
     use axum::http::StatusCode;
 
+    let client = state.rate_limiter().identify(&req);
+
     match get_mime_type(&req) {
         Some(mime) if mime.starts_with(APPLICATION_JSON) => match req.extract().await {
-            Ok(method) => jrpc::route(state, method).await,
+            Ok(JrpcBatch::Single(method)) => jrpc::route(state, method, client.as_deref()).await,
+            Ok(JrpcBatch::Batch(items)) => jrpc::route_batch(state, items, client.as_deref()).await,
             Err(e) => e.into_response(),
         },
         Some(mime) if mime.starts_with(APPLICATION_PROTOBUF) => match req.extract().await {
-            Ok(request) => proto::route(state, request).await,
+            Ok(request) => proto::route(state, request, client.as_deref()).await,
             Err(e) => e.into_response(),
         },
         _ => StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response(),
@@ -97,6 +110,11 @@ const NOT_READY_CODE: i32 = -32001;
 const NOT_SUPPORTED_CODE: i32 = -32002;
 const INVALID_BOC_CODE: i32 = -32003;
 const TOO_LARGE_LIMIT_CODE: i32 = -32004;
+const TOO_MANY_SUBSCRIPTIONS_CODE: i32 = -32005;
+const STATE_PRUNED_CODE: i32 = -32006;
+const TOO_MANY_REQUESTS_CODE: i32 = -32007;
+const TIMEOUT_CODE: i32 = -32008;
+const TOO_MANY_NOTIFICATIONS_CODE: i32 = -32009;
 
 const PARSE_ERROR_CODE: i32 = -32700;
 const INVALID_REQUEST_CODE: i32 = -32600;
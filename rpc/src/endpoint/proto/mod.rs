@@ -8,6 +8,7 @@ use bytes::Bytes;
 use everscale_types::cell::HashBytes;
 use everscale_types::models::*;
 use everscale_types::prelude::*;
+use tycho_storage::McBlockIndexEntry;
 use tycho_util::bc::ExtMsgRepr;
 
 pub use self::cache::ProtoEndpointCache;
@@ -18,16 +19,30 @@ use crate::endpoint::proto::extractor::{
 };
 use crate::endpoint::proto::protos::rpc::response::GetLibraryCell;
 use crate::endpoint::{
-    INTERNAL_ERROR_CODE, INVALID_BOC_CODE, METHOD_NOT_FOUND_CODE, NOT_READY_CODE,
-    NOT_SUPPORTED_CODE, TOO_LARGE_LIMIT_CODE,
+    ClientBuckets, INTERNAL_ERROR_CODE, INVALID_BOC_CODE, METHOD_NOT_FOUND_CODE, NOT_READY_CODE,
+    NOT_SUPPORTED_CODE, STATE_PRUNED_CODE, TIMEOUT_CODE, TOO_LARGE_LIMIT_CODE,
+    TOO_MANY_REQUESTS_CODE,
+};
+use crate::state::{
+    method_id_from_name, BlockProofChain, EmulationOutput, LoadedAccountState, MessageDelivery,
+    RpcState, RpcStateError, RunGetMethodResult, StateAt,
 };
-use crate::state::{LoadedAccountState, RpcState, RpcStateError};
 
 mod cache;
 mod extractor;
 mod protos;
 
-pub async fn route(State(state): State<RpcState>, Protobuf(req): Protobuf<Request>) -> Response {
+pub async fn route(
+    State(state): State<RpcState>,
+    Protobuf(req): Protobuf<Request>,
+    client: Option<&ClientBuckets>,
+) -> Response {
+    if let Some(client) = client {
+        if !client.try_acquire(is_heavy(&req)) {
+            return too_many_requests_response();
+        }
+    }
+
     match req.call {
         Some(request::Call::GetCapabilities(())) => {
             let result = get_capabilities(&state);
@@ -101,6 +116,9 @@ pub async fn route(State(state): State<RpcState>, Protobuf(req): Protobuf<Reques
                 cell: cell_opt,
             }))
         }
+        Some(request::Call::GetContractState(p)) if p.with_proof => {
+            get_contract_state_with_proof(&state, p).await
+        }
         Some(request::Call::GetContractState(p)) => {
             let Some(address) = addr_from_bytes(p.address) else {
                 return ProtoErrorResponse {
@@ -129,6 +147,7 @@ pub async fn route(State(state): State<RpcState>, Protobuf(req): Protobuf<Reques
                             ),
                         },
                     )),
+                    proof: None,
                 },
                 LoadedAccountState::Found {
                     state, gen_utime, ..
@@ -140,6 +159,7 @@ pub async fn route(State(state): State<RpcState>, Protobuf(req): Protobuf<Reques
                                 gen_utime: *gen_utime,
                             },
                         )),
+                        proof: None,
                     }
                 }
                 LoadedAccountState::Found {
@@ -190,7 +210,10 @@ pub async fn route(State(state): State<RpcState>, Protobuf(req): Protobuf<Reques
                         Err(e) => return error_to_response(RpcStateError::Internal(e.into())),
                     };
 
-                    response::GetContractState { state: Some(state) }
+                    response::GetContractState {
+                        state: Some(state),
+                        proof: None,
+                    }
                 }
             };
 
@@ -240,6 +263,119 @@ pub async fn route(State(state): State<RpcState>, Protobuf(req): Protobuf<Reques
                 Err(e) => error_to_response(e),
             }
         }
+        Some(request::Call::GetAccountsByBalance(p)) => {
+            if p.limit == 0 {
+                let result =
+                    response::Result::GetAccountsByBalance(response::GetAccountsByBalance {
+                        accounts: Vec::new(),
+                    });
+                return ok_to_response(result);
+            } else if p.limit > MAX_LIMIT {
+                return too_large_limit_response();
+            }
+
+            let Ok(workchain) = i8::try_from(p.workchain) else {
+                return ProtoErrorResponse {
+                    code: INVALID_PARAMS_CODE,
+                    message: "invalid workchain".into(),
+                }
+                .into_response();
+            };
+
+            let continuation = match p.continuation {
+                Some(item) => match addr_from_bytes(item.address) {
+                    Some(address) => Some((address, Tokens::new(item.balance as u128))),
+                    None => {
+                        return ProtoErrorResponse {
+                            code: INVALID_PARAMS_CODE,
+                            message: "invalid continuation".into(),
+                        }
+                        .into_response()
+                    }
+                },
+                None => None,
+            };
+
+            let continuation = continuation
+                .as_ref()
+                .map(|(address, balance)| (address, *balance));
+            match state.get_accounts_by_balance(workchain, continuation) {
+                Ok(list) => {
+                    let mut accounts = Vec::with_capacity(p.limit as usize);
+                    for (address, balance) in list.take(p.limit as usize) {
+                        let Ok(balance) = u64::try_from(balance.into_inner()) else {
+                            return error_to_response(RpcStateError::Internal(anyhow::anyhow!(
+                                "balance overflow"
+                            )));
+                        };
+                        accounts.push(rpc::AccountBalance {
+                            address: addr_to_bytes(&address),
+                            balance,
+                        });
+                    }
+
+                    let result =
+                        response::Result::GetAccountsByBalance(response::GetAccountsByBalance {
+                            accounts,
+                        });
+                    ok_to_response(result)
+                }
+                Err(e) => error_to_response(e),
+            }
+        }
+        Some(request::Call::GetActiveAccountsByCodeHash(p)) => {
+            if p.limit == 0 {
+                let result = response::Result::GetActiveAccountsByCodeHash(
+                    response::GetActiveAccountsByCodeHash {
+                        accounts: Vec::new(),
+                    },
+                );
+                return ok_to_response(result);
+            } else if p.limit > MAX_LIMIT {
+                return too_large_limit_response();
+            }
+
+            let Some(code_hash) = hash_from_bytes(p.code_hash) else {
+                return ProtoErrorResponse {
+                    code: INVALID_PARAMS_CODE,
+                    message: "invalid code hash".into(),
+                }
+                .into_response();
+            };
+
+            let continuation = match p.continuation {
+                Some(item) => match addr_from_bytes(item.address) {
+                    Some(address) => Some((address, item.last_transaction_lt)),
+                    None => {
+                        return ProtoErrorResponse {
+                            code: INVALID_PARAMS_CODE,
+                            message: "invalid continuation".into(),
+                        }
+                        .into_response()
+                    }
+                },
+                None => None,
+            };
+
+            let continuation = continuation.as_ref().map(|(address, lt)| (address, *lt));
+            match state.get_active_accounts_by_code_hash(&code_hash, p.since_lt, continuation) {
+                Ok(list) => {
+                    let result = response::Result::GetActiveAccountsByCodeHash(
+                        response::GetActiveAccountsByCodeHash {
+                            accounts: list
+                                .take(p.limit as usize)
+                                .map(|(address, last_transaction_lt)| rpc::ActiveAccount {
+                                    address: addr_to_bytes(&address),
+                                    last_transaction_lt,
+                                })
+                                .collect(),
+                        },
+                    );
+                    ok_to_response(result)
+                }
+                Err(e) => error_to_response(e),
+            }
+        }
         Some(request::Call::GetTransactionsList(p)) => {
             if p.limit == 0 {
                 let result = response::Result::GetTransactionsList(response::GetTransactionsList {
@@ -282,15 +418,29 @@ pub async fn route(State(state): State<RpcState>, Protobuf(req): Protobuf<Reques
                 .into_response();
             };
 
-            match state.get_transaction(&hash) {
-                Ok(tx) => {
-                    let result = response::Result::GetRawTransaction(response::GetRawTransaction {
-                        transaction: tx.map(|slice| Bytes::copy_from_slice(slice.as_ref())),
-                    });
-                    ok_to_response(result)
+            let transaction = match state.get_transaction(&hash) {
+                Ok(tx) => tx.map(|slice| Bytes::copy_from_slice(slice.as_ref())),
+                Err(e) => return error_to_response(e),
+            };
+
+            let mut proof = None;
+            if transaction.is_some() && p.with_proof {
+                match state
+                    .get_transaction_proof(&hash, p.known_key_block_seqno)
+                    .await
+                {
+                    Ok(tx_proof) => {
+                        proof = tx_proof.map(|tx_proof| {
+                            proof_to_proto(&tx_proof.proof, &tx_proof.block_id, &tx_proof.chain)
+                        });
+                    }
+                    Err(e) => return error_to_response(e),
                 }
-                Err(e) => error_to_response(e),
             }
+
+            ok_to_response(response::Result::GetRawTransaction(
+                response::GetRawTransaction { transaction, proof },
+            ))
         }
         Some(request::Call::GetDstTransaction(p)) => {
             let Some(hash) = hash_from_bytes(p.message_hash) else {
@@ -305,12 +455,285 @@ pub async fn route(State(state): State<RpcState>, Protobuf(req): Protobuf<Reques
                 Ok(tx) => {
                     let result = response::Result::GetRawTransaction(response::GetRawTransaction {
                         transaction: tx.map(|slice| Bytes::copy_from_slice(slice.as_ref())),
+                        proof: None,
+                    });
+                    ok_to_response(result)
+                }
+                Err(e) => error_to_response(e),
+            }
+        }
+        Some(request::Call::GetBlock(p)) => {
+            let Some(block_id) = p.block_id.and_then(block_id_from_proto) else {
+                return invalid_block_id_response();
+            };
+
+            match state.get_block_data(&block_id).await {
+                Ok(data) => ok_to_response(response::Result::GetBlock(response::GetBlock {
+                    block: data.map(|data| Bytes::copy_from_slice(data.as_ref())),
+                })),
+                Err(e) => error_to_response(e),
+            }
+        }
+        Some(request::Call::GetBlockHeader(p)) => {
+            let Some(block_id) = p.block_id.and_then(block_id_from_proto) else {
+                return invalid_block_id_response();
+            };
+
+            match state.get_block_header(&block_id).await {
+                Ok(header) => {
+                    let result = response::Result::GetBlockHeader(response::GetBlockHeader {
+                        header: header.map(|cell| Boc::encode(cell).into()),
                     });
                     ok_to_response(result)
                 }
                 Err(e) => error_to_response(e),
             }
         }
+        Some(request::Call::GetBlockProof(p)) => {
+            let Some(block_id) = p.block_id.and_then(block_id_from_proto) else {
+                return invalid_block_id_response();
+            };
+
+            match state.get_block_proof(&block_id).await {
+                Ok(data) => {
+                    ok_to_response(response::Result::GetBlockProof(response::GetBlockProof {
+                        proof: data.map(|data| Bytes::copy_from_slice(data.as_ref())),
+                    }))
+                }
+                Err(e) => error_to_response(e),
+            }
+        }
+        Some(request::Call::GetShardBlocksForMc(p)) => {
+            match state.get_shard_blocks_for_mc(p.mc_seqno) {
+                Ok(block_ids) => {
+                    let shard_blocks =
+                        block_ids.map(|block_ids| response::get_shard_blocks_for_mc::ShardBlocks {
+                            block_ids: block_ids.iter().map(block_id_to_proto).collect(),
+                        });
+                    let result =
+                        response::Result::GetShardBlocksForMc(response::GetShardBlocksForMc {
+                            shard_blocks,
+                        });
+                    ok_to_response(result)
+                }
+                Err(e) => error_to_response(e),
+            }
+        }
+        Some(request::Call::GetLatestMcBlockId(())) => match state.get_latest_mc_block_id() {
+            Ok(block_id) => {
+                let result = response::Result::GetLatestMcBlockId(response::GetLatestMcBlockId {
+                    block_id: Some(block_id_to_proto(&block_id)),
+                });
+                ok_to_response(result)
+            }
+            Err(e) => error_to_response(e),
+        },
+        Some(request::Call::GetMcBlockByUtime(p)) => match state.find_mc_block_by_utime(p.utime) {
+            Ok(entry) => {
+                let result = response::Result::GetMcBlockByUtime(response::GetMcBlock {
+                    block: entry.as_ref().map(mc_block_to_proto),
+                });
+                ok_to_response(result)
+            }
+            Err(e) => error_to_response(e),
+        },
+        Some(request::Call::GetMcBlockByLt(p)) => match state.find_mc_block_by_lt(p.lt) {
+            Ok(entry) => {
+                let result = response::Result::GetMcBlockByLt(response::GetMcBlock {
+                    block: entry.as_ref().map(mc_block_to_proto),
+                });
+                ok_to_response(result)
+            }
+            Err(e) => error_to_response(e),
+        },
+        Some(request::Call::RunGetMethod(p)) => {
+            let Some(address) = addr_from_bytes(p.address) else {
+                return ProtoErrorResponse {
+                    code: INVALID_PARAMS_CODE,
+                    message: "invalid address".into(),
+                }
+                .into_response();
+            };
+
+            let method_id = match &p.method {
+                Some(request::run_get_method::Method::Name(name)) => method_id_from_name(name),
+                Some(request::run_get_method::Method::Id(id)) => *id,
+                None => {
+                    return ProtoErrorResponse {
+                        code: INVALID_PARAMS_CODE,
+                        message: "method not specified".into(),
+                    }
+                    .into_response()
+                }
+            };
+
+            let args = match p.stack.as_deref().map(Boc::decode).transpose() {
+                Ok(args) => args,
+                Err(e) => {
+                    return ProtoErrorResponse {
+                        code: INVALID_BOC_CODE,
+                        message: e.to_string().into(),
+                    }
+                    .into_response()
+                }
+            };
+
+            let (result, timings) = match state.run_get_method(&address, method_id, args).await {
+                Ok(RunGetMethodResult::NotExists { timings }) => {
+                    (response::run_get_method::Result::NotExists(()), timings)
+                }
+                Ok(RunGetMethodResult::NotActive { timings }) => {
+                    (response::run_get_method::Result::NotActive(()), timings)
+                }
+                Ok(RunGetMethodResult::Executed { output, timings }) => {
+                    let executed = response::run_get_method::Executed {
+                        exit_code: output.exit_code,
+                        gas_used: output.gas_used,
+                        stack: Boc::encode(output.stack).into(),
+                    };
+                    (
+                        response::run_get_method::Result::Executed(executed),
+                        timings,
+                    )
+                }
+                Err(e) => return error_to_response(e),
+            };
+
+            ok_to_response(response::Result::RunGetMethod(response::RunGetMethod {
+                gen_timings: Some(response::get_contract_state::Timings {
+                    gen_lt: timings.gen_lt,
+                    gen_utime: timings.gen_utime,
+                }),
+                result: Some(result),
+            }))
+        }
+        Some(request::Call::EmulateMessage(p)) => {
+            let message = match ExtMsgRepr::decode(&p.message) {
+                Ok(message) => message,
+                Err(e) => {
+                    return ProtoErrorResponse {
+                        code: INVALID_BOC_CODE,
+                        message: e.to_string().into(),
+                    }
+                    .into_response()
+                }
+            };
+
+            let result = match state.emulate_message(&message).await {
+                Ok(EmulationOutput::Rejected { error }) => {
+                    response::emulate_message::Result::Rejected(error)
+                }
+                Ok(EmulationOutput::Executed(tx)) => {
+                    let Ok(total_fees) = u64::try_from(tx.total_fees.tokens.into_inner()) else {
+                        return error_to_response(RpcStateError::Internal(anyhow::anyhow!(
+                            "total fees overflow"
+                        )));
+                    };
+                    response::emulate_message::Result::Executed(
+                        response::emulate_message::Executed {
+                            transaction: Boc::encode(&tx.transaction).into(),
+                            out_messages: tx
+                                .out_msgs
+                                .iter()
+                                .map(|cell| Boc::encode(cell).into())
+                                .collect(),
+                            total_fees,
+                            exit_code: tx.exit_code,
+                            aborted: tx.aborted,
+                        },
+                    )
+                }
+                Err(e) => return error_to_response(e),
+            };
+
+            ok_to_response(response::Result::EmulateMessage(response::EmulateMessage {
+                result: Some(result),
+            }))
+        }
+        Some(request::Call::SendMessageAndWait(p)) => {
+            if let Err(e) = ExtMsgRepr::decode(&p.message) {
+                return ProtoErrorResponse {
+                    code: INVALID_BOC_CODE,
+                    message: e.to_string().into(),
+                }
+                .into_response();
+            };
+            // NOTE: Message BOC was already validated above.
+            let message = Boc::decode(&p.message).expect("valid message BOC");
+
+            let result = match state.send_message_and_wait(message, p.expire_at).await {
+                Ok(Some(MessageDelivery::Delivered { transaction })) => {
+                    response::send_message_and_wait::Result::Delivered(
+                        Boc::encode(transaction).into(),
+                    )
+                }
+                Ok(Some(MessageDelivery::Expired)) => {
+                    response::send_message_and_wait::Result::Expired(())
+                }
+                Ok(None) => response::send_message_and_wait::Result::Pending(()),
+                Err(e) => return error_to_response(e),
+            };
+
+            ok_to_response(response::Result::SendMessageAndWait(
+                response::SendMessageAndWait {
+                    result: Some(result),
+                },
+            ))
+        }
+        Some(request::Call::GetContractStateAt(p)) => {
+            let Some(address) = addr_from_bytes(p.address) else {
+                return ProtoErrorResponse {
+                    code: INVALID_PARAMS_CODE,
+                    message: "invalid address".into(),
+                }
+                .into_response();
+            };
+
+            let at = match p.at {
+                Some(request::get_contract_state_at::At::McSeqno(mc_seqno)) => {
+                    StateAt::McSeqno(mc_seqno)
+                }
+                Some(request::get_contract_state_at::At::Utime(utime)) => StateAt::Utime(utime),
+                None => {
+                    return ProtoErrorResponse {
+                        code: INVALID_PARAMS_CODE,
+                        message: "`mc_seqno` or `utime` is required".into(),
+                    }
+                    .into_response()
+                }
+            };
+
+            let item = match state.get_account_state_at(&address, at).await {
+                Ok(item) => item,
+                Err(e) => return error_to_response(e),
+            };
+
+            let state = match &item.account {
+                Some(account) => response::get_contract_state_at::State::Exists(
+                    response::get_contract_state_at::Exists {
+                        account: Boc::encode(account.account.inner()).into(),
+                        last_transaction_id: Some(response::get_contract_state::exists::Exact {
+                            lt: account.last_trans_lt,
+                            hash: Bytes::copy_from_slice(account.last_trans_hash.as_slice()),
+                        }),
+                    },
+                ),
+                None => response::get_contract_state_at::State::NotExists(()),
+            };
+
+            ok_to_response(response::Result::GetContractStateAt(
+                response::GetContractStateAt {
+                    mc_block_id: Some(block_id_to_proto(&item.mc_block_id)),
+                    block_id: Some(block_id_to_proto(&item.block_id)),
+                    gen_timings: Some(response::get_contract_state::Timings {
+                        gen_lt: item.timings.gen_lt,
+                        gen_utime: item.timings.gen_utime,
+                    }),
+                    proof: Boc::encode(&item.proof).into(),
+                    state: Some(state),
+                },
+            ))
+        }
         None => ProtoErrorResponse {
             code: METHOD_NOT_FOUND_CODE,
             message: "unknown method".into(),
@@ -334,6 +757,15 @@ fn get_capabilities(state: &RpcState) -> &'static rpc::Response {
             "getTimings",
             "getContractState",
             "sendMessage",
+            "getBlock",
+            "getBlockHeader",
+            "getBlockProof",
+            "getShardBlocksForMc",
+            "getLatestMcBlockId",
+            "runGetMethod",
+            "emulateMessage",
+            "sendMessageAndWait",
+            "getContractStateAt",
         ];
 
         if state.is_full() {
@@ -342,9 +774,15 @@ fn get_capabilities(state: &RpcState) -> &'static rpc::Response {
                 "getTransaction",
                 "getDstTransaction",
                 "getAccountsByCodeHash",
+                "getAccountsByBalance",
+                "getActiveAccountsByCodeHash",
             ]);
         }
 
+        if state.is_archive_node() {
+            capabilities.extend(["getMcBlockByUtime", "getMcBlockByLt"]);
+        }
+
         rpc::Response {
             result: Some(response::Result::GetCapabilities(
                 response::GetCapabilities {
@@ -359,16 +797,148 @@ fn ok_to_response(result: response::Result) -> Response {
     ProtoOkResponse::new(result).into_response()
 }
 
+async fn get_contract_state_with_proof(state: &RpcState, p: request::GetContractState) -> Response {
+    let Some(address) = addr_from_bytes(p.address) else {
+        return ProtoErrorResponse {
+            code: INVALID_PARAMS_CODE,
+            message: "invalid address".into(),
+        }
+        .into_response();
+    };
+
+    let latest_seqno = match state.get_latest_mc_block_id() {
+        Ok(block_id) => block_id.seqno,
+        Err(e) => return error_to_response(e),
+    };
+
+    let item = match state
+        .get_account_state_at(&address, StateAt::McSeqno(latest_seqno))
+        .await
+    {
+        Ok(item) => item,
+        Err(e) => return error_to_response(e),
+    };
+
+    let chain = match state
+        .get_block_proof_chain(
+            &item.block_id,
+            item.mc_block_id.seqno,
+            p.known_key_block_seqno,
+        )
+        .await
+    {
+        Ok(chain) => chain,
+        Err(e) => return error_to_response(e),
+    };
+
+    let not_exists = || {
+        response::get_contract_state::State::NotExists(response::get_contract_state::NotExist {
+            gen_timings: Some(response::get_contract_state::not_exist::GenTimings::Known(
+                response::get_contract_state::Timings {
+                    gen_lt: item.timings.gen_lt,
+                    gen_utime: item.timings.gen_utime,
+                },
+            )),
+        })
+    };
+
+    let state = match &item.account {
+        None => not_exists(),
+        Some(state) => {
+            let timings = response::get_contract_state::Timings {
+                gen_lt: state.last_trans_lt,
+                gen_utime: item.timings.gen_utime,
+            };
+
+            if Some(state.last_trans_lt) <= p.last_transaction_lt {
+                response::get_contract_state::State::Unchanged(timings)
+            } else {
+                match state.load_account() {
+                    Ok(Some(loaded)) => {
+                        let account = match serialize_account(&loaded) {
+                            Ok(account) => account,
+                            Err(e) => return error_to_response(RpcStateError::Internal(e.into())),
+                        };
+                        response::get_contract_state::State::Exists(
+                            response::get_contract_state::Exists {
+                                account,
+                                gen_timings: Some(timings),
+                                last_transaction_id: Some(
+                                    response::get_contract_state::exists::LastTransactionId::Exact(
+                                        response::get_contract_state::exists::Exact {
+                                            lt: state.last_trans_lt,
+                                            hash: Bytes::copy_from_slice(
+                                                state.last_trans_hash.as_slice(),
+                                            ),
+                                        },
+                                    ),
+                                ),
+                            },
+                        )
+                    }
+                    Ok(None) => not_exists(),
+                    Err(e) => return error_to_response(RpcStateError::Internal(e.into())),
+                }
+            }
+        }
+    };
+
+    ok_to_response(response::Result::GetContractState(
+        response::GetContractState {
+            state: Some(state),
+            proof: Some(proof_to_proto(&item.proof, &item.block_id, &chain)),
+        },
+    ))
+}
+
 fn error_to_response(e: RpcStateError) -> Response {
     let (code, message) = match e {
         RpcStateError::NotReady => (NOT_READY_CODE, Cow::Borrowed("not ready")),
         RpcStateError::NotSupported => (NOT_SUPPORTED_CODE, Cow::Borrowed("method not supported")),
+        RpcStateError::StatePruned => (STATE_PRUNED_CODE, Cow::Borrowed("state pruned")),
+        RpcStateError::InvalidParams(e) => (INVALID_PARAMS_CODE, Cow::Borrowed(e)),
+        RpcStateError::LimitExceeded(e) => (TOO_MANY_REQUESTS_CODE, Cow::Borrowed(e)),
+        RpcStateError::Timeout => (TIMEOUT_CODE, Cow::Borrowed("timeout")),
         RpcStateError::Internal(e) => (INTERNAL_ERROR_CODE, e.to_string().into()),
     };
 
     ProtoErrorResponse { code, message }.into_response()
 }
 
+fn is_heavy(req: &Request) -> bool {
+    match &req.call {
+        Some(request::Call::GetContractState(p)) => return p.with_proof,
+        Some(request::Call::GetTransaction(p)) => return p.with_proof,
+        _ => {}
+    }
+
+    matches!(
+        req.call,
+        Some(
+            request::Call::SendMessage(_)
+                | request::Call::SendMessageAndWait(_)
+                | request::Call::GetTransactionsList(_)
+                | request::Call::GetAccountsByCodeHash(_)
+                | request::Call::GetAccountsByBalance(_)
+                | request::Call::GetActiveAccountsByCodeHash(_)
+                | request::Call::RunGetMethod(_)
+                | request::Call::EmulateMessage(_)
+                | request::Call::GetContractStateAt(_)
+                | request::Call::GetBlock(_)
+                | request::Call::GetBlockHeader(_)
+                | request::Call::GetShardBlocksForMc(_)
+        )
+    )
+}
+
+fn too_many_requests_response() -> Response {
+    ProtoErrorResponse {
+        code: TOO_MANY_REQUESTS_CODE,
+        message: Cow::Borrowed("too many requests"),
+    }
+    .into_response()
+}
+
 fn too_large_limit_response() -> Response {
     ProtoErrorResponse {
         code: TOO_LARGE_LIMIT_CODE,
@@ -377,15 +947,75 @@ fn too_large_limit_response() -> Response {
     .into_response()
 }
 
+fn invalid_block_id_response() -> Response {
+    ProtoErrorResponse {
+        code: INVALID_PARAMS_CODE,
+        message: Cow::Borrowed("invalid block id"),
+    }
+    .into_response()
+}
+
 fn addr_from_bytes(bytes: Bytes) -> Option<StdAddr> {
     (bytes.len() == 33)
         .then(|| StdAddr::new(bytes[0] as i8, HashBytes(bytes[1..33].try_into().unwrap())))
 }
 
+fn addr_to_bytes(addr: &StdAddr) -> Bytes {
+    let mut bytes = Vec::with_capacity(33);
+    bytes.push(addr.workchain as u8);
+    bytes.extend_from_slice(addr.address.as_slice());
+    bytes.into()
+}
+
 fn hash_from_bytes(bytes: Bytes) -> Option<HashBytes> {
     (bytes.len() == 32).then(|| HashBytes::from_slice(&bytes))
 }
 
+fn block_id_from_proto(block_id: rpc::BlockId) -> Option<BlockId> {
+    Some(BlockId {
+        shard: ShardIdent::new(block_id.workchain, block_id.shard)?,
+        seqno: block_id.seqno,
+        root_hash: hash_from_bytes(block_id.root_hash)?,
+        file_hash: hash_from_bytes(block_id.file_hash)?,
+    })
+}
+
+fn block_id_to_proto(block_id: &BlockId) -> rpc::BlockId {
+    rpc::BlockId {
+        workchain: block_id.shard.workchain(),
+        shard: block_id.shard.prefix(),
+        seqno: block_id.seqno,
+        root_hash: Bytes::copy_from_slice(block_id.root_hash.as_slice()),
+        file_hash: Bytes::copy_from_slice(block_id.file_hash.as_slice()),
+    }
+}
+
+fn mc_block_to_proto(entry: &McBlockIndexEntry) -> response::get_mc_block::McBlock {
+    response::get_mc_block::McBlock {
+        block_id: Some(block_id_to_proto(&entry.block_id)),
+        gen_utime: entry.gen_utime,
+        start_lt: entry.start_lt,
+        end_lt: entry.end_lt,
+    }
+}
+
+fn proof_to_proto(item: &Cell, block_id: &BlockId, chain: &BlockProofChain) -> rpc::Proof {
+    rpc::Proof {
+        item: Boc::encode(item).into(),
+        block_id: Some(block_id_to_proto(block_id)),
+        mc_block_id: Some(block_id_to_proto(&chain.mc_block_id)),
+        mc_block_header: Boc::encode(&chain.mc_block_header).into(),
+        mc_block_proof: chain.mc_block_proof.clone(),
+        shard_block_headers: chain
+            .shard_block_headers
+            .iter()
+            .map(|cell| Boc::encode(cell).into())
+            .collect(),
+        key_block_proofs: chain.key_block_proofs.clone(),
+        has_more_key_blocks: chain.has_more_key_blocks,
+    }
+}
+
 fn serialize_account(account: &Account) -> Result<Bytes, everscale_types::error::Error> {
     let cell = crate::models::serialize_account(account)?;
     Ok(Boc::encode(cell).into())
@@ -1,7 +1,10 @@
 // This file is @generated by prost-build.
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Request {
-    #[prost(oneof = "request::Call", tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12")]
+    #[prost(
+        oneof = "request::Call",
+        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25"
+    )]
     pub call: ::core::option::Option<request::Call>,
 }
 /// Nested message and enum types in `Request`.
@@ -17,11 +20,21 @@ pub mod request {
         pub address: ::prost::bytes::Bytes,
         #[prost(uint64, optional, tag = "2")]
         pub last_transaction_lt: ::core::option::Option<u64>,
+        #[prost(bool, tag = "3")]
+        pub with_proof: bool,
+        /// Seqno of the latest key block trusted by the client.
+        #[prost(uint32, optional, tag = "4")]
+        pub known_key_block_seqno: ::core::option::Option<u32>,
     }
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct GetTransaction {
         #[prost(bytes = "bytes", tag = "1")]
         pub id: ::prost::bytes::Bytes,
+        #[prost(bool, tag = "2")]
+        pub with_proof: bool,
+        /// Seqno of the latest key block trusted by the client.
+        #[prost(uint32, optional, tag = "3")]
+        pub known_key_block_seqno: ::core::option::Option<u32>,
     }
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct GetDstTransaction {
@@ -47,10 +60,93 @@ pub mod request {
         pub limit: u32,
     }
     #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetAccountsByBalance {
+        #[prost(int32, tag = "1")]
+        pub workchain: i32,
+        #[prost(message, optional, tag = "2")]
+        pub continuation: ::core::option::Option<super::AccountBalance>,
+        #[prost(uint32, tag = "3")]
+        pub limit: u32,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetActiveAccountsByCodeHash {
+        #[prost(bytes = "bytes", tag = "1")]
+        pub code_hash: ::prost::bytes::Bytes,
+        #[prost(uint64, tag = "2")]
+        pub since_lt: u64,
+        #[prost(message, optional, tag = "3")]
+        pub continuation: ::core::option::Option<super::ActiveAccount>,
+        #[prost(uint32, tag = "4")]
+        pub limit: u32,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct SendMessage {
         #[prost(bytes = "bytes", tag = "1")]
         pub message: ::prost::bytes::Bytes,
     }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetBlock {
+        #[prost(message, optional, tag = "1")]
+        pub block_id: ::core::option::Option<super::BlockId>,
+    }
+    #[derive(Clone, Copy, PartialEq, ::prost::Message)]
+    pub struct GetShardBlocksForMc {
+        #[prost(uint32, tag = "1")]
+        pub mc_seqno: u32,
+    }
+    #[derive(Clone, Copy, PartialEq, ::prost::Message)]
+    pub struct GetMcBlockByUtime {
+        #[prost(uint32, tag = "1")]
+        pub utime: u32,
+    }
+    #[derive(Clone, Copy, PartialEq, ::prost::Message)]
+    pub struct GetMcBlockByLt {
+        #[prost(uint64, tag = "1")]
+        pub lt: u64,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct RunGetMethod {
+        #[prost(bytes = "bytes", tag = "1")]
+        pub address: ::prost::bytes::Bytes,
+        #[prost(bytes = "bytes", optional, tag = "4")]
+        pub stack: ::core::option::Option<::prost::bytes::Bytes>,
+        #[prost(oneof = "run_get_method::Method", tags = "2, 3")]
+        pub method: ::core::option::Option<run_get_method::Method>,
+    }
+    /// Nested message and enum types in `RunGetMethod`.
+    pub mod run_get_method {
+        #[derive(Clone, PartialEq, ::prost::Oneof)]
+        pub enum Method {
+            #[prost(string, tag = "2")]
+            Name(::prost::alloc::string::String),
+            #[prost(uint32, tag = "3")]
+            Id(u32),
+        }
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct SendMessageAndWait {
+        #[prost(bytes = "bytes", tag = "1")]
+        pub message: ::prost::bytes::Bytes,
+        #[prost(uint32, optional, tag = "2")]
+        pub expire_at: ::core::option::Option<u32>,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetContractStateAt {
+        #[prost(bytes = "bytes", tag = "1")]
+        pub address: ::prost::bytes::Bytes,
+        #[prost(oneof = "get_contract_state_at::At", tags = "2, 3")]
+        pub at: ::core::option::Option<get_contract_state_at::At>,
+    }
+    /// Nested message and enum types in `GetContractStateAt`.
+    pub mod get_contract_state_at {
+        #[derive(Clone, Copy, PartialEq, ::prost::Oneof)]
+        pub enum At {
+            #[prost(uint32, tag = "2")]
+            McSeqno(u32),
+            #[prost(uint32, tag = "3")]
+            Utime(u32),
+        }
+    }
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Call {
         #[prost(message, tag = "1")]
@@ -77,11 +173,40 @@ pub mod request {
         SendMessage(SendMessage),
         #[prost(message, tag = "12")]
         GetLibraryCell(GetLibraryCell),
+        #[prost(message, tag = "13")]
+        GetBlock(GetBlock),
+        #[prost(message, tag = "14")]
+        GetBlockHeader(GetBlock),
+        #[prost(message, tag = "15")]
+        GetBlockProof(GetBlock),
+        #[prost(message, tag = "16")]
+        GetShardBlocksForMc(GetShardBlocksForMc),
+        #[prost(message, tag = "17")]
+        GetLatestMcBlockId(()),
+        #[prost(message, tag = "18")]
+        RunGetMethod(RunGetMethod),
+        #[prost(message, tag = "19")]
+        EmulateMessage(SendMessage),
+        #[prost(message, tag = "20")]
+        SendMessageAndWait(SendMessageAndWait),
+        #[prost(message, tag = "21")]
+        GetContractStateAt(GetContractStateAt),
+        #[prost(message, tag = "22")]
+        GetAccountsByBalance(GetAccountsByBalance),
+        #[prost(message, tag = "23")]
+        GetActiveAccountsByCodeHash(GetActiveAccountsByCodeHash),
+        #[prost(message, tag = "24")]
+        GetMcBlockByUtime(GetMcBlockByUtime),
+        #[prost(message, tag = "25")]
+        GetMcBlockByLt(GetMcBlockByLt),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Response {
-    #[prost(oneof = "response::Result", tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11")]
+    #[prost(
+        oneof = "response::Result",
+        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24"
+    )]
     pub result: ::core::option::Option<response::Result>,
 }
 /// Nested message and enum types in `Response`.
@@ -90,6 +215,8 @@ pub mod response {
     pub struct GetRawTransaction {
         #[prost(bytes = "bytes", optional, tag = "1")]
         pub transaction: ::core::option::Option<::prost::bytes::Bytes>,
+        #[prost(message, optional, tag = "2")]
+        pub proof: ::core::option::Option<super::Proof>,
     }
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct GetTransactionsList {
@@ -104,6 +231,8 @@ pub mod response {
         pub last_mc_utime: u32,
         #[prost(int64, tag = "4")]
         pub mc_time_diff: i64,
+        /// Global lower bound of the stored transactions. Accounts matching
+        /// retention rules or the watch list may keep older transactions.
         #[prost(uint64, optional, tag = "6")]
         pub smallest_known_lt: ::core::option::Option<u64>,
     }
@@ -137,12 +266,181 @@ pub mod response {
         pub account: ::prost::alloc::vec::Vec<::prost::bytes::Bytes>,
     }
     #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetAccountsByBalance {
+        #[prost(message, repeated, tag = "1")]
+        pub accounts: ::prost::alloc::vec::Vec<super::AccountBalance>,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetActiveAccountsByCodeHash {
+        #[prost(message, repeated, tag = "1")]
+        pub accounts: ::prost::alloc::vec::Vec<super::ActiveAccount>,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct GetLibraryCell {
         #[prost(bytes = "bytes", optional, tag = "1")]
         pub cell: ::core::option::Option<::prost::bytes::Bytes>,
     }
     #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetBlock {
+        #[prost(bytes = "bytes", optional, tag = "1")]
+        pub block: ::core::option::Option<::prost::bytes::Bytes>,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetBlockHeader {
+        #[prost(bytes = "bytes", optional, tag = "1")]
+        pub header: ::core::option::Option<::prost::bytes::Bytes>,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetBlockProof {
+        #[prost(bytes = "bytes", optional, tag = "1")]
+        pub proof: ::core::option::Option<::prost::bytes::Bytes>,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetShardBlocksForMc {
+        #[prost(message, optional, tag = "1")]
+        pub shard_blocks: ::core::option::Option<get_shard_blocks_for_mc::ShardBlocks>,
+    }
+    /// Nested message and enum types in `GetShardBlocksForMc`.
+    pub mod get_shard_blocks_for_mc {
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct ShardBlocks {
+            #[prost(message, repeated, tag = "1")]
+            pub block_ids: ::prost::alloc::vec::Vec<super::super::BlockId>,
+        }
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetLatestMcBlockId {
+        #[prost(message, optional, tag = "1")]
+        pub block_id: ::core::option::Option<super::BlockId>,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetMcBlock {
+        #[prost(message, optional, tag = "1")]
+        pub block: ::core::option::Option<get_mc_block::McBlock>,
+    }
+    /// Nested message and enum types in `GetMcBlock`.
+    pub mod get_mc_block {
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct McBlock {
+            #[prost(message, optional, tag = "1")]
+            pub block_id: ::core::option::Option<super::super::BlockId>,
+            #[prost(uint32, tag = "2")]
+            pub gen_utime: u32,
+            #[prost(uint64, tag = "3")]
+            pub start_lt: u64,
+            #[prost(uint64, tag = "4")]
+            pub end_lt: u64,
+        }
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct RunGetMethod {
+        #[prost(message, optional, tag = "1")]
+        pub gen_timings: ::core::option::Option<get_contract_state::Timings>,
+        #[prost(oneof = "run_get_method::Result", tags = "2, 3, 4")]
+        pub result: ::core::option::Option<run_get_method::Result>,
+    }
+    /// Nested message and enum types in `RunGetMethod`.
+    pub mod run_get_method {
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct Executed {
+            #[prost(int32, tag = "1")]
+            pub exit_code: i32,
+            #[prost(uint64, tag = "2")]
+            pub gas_used: u64,
+            #[prost(bytes = "bytes", tag = "3")]
+            pub stack: ::prost::bytes::Bytes,
+        }
+        #[derive(Clone, PartialEq, ::prost::Oneof)]
+        pub enum Result {
+            #[prost(message, tag = "2")]
+            NotExists(()),
+            #[prost(message, tag = "3")]
+            NotActive(()),
+            #[prost(message, tag = "4")]
+            Executed(Executed),
+        }
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct EmulateMessage {
+        #[prost(oneof = "emulate_message::Result", tags = "1, 2")]
+        pub result: ::core::option::Option<emulate_message::Result>,
+    }
+    /// Nested message and enum types in `EmulateMessage`.
+    pub mod emulate_message {
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct Executed {
+            #[prost(bytes = "bytes", tag = "1")]
+            pub transaction: ::prost::bytes::Bytes,
+            #[prost(bytes = "bytes", repeated, tag = "2")]
+            pub out_messages: ::prost::alloc::vec::Vec<::prost::bytes::Bytes>,
+            #[prost(uint64, tag = "3")]
+            pub total_fees: u64,
+            #[prost(int32, optional, tag = "4")]
+            pub exit_code: ::core::option::Option<i32>,
+            #[prost(bool, tag = "5")]
+            pub aborted: bool,
+        }
+        #[derive(Clone, PartialEq, ::prost::Oneof)]
+        pub enum Result {
+            #[prost(string, tag = "1")]
+            Rejected(::prost::alloc::string::String),
+            #[prost(message, tag = "2")]
+            Executed(Executed),
+        }
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct SendMessageAndWait {
+        #[prost(oneof = "send_message_and_wait::Result", tags = "1, 2, 3")]
+        pub result: ::core::option::Option<send_message_and_wait::Result>,
+    }
+    /// Nested message and enum types in `SendMessageAndWait`.
+    pub mod send_message_and_wait {
+        #[derive(Clone, PartialEq, ::prost::Oneof)]
+        pub enum Result {
+            #[prost(bytes = "bytes", tag = "1")]
+            Delivered(::prost::bytes::Bytes),
+            #[prost(message, tag = "2")]
+            Expired(()),
+            #[prost(message, tag = "3")]
+            Pending(()),
+        }
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct GetContractStateAt {
+        #[prost(message, optional, tag = "1")]
+        pub mc_block_id: ::core::option::Option<super::BlockId>,
+        #[prost(message, optional, tag = "2")]
+        pub block_id: ::core::option::Option<super::BlockId>,
+        #[prost(message, optional, tag = "3")]
+        pub gen_timings: ::core::option::Option<get_contract_state::Timings>,
+        #[prost(bytes = "bytes", tag = "4")]
+        pub proof: ::prost::bytes::Bytes,
+        #[prost(oneof = "get_contract_state_at::State", tags = "5, 6")]
+        pub state: ::core::option::Option<get_contract_state_at::State>,
+    }
+    /// Nested message and enum types in `GetContractStateAt`.
+    pub mod get_contract_state_at {
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct Exists {
+            #[prost(bytes = "bytes", tag = "1")]
+            pub account: ::prost::bytes::Bytes,
+            #[prost(message, optional, tag = "2")]
+            pub last_transaction_id: ::core::option::Option<
+                super::get_contract_state::exists::Exact,
+            >,
+        }
+        #[derive(Clone, PartialEq, ::prost::Oneof)]
+        pub enum State {
+            #[prost(message, tag = "5")]
+            NotExists(()),
+            #[prost(message, tag = "6")]
+            Exists(Exists),
+        }
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct GetContractState {
+        #[prost(message, optional, tag = "4")]
+        pub proof: ::core::option::Option<super::Proof>,
         #[prost(oneof = "get_contract_state::State", tags = "1, 2, 3")]
         pub state: ::core::option::Option<get_contract_state::State>,
     }
@@ -235,6 +533,32 @@ pub mod response {
         SendMessage(()),
         #[prost(message, tag = "11")]
         GetLibraryCell(GetLibraryCell),
+        #[prost(message, tag = "12")]
+        GetBlock(GetBlock),
+        #[prost(message, tag = "13")]
+        GetBlockHeader(GetBlockHeader),
+        #[prost(message, tag = "14")]
+        GetBlockProof(GetBlockProof),
+        #[prost(message, tag = "15")]
+        GetShardBlocksForMc(GetShardBlocksForMc),
+        #[prost(message, tag = "16")]
+        GetLatestMcBlockId(GetLatestMcBlockId),
+        #[prost(message, tag = "17")]
+        RunGetMethod(RunGetMethod),
+        #[prost(message, tag = "18")]
+        EmulateMessage(EmulateMessage),
+        #[prost(message, tag = "19")]
+        SendMessageAndWait(SendMessageAndWait),
+        #[prost(message, tag = "20")]
+        GetContractStateAt(GetContractStateAt),
+        #[prost(message, tag = "21")]
+        GetAccountsByBalance(GetAccountsByBalance),
+        #[prost(message, tag = "22")]
+        GetActiveAccountsByCodeHash(GetActiveAccountsByCodeHash),
+        #[prost(message, tag = "23")]
+        GetMcBlockByUtime(GetMcBlock),
+        #[prost(message, tag = "24")]
+        GetMcBlockByLt(GetMcBlock),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -244,3 +568,51 @@ pub struct Error {
     #[prost(string, tag = "2")]
     pub message: ::prost::alloc::string::String,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BlockId {
+    #[prost(int32, tag = "1")]
+    pub workchain: i32,
+    #[prost(uint64, tag = "2")]
+    pub shard: u64,
+    #[prost(uint32, tag = "3")]
+    pub seqno: u32,
+    #[prost(bytes = "bytes", tag = "4")]
+    pub root_hash: ::prost::bytes::Bytes,
+    #[prost(bytes = "bytes", tag = "5")]
+    pub file_hash: ::prost::bytes::Bytes,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Proof {
+    /// Merkle proof of the item against the block or shard state root.
+    #[prost(bytes = "bytes", tag = "1")]
+    pub item: ::prost::bytes::Bytes,
+    #[prost(message, optional, tag = "2")]
+    pub block_id: ::core::option::Option<BlockId>,
+    #[prost(message, optional, tag = "3")]
+    pub mc_block_id: ::core::option::Option<BlockId>,
+    #[prost(bytes = "bytes", tag = "4")]
+    pub mc_block_header: ::prost::bytes::Bytes,
+    #[prost(bytes = "bytes", tag = "5")]
+    pub mc_block_proof: ::prost::bytes::Bytes,
+    #[prost(bytes = "bytes", repeated, tag = "6")]
+    pub shard_block_headers: ::prost::alloc::vec::Vec<::prost::bytes::Bytes>,
+    #[prost(bytes = "bytes", repeated, tag = "7")]
+    pub key_block_proofs: ::prost::alloc::vec::Vec<::prost::bytes::Bytes>,
+    /// Whether the key block proofs chain was truncated.
+    #[prost(bool, tag = "8")]
+    pub has_more_key_blocks: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AccountBalance {
+    #[prost(bytes = "bytes", tag = "1")]
+    pub address: ::prost::bytes::Bytes,
+    #[prost(uint64, tag = "2")]
+    pub balance: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ActiveAccount {
+    #[prost(bytes = "bytes", tag = "1")]
+    pub address: ::prost::bytes::Bytes,
+    #[prost(uint64, tag = "2")]
+    pub last_transaction_lt: u64,
+}
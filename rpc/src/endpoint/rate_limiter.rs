@@ -0,0 +1,271 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::{ConnectInfo, Request};
+use axum::http::HeaderMap;
+use parking_lot::Mutex;
+use tycho_util::{FastHashMap, FastHashSet, FastHasherState};
+
+use crate::config::{ClientLimits, RateLimitsConfig, TokenBucketConfig};
+
+const ANONYMOUS_CLIENT: &str = "anonymous";
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Token-bucket rate limiter for incoming requests.
+pub struct RateLimiter {
+    enabled: bool,
+    api_key_header: String,
+    per_ip: ClientLimits,
+    api_keys: FastHashMap<String, Arc<ClientBuckets>>,
+    ips: moka::sync::Cache<IpAddr, Arc<ClientBuckets>, FastHasherState>,
+    trusted_proxies: FastHashSet<IpAddr>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitsConfig) -> Self {
+        let api_keys = config
+            .api_keys
+            .iter()
+            .map(|item| {
+                let buckets = ClientBuckets::new(item.name.clone(), item.limits.clone());
+                (item.key.clone(), Arc::new(buckets))
+            })
+            .collect();
+
+        Self {
+            enabled: config.enabled,
+            api_key_header: config.api_key_header.clone(),
+            per_ip: config.per_ip.clone(),
+            api_keys,
+            ips: moka::sync::Cache::builder()
+                .max_capacity(config.max_tracked_ips)
+                .time_to_idle(IP_IDLE_TIMEOUT)
+                .build_with_hasher(Default::default()),
+            trusted_proxies: config.trusted_proxies.iter().copied().collect(),
+        }
+    }
+
+    /// Finds buckets for the request sender.
+    ///
+    /// Returns `None` if rate limiting is disabled.
+    pub fn identify(&self, req: &Request) -> Option<Arc<ClientBuckets>> {
+        if !self.enabled {
+            return None;
+        }
+
+        let api_key = req
+            .headers()
+            .get(self.api_key_header.as_str())
+            .and_then(|value| value.to_str().ok());
+
+        if let Some(buckets) = api_key.and_then(|key| self.api_keys.get(key)) {
+            return Some(buckets.clone());
+        }
+
+        let remote_ip = match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+            Some(ConnectInfo(addr)) => addr.ip(),
+            None => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        };
+        let ip = self.client_ip(req.headers(), remote_ip);
+
+        Some(self.ips.get_with(ip, || {
+            Arc::new(ClientBuckets::new(
+                ANONYMOUS_CLIENT.to_owned(),
+                self.per_ip.clone(),
+            ))
+        }))
+    }
+
+    /// Resolves the client IP using `X-Forwarded-For` from trusted proxies.
+    fn client_ip(&self, headers: &HeaderMap, remote_ip: IpAddr) -> IpAddr {
+        if !self.trusted_proxies.contains(&remote_ip) {
+            return remote_ip;
+        }
+
+        let forwarded = headers
+            .get_all(X_FORWARDED_FOR)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect::<Vec<_>>();
+
+        // NOTE: Each proxy appends the address of its peer, so the first
+        // untrusted address from the end is the real client.
+        for item in forwarded.into_iter().rev() {
+            match item.trim().parse::<IpAddr>() {
+                Ok(ip) if self.trusted_proxies.contains(&ip) => continue,
+                Ok(ip) => return ip,
+                Err(_) => break,
+            }
+        }
+
+        remote_ip
+    }
+}
+
+pub struct ClientBuckets {
+    name: String,
+    limits: ClientLimits,
+    state: Mutex<BucketsState>,
+}
+
+impl ClientBuckets {
+    fn new(name: String, limits: ClientLimits) -> Self {
+        let now = Instant::now();
+        Self {
+            state: Mutex::new(BucketsState {
+                requests: TokenBucket::new(&limits.requests, now),
+                heavy_requests: TokenBucket::new(&limits.heavy_requests, now),
+            }),
+            name,
+            limits,
+        }
+    }
+
+    /// Consumes a token from the buckets.
+    ///
+    /// Returns `false` if the request must be rejected.
+    pub fn try_acquire(&self, heavy: bool) -> bool {
+        let labels = [("client", self.name.clone())];
+        metrics::counter!("tycho_rpc_client_requests_total", &labels).increment(1);
+
+        let now = Instant::now();
+        let allowed = {
+            let mut state = self.state.lock();
+            let BucketsState {
+                requests,
+                heavy_requests,
+            } = &mut *state;
+
+            requests.refill(&self.limits.requests, now);
+            heavy_requests.refill(&self.limits.heavy_requests, now);
+
+            let allowed = requests.has_tokens(&self.limits.requests)
+                && (!heavy || heavy_requests.has_tokens(&self.limits.heavy_requests));
+            if allowed {
+                requests.consume();
+                if heavy {
+                    heavy_requests.consume();
+                }
+            }
+            allowed
+        };
+
+        if !allowed {
+            metrics::counter!("tycho_rpc_client_rate_limited_total", &labels).increment(1);
+        }
+        allowed
+    }
+}
+
+struct BucketsState {
+    requests: TokenBucket,
+    heavy_requests: TokenBucket,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: &TokenBucketConfig, now: Instant) -> Self {
+        Self {
+            tokens: config.burst as f64,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, config: &TokenBucketConfig, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * config.rate as f64).min(config.burst as f64);
+        self.updated_at = now;
+    }
+
+    fn has_tokens(&self, config: &TokenBucketConfig) -> bool {
+        config.rate == 0 || self.tokens >= 1.0
+    }
+
+    fn consume(&mut self) {
+        self.tokens = (self.tokens - 1.0).max(0.0);
+    }
+}
+
+const IP_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket_refills() {
+        let config = TokenBucketConfig { rate: 10, burst: 2 };
+
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(&config, now);
+
+        for _ in 0..2 {
+            assert!(bucket.has_tokens(&config));
+            bucket.consume();
+        }
+        assert!(!bucket.has_tokens(&config));
+
+        bucket.refill(&config, now + Duration::from_millis(100));
+        assert!(bucket.has_tokens(&config));
+        bucket.consume();
+        assert!(!bucket.has_tokens(&config));
+
+        // Refill is capped by the burst size
+        bucket.refill(&config, now + Duration::from_secs(10));
+        assert_eq!(bucket.tokens, 2.0);
+    }
+
+    #[test]
+    fn heavy_requests_use_both_buckets() {
+        let limits = ClientLimits {
+            requests: TokenBucketConfig { rate: 1, burst: 3 },
+            heavy_requests: TokenBucketConfig { rate: 1, burst: 1 },
+        };
+        let buckets = ClientBuckets::new("test".to_owned(), limits);
+
+        assert!(buckets.try_acquire(true));
+        assert!(!buckets.try_acquire(true));
+        assert!(buckets.try_acquire(false));
+        assert!(buckets.try_acquire(false));
+        assert!(!buckets.try_acquire(false));
+    }
+
+    #[test]
+    fn forwarded_ip_from_trusted_proxies() {
+        let proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let client = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        let spoofed = IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8));
+
+        let limiter = RateLimiter::new(&RateLimitsConfig {
+            enabled: true,
+            trusted_proxies: vec![proxy],
+            ..Default::default()
+        });
+
+        let mut headers = HeaderMap::new();
+        headers.append(
+            X_FORWARDED_FOR,
+            format!("{spoofed}, {client}").parse().unwrap(),
+        );
+        headers.append(X_FORWARDED_FOR, proxy.to_string().parse().unwrap());
+
+        // Header is ignored for untrusted peers
+        assert_eq!(limiter.client_ip(&headers, client), client);
+        assert_eq!(limiter.client_ip(&headers, spoofed), spoofed);
+
+        // The rightmost untrusted address is used otherwise
+        assert_eq!(limiter.client_ip(&headers, proxy), client);
+
+        // Invalid or missing header falls back to the proxy address
+        assert_eq!(limiter.client_ip(&HeaderMap::new(), proxy), proxy);
+        let mut headers = HeaderMap::new();
+        headers.insert(X_FORWARDED_FOR, "garbage".parse().unwrap());
+        assert_eq!(limiter.client_ip(&headers, proxy), proxy);
+    }
+}
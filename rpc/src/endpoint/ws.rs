@@ -0,0 +1,210 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Request, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::RequestExt;
+use everscale_types::models::StdAddr;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use tycho_util::metrics::HistogramGuard;
+
+use crate::endpoint::jrpc::extractor::{
+    declare_jrpc_method, Jrpc, JrpcErrorResponse, JrpcOkResponse,
+};
+use crate::endpoint::{
+    ClientBuckets, INVALID_PARAMS_CODE, MAX_REQUEST_SIZE, TOO_LARGE_LIMIT_CODE,
+    TOO_MANY_NOTIFICATIONS_CODE, TOO_MANY_REQUESTS_CODE, TOO_MANY_SUBSCRIPTIONS_CODE,
+};
+use crate::state::{RpcState, SubscribeError, SubscriptionKind, SubscriptionsClient};
+
+declare_jrpc_method! {
+    pub enum WsMethodParams: WsMethod {
+        Subscribe(SubscribeRequest),
+        Unsubscribe(UnsubscribeRequest),
+    }
+}
+
+pub async fn route(State(state): State<RpcState>, req: Request) -> Response {
+    // NOTE: Establishing a connection consumes a token as a regular request.
+    let buckets = state.rate_limiter().identify(&req);
+    if let Some(buckets) = &buckets {
+        if !buckets.try_acquire(false) {
+            return (StatusCode::TOO_MANY_REQUESTS, "too many requests").into_response();
+        }
+    }
+
+    let ws = match req.extract::<WebSocketUpgrade, _>().await {
+        Ok(ws) => ws,
+        Err(e) => return e.into_response(),
+    };
+
+    let Some(client) = state.subscriptions().register_client() else {
+        return (StatusCode::SERVICE_UNAVAILABLE, "too many connections").into_response();
+    };
+
+    ws.max_message_size(MAX_REQUEST_SIZE)
+        .on_upgrade(move |socket| handle_socket(socket, client, buckets))
+}
+
+async fn handle_socket(
+    mut socket: WebSocket,
+    mut client: SubscriptionsClient,
+    buckets: Option<Arc<ClientBuckets>>,
+) {
+    let buckets = buckets.as_deref();
+
+    let client_id = client.id();
+    tracing::debug!(client_id, "websocket client connected");
+
+    loop {
+        let response = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => handle_request(&client, buckets, text.as_bytes()),
+                Some(Ok(Message::Binary(data))) => handle_request(&client, buckets, &data),
+                // NOTE: Pings are answered automatically.
+                Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+            },
+            notification = client.recv() => match notification {
+                Ok(notification) => serialize(&SubscriptionNotification {
+                    subscription: notification.subscription,
+                    result: &notification.result,
+                }),
+                Err(e) => {
+                    // The client was removed because it was not able to keep up
+                    tracing::debug!(client_id, "{e}");
+
+                    let error = serialize(&JrpcErrorResponse {
+                        id: None,
+                        code: TOO_MANY_NOTIFICATIONS_CODE,
+                        message: e.to_string().into(),
+                    });
+                    if socket.send(Message::Text(error)).await.is_ok() {
+                        let frame = CloseFrame {
+                            code: close_code::AGAIN,
+                            reason: Cow::Owned(e.to_string()),
+                        };
+                        socket.send(Message::Close(Some(frame))).await.ok();
+                    }
+                    break;
+                }
+            },
+        };
+
+        if socket.send(Message::Text(response)).await.is_err() {
+            break;
+        }
+    }
+
+    tracing::debug!(client_id, "websocket client disconnected");
+}
+
+fn handle_request(
+    client: &SubscriptionsClient,
+    buckets: Option<&ClientBuckets>,
+    data: &[u8],
+) -> String {
+    let _hist = HistogramGuard::begin("tycho_rpc_ws_request_time");
+
+    let req = match Jrpc::<WsMethod>::parse(data) {
+        Ok(req) => req,
+        Err(e) => return serialize(&e),
+    };
+
+    if let Some(buckets) = buckets {
+        if !buckets.try_acquire(false) {
+            return serialize(&JrpcErrorResponse {
+                id: Some(req.id),
+                code: TOO_MANY_REQUESTS_CODE,
+                message: Cow::Borrowed("too many requests"),
+            });
+        }
+    }
+
+    match req.params {
+        WsMethodParams::Subscribe(p) => {
+            let kind = match p {
+                SubscribeRequest::McBlocks => SubscriptionKind::McBlocks,
+                SubscribeRequest::Transactions { addresses } => {
+                    SubscriptionKind::Transactions(addresses)
+                }
+                SubscribeRequest::AccountStates { addresses } => {
+                    SubscriptionKind::AccountStates(addresses)
+                }
+            };
+
+            match client.subscribe(kind) {
+                Ok(subscription) => serialize(&JrpcOkResponse::new(req.id, subscription)),
+                Err(e) => {
+                    let code = match &e {
+                        SubscribeError::TooManySubscriptions => TOO_MANY_SUBSCRIPTIONS_CODE,
+                        SubscribeError::TooManyAddresses => TOO_LARGE_LIMIT_CODE,
+                        SubscribeError::NoAddresses | SubscribeError::Closed => INVALID_PARAMS_CODE,
+                    };
+                    serialize(&JrpcErrorResponse {
+                        id: Some(req.id),
+                        code,
+                        message: e.to_string().into(),
+                    })
+                }
+            }
+        }
+        WsMethodParams::Unsubscribe(p) => {
+            let removed = client.unsubscribe(p.subscription);
+            serialize(&JrpcOkResponse::new(req.id, removed))
+        }
+    }
+}
+
+fn serialize<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("shouldn't fail")
+}
+
+// === Requests ===
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SubscribeRequest {
+    McBlocks,
+    Transactions { addresses: Vec<StdAddr> },
+    AccountStates { addresses: Vec<StdAddr> },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnsubscribeRequest {
+    pub subscription: u64,
+}
+
+// === Notifications ===
+
+struct SubscriptionNotification<'a> {
+    subscription: u64,
+    result: &'a RawValue,
+}
+
+impl Serialize for SubscriptionNotification<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        #[derive(Serialize)]
+        struct Params<'a> {
+            subscription: u64,
+            result: &'a RawValue,
+        }
+
+        let mut ser = serializer.serialize_struct("JrpcNotification", 3)?;
+        ser.serialize_field("jsonrpc", "2.0")?;
+        ser.serialize_field("method", "subscription")?;
+        ser.serialize_field("params", &Params {
+            subscription: self.subscription,
+            result: self.result,
+        })?;
+        ser.end()
+    }
+}
@@ -48,6 +48,11 @@ pub struct StateTimings {
     pub last_mc_block_seqno: u32,
     pub last_mc_utime: u32,
     pub mc_time_diff: i64,
+    /// Logical time since which transactions of all accounts are stored.
+    ///
+    /// NOTE: This is a global lower bound. Accounts matching retention rules
+    /// or the watch list may keep older transactions, so the history of a
+    /// specific account can start earlier.
     pub smallest_known_lt: Option<u64>,
 }
 
@@ -2,12 +2,14 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use arc_swap::ArcSwap;
+use arc_swap::{ArcSwap, ArcSwapOption};
+use bytes::Bytes;
+use everscale_types::merkle::MerkleProof;
 use everscale_types::models::*;
 use everscale_types::prelude::*;
 use futures_util::future::BoxFuture;
 use parking_lot::RwLock;
-use tokio::sync::Notify;
+use tokio::sync::{Notify, Semaphore};
 use tokio::task::JoinHandle;
 use tycho_block_util::block::BlockStuff;
 use tycho_block_util::state::{RefMcStateHandle, ShardStateStuff};
@@ -15,15 +17,33 @@ use tycho_core::block_strider::{
     BlockSubscriber, BlockSubscriberContext, StateSubscriber, StateSubscriberContext,
 };
 use tycho_core::blockchain_rpc::BlockchainRpcClient;
-use tycho_storage::{CodeHashesIter, KeyBlocksDirection, Storage, TransactionsIterBuilder};
+use tycho_storage::{
+    AccountsByBalanceIter, ActiveAccountsIter, BlockHandleStorage, CodeHashesIter,
+    KeyBlocksDirection, McBlockIndexEntry, Storage, TransactionInfo, TransactionsIterBuilder,
+    TransactionsRetention,
+};
 use tycho_util::metrics::HistogramGuard;
+use tycho_util::sync::CancellationFlag;
 use tycho_util::time::now_sec;
 use tycho_util::FastHashMap;
 
-use crate::config::{RpcConfig, RpcStorage, TransactionsGcConfig};
-use crate::endpoint::{JrpcEndpointCache, ProtoEndpointCache, RpcEndpoint};
+pub use self::pending_messages::MessageDelivery;
+use self::pending_messages::{PendingMessages, RegisterError};
+pub use self::subscriptions::{
+    ClientLagged, RpcSubscriptions, SubscribeError, SubscriptionKind, SubscriptionsClient,
+};
+pub use self::tvm::{method_id_from_name, EmulatedTransaction, EmulationOutput, GetMethodOutput};
+use crate::config::{RpcConfig, RpcStorage, TransactionsGcConfig, TransactionsRetentionRule};
+use crate::endpoint::{JrpcEndpointCache, ProtoEndpointCache, RateLimiter, RpcEndpoint};
 use crate::models::{GenTimings, StateTimings};
 
+mod pending_messages;
+mod subscriptions;
+mod tvm;
+
+#[cfg(test)]
+mod test_utils;
+
 pub struct RpcStateBuilder<MandatoryFields = (Storage, BlockchainRpcClient)> {
     config: RpcConfig,
     mandatory_fields: MandatoryFields,
@@ -45,6 +65,12 @@ impl RpcStateBuilder {
             RpcStorage::StateOnly => None,
         };
 
+        let subscriptions = RpcSubscriptions::new(self.config.subscriptions.clone());
+        let pending_messages = PendingMessages::new(self.config.pending_messages.clone());
+        let rate_limiter = RateLimiter::new(&self.config.rate_limits);
+        let get_methods_semaphore =
+            Arc::new(Semaphore::new(self.config.run_get_method.max_concurrent));
+
         RpcState {
             inner: Arc::new(Inner {
                 config: self.config,
@@ -54,8 +80,13 @@ impl RpcStateBuilder {
                 sc_accounts: Default::default(),
                 is_ready: AtomicBool::new(false),
                 timings: ArcSwap::new(Default::default()),
+                blockchain_config: Default::default(),
                 jrpc_cache: Default::default(),
                 proto_cache: Default::default(),
+                subscriptions,
+                pending_messages,
+                rate_limiter,
+                get_methods_semaphore,
                 gc_notify,
                 gc_handle,
             }),
@@ -138,6 +169,10 @@ impl RpcState {
         self.inner.storage.rpc_storage().is_some()
     }
 
+    pub fn is_archive_node(&self) -> bool {
+        self.inner.storage.block_storage().is_archive_node()
+    }
+
     pub fn load_timings(&self) -> arc_swap::Guard<Arc<StateTimings>> {
         self.inner.timings.load()
     }
@@ -150,6 +185,14 @@ impl RpcState {
         &self.inner.proto_cache
     }
 
+    pub fn subscriptions(&self) -> &RpcSubscriptions {
+        &self.inner.subscriptions
+    }
+
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.inner.rate_limiter
+    }
+
     pub async fn broadcast_external_message(&self, message: &[u8]) {
         metrics::counter!("tycho_rpc_broadcast_external_message_tx_bytes_total")
             .increment(message.len() as u64);
@@ -159,6 +202,55 @@ impl RpcState {
             .await;
     }
 
+    /// Broadcasts an external message and waits until it is included
+    /// into some block or expired.
+    ///
+    /// Returns `None` if the message is still pending after the max wait time.
+    pub async fn send_message_and_wait(
+        &self,
+        message: Cell,
+        expire_at: Option<u32>,
+    ) -> Result<Option<MessageDelivery>, RpcStateError> {
+        let pending_messages = &self.inner.pending_messages;
+        let config = pending_messages.config();
+
+        let msg_hash = *message.repr_hash();
+        let expire_at =
+            expire_at.unwrap_or_else(|| now_sec() + config.default_ttl.as_secs() as u32);
+
+        let rx = match pending_messages.register(msg_hash, expire_at) {
+            Ok(rx) => rx,
+            Err(RegisterError::AlreadyExpired) => {
+                return Err(RpcStateError::InvalidParams("message already expired"))
+            }
+            Err(RegisterError::TooManyPending) => {
+                return Err(RpcStateError::LimitExceeded("too many pending messages"))
+            }
+        };
+
+        // Check whether the message was already delivered
+        if let Some(storage) = self.inner.storage.rpc_storage() {
+            let tx = storage
+                .get_dst_transaction(&msg_hash)
+                .map_err(RpcStateError::Internal)?;
+            if let Some(tx) = tx {
+                let transaction = Boc::decode(tx).map_err(|e| RpcStateError::Internal(e.into()))?;
+                return Ok(Some(MessageDelivery::Delivered { transaction }));
+            }
+        }
+
+        self.broadcast_external_message(Boc::encode(&message).as_slice())
+            .await;
+
+        match tokio::time::timeout(config.max_wait, rx).await {
+            Ok(Ok(delivery)) => Ok(Some(delivery)),
+            Ok(Err(_)) => Err(RpcStateError::Internal(anyhow::anyhow!(
+                "pending message dropped"
+            ))),
+            Err(_) => Ok(None),
+        }
+    }
+
     pub fn get_raw_library(&self, hash: &HashBytes) -> Result<Option<Cell>> {
         let guard = self.inner.mc_accounts.read();
         match guard.as_ref() {
@@ -187,6 +279,33 @@ impl RpcState {
             .map_err(RpcStateError::Internal)
     }
 
+    pub fn get_accounts_by_balance(
+        &self,
+        workchain: i8,
+        continuation: Option<(&StdAddr, Tokens)>,
+    ) -> Result<AccountsByBalanceIter<'_>, RpcStateError> {
+        let Some(storage) = &self.inner.storage.rpc_storage() else {
+            return Err(RpcStateError::NotSupported);
+        };
+        storage
+            .get_accounts_by_balance(workchain, continuation)
+            .map_err(RpcStateError::Internal)
+    }
+
+    pub fn get_active_accounts_by_code_hash(
+        &self,
+        code_hash: &HashBytes,
+        since_lt: u64,
+        continuation: Option<(&StdAddr, u64)>,
+    ) -> Result<ActiveAccountsIter<'_>, RpcStateError> {
+        let Some(storage) = &self.inner.storage.rpc_storage() else {
+            return Err(RpcStateError::NotSupported);
+        };
+        storage
+            .get_active_accounts_by_code_hash(code_hash, since_lt, continuation)
+            .map_err(RpcStateError::Internal)
+    }
+
     pub fn get_transactions(
         &self,
         account: &StdAddr,
@@ -223,6 +342,604 @@ impl RpcState {
             .get_dst_transaction(in_msg_hash)
             .map_err(RpcStateError::Internal)
     }
+
+    pub async fn run_get_method(
+        &self,
+        address: &StdAddr,
+        method_id: u32,
+        args: Option<Cell>,
+    ) -> Result<RunGetMethodResult, RpcStateError> {
+        let Some(config) = self.inner.blockchain_config.load_full() else {
+            return Err(RpcStateError::NotReady);
+        };
+
+        let (state, gen_utime) = match self.inner.get_account_state(address)? {
+            LoadedAccountState::NotFound { timings } => {
+                return Ok(RunGetMethodResult::NotExists { timings })
+            }
+            LoadedAccountState::Found {
+                state, gen_utime, ..
+            } => (state, gen_utime),
+        };
+        let timings = GenTimings {
+            gen_lt: state.last_trans_lt,
+            gen_utime,
+        };
+
+        let account = match state.load_account() {
+            Ok(Some(account)) => account,
+            Ok(None) => return Ok(RunGetMethodResult::NotExists { timings }),
+            Err(e) => return Err(RpcStateError::Internal(e.into())),
+        };
+        let AccountState::Active(StateInit {
+            code: Some(code),
+            data,
+            ..
+        }) = account.state
+        else {
+            return Ok(RunGetMethodResult::NotActive { timings });
+        };
+
+        let libraries = self.inner.load_libraries()?;
+
+        let context = tvm::GetMethodContext {
+            address: address.clone(),
+            balance: account.balance,
+            code,
+            data: data.unwrap_or_default(),
+            libraries,
+            config: config.config.params.clone(),
+            gen_utime,
+            gen_lt: state.last_trans_lt,
+        };
+
+        let run_config = &self.inner.config.run_get_method;
+        let gas_limit = run_config.gas_limit;
+
+        // NOTE: The permit is held by the blocking task so that timed out
+        // executions are still counted until they finish.
+        let Ok(permit) = self.inner.get_methods_semaphore.clone().try_acquire_owned() else {
+            return Err(RpcStateError::LimitExceeded("too many running get-methods"));
+        };
+
+        // NOTE: The flag is also set when the request is dropped.
+        let cancelled = CancellationFlag::new();
+        scopeguard::defer! {
+            cancelled.cancel();
+        }
+
+        // NOTE: The VM itself is not interrupted on timeout (it is still
+        // limited by the gas), but all other steps are skipped.
+        let handle = tokio::task::spawn_blocking({
+            let cancelled = cancelled.clone();
+            move || {
+                let _permit = permit;
+                context.run(method_id, args, gas_limit, &cancelled)
+            }
+        });
+        let output = match tokio::time::timeout(run_config.time_limit, handle).await {
+            Ok(Ok(Ok(output))) => output,
+            Ok(Ok(Err(e))) => return Err(RpcStateError::Internal(e)),
+            Ok(Err(e)) => return Err(RpcStateError::Internal(e.into())),
+            Err(_) => return Err(RpcStateError::Timeout),
+        };
+
+        Ok(RunGetMethodResult::Executed { output, timings })
+    }
+
+    /// Executes an external message on the latest known account state
+    /// without broadcasting it.
+    pub async fn emulate_message(
+        &self,
+        message: &OwnedMessage,
+    ) -> Result<EmulationOutput, RpcStateError> {
+        let Some(config) = self.inner.blockchain_config.load_full() else {
+            return Err(RpcStateError::NotReady);
+        };
+
+        let address = match &message.info {
+            MsgInfo::ExtIn(info) => match info.dst.as_std() {
+                Some(address) => address,
+                None => {
+                    return Err(RpcStateError::InvalidParams(
+                        "unsupported destination address",
+                    ))
+                }
+            },
+            _ => return Err(RpcStateError::InvalidParams("expected an external message")),
+        };
+
+        let shard_account = match self.inner.get_account_state(address)? {
+            LoadedAccountState::Found { state, .. } => state,
+            LoadedAccountState::NotFound { .. } => ShardAccount {
+                account: Lazy::new(&OptionalAccount::EMPTY)
+                    .map_err(|e| RpcStateError::Internal(e.into()))?,
+                last_trans_hash: HashBytes::ZERO,
+                last_trans_lt: 0,
+            },
+        };
+
+        let message = match CellBuilder::build_from(message) {
+            Ok(message) => message,
+            Err(e) => return Err(RpcStateError::Internal(e.into())),
+        };
+
+        let context = tvm::EmulationContext {
+            shard_account,
+            libraries: self.inner.load_libraries()?,
+            config: config.config.clone(),
+            global_id: config.global_id,
+            now: now_sec(),
+        };
+
+        match tokio::task::spawn_blocking(move || context.run(&message)).await {
+            Ok(Ok(output)) => Ok(output),
+            Ok(Err(e)) => Err(RpcStateError::Internal(e)),
+            Err(e) => Err(RpcStateError::Internal(e.into())),
+        }
+    }
+
+    pub fn get_latest_mc_block_id(&self) -> Result<BlockId, RpcStateError> {
+        match self.inner.storage.node_state().load_last_mc_block_id() {
+            Some(block_id) => Ok(block_id),
+            None => Err(RpcStateError::NotReady),
+        }
+    }
+
+    pub async fn get_block_data(
+        &self,
+        block_id: &BlockId,
+    ) -> Result<Option<impl AsRef<[u8]>>, RpcStateError> {
+        let storage = &self.inner.storage;
+        let Some(handle) = storage.block_handle_storage().load_handle(block_id) else {
+            return Ok(None);
+        };
+        if !handle.has_data() {
+            return Ok(None);
+        }
+
+        match storage.block_storage().load_block_data_raw(&handle).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) => Err(RpcStateError::Internal(e)),
+        }
+    }
+
+    pub async fn get_block_proof(
+        &self,
+        block_id: &BlockId,
+    ) -> Result<Option<impl AsRef<[u8]>>, RpcStateError> {
+        let storage = &self.inner.storage;
+        let Some(handle) = storage.block_handle_storage().load_handle(block_id) else {
+            return Ok(None);
+        };
+        if !handle.has_proof() {
+            return Ok(None);
+        }
+
+        match storage.block_storage().load_block_proof_raw(&handle).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) => Err(RpcStateError::Internal(e)),
+        }
+    }
+
+    /// Returns a merkle proof of the block with only its info and
+    /// state update hashes.
+    pub async fn get_block_header(
+        &self,
+        block_id: &BlockId,
+    ) -> Result<Option<Cell>, RpcStateError> {
+        let storage = &self.inner.storage;
+        let Some(handle) = storage.block_handle_storage().load_handle(block_id) else {
+            return Ok(None);
+        };
+        if !handle.has_data() {
+            return Ok(None);
+        }
+
+        let block = match storage.block_storage().load_block_data(&handle).await {
+            Ok(block) => block,
+            Err(e) => return Err(RpcStateError::Internal(e)),
+        };
+        make_block_header_proof(block.root_cell())
+            .map(Some)
+            .map_err(RpcStateError::Internal)
+    }
+
+    /// Returns the latest shard block ids referenced by the masterchain block.
+    pub fn get_shard_blocks_for_mc(
+        &self,
+        mc_seqno: u32,
+    ) -> Result<Option<Vec<BlockId>>, RpcStateError> {
+        let blocks = self.inner.storage.block_storage();
+
+        let f = || {
+            let Some(block) = blocks.find_mc_block_data(mc_seqno)? else {
+                return Ok(None);
+            };
+
+            let Some(custom) = block.load_extra()?.load_custom()? else {
+                anyhow::bail!("masterchain block without custom");
+            };
+
+            let block_ids = custom
+                .shards
+                .latest_blocks()
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Some(block_ids))
+        };
+
+        f().map_err(RpcStateError::Internal)
+    }
+
+    /// Returns the latest masterchain block produced not later than `utime`.
+    ///
+    /// Requires the archive node mode.
+    pub fn find_mc_block_by_utime(
+        &self,
+        utime: u32,
+    ) -> Result<Option<McBlockIndexEntry>, RpcStateError> {
+        let blocks = self.inner.storage.block_storage();
+        if !blocks.is_archive_node() {
+            return Err(RpcStateError::NotSupported);
+        }
+        blocks
+            .find_mc_block_by_utime(utime)
+            .map_err(RpcStateError::Internal)
+    }
+
+    /// Returns the first masterchain block with `end_lt` not less than `lt`.
+    ///
+    /// Requires the archive node mode.
+    pub fn find_mc_block_by_lt(&self, lt: u64) -> Result<Option<McBlockIndexEntry>, RpcStateError> {
+        let blocks = self.inner.storage.block_storage();
+        if !blocks.is_archive_node() {
+            return Err(RpcStateError::NotSupported);
+        }
+        blocks
+            .find_mc_block_by_lt(lt)
+            .map_err(RpcStateError::Internal)
+    }
+
+    /// Loads the account state as of the specified masterchain block
+    /// along with a Merkle proof against the shard state root.
+    ///
+    /// Returns [`RpcStateError::StatePruned`] if the requested state
+    /// was already removed.
+    pub async fn get_account_state_at(
+        &self,
+        address: &StdAddr,
+        at: StateAt,
+    ) -> Result<AccountStateAt, RpcStateError> {
+        let latest_seqno = self.get_latest_mc_block_id()?.seqno;
+        let shard_states = self.inner.storage.shard_state_storage();
+
+        let mc_seqno = match at {
+            StateAt::McSeqno(seqno) if seqno > latest_seqno => {
+                return Err(RpcStateError::InvalidParams("unknown masterchain block"));
+            }
+            StateAt::McSeqno(seqno) => seqno,
+            StateAt::Utime(utime) => {
+                match find_mc_seqno_by_utime(&self.inner.storage, utime, latest_seqno) {
+                    Ok(Some(seqno)) => seqno,
+                    Ok(None) => return Err(RpcStateError::StatePruned),
+                    Err(e) => return Err(RpcStateError::Internal(e)),
+                }
+            }
+        };
+
+        let mc_block_id = match shard_states.find_mc_state_id(mc_seqno) {
+            Ok(Some(block_id)) => block_id,
+            Ok(None) => return Err(RpcStateError::StatePruned),
+            Err(e) => return Err(RpcStateError::Internal(e)),
+        };
+        let mc_state = shard_states
+            .load_state(&mc_block_id)
+            .await
+            .map_err(RpcStateError::Internal)?;
+
+        let state = if address.is_masterchain() {
+            mc_state
+        } else {
+            let find_shard_block = || {
+                for block_id in mc_state.shards()?.latest_blocks() {
+                    let block_id = block_id?;
+                    if block_id.shard.workchain() == address.workchain as i32
+                        && block_id.shard.contains_account(&address.address)
+                    {
+                        return Ok(Some(block_id));
+                    }
+                }
+                Ok::<_, anyhow::Error>(None)
+            };
+
+            let block_id = match find_shard_block() {
+                Ok(Some(block_id)) => block_id,
+                Ok(None) => return Err(RpcStateError::InvalidParams("workchain not found")),
+                Err(e) => return Err(RpcStateError::Internal(e)),
+            };
+
+            match shard_states.contains_state(&block_id) {
+                Ok(true) => {}
+                Ok(false) => return Err(RpcStateError::StatePruned),
+                Err(e) => return Err(RpcStateError::Internal(e)),
+            }
+            shard_states
+                .load_state(&block_id)
+                .await
+                .map_err(RpcStateError::Internal)?
+        };
+
+        let (account, proof) = make_account_state_proof(state.root_cell(), &address.address)
+            .map_err(RpcStateError::Internal)?;
+
+        let state_info = state.state();
+        Ok(AccountStateAt {
+            mc_block_id,
+            block_id: *state.block_id(),
+            timings: GenTimings {
+                gen_lt: state_info.gen_lt,
+                gen_utime: state_info.gen_utime,
+            },
+            account,
+            proof,
+        })
+    }
+
+    /// Builds a chain of proofs which links the specified block
+    /// to the signed masterchain block and the latest key block.
+    ///
+    /// `mc_seqno` is the seqno of the masterchain block which references the block.
+    /// Key blocks up to `known_key_block_seqno` (inclusive) are not included.
+    pub async fn get_block_proof_chain(
+        &self,
+        block_id: &BlockId,
+        mc_seqno: u32,
+        known_key_block_seqno: Option<u32>,
+    ) -> Result<BlockProofChain, RpcStateError> {
+        self.make_block_proof_chain(block_id, mc_seqno, known_key_block_seqno)
+            .await
+            .map_err(RpcStateError::Internal)
+    }
+
+    pub async fn get_transaction_proof(
+        &self,
+        hash: &HashBytes,
+        known_key_block_seqno: Option<u32>,
+    ) -> Result<Option<TransactionProof>, RpcStateError> {
+        let Some(storage) = &self.inner.storage.rpc_storage() else {
+            return Err(RpcStateError::NotSupported);
+        };
+        let info = match storage.get_transaction_info(hash) {
+            Ok(Some(info)) => info,
+            Ok(None) => return Ok(None),
+            Err(e) => return Err(RpcStateError::Internal(e)),
+        };
+
+        self.make_transaction_proof(info, known_key_block_seqno)
+            .await
+            .map(Some)
+            .map_err(RpcStateError::Internal)
+    }
+
+    async fn make_transaction_proof(
+        &self,
+        info: TransactionInfo,
+        known_key_block_seqno: Option<u32>,
+    ) -> Result<TransactionProof> {
+        let storage = &self.inner.storage;
+        let block_handles = storage.block_handle_storage();
+        let blocks = storage.block_storage();
+
+        let short_id = info.block_id.context("transaction block is unknown")?;
+        let block_id = block_handles
+            .find_block_id(&short_id)
+            .context("transaction block not found")?;
+        let handle = block_handles
+            .load_handle(&block_id)
+            .context("block handle not found")?;
+        let block = blocks.load_block_data(&handle).await?;
+
+        let proof = make_block_proof(block.root_cell(), |block| {
+            let account_blocks = block.load_extra()?.account_blocks.load()?;
+            let (_, account_block) = account_blocks
+                .get(&info.account.address)?
+                .context("account block not found")?;
+            account_block
+                .transactions
+                .get(info.lt)?
+                .context("transaction not found")?;
+            Ok(())
+        })?;
+
+        let chain = self
+            .make_block_proof_chain(&block_id, handle.ref_by_mc_seqno(), known_key_block_seqno)
+            .await?;
+
+        Ok(TransactionProof {
+            block_id,
+            proof,
+            chain,
+        })
+    }
+
+    async fn make_block_proof_chain(
+        &self,
+        block_id: &BlockId,
+        mc_seqno: u32,
+        known_key_block_seqno: Option<u32>,
+    ) -> Result<BlockProofChain> {
+        const MAX_SHARD_BLOCK_HEADERS: usize = 32;
+        const MAX_KEY_BLOCK_PROOFS: usize = 16;
+
+        let storage = &self.inner.storage;
+        let block_handles = storage.block_handle_storage();
+        let blocks = storage.block_storage();
+
+        let load_block = |block_id: BlockId| async move {
+            let handle = block_handles
+                .load_handle(&block_id)
+                .context("block handle not found")?;
+            blocks.load_block_data(&handle).await
+        };
+
+        let mc_block_id = if block_id.is_masterchain() {
+            *block_id
+        } else {
+            block_handles
+                .find_block_id(&BlockIdShort {
+                    shard: ShardIdent::MASTERCHAIN,
+                    seqno: mc_seqno,
+                })
+                .context("masterchain block not found")?
+        };
+        let mc_handle = block_handles
+            .load_handle(&mc_block_id)
+            .context("masterchain block handle not found")?;
+        let mc_block = blocks.load_block_data(&mc_handle).await?;
+        let mc_block_proof = blocks.load_block_proof_raw(&mc_handle).await?;
+        let mc_block_proof = Bytes::copy_from_slice(mc_block_proof.as_ref());
+
+        let mut shard_block_headers = Vec::new();
+        let mc_block_header = if block_id.is_masterchain() {
+            make_block_header_proof(mc_block.root_cell())?
+        } else {
+            let mut top_block_id = None;
+            let mc_block_header = make_block_proof(mc_block.root_cell(), |block| {
+                let Some(custom) = block.load_extra()?.load_custom()? else {
+                    anyhow::bail!("masterchain block without custom");
+                };
+                for item in custom.shards.latest_blocks() {
+                    let item = item?;
+                    if item.shard.intersects(&block_id.shard) {
+                        top_block_id = Some(item);
+                    }
+                }
+                Ok(())
+            })?;
+
+            // Walk from the top shard block down to the target block
+            let mut current = top_block_id.context("shard not found in masterchain block")?;
+            loop {
+                anyhow::ensure!(
+                    current.seqno >= block_id.seqno
+                        && shard_block_headers.len() < MAX_SHARD_BLOCK_HEADERS,
+                    "block is not referenced by the masterchain block"
+                );
+
+                let block = load_block(current).await?;
+                shard_block_headers.push(make_block_header_proof(block.root_cell())?);
+                if current == *block_id {
+                    break;
+                }
+
+                current = match block.construct_prev_id()? {
+                    (_, Some(prev2)) if prev2.shard.intersects(&block_id.shard) => prev2,
+                    (prev1, _) => prev1,
+                };
+            }
+
+            mc_block_header
+        };
+
+        let mut from_seqno = mc_block.load_info()?.prev_key_block_seqno;
+        if let Some(known_seqno) = known_key_block_seqno {
+            from_seqno = std::cmp::max(from_seqno, known_seqno.saturating_add(1));
+        }
+
+        let mut key_block_ids =
+            collect_key_block_ids(block_handles, from_seqno, MAX_KEY_BLOCK_PROOFS + 1);
+        let has_more_key_blocks = key_block_ids.len() > MAX_KEY_BLOCK_PROOFS;
+        key_block_ids.truncate(MAX_KEY_BLOCK_PROOFS);
+
+        let mut key_block_proofs = Vec::with_capacity(key_block_ids.len());
+        for key_block_id in key_block_ids {
+            let handle = block_handles
+                .load_handle(&key_block_id)
+                .context("key block handle not found")?;
+
+            // NOTE: Zerostate has no proof.
+            if !handle.has_proof() {
+                continue;
+            }
+
+            let proof = blocks.load_block_proof_raw(&handle).await?;
+            key_block_proofs.push(Bytes::copy_from_slice(proof.as_ref()));
+        }
+
+        Ok(BlockProofChain {
+            mc_block_id,
+            mc_block_header,
+            mc_block_proof,
+            shard_block_headers,
+            key_block_proofs,
+            has_more_key_blocks,
+        })
+    }
+}
+
+/// Finds the latest masterchain block generated at or before the specified time.
+///
+/// Returns `None` if the block can't be determined since it was already removed.
+fn find_mc_seqno_by_utime(storage: &Storage, utime: u32, latest_seqno: u32) -> Result<Option<u32>> {
+    let blocks = storage.block_storage();
+    if blocks.is_archive_node() {
+        // NOTE: Blocks stored before the archive node mode was enabled
+        // are not indexed, so the search below is used for them.
+        if let Some(entry) = blocks.find_mc_block_by_utime(utime)? {
+            return Ok(Some(entry.block_id.seqno));
+        }
+    }
+
+    let block_handles = storage.block_handle_storage();
+    let gen_utime = |seqno: u32| {
+        let block_id = block_handles.find_block_id(&BlockIdShort {
+            shard: ShardIdent::MASTERCHAIN,
+            seqno,
+        })?;
+        block_handles
+            .load_handle(&block_id)
+            .map(|handle| handle.gen_utime())
+    };
+
+    // NOTE: GC always removes the oldest blocks first,
+    // so missing blocks are treated as generated before `utime`.
+    let (mut lo, mut hi) = (0, latest_seqno + 1);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if gen_utime(mid).map_or(true, |gen_utime| gen_utime <= utime) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+
+    let Some(seqno) = lo.checked_sub(1) else {
+        return Ok(None);
+    };
+
+    // Key blocks can outlive GC, so the search can end up near them
+    // even if the exact block is gone. Check that the found block
+    // is known and the next one is generated after `utime`.
+    let found = gen_utime(seqno).is_some();
+    let is_latest =
+        seqno == latest_seqno || gen_utime(seqno + 1).is_some_and(|gen_utime| gen_utime > utime);
+    Ok((found && is_latest).then_some(seqno))
+}
+
+/// Collects ids of at most `limit` key blocks starting from `from_seqno`.
+///
+/// NOTE: The chain must not have gaps since each key block proof
+/// is checked using the validator set from the previous key block.
+/// Ids are collected first since the iterator is not `Send`.
+fn collect_key_block_ids(
+    block_handles: &BlockHandleStorage,
+    from_seqno: u32,
+    limit: usize,
+) -> Vec<BlockId> {
+    block_handles
+        .key_blocks_iterator(KeyBlocksDirection::ForwardFrom(from_seqno))
+        .take(limit)
+        .collect()
 }
 
 pub struct RpcStateSubscriber {
@@ -259,14 +976,30 @@ impl BlockSubscriber for RpcBlockSubscriber {
 
     fn handle_block<'a>(
         &'a self,
-        _: &'a BlockSubscriberContext,
+        cx: &'a BlockSubscriberContext,
         prepared: Self::Prepared,
     ) -> Self::HandleBlockFut<'a> {
-        Box::pin(async {
+        Box::pin(async move {
             match prepared.await {
-                Ok(res) => res,
-                Err(e) => Err(e.into()),
+                Ok(res) => res?,
+                Err(e) => return Err(e.into()),
+            }
+
+            // NOTE: Notifications are sent only after the storage is updated
+            // so that clients can immediately query the new data.
+            if let Err(e) = self.inner.subscriptions.handle_block(&cx.block) {
+                tracing::error!(
+                    block_id = %cx.block.id(),
+                    "failed to notify rpc subscribers: {e:?}"
+                );
+            }
+            if let Err(e) = self.inner.pending_messages.handle_block(&cx.block) {
+                tracing::error!(
+                    block_id = %cx.block.id(),
+                    "failed to update pending messages: {e:?}"
+                );
             }
+            Ok(())
         })
     }
 }
@@ -279,8 +1012,13 @@ struct Inner {
     sc_accounts: RwLock<FastHashMap<ShardIdent, CachedAccounts>>,
     is_ready: AtomicBool,
     timings: ArcSwap<StateTimings>,
+    blockchain_config: ArcSwapOption<LatestBlockchainConfig>,
     jrpc_cache: JrpcEndpointCache,
     proto_cache: ProtoEndpointCache,
+    subscriptions: RpcSubscriptions,
+    pending_messages: PendingMessages,
+    rate_limiter: RateLimiter,
+    get_methods_semaphore: Arc<Semaphore>,
     // GC
     gc_notify: Arc<Notify>,
     gc_handle: Option<JoinHandle<()>>,
@@ -366,6 +1104,13 @@ impl Inner {
         Ok(())
     }
 
+    fn load_libraries(&self) -> Result<Dict<HashBytes, LibDescr>, RpcStateError> {
+        match &*self.mc_accounts.read() {
+            Some(cache) => Ok(cache.libraries.clone()),
+            None => Err(RpcStateError::NotReady),
+        }
+    }
+
     fn get_account_state(&self, address: &StdAddr) -> Result<LoadedAccountState, RpcStateError> {
         let is_masterchain = address.is_masterchain();
 
@@ -468,6 +1213,11 @@ impl Inner {
     }
 
     fn update_config(&self, global_id: i32, seqno: u32, config: &BlockchainConfig) {
+        self.blockchain_config
+            .store(Some(Arc::new(LatestBlockchainConfig {
+                global_id,
+                config: config.clone(),
+            })));
         self.jrpc_cache.handle_config(global_id, seqno, config);
         self.proto_cache.handle_config(global_id, seqno, config);
     }
@@ -561,6 +1311,69 @@ pub enum LoadedAccountState {
     },
 }
 
+struct LatestBlockchainConfig {
+    global_id: i32,
+    config: BlockchainConfig,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StateAt {
+    McSeqno(u32),
+    Utime(u32),
+}
+
+pub struct AccountStateAt {
+    /// Masterchain block for which the state was requested.
+    pub mc_block_id: BlockId,
+    /// Block of the shard state with the account.
+    pub block_id: BlockId,
+    pub timings: GenTimings,
+    /// `None` if the account does not exist in the shard state.
+    pub account: Option<ShardAccount>,
+    /// Merkle proof of the account against the shard state root.
+    pub proof: Cell,
+}
+
+pub struct BlockProofChain {
+    /// Masterchain block which references the target block.
+    pub mc_block_id: BlockId,
+    /// Merkle proof of the masterchain block header.
+    /// Also contains shard descriptions when the target is a shard block.
+    pub mc_block_header: Cell,
+    /// Signed masterchain block proof (`BlockProof` BOC).
+    pub mc_block_proof: Bytes,
+    /// Merkle proofs of shard block headers starting from the block
+    /// referenced by the masterchain block down to the target block.
+    pub shard_block_headers: Vec<Cell>,
+    /// Signed key block proofs starting from the key block with validators
+    /// of the masterchain block (or the first key block after the one
+    /// known to the client) towards the latest known key block.
+    pub key_block_proofs: Vec<Bytes>,
+    /// Whether the chain was truncated. The rest of it can be requested
+    /// using the last returned key block as the known one.
+    pub has_more_key_blocks: bool,
+}
+
+pub struct TransactionProof {
+    pub block_id: BlockId,
+    /// Merkle proof of the transaction against the block root.
+    pub proof: Cell,
+    pub chain: BlockProofChain,
+}
+
+pub enum RunGetMethodResult {
+    NotExists {
+        timings: GenTimings,
+    },
+    NotActive {
+        timings: GenTimings,
+    },
+    Executed {
+        output: GetMethodOutput,
+        timings: GenTimings,
+    },
+}
+
 struct CachedAccounts {
     libraries: Dict<HashBytes, LibDescr>,
     accounts: ShardAccountsDict,
@@ -611,7 +1424,18 @@ async fn transactions_gc(config: TransactionsGcConfig, storage: Storage, gc_noti
             }
         };
 
-        if let Err(e) = persistent_storage.remove_old_transactions(min_lt).await {
+        let retention = match make_transactions_retention(&storage, &config.retention).await {
+            Ok(retention) => retention,
+            Err(e) => {
+                tracing::error!("failed to prepare transactions retention rules: {e:?}");
+                continue;
+            }
+        };
+
+        if let Err(e) = persistent_storage
+            .remove_old_transactions(min_lt, &retention)
+            .await
+        {
             tracing::error!(
                 target_utime,
                 min_lt,
@@ -621,7 +1445,41 @@ async fn transactions_gc(config: TransactionsGcConfig, storage: Storage, gc_noti
     }
 }
 
+async fn make_transactions_retention(
+    storage: &Storage,
+    rules: &[TransactionsRetentionRule],
+) -> Result<TransactionsRetention> {
+    let mut retention = TransactionsRetention::default();
+    for rule in rules {
+        let min_lt = match rule.tx_ttl {
+            Some(tx_ttl) => {
+                let tx_ttl_sec = tx_ttl.as_secs().try_into().unwrap_or(u32::MAX);
+                let target_utime = now_sec().saturating_sub(tx_ttl_sec);
+                find_closest_key_block_lt(storage, target_utime).await?
+            }
+            None => 0,
+        };
+
+        for address in &rule.addresses {
+            retention.add_address(address, min_lt);
+        }
+        for code_hash in &rule.code_hashes {
+            retention.add_code_hash(code_hash, min_lt);
+        }
+    }
+    Ok(retention)
+}
+
 async fn find_closest_key_block_lt(storage: &Storage, utime: u32) -> Result<u64> {
+    // Use the masterchain blocks index if available
+    let block_storage = storage.block_storage();
+    if block_storage.is_archive_node() {
+        return Ok(match block_storage.find_mc_block_by_utime(utime)? {
+            Some(entry) => entry.start_lt,
+            None => 0,
+        });
+    }
+
     let block_handle_storage = storage.block_handle_storage();
 
     // Find the key block with max seqno which was preduced not later than `utime`
@@ -649,12 +1507,75 @@ async fn find_closest_key_block_lt(storage: &Storage, utime: u32) -> Result<u64>
     Ok(info.start_lt)
 }
 
+fn make_block_header_proof(root: &Cell) -> Result<Cell> {
+    make_block_proof(root, |_| Ok(()))
+}
+
+/// Builds a Merkle proof of the block header and everything
+/// visited by the provided closure.
+fn make_block_proof<F>(root: &Cell, f: F) -> Result<Cell>
+where
+    F: FnOnce(&Block) -> Result<()>,
+{
+    let usage_tree = UsageTree::new(UsageTreeMode::OnLoad);
+    let tracked_root = usage_tree.track(root);
+
+    let block = tracked_root.parse::<Block>()?;
+
+    let info = block.load_info()?;
+    info.load_prev_ref()?;
+    if let Some(prev_vert_ref) = &info.prev_vert_ref {
+        prev_vert_ref.load()?;
+    }
+    if let Some(master_ref) = &info.master_ref {
+        master_ref.load()?;
+    }
+
+    // NOTE: Only the root of the merkle update is included,
+    // it is enough to get the old and new state hashes.
+    block.load_state_update()?;
+
+    f(&block)?;
+
+    let proof = MerkleProof::create(root.as_ref(), usage_tree).build()?;
+    CellBuilder::build_from(proof).map_err(Into::into)
+}
+
+fn make_account_state_proof(
+    root: &Cell,
+    address: &HashBytes,
+) -> Result<(Option<ShardAccount>, Cell)> {
+    let usage_tree = UsageTree::new(UsageTreeMode::OnLoad);
+    let tracked_root = usage_tree.track(root);
+
+    let state = tracked_root.parse::<ShardStateUnsplit>()?;
+    let account = state.load_accounts()?.get(address)?.map(|(_, item)| item);
+
+    // NOTE: Account subtree is pruned, its hash can be checked
+    // against the account BOC which is returned separately.
+    let proof = MerkleProof::create(root.as_ref(), usage_tree).build()?;
+
+    let account = match account {
+        Some(item) if item.load_account()?.is_some() => Some(item),
+        _ => None,
+    };
+    Ok((account, CellBuilder::build_from(proof)?))
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum RpcStateError {
     #[error("not ready")]
     NotReady,
     #[error("not supported")]
     NotSupported,
+    #[error("state pruned")]
+    StatePruned,
+    #[error("invalid params: {0}")]
+    InvalidParams(&'static str),
+    #[error("limit exceeded: {0}")]
+    LimitExceeded(&'static str),
+    #[error("timeout")]
+    Timeout,
     #[error("internal: {0}")]
     Internal(#[from] anyhow::Error),
 }
@@ -665,7 +1586,7 @@ mod test {
 
     use anyhow::Result;
     use everscale_types::boc::Boc;
-    use everscale_types::models::{Block, BlockId};
+    use everscale_types::models::{Block, BlockId, ShardIdent};
     use everscale_types::prelude::HashBytes;
     use tycho_block_util::block::{BlockStuff, BlockStuffAug};
     use tycho_core::block_strider::{BlockSubscriber, BlockSubscriberContext};
@@ -675,8 +1596,9 @@ mod test {
         service_query_fn, BoxCloneService, Network, NetworkConfig, OverlayId, PublicOverlay,
         Response, ServiceExt, ServiceRequest,
     };
-    use tycho_storage::{Storage, StorageConfig};
+    use tycho_storage::{NewBlockMeta, Storage, StorageConfig};
 
+    use super::{collect_key_block_ids, find_mc_seqno_by_utime};
     use crate::{RpcConfig, RpcState};
 
     fn echo_service() -> BoxCloneService<ServiceRequest, Response> {
@@ -796,6 +1718,11 @@ mod test {
 
         assert_eq!(account, account_by_code_hash.address);
 
+        let is_active = rpc_state
+            .get_active_accounts_by_code_hash(&new_code_hash, 0, None)?
+            .any(|(address, _)| address.address == account);
+        assert!(is_active);
+
         Ok(())
     }
 
@@ -853,5 +1780,90 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn key_block_ids_in_range() -> Result<()> {
+        const KEY_BLOCK_COUNT: u32 = 40;
+
+        let tmp_dir = tempfile::tempdir()?;
+        let storage = Storage::builder()
+            .with_config(StorageConfig::new_potato(tmp_dir.path()))
+            .build()
+            .await?;
+        let block_handles = storage.block_handle_storage();
+
+        let mut handles = Vec::new();
+        for seqno in 1..=KEY_BLOCK_COUNT {
+            let block_id = BlockId {
+                shard: ShardIdent::MASTERCHAIN,
+                seqno: seqno * 10,
+                root_hash: HashBytes([seqno as u8; 32]),
+                file_hash: HashBytes([seqno as u8; 32]),
+            };
+            let (handle, _) = block_handles.create_or_load_handle(&block_id, NewBlockMeta {
+                is_key_block: true,
+                gen_utime: seqno,
+                ref_by_mc_seqno: block_id.seqno,
+            });
+            handles.push(handle);
+        }
+
+        // All key blocks up to the latest one must be included
+        let key_block_ids = collect_key_block_ids(block_handles, 15, usize::MAX);
+        assert_eq!(key_block_ids.len(), KEY_BLOCK_COUNT as usize - 1);
+        assert_eq!(key_block_ids.first().unwrap().seqno, 20);
+        assert_eq!(key_block_ids.last(), handles.last().map(|h| h.id()));
+
+        // The chain is truncated without gaps
+        let key_block_ids = collect_key_block_ids(block_handles, 15, 5);
+        let seqnos = key_block_ids.iter().map(|id| id.seqno).collect::<Vec<_>>();
+        assert_eq!(seqnos, [20, 30, 40, 50, 60]);
+
+        // And can be continued from the last returned key block
+        let key_block_ids = collect_key_block_ids(block_handles, 61, 5);
+        assert_eq!(key_block_ids.first().unwrap().seqno, 70);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn mc_seqno_by_utime_with_gaps() -> Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let storage = Storage::builder()
+            .with_config(StorageConfig::new_potato(tmp_dir.path()))
+            .build()
+            .await?;
+        let block_handles = storage.block_handle_storage();
+
+        // Blocks before 30 are removed except for the key block 10
+        for seqno in std::iter::once(10).chain(30..=50) {
+            let block_id = BlockId {
+                shard: ShardIdent::MASTERCHAIN,
+                seqno,
+                root_hash: HashBytes([seqno as u8; 32]),
+                file_hash: HashBytes([seqno as u8; 32]),
+            };
+            block_handles.create_or_load_handle(&block_id, NewBlockMeta {
+                is_key_block: seqno == 10,
+                gen_utime: seqno * 10,
+                ref_by_mc_seqno: seqno,
+            });
+        }
+
+        let find = |utime| find_mc_seqno_by_utime(&storage, utime, 50).unwrap();
+        assert_eq!(find(300), Some(30));
+        assert_eq!(find(355), Some(35));
+        assert_eq!(find(500), Some(50));
+        assert_eq!(find(1000), Some(50));
+
+        // Removed blocks can't be found
+        assert_eq!(find(295), None);
+        assert_eq!(find(5), None);
+
+        // Even if the search ends up at the key block
+        assert_eq!(find(105), None);
+
+        Ok(())
+    }
+
     static PUBLIC_OVERLAY_ID: OverlayId = OverlayId([1; 32]);
 }
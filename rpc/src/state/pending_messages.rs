@@ -0,0 +1,219 @@
+use anyhow::Result;
+use everscale_types::models::*;
+use everscale_types::prelude::*;
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+use tycho_block_util::block::BlockStuff;
+use tycho_util::time::now_sec;
+use tycho_util::FastHashMap;
+
+use crate::config::PendingMessagesConfig;
+
+/// Tracks broadcasted external messages until they are
+/// included into some block or expired.
+pub struct PendingMessages {
+    config: PendingMessagesConfig,
+    items: Mutex<FastHashMap<HashBytes, PendingMessage>>,
+}
+
+impl PendingMessages {
+    pub fn new(config: PendingMessagesConfig) -> Self {
+        Self {
+            config,
+            items: Default::default(),
+        }
+    }
+
+    pub fn config(&self) -> &PendingMessagesConfig {
+        &self.config
+    }
+
+    /// Starts tracking the message with the specified hash.
+    pub fn register(
+        &self,
+        hash: HashBytes,
+        expire_at: u32,
+    ) -> Result<oneshot::Receiver<MessageDelivery>, RegisterError> {
+        if expire_at < now_sec() {
+            return Err(RegisterError::AlreadyExpired);
+        }
+
+        let (tx, rx) = oneshot::channel();
+
+        let mut items = self.items.lock();
+        if let Some(item) = items.get_mut(&hash) {
+            item.expire_at = std::cmp::max(item.expire_at, expire_at);
+            item.waiters.push(tx);
+            return Ok(rx);
+        }
+
+        if items.len() >= self.config.max_pending {
+            return Err(RegisterError::TooManyPending);
+        }
+
+        items.insert(hash, PendingMessage {
+            expire_at,
+            waiters: vec![tx],
+        });
+        metrics::gauge!("tycho_rpc_pending_messages").set(items.len() as f64);
+
+        Ok(rx)
+    }
+
+    pub fn handle_block(&self, block: &BlockStuff) -> Result<()> {
+        if self.items.lock().is_empty() {
+            return Ok(());
+        }
+
+        // Collect all external messages from the block
+        let mut delivered = Vec::new();
+
+        let extra = block.load_extra()?;
+        let account_blocks = extra.account_blocks.load()?;
+        for item in account_blocks.iter() {
+            let (_, _, account_block) = item?;
+            for item in account_block.transactions.iter() {
+                let (_, _, tx_cell) = item?;
+                let tx_cell = tx_cell.into_inner();
+
+                let tx = tx_cell.parse::<Transaction>()?;
+                let Some(in_msg) = &tx.in_msg else {
+                    continue;
+                };
+
+                // NOTE: Only external messages can be tracked.
+                if in_msg.parse::<MsgType>()?.is_external_in() {
+                    delivered.push((*in_msg.repr_hash(), tx_cell));
+                }
+            }
+        }
+
+        let mut items = self.items.lock();
+
+        for (hash, transaction) in delivered {
+            if let Some(item) = items.remove(&hash) {
+                item.resolve(MessageDelivery::Delivered { transaction });
+            }
+        }
+
+        // Remove expired messages
+        let block_id = block.id();
+        if block_id.is_masterchain() {
+            let gen_utime = block.load_info()?.gen_utime;
+            items.retain(|_, item| {
+                item.waiters.retain(|tx| !tx.is_closed());
+                if item.waiters.is_empty() {
+                    return false;
+                }
+
+                if item.expire_at < gen_utime {
+                    for tx in item.waiters.drain(..) {
+                        tx.send(MessageDelivery::Expired).ok();
+                    }
+                    return false;
+                }
+
+                true
+            });
+        }
+
+        metrics::gauge!("tycho_rpc_pending_messages").set(items.len() as f64);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum RegisterError {
+    #[error("message already expired")]
+    AlreadyExpired,
+    #[error("too many pending messages")]
+    TooManyPending,
+}
+
+#[derive(Debug, Clone)]
+pub enum MessageDelivery {
+    /// Message was included into the transaction.
+    Delivered { transaction: Cell },
+    /// Message was not included into any block before its expiration.
+    Expired,
+}
+
+struct PendingMessage {
+    expire_at: u32,
+    waiters: Vec<oneshot::Sender<MessageDelivery>>,
+}
+
+impl PendingMessage {
+    fn resolve(self, delivery: MessageDelivery) {
+        for tx in self.waiters {
+            tx.send(delivery.clone()).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::test_utils::make_block;
+
+    fn make_pending_messages(max_pending: usize) -> PendingMessages {
+        PendingMessages::new(PendingMessagesConfig {
+            max_pending,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn capacity_limit() {
+        let pending = make_pending_messages(2);
+        let expire_at = now_sec() + 60;
+
+        let _rx1 = pending.register(HashBytes([1; 32]), expire_at).unwrap();
+        let _rx2 = pending.register(HashBytes([2; 32]), expire_at).unwrap();
+
+        // New messages are rejected when the limit is reached
+        assert_eq!(
+            pending.register(HashBytes([3; 32]), expire_at).err(),
+            Some(RegisterError::TooManyPending)
+        );
+
+        // But waiting for the already tracked ones is still allowed
+        let _rx3 = pending.register(HashBytes([1; 32]), expire_at).unwrap();
+    }
+
+    #[test]
+    fn expired_messages() {
+        let pending = make_pending_messages(10);
+        let now = now_sec();
+
+        // Messages which are already expired are not tracked
+        assert_eq!(
+            pending.register(HashBytes([1; 32]), now - 10).err(),
+            Some(RegisterError::AlreadyExpired)
+        );
+        assert!(pending.items.lock().is_empty());
+
+        let mut rx_expired = pending.register(HashBytes([2; 32]), now + 10).unwrap();
+        let mut rx_pending = pending.register(HashBytes([3; 32]), now + 1000).unwrap();
+        let rx_dropped = pending.register(HashBytes([4; 32]), now + 1000).unwrap();
+        drop(rx_dropped);
+
+        // Shard blocks don't affect expiration
+        let shard_block = make_block(ShardIdent::BASECHAIN, 1, now + 100);
+        pending.handle_block(&shard_block).unwrap();
+        assert_eq!(pending.items.lock().len(), 3);
+
+        let mc_block = make_block(ShardIdent::MASTERCHAIN, 1, now + 100);
+        pending.handle_block(&mc_block).unwrap();
+        assert!(matches!(
+            rx_expired.try_recv(),
+            Ok(MessageDelivery::Expired)
+        ));
+        assert!(rx_pending.try_recv().is_err());
+
+        // Messages without waiters are removed as well
+        let items = pending.items.lock();
+        assert_eq!(items.len(), 1);
+        assert!(items.contains_key(&HashBytes([3; 32])));
+    }
+}
@@ -0,0 +1,662 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::Result;
+use everscale_types::models::*;
+use everscale_types::prelude::*;
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde_json::value::RawValue;
+use tokio::sync::mpsc;
+use tycho_block_util::block::BlockStuff;
+use tycho_util::{serde_helpers, FastHashMap, FastHashSet};
+
+use crate::config::SubscriptionsConfig;
+use crate::models::LastTransactionId;
+
+/// Registry of WebSocket clients and their subscriptions.
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct RpcSubscriptions {
+    inner: Arc<Inner>,
+}
+
+impl RpcSubscriptions {
+    pub fn new(config: SubscriptionsConfig) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                config,
+                next_client_id: AtomicU64::new(0),
+                state: Default::default(),
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &SubscriptionsConfig {
+        &self.inner.config
+    }
+
+    /// Registers a new client. Returns `None` if the connections limit is reached.
+    pub fn register_client(&self) -> Option<SubscriptionsClient> {
+        let config = &self.inner.config;
+
+        let mut state = self.inner.state.write();
+        if state.clients.len() >= config.max_connections {
+            return None;
+        }
+
+        let id = self.inner.next_client_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = mpsc::channel(std::cmp::max(config.queue_capacity, 1));
+        let lagged = Arc::new(AtomicBool::new(false));
+        state.clients.insert(id, ClientEntry {
+            tx,
+            lagged: lagged.clone(),
+            subscriptions: Default::default(),
+            next_subscription_id: 0,
+        });
+
+        metrics::gauge!("tycho_rpc_ws_connections").set(state.clients.len() as f64);
+
+        Some(SubscriptionsClient {
+            id,
+            rx,
+            lagged,
+            subscriptions: self.clone(),
+        })
+    }
+
+    pub fn handle_block(&self, block: &BlockStuff) -> Result<()> {
+        let mut lagged = Vec::new();
+
+        {
+            let state = self.inner.state.read();
+            if state.is_empty() {
+                return Ok(());
+            }
+
+            let mut notify = |subscribers: &FastHashSet<SubscriberId>, result: &Arc<RawValue>| {
+                for &(client_id, subscription) in subscribers {
+                    let Some(client) = state.clients.get(&client_id) else {
+                        continue;
+                    };
+
+                    let notification = Notification {
+                        subscription,
+                        result: result.clone(),
+                    };
+                    if let Err(mpsc::error::TrySendError::Full(_)) =
+                        client.tx.try_send(notification)
+                    {
+                        client.lagged.store(true, Ordering::Release);
+                        lagged.push(client_id);
+                    }
+                }
+            };
+
+            let block_id = block.id();
+            let info = block.load_info()?;
+
+            // Notify about a new masterchain block
+            if block_id.is_masterchain() && !state.mc_blocks.is_empty() {
+                let result = to_raw_value(&McBlockNotification {
+                    block_id,
+                    gen_utime: info.gen_utime,
+                    start_lt: info.start_lt,
+                    end_lt: info.end_lt,
+                    is_key_block: info.key_block,
+                })?;
+                notify(&state.mc_blocks, &result);
+            }
+
+            // Notify about account transactions and states
+            'accounts: {
+                if state.transactions.is_empty() && state.account_states.is_empty() {
+                    break 'accounts;
+                }
+
+                let Ok(workchain) = i8::try_from(block_id.shard.workchain()) else {
+                    break 'accounts;
+                };
+
+                let extra = block.load_extra()?;
+                let account_blocks = extra.account_blocks.load()?;
+
+                let mut accounts = None::<ShardAccountsDict>;
+                for item in account_blocks.iter() {
+                    let (account, _, account_block) = item?;
+                    let address = StdAddr::new(workchain, account);
+
+                    if let Some(subscribers) = state.transactions.get(&address) {
+                        for item in account_block.transactions.iter() {
+                            let (lt, _, tx_cell) = item?;
+                            let tx_cell = tx_cell.inner();
+
+                            let result = to_raw_value(&TransactionNotification {
+                                address: &address,
+                                block_id,
+                                lt,
+                                hash: tx_cell.repr_hash(),
+                                transaction: Boc::encode_base64(tx_cell),
+                            })?;
+                            notify(subscribers, &result);
+                        }
+                    }
+
+                    if let Some(subscribers) = state.account_states.get(&address) {
+                        let accounts = match &mut accounts {
+                            Some(accounts) => accounts,
+                            None => accounts.insert(load_updated_accounts(block)?),
+                        };
+
+                        let result = match accounts.get(&address.address)? {
+                            Some((_, shard_account)) => {
+                                let (status, balance) = match shard_account.load_account()? {
+                                    Some(account) => (
+                                        AccountStatusRepr::from(&account.state),
+                                        account.balance.tokens.into_inner(),
+                                    ),
+                                    None => (AccountStatusRepr::NotExists, 0),
+                                };
+
+                                AccountStateNotification {
+                                    address: &address,
+                                    block_id,
+                                    gen_utime: info.gen_utime,
+                                    status,
+                                    balance,
+                                    last_transaction_id: Some(LastTransactionId {
+                                        lt: shard_account.last_trans_lt,
+                                        hash: shard_account.last_trans_hash,
+                                    }),
+                                }
+                            }
+                            None => AccountStateNotification {
+                                address: &address,
+                                block_id,
+                                gen_utime: info.gen_utime,
+                                status: AccountStatusRepr::NotExists,
+                                balance: 0,
+                                last_transaction_id: None,
+                            },
+                        };
+
+                        notify(subscribers, &to_raw_value(&result)?);
+                    }
+                }
+            }
+        }
+
+        if !lagged.is_empty() {
+            let mut state = self.inner.state.write();
+            for client_id in lagged {
+                if state.remove_client(client_id) {
+                    tracing::debug!(client_id, "closing lagged websocket client");
+                    metrics::counter!("tycho_rpc_ws_lagged_clients_total").increment(1);
+                }
+            }
+            state.update_metrics();
+        }
+
+        Ok(())
+    }
+
+    fn subscribe(
+        &self,
+        client_id: ClientId,
+        kind: SubscriptionKind,
+    ) -> Result<u64, SubscribeError> {
+        let config = &self.inner.config;
+
+        let mut state = self.inner.state.write();
+        let state = &mut *state;
+
+        let Some(client) = state.clients.get_mut(&client_id) else {
+            return Err(SubscribeError::Closed);
+        };
+        if client.subscriptions.len() >= config.max_subscriptions_per_connection {
+            return Err(SubscribeError::TooManySubscriptions);
+        }
+
+        let subscription = client.next_subscription_id;
+        let id = (client_id, subscription);
+
+        let kind = match kind {
+            SubscriptionKind::McBlocks => {
+                state.mc_blocks.insert(id);
+                Subscription::McBlocks
+            }
+            SubscriptionKind::Transactions(addresses) => {
+                let addresses = normalize_addresses(addresses, config)?;
+                for address in &addresses {
+                    state
+                        .transactions
+                        .entry(address.clone())
+                        .or_default()
+                        .insert(id);
+                }
+                Subscription::Transactions(addresses)
+            }
+            SubscriptionKind::AccountStates(addresses) => {
+                let addresses = normalize_addresses(addresses, config)?;
+                for address in &addresses {
+                    state
+                        .account_states
+                        .entry(address.clone())
+                        .or_default()
+                        .insert(id);
+                }
+                Subscription::AccountStates(addresses)
+            }
+        };
+
+        client.next_subscription_id += 1;
+        client.subscriptions.insert(subscription, kind);
+
+        state.update_metrics();
+        Ok(subscription)
+    }
+
+    fn unsubscribe(&self, client_id: ClientId, subscription: u64) -> bool {
+        let mut state = self.inner.state.write();
+
+        let Some(client) = state.clients.get_mut(&client_id) else {
+            return false;
+        };
+        let Some(removed) = client.subscriptions.remove(&subscription) else {
+            return false;
+        };
+
+        state.remove_subscription((client_id, subscription), &removed);
+        state.update_metrics();
+        true
+    }
+}
+
+struct Inner {
+    config: SubscriptionsConfig,
+    next_client_id: AtomicU64,
+    state: RwLock<SubscriptionsState>,
+}
+
+/// A handle to the registered client.
+///
+/// Removes all client subscriptions on drop.
+pub struct SubscriptionsClient {
+    id: ClientId,
+    rx: mpsc::Receiver<Notification>,
+    lagged: Arc<AtomicBool>,
+    subscriptions: RpcSubscriptions,
+}
+
+impl SubscriptionsClient {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn subscribe(&self, kind: SubscriptionKind) -> Result<u64, SubscribeError> {
+        self.subscriptions.subscribe(self.id, kind)
+    }
+
+    pub fn unsubscribe(&self, subscription: u64) -> bool {
+        self.subscriptions.unsubscribe(self.id, subscription)
+    }
+
+    /// Waits for the next notification.
+    ///
+    /// Returns an error if the client was removed because it was not
+    /// able to keep up with the notifications. Queued notifications
+    /// are discarded in that case.
+    pub async fn recv(&mut self) -> Result<Notification, ClientLagged> {
+        if !self.lagged.load(Ordering::Acquire) {
+            // NOTE: The channel is closed only when the client is removed as lagged.
+            if let Some(notification) = self.rx.recv().await {
+                return Ok(notification);
+            }
+        }
+        Err(ClientLagged)
+    }
+}
+
+impl Drop for SubscriptionsClient {
+    fn drop(&mut self) {
+        let mut state = self.subscriptions.inner.state.write();
+        state.remove_client(self.id);
+        state.update_metrics();
+    }
+}
+
+pub enum SubscriptionKind {
+    McBlocks,
+    Transactions(Vec<StdAddr>),
+    AccountStates(Vec<StdAddr>),
+}
+
+pub struct Notification {
+    pub subscription: u64,
+    pub result: Arc<RawValue>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SubscribeError {
+    #[error("too many subscriptions")]
+    TooManySubscriptions,
+    #[error("too many addresses")]
+    TooManyAddresses,
+    #[error("no addresses specified")]
+    NoAddresses,
+    #[error("connection closed")]
+    Closed,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("too many pending notifications")]
+pub struct ClientLagged;
+
+type ClientId = u64;
+type SubscriberId = (ClientId, u64);
+
+#[derive(Default)]
+struct SubscriptionsState {
+    clients: FastHashMap<ClientId, ClientEntry>,
+    mc_blocks: FastHashSet<SubscriberId>,
+    transactions: FastHashMap<StdAddr, FastHashSet<SubscriberId>>,
+    account_states: FastHashMap<StdAddr, FastHashSet<SubscriberId>>,
+}
+
+impl SubscriptionsState {
+    fn is_empty(&self) -> bool {
+        self.mc_blocks.is_empty() && self.transactions.is_empty() && self.account_states.is_empty()
+    }
+
+    fn remove_client(&mut self, client_id: ClientId) -> bool {
+        let Some(client) = self.clients.remove(&client_id) else {
+            return false;
+        };
+
+        for (subscription, removed) in &client.subscriptions {
+            self.remove_subscription((client_id, *subscription), removed);
+        }
+        true
+    }
+
+    fn remove_subscription(&mut self, id: SubscriberId, subscription: &Subscription) {
+        fn remove_addresses(
+            map: &mut FastHashMap<StdAddr, FastHashSet<SubscriberId>>,
+            addresses: &[StdAddr],
+            id: &SubscriberId,
+        ) {
+            for address in addresses {
+                if let Some(subscribers) = map.get_mut(address) {
+                    subscribers.remove(id);
+                    if subscribers.is_empty() {
+                        map.remove(address);
+                    }
+                }
+            }
+        }
+
+        match subscription {
+            Subscription::McBlocks => {
+                self.mc_blocks.remove(&id);
+            }
+            Subscription::Transactions(addresses) => {
+                remove_addresses(&mut self.transactions, addresses, &id);
+            }
+            Subscription::AccountStates(addresses) => {
+                remove_addresses(&mut self.account_states, addresses, &id);
+            }
+        }
+    }
+
+    fn update_metrics(&self) {
+        let subscriptions = self
+            .clients
+            .values()
+            .map(|client| client.subscriptions.len())
+            .sum::<usize>();
+
+        metrics::gauge!("tycho_rpc_ws_connections").set(self.clients.len() as f64);
+        metrics::gauge!("tycho_rpc_ws_subscriptions").set(subscriptions as f64);
+    }
+}
+
+struct ClientEntry {
+    tx: mpsc::Sender<Notification>,
+    lagged: Arc<AtomicBool>,
+    subscriptions: FastHashMap<u64, Subscription>,
+    next_subscription_id: u64,
+}
+
+enum Subscription {
+    McBlocks,
+    Transactions(Vec<StdAddr>),
+    AccountStates(Vec<StdAddr>),
+}
+
+fn normalize_addresses(
+    addresses: Vec<StdAddr>,
+    config: &SubscriptionsConfig,
+) -> Result<Vec<StdAddr>, SubscribeError> {
+    if addresses.is_empty() {
+        return Err(SubscribeError::NoAddresses);
+    } else if addresses.len() > config.max_addresses_per_subscription {
+        return Err(SubscribeError::TooManyAddresses);
+    }
+
+    // NOTE: Anycast is not used in the address keys of the block.
+    let mut addresses = addresses
+        .into_iter()
+        .map(|addr| StdAddr::new(addr.workchain, addr.address))
+        .collect::<Vec<_>>();
+    addresses.sort_unstable();
+    addresses.dedup();
+    Ok(addresses)
+}
+
+fn load_updated_accounts(block: &BlockStuff) -> Result<ShardAccountsDict> {
+    let merkle_update = block.as_ref().state_update.load()?;
+
+    // Accounts dict is stored in the second cell.
+    let mut cs = merkle_update.new.as_slice()?;
+    cs.skip_first(0, 1)?;
+    let new_accounts = cs.load_reference_cloned().map(Cell::virtualize)?;
+
+    let accounts = Lazy::<ShardAccounts>::from_raw(new_accounts).load()?;
+    let (accounts, _) = accounts.into_parts();
+    Ok(accounts)
+}
+
+fn to_raw_value<T: Serialize>(value: &T) -> Result<Arc<RawValue>> {
+    serde_json::value::to_raw_value(value)
+        .map(Arc::from)
+        .map_err(Into::into)
+}
+
+type ShardAccountsDict = Dict<HashBytes, (DepthBalanceInfo, ShardAccount)>;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", tag = "type", rename = "mcBlock")]
+struct McBlockNotification<'a> {
+    block_id: &'a BlockId,
+    gen_utime: u32,
+    #[serde(with = "serde_helpers::string")]
+    start_lt: u64,
+    #[serde(with = "serde_helpers::string")]
+    end_lt: u64,
+    is_key_block: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", tag = "type", rename = "transaction")]
+struct TransactionNotification<'a> {
+    address: &'a StdAddr,
+    block_id: &'a BlockId,
+    #[serde(with = "serde_helpers::string")]
+    lt: u64,
+    hash: &'a HashBytes,
+    transaction: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", tag = "type", rename = "accountState")]
+struct AccountStateNotification<'a> {
+    address: &'a StdAddr,
+    block_id: &'a BlockId,
+    gen_utime: u32,
+    status: AccountStatusRepr,
+    #[serde(with = "serde_helpers::string")]
+    balance: u128,
+    last_transaction_id: Option<LastTransactionId>,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum AccountStatusRepr {
+    NotExists,
+    Uninit,
+    Active,
+    Frozen,
+}
+
+impl From<&AccountState> for AccountStatusRepr {
+    fn from(value: &AccountState) -> Self {
+        match value {
+            AccountState::Uninit => Self::Uninit,
+            AccountState::Active(_) => Self::Active,
+            AccountState::Frozen(_) => Self::Frozen,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::test_utils::make_block;
+
+    fn make_subscriptions(queue_capacity: usize) -> RpcSubscriptions {
+        RpcSubscriptions::new(SubscriptionsConfig {
+            max_connections: 2,
+            max_subscriptions_per_connection: 2,
+            max_addresses_per_subscription: 2,
+            queue_capacity,
+        })
+    }
+
+    fn clients_count(subscriptions: &RpcSubscriptions) -> usize {
+        subscriptions.inner.state.read().clients.len()
+    }
+
+    #[test]
+    fn subscribe_and_unsubscribe() {
+        let subscriptions = make_subscriptions(16);
+
+        let client = subscriptions.register_client().unwrap();
+        let _other = subscriptions.register_client().unwrap();
+        assert!(subscriptions.register_client().is_none());
+
+        let addr = |byte: u8| StdAddr::new(0, HashBytes([byte; 32]));
+
+        assert!(matches!(
+            client.subscribe(SubscriptionKind::Transactions(Vec::new())),
+            Err(SubscribeError::NoAddresses)
+        ));
+        assert!(matches!(
+            client.subscribe(SubscriptionKind::Transactions(vec![
+                addr(1),
+                addr(2),
+                addr(3)
+            ])),
+            Err(SubscribeError::TooManyAddresses)
+        ));
+
+        let mc_blocks = client.subscribe(SubscriptionKind::McBlocks).unwrap();
+        let states = client
+            .subscribe(SubscriptionKind::AccountStates(vec![addr(1), addr(1)]))
+            .unwrap();
+        assert_ne!(mc_blocks, states);
+        assert!(matches!(
+            client.subscribe(SubscriptionKind::McBlocks),
+            Err(SubscribeError::TooManySubscriptions)
+        ));
+
+        {
+            let state = subscriptions.inner.state.read();
+            assert_eq!(state.mc_blocks.len(), 1);
+            assert_eq!(state.account_states[&addr(1)].len(), 1);
+        }
+
+        assert!(client.unsubscribe(states));
+        assert!(!client.unsubscribe(states));
+        assert!(subscriptions.inner.state.read().account_states.is_empty());
+
+        // All subscriptions are removed with the client
+        drop(client);
+        assert_eq!(clients_count(&subscriptions), 1);
+        assert!(subscriptions.inner.state.read().is_empty());
+        assert!(subscriptions.register_client().is_some());
+    }
+
+    #[tokio::test]
+    async fn notify_mc_blocks() {
+        let subscriptions = make_subscriptions(16);
+
+        let mut client = subscriptions.register_client().unwrap();
+        let subscription = client.subscribe(SubscriptionKind::McBlocks).unwrap();
+
+        // Shard blocks are ignored
+        subscriptions
+            .handle_block(&make_block(ShardIdent::BASECHAIN, 1, 0))
+            .unwrap();
+        assert!(client.rx.try_recv().is_err());
+
+        let block = make_block(ShardIdent::MASTERCHAIN, 1, 0);
+        subscriptions.handle_block(&block).unwrap();
+
+        let notification = client.recv().await.unwrap();
+        assert_eq!(notification.subscription, subscription);
+
+        let result: serde_json::Value = serde_json::from_str(notification.result.get()).unwrap();
+        assert_eq!(result["type"], "mcBlock");
+        assert_eq!(result["blockId"], serde_json::to_value(block.id()).unwrap());
+
+        // No notifications after unsubscribing
+        assert!(client.unsubscribe(subscription));
+        subscriptions
+            .handle_block(&make_block(ShardIdent::MASTERCHAIN, 2, 0))
+            .unwrap();
+        assert!(client.rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn lagged_client_is_removed() {
+        let subscriptions = make_subscriptions(1);
+
+        let mut client = subscriptions.register_client().unwrap();
+        client.subscribe(SubscriptionKind::McBlocks).unwrap();
+
+        let mut other = subscriptions.register_client().unwrap();
+        other.subscribe(SubscriptionKind::McBlocks).unwrap();
+
+        subscriptions
+            .handle_block(&make_block(ShardIdent::MASTERCHAIN, 1, 0))
+            .unwrap();
+        assert!(other.recv().await.is_ok());
+
+        // The queue of the first client is full now
+        let block = make_block(ShardIdent::MASTERCHAIN, 2, 0);
+        subscriptions.handle_block(&block).unwrap();
+        assert_eq!(clients_count(&subscriptions), 1);
+
+        // Queued notifications are discarded for the lagged client
+        assert!(client.recv().await.is_err());
+        assert!(matches!(
+            client.subscribe(SubscriptionKind::McBlocks),
+            Err(SubscribeError::Closed)
+        ));
+
+        // Other clients are not affected
+        let notification = other.recv().await.unwrap();
+        let result: serde_json::Value = serde_json::from_str(notification.result.get()).unwrap();
+        assert_eq!(result["blockId"], serde_json::to_value(block.id()).unwrap());
+    }
+}
@@ -0,0 +1,35 @@
+use everscale_types::merkle::MerkleUpdate;
+use everscale_types::models::*;
+use everscale_types::prelude::*;
+use tycho_block_util::block::BlockStuff;
+
+/// Builds an empty block with the specified info fields.
+pub fn make_block(shard: ShardIdent, seqno: u32, gen_utime: u32) -> BlockStuff {
+    let info = BlockInfo {
+        shard,
+        seqno,
+        gen_utime,
+        ..Default::default()
+    };
+
+    let block = Block {
+        global_id: 0,
+        info: Lazy::new(&info).unwrap(),
+        value_flow: Lazy::new(&ValueFlow::default()).unwrap(),
+        state_update: Lazy::new(&MerkleUpdate::default()).unwrap(),
+        out_msg_queue_updates: OutMsgQueueUpdates {
+            diff_hash: Default::default(),
+            tail_len: 0,
+        },
+        extra: Lazy::new(&BlockExtra::default()).unwrap(),
+    };
+
+    let root = CellBuilder::build_from(&block).unwrap();
+    let block_id = BlockId {
+        shard: info.shard,
+        seqno,
+        root_hash: *root.repr_hash(),
+        file_hash: Boc::file_hash_blake(Boc::encode(&root)),
+    };
+    BlockStuff::from_block_and_root(&block_id, block, root, 1024)
+}
@@ -0,0 +1,222 @@
+use anyhow::Result;
+use everscale_types::models::*;
+use everscale_types::prelude::*;
+use num_bigint::BigInt;
+use ton_executor::{
+    ExecuteParams, ExecutorOutput, OrdinaryTransactionExecutor, PreloadedBlockchainConfig,
+    TransactionExecutor,
+};
+use tycho_util::sync::CancellationFlag;
+use tycho_vm::{BehaviourModifiers, GasParams, SafeRc, SmcInfoBase, SmcInfoTonV6, Stack, VmState};
+
+/// Everything needed to run a get-method of the account.
+pub struct GetMethodContext {
+    pub address: StdAddr,
+    pub balance: CurrencyCollection,
+    pub code: Cell,
+    pub data: Cell,
+    pub libraries: Dict<HashBytes, LibDescr>,
+    pub config: BlockchainConfigParams,
+    pub gen_utime: u32,
+    pub gen_lt: u64,
+}
+
+pub struct GetMethodOutput {
+    pub exit_code: i32,
+    pub gas_used: u64,
+    /// Serialized resulting stack (`VmStack`).
+    pub stack: Cell,
+}
+
+impl GetMethodContext {
+    /// Runs the get-method with the specified id.
+    ///
+    /// `args` is an optional serialized stack (`VmStack`) with method arguments.
+    ///
+    /// Stops before each step if the execution was `cancelled`.
+    pub fn run(
+        self,
+        method_id: u32,
+        args: Option<Cell>,
+        gas_limit: u64,
+        cancelled: &CancellationFlag,
+    ) -> Result<GetMethodOutput> {
+        anyhow::ensure!(!cancelled.check(), "get-method execution cancelled");
+
+        let mut stack = match args {
+            Some(args) => Stack::load_from(&mut args.as_slice()?)?.items,
+            None => Vec::new(),
+        };
+        stack.push(SafeRc::new_dyn_value(BigInt::from(method_id)));
+
+        let unpacked_config = SmcInfoTonV6::unpack_config(&self.config, self.gen_utime)?;
+        let smc_info = SmcInfoBase::new()
+            .with_now(self.gen_utime)
+            .with_block_lt(self.gen_lt)
+            .with_tx_lt(self.gen_lt)
+            .with_account_balance(self.balance)
+            .with_account_addr(self.address.into())
+            .with_config(self.config)
+            .require_ton_v4()
+            .with_code(self.code.clone())
+            .require_ton_v6()
+            .with_unpacked_config(unpacked_config);
+
+        let mut vm = VmState::builder()
+            .with_smc_info(smc_info)
+            .with_code(self.code)
+            .with_data(self.data)
+            .with_stack(stack)
+            .with_libraries(&self.libraries)
+            .with_gas(GasParams {
+                max: gas_limit,
+                limit: gas_limit,
+                credit: 0,
+                price: 0,
+            })
+            .build();
+
+        anyhow::ensure!(!cancelled.check(), "get-method execution cancelled");
+
+        // NOTE: VM returns an inverted exit code.
+        let exit_code = !vm.run();
+        let gas_used = vm.gas.consumed();
+
+        anyhow::ensure!(!cancelled.check(), "get-method execution cancelled");
+        let stack = CellBuilder::build_from(&*vm.stack)?;
+
+        Ok(GetMethodOutput {
+            exit_code,
+            gas_used,
+            stack,
+        })
+    }
+}
+
+/// Everything needed to execute an external message without committing the result.
+pub struct EmulationContext {
+    pub shard_account: ShardAccount,
+    pub libraries: Dict<HashBytes, LibDescr>,
+    pub config: BlockchainConfig,
+    pub global_id: i32,
+    pub now: u32,
+}
+
+pub enum EmulationOutput {
+    /// Message was not accepted by the contract.
+    Rejected { error: String },
+    /// Message produced a transaction.
+    Executed(EmulatedTransaction),
+}
+
+pub struct EmulatedTransaction {
+    pub transaction: Cell,
+    pub out_msgs: Vec<Cell>,
+    pub total_fees: CurrencyCollection,
+    /// Compute phase exit code. `None` if the compute phase was skipped.
+    pub exit_code: Option<i32>,
+    pub aborted: bool,
+}
+
+impl EmulationContext {
+    pub fn run(mut self, message: &Cell) -> Result<EmulationOutput> {
+        let config = PreloadedBlockchainConfig::with_config(self.config, self.global_id)?;
+
+        let global_version = config.global_version();
+        let signature_with_id = global_version
+            .capabilities
+            .contains(GlobalCapability::CapSignatureWithId)
+            .then_some(self.global_id);
+
+        let min_lt = self.shard_account.last_trans_lt + 1;
+        let params = ExecuteParams {
+            state_libs: self.libraries,
+            block_unixtime: self.now,
+            block_lt: min_lt,
+            seed_block: HashBytes::ZERO,
+            block_version: global_version.version,
+            behavior_modifiers: Some(BehaviourModifiers {
+                signature_with_id,
+                ..Default::default()
+            }),
+            debug: false,
+        };
+        let unpacked_config =
+            SmcInfoTonV6::unpack_config(&config.raw_config().params, params.block_unixtime)?;
+
+        let result = OrdinaryTransactionExecutor::new().execute_with_libs_and_params(
+            Some(message),
+            &mut self.shard_account,
+            min_lt,
+            &params,
+            &config,
+            unpacked_config,
+        );
+
+        let (total_fees, ExecutorOutput { transaction, .. }) = match result {
+            Ok(output) => output,
+            Err(e) => {
+                return Ok(EmulationOutput::Rejected {
+                    error: e.to_string(),
+                })
+            }
+        };
+
+        let out_msgs = transaction
+            .out_msgs
+            .values()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let tx = transaction.transaction.load()?;
+        let (exit_code, aborted) = match tx.load_info()? {
+            TxInfo::Ordinary(info) => {
+                let exit_code = match &info.compute_phase {
+                    ComputePhase::Executed(phase) => Some(phase.exit_code),
+                    ComputePhase::Skipped(_) => None,
+                };
+                (exit_code, info.aborted)
+            }
+            TxInfo::TickTock(_) => anyhow::bail!("unexpected tick-tock transaction"),
+        };
+
+        Ok(EmulationOutput::Executed(EmulatedTransaction {
+            transaction: transaction.transaction.into_inner(),
+            out_msgs,
+            total_fees,
+            exit_code,
+            aborted,
+        }))
+    }
+}
+
+/// Computes a get-method id from its name.
+pub fn method_id_from_name(name: &str) -> u32 {
+    crc16(name.as_bytes()) as u32 | 0x10000
+}
+
+// CRC-16/XMODEM
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn well_known_method_ids() {
+        assert_eq!(method_id_from_name("seqno"), 85143);
+        assert_eq!(method_id_from_name("get_public_key"), 78748);
+    }
+}
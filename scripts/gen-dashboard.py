@@ -608,6 +608,18 @@ def net_conn_manager() -> RowPanel:
             "tycho_net_active_peers", "Number of currently active peers"
         ),
         create_gauge_panel("tycho_net_known_peers", "Number of currently known peers"),
+        create_gauge_panel(
+            "tycho_net_banned_peers", "Number of temporarily banned peers"
+        ),
+        create_counter_panel(
+            "tycho_net_peer_penalties_total",
+            "Number of peer penalties over time",
+            legend_format="{{instance}} {{kind}}",
+            by_labels=["instance", "kind"],
+        ),
+        create_counter_panel(
+            "tycho_net_peer_bans_total", "Number of peer bans over time"
+        ),
     ]
     return create_row("network: Connection Manager", metrics)
 
@@ -632,6 +644,27 @@ def net_request_handler() -> RowPanel:
         create_gauge_panel(
             "tycho_net_req_handlers", "Current number of incoming request handlers"
         ),
+        create_counter_panel(
+            "tycho_net_in_requests_rejected_total",
+            "Number of rejected incoming requests",
+            legend_format="{{instance}} {{reason}}",
+            by_labels=["instance", "reason"],
+        ),
+        create_counter_panel(
+            "tycho_net_in_request_bytes_total",
+            "Incoming requests traffic",
+            UNITS.BYTES_SEC_IEC,
+        ),
+        create_counter_panel(
+            "tycho_net_compression_raw_bytes_total",
+            "Compressed payloads traffic before compression",
+            UNITS.BYTES_SEC_IEC,
+        ),
+        create_counter_panel(
+            "tycho_net_compression_compressed_bytes_total",
+            "Compressed payloads traffic after compression",
+            UNITS.BYTES_SEC_IEC,
+        ),
     ]
     return create_row("network: Request Handler", metrics)
 
@@ -755,6 +788,32 @@ def core_block_strider() -> RowPanel:
             "tycho_core_subscriber_handle_archive_time",
             "Total time to handle archive by all subscribers",
         ),
+        create_heatmap_panel(
+            "tycho_core_archive_upload_time",
+            "Time to upload archive to the object storage",
+        ),
+        create_counter_panel(
+            "tycho_core_archive_uploaded_bytes_total",
+            "Uploaded archives size",
+            UNITS.BYTES,
+        ),
+        create_counter_panel(
+            "tycho_core_archive_upload_errors_total",
+            "Archive upload errors",
+        ),
+        create_gauge_panel(
+            "tycho_core_last_uploaded_archive_id",
+            "Last uploaded archive id",
+        ),
+        create_heatmap_panel(
+            "tycho_core_archive_mirror_download_time",
+            "Time to download archive from the mirror",
+        ),
+        create_counter_panel(
+            "tycho_core_archive_mirror_downloaded_bytes_total",
+            "Downloaded archives size from the mirror",
+            UNITS.BYTES,
+        ),
         create_heatmap_panel(
             "tycho_core_apply_block_time",
             "Time to apply and save block state",
@@ -923,6 +982,25 @@ def storage() -> RowPanel:
             "tycho_storage_load_block_total",
             "Block cache hit ratio",
         ),
+        create_heatmap_panel(
+            "tycho_storage_cold_migration_time", "Time to move data to cold storage"
+        ),
+        create_counter_panel(
+            "tycho_storage_cold_moved_block_entries_total",
+            "Number of block parts moved to cold storage",
+        ),
+        create_counter_panel(
+            "tycho_storage_cold_moved_states_total",
+            "Number of states moved to cold storage",
+        ),
+        create_counter_panel(
+            "tycho_storage_cold_new_cells_total",
+            "Number of new cells in cold storage",
+        ),
+        create_counter_panel(
+            "tycho_storage_cold_cell_loads_total",
+            "Number of cells loaded from cold storage",
+        ),
     ]
     return create_row("Storage", metrics)
 
@@ -940,6 +1018,17 @@ def jrpc() -> RowPanel:
         "GetTransactionsList",
         "GetTransaction",
         "GetDstTransaction",
+        "GetBlock",
+        "GetBlockHeader",
+        "GetBlockProof",
+        "GetShardBlocksForMc",
+        "GetLatestMcBlockId",
+        "RunGetMethod",
+        "EmulateMessage",
+        "SendMessageAndWait",
+        "GetContractStateAt",
+        "GetAccountsByBalance",
+        "GetActiveAccountsByCodeHash",
     ]
 
     metrics = [
@@ -966,6 +1055,34 @@ def jrpc() -> RowPanel:
             "tycho_rpc_in_req_fail_total",
             "Number of failed incoming JRPC requests over time",
         ),
+        create_gauge_panel(
+            "tycho_rpc_ws_connections", "Number of active WebSocket connections"
+        ),
+        create_gauge_panel(
+            "tycho_rpc_ws_subscriptions", "Number of active WebSocket subscriptions"
+        ),
+        create_counter_panel(
+            "tycho_rpc_ws_lagged_clients_total",
+            "Number of WebSocket clients dropped due to backpressure",
+        ),
+        create_heatmap_panel(
+            "tycho_rpc_ws_request_time", "Time to handle WebSocket requests"
+        ),
+        create_gauge_panel(
+            "tycho_rpc_pending_messages", "Number of messages waiting for delivery"
+        ),
+        create_counter_panel(
+            "tycho_rpc_client_requests_total",
+            "Number of incoming requests per client",
+            legend_format="{{instance}} {{client}}",
+            by_labels=["instance", "client"],
+        ),
+        create_counter_panel(
+            "tycho_rpc_client_rate_limited_total",
+            "Number of rate limited requests per client",
+            legend_format="{{instance}} {{client}}",
+            by_labels=["instance", "client"],
+        ),
     ]
     for method in methods:
         metrics.append(
@@ -0,0 +1,357 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use everscale_types::models::BlockId;
+use weedb::rocksdb;
+
+use crate::db::*;
+use crate::store::{NodeStateStorage, RpcStorage};
+use crate::util::InstanceId;
+use crate::{
+    Storage, StorageConfig, BASE_DB_SUBDIR, COLD_DB_SUBDIR, FILES_SUBDIR, MEMPOOL_SUBDIR,
+    RPC_DB_SUBDIR,
+};
+
+impl Storage {
+    /// Creates a consistent checkpoint of all databases and persistent states
+    /// in the specified directory. The directory must not exist.
+    ///
+    /// NOTE: Database files are hard-linked when the checkpoint is
+    /// on the same filesystem, so it is cheap to create.
+    pub async fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<CheckpointInfo> {
+        let path = path.as_ref().to_path_buf();
+        anyhow::ensure!(
+            !path.exists(),
+            "checkpoint directory already exists: {}",
+            path.display()
+        );
+
+        // NOTE: Blocks are not processed while the lock is held,
+        // so the base and RPC databases are consistent with each other.
+        // Must be acquired before the GC lock since block processing
+        // acquires the GC lock while holding the pause guard.
+        let _checkpoint_lock = self.inner.checkpoint_lock.write().await;
+
+        // NOTE: States are not stored or removed while the lock is held,
+        // so all cells in the checkpoint are referenced by some state.
+        let _gc_lock = self.shard_state_storage().lock_gc().await;
+
+        let this = self.clone();
+        let span = tracing::Span::current();
+        tokio::task::spawn_blocking(move || {
+            let _span = span.enter();
+
+            tracing::info!(path = %path.display(), "creating storage checkpoint");
+
+            std::fs::create_dir_all(&path)?;
+
+            let base_db_path = path.join(BASE_DB_SUBDIR);
+            create_db_checkpoint(this.base_db().rocksdb(), &base_db_path)?;
+
+            // NOTE: Info is read from the checkpoint itself
+            // to describe exactly the data which was saved.
+            let info = {
+                let caches =
+                    weedb::Caches::with_capacity(this.config().rocksdb_lru_capacity.as_u64() as _);
+                let db = open_read_only(&base_db_path, [cf_descriptor::<tables::State>(&caches)])?;
+                let (last_mc_block_id, init_mc_block_id) =
+                    NodeStateStorage::load_mc_block_ids_raw(&db)?;
+                CheckpointInfo {
+                    last_mc_block_id,
+                    init_mc_block_id,
+                    has_rpc: this.rpc_storage().is_some(),
+                }
+            };
+
+            if let Some(rpc_storage) = this.rpc_storage() {
+                create_db_checkpoint(rpc_storage.db().rocksdb(), &path.join(RPC_DB_SUBDIR))?;
+            }
+            create_db_checkpoint(
+                this.mempool_storage().db.rocksdb(),
+                &path.join(MEMPOOL_SUBDIR),
+            )?;
+
+            // NOTE: Block parts are written to the cold DB before they are
+            // removed from the base DB, so the cold DB checkpoint must be created last.
+            if let Some(cold_db) = this.cold_db() {
+                create_db_checkpoint(cold_db.rocksdb(), &path.join(COLD_DB_SUBDIR))?;
+            }
+
+            // Persistent states are immutable after they are written,
+            // so they are just hard-linked (if possible).
+            copy_dir(
+                &this.config().root_dir.join(FILES_SUBDIR),
+                &path.join(FILES_SUBDIR),
+                true,
+            )
+            .context("failed to copy files")?;
+
+            tracing::info!(?info, "created storage checkpoint");
+            Ok(info)
+        })
+        .await?
+    }
+
+    /// Delays checkpoints until the returned guard is dropped.
+    ///
+    /// Must be held while writing data which must be consistent
+    /// between databases (e.g. an applied block and its RPC index).
+    pub async fn pause_checkpoints(&self) -> CheckpointPauseGuard {
+        CheckpointPauseGuard {
+            _guard: self.inner.checkpoint_lock.clone().read_owned().await,
+        }
+    }
+}
+
+/// See [`Storage::pause_checkpoints`].
+#[must_use]
+pub struct CheckpointPauseGuard {
+    _guard: tokio::sync::OwnedRwLockReadGuard<()>,
+}
+
+/// Restores the storage from the checkpoint created by [`Storage::create_checkpoint`].
+///
+/// The node must be stopped. The restored node will start from the last
+/// applied masterchain block of the checkpoint, so the cold boot is skipped.
+///
+/// The checkpoint is copied into a temporary directory first, so the existing
+/// storage is replaced (with `overwrite`) only after everything is copied.
+pub fn restore_checkpoint<P: AsRef<Path>>(
+    checkpoint_dir: P,
+    config: &StorageConfig,
+    overwrite: bool,
+) -> Result<CheckpointInfo> {
+    const SUBDIRS: [&str; 4] = [BASE_DB_SUBDIR, RPC_DB_SUBDIR, MEMPOOL_SUBDIR, FILES_SUBDIR];
+
+    let checkpoint_dir = checkpoint_dir.as_ref();
+    let root_dir = &config.root_dir;
+
+    anyhow::ensure!(
+        checkpoint_dir.join(BASE_DB_SUBDIR).exists(),
+        "invalid checkpoint: base DB not found"
+    );
+
+    // NOTE: Cold DB can be placed outside of the storage root.
+    let cold_db_source = checkpoint_dir.join(COLD_DB_SUBDIR);
+    let cold_db_target = match config.cold_storage_dir() {
+        Some(path) => Some(path),
+        None if cold_db_source.exists() => {
+            anyhow::bail!("checkpoint has cold storage data but cold storage is disabled")
+        }
+        None => None,
+    };
+
+    let targets = SUBDIRS
+        .into_iter()
+        .map(|subdir| root_dir.join(subdir))
+        .chain(cold_db_target.clone())
+        .filter(|target| target.exists())
+        .collect::<Vec<_>>();
+    anyhow::ensure!(
+        overwrite || targets.is_empty(),
+        "storage already exists at {}",
+        root_dir.display()
+    );
+
+    std::fs::create_dir_all(root_dir)?;
+
+    // NOTE: Temp dirs are placed next to the targets
+    // so that they can be renamed without copying.
+    let temp_dir = make_restore_temp_dir(root_dir)?;
+    let mut restored = Vec::new();
+
+    // NOTE: Database files must be copied since RocksDB can reuse
+    // some of them (e.g. WAL) which will modify the checkpoint itself.
+    for subdir in SUBDIRS {
+        let source = checkpoint_dir.join(subdir);
+        if !source.exists() {
+            continue;
+        }
+
+        tracing::info!(subdir, "restoring from checkpoint");
+        let temp_path = temp_dir.path().join(subdir);
+        copy_dir(&source, &temp_path, subdir == FILES_SUBDIR)
+            .with_context(|| format!("failed to restore {subdir}"))?;
+        restored.push((temp_path, root_dir.join(subdir)));
+    }
+
+    // NOTE: Must live until the restored data is moved
+    let _cold_temp_dir = match &cold_db_target {
+        Some(target) if cold_db_source.exists() => {
+            tracing::info!(subdir = COLD_DB_SUBDIR, "restoring from checkpoint");
+            let parent = target.parent().context("invalid cold storage path")?;
+            std::fs::create_dir_all(parent)?;
+
+            let cold_temp_dir = make_restore_temp_dir(parent)?;
+            let temp_path = cold_temp_dir.path().join(COLD_DB_SUBDIR);
+            copy_dir(&cold_db_source, &temp_path, false).context("failed to restore cold DB")?;
+            restored.push((temp_path, target.clone()));
+            Some(cold_temp_dir)
+        }
+        _ => None,
+    };
+
+    let info = prepare_restored_storage(temp_dir.path(), config)?;
+
+    // Replace the existing storage only after everything is restored
+    for target in targets {
+        tracing::warn!(path = %target.display(), "removing existing storage data");
+        std::fs::remove_dir_all(&target)?;
+    }
+    for (temp_path, target) in restored {
+        std::fs::rename(&temp_path, &target)
+            .with_context(|| format!("failed to move restored data to {}", target.display()))?;
+    }
+
+    tracing::info!(?info, "restored storage from checkpoint");
+    Ok(info)
+}
+
+/// Updates the instance id of the restored storage.
+fn prepare_restored_storage(path: &Path, config: &StorageConfig) -> Result<CheckpointInfo> {
+    let caches = weedb::Caches::with_capacity(config.rocksdb_lru_capacity.as_u64() as _);
+
+    let base_db = BaseDb::builder_prepared(path.join(BASE_DB_SUBDIR), caches.clone()).build()?;
+    let node_state = NodeStateStorage::new(base_db);
+
+    let last_mc_block_id = node_state
+        .load_last_mc_block_id()
+        .context("checkpoint has no applied blocks")?;
+
+    // Restored storage is a new instance, so it must have a new id.
+    // The RPC storage keeps the same id as the node storage to prevent reindexing.
+    let old_instance_id = node_state.load_instance_id();
+    let new_instance_id = rand::random::<InstanceId>();
+    node_state.store_instance_id(new_instance_id);
+
+    let rpc_path = path.join(RPC_DB_SUBDIR);
+    let has_rpc = rpc_path.exists();
+    if has_rpc {
+        let rpc_db = RpcDb::builder_prepared(rpc_path, caches).build()?;
+        let rpc_storage = RpcStorage::new(rpc_db);
+        if rpc_storage.load_instance_id() == old_instance_id {
+            rpc_storage.store_instance_id(new_instance_id);
+        }
+    }
+
+    Ok(CheckpointInfo {
+        last_mc_block_id: Some(last_mc_block_id),
+        init_mc_block_id: node_state.load_init_mc_block_id(),
+        has_rpc,
+    })
+}
+
+fn make_restore_temp_dir(parent: &Path) -> Result<tempfile::TempDir> {
+    tempfile::Builder::new()
+        .prefix(".restore")
+        .tempdir_in(parent)
+        .with_context(|| format!("failed to create temp dir in {}", parent.display()))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CheckpointInfo {
+    pub last_mc_block_id: Option<BlockId>,
+    pub init_mc_block_id: Option<BlockId>,
+    pub has_rpc: bool,
+}
+
+fn create_db_checkpoint(db: &rocksdb::DB, path: &Path) -> Result<()> {
+    let checkpoint = rocksdb::checkpoint::Checkpoint::new(db)?;
+    checkpoint.create_checkpoint(path)?;
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path, hard_link: bool) -> Result<()> {
+    std::fs::create_dir_all(to)?;
+
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_dir(&source, &target, hard_link)?;
+            continue;
+        }
+
+        // Skip partially written files
+        if source
+            .extension()
+            .is_some_and(|extension| extension == "temp")
+        {
+            continue;
+        }
+
+        if !hard_link || std::fs::hard_link(&source, &target).is_err() {
+            std::fs::copy(&source, &target)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn checkpoint_roundtrip() -> Result<()> {
+        let (storage, tmp_dir) = Storage::new_temp().await?;
+
+        let block_id = BlockId {
+            shard: everscale_types::models::ShardIdent::MASTERCHAIN,
+            seqno: 123,
+            ..Default::default()
+        };
+        storage.node_state().store_init_mc_block_id(&block_id);
+        storage.node_state().store_last_mc_block_id(&block_id);
+        let instance_id = storage.node_state().load_instance_id();
+
+        let checkpoint_dir = tmp_dir.path().join("checkpoint");
+        let info = storage.create_checkpoint(&checkpoint_dir).await?;
+        assert_eq!(info.last_mc_block_id, Some(block_id));
+        drop(storage);
+
+        let restored_dir = tempfile::tempdir()?;
+        let config = StorageConfig::new_potato(restored_dir.path());
+        let info = restore_checkpoint(&checkpoint_dir, &config, false)?;
+        assert_eq!(info.last_mc_block_id, Some(block_id));
+        assert_eq!(info.init_mc_block_id, Some(block_id));
+
+        // Restoring into an existing storage requires an explicit overwrite
+        assert!(restore_checkpoint(&checkpoint_dir, &config, false).is_err());
+
+        let storage = Storage::builder()
+            .with_config(config.clone())
+            .build()
+            .await?;
+        assert_eq!(storage.node_state().load_last_mc_block_id(), Some(block_id));
+        assert!(storage.node_state().load_instance_id() != instance_id);
+        drop(storage);
+
+        // Failed restore must keep the existing storage
+        let (empty_storage, empty_tmp_dir) = Storage::new_temp().await?;
+        let empty_checkpoint_dir = empty_tmp_dir.path().join("checkpoint");
+        let info = empty_storage
+            .create_checkpoint(&empty_checkpoint_dir)
+            .await?;
+        assert_eq!(info.last_mc_block_id, None);
+        drop(empty_storage);
+
+        assert!(restore_checkpoint(&empty_checkpoint_dir, &config, true).is_err());
+
+        let storage = Storage::builder()
+            .with_config(config.clone())
+            .build()
+            .await?;
+        assert_eq!(storage.node_state().load_last_mc_block_id(), Some(block_id));
+        drop(storage);
+
+        // Existing storage is replaced with an explicit overwrite
+        let info = restore_checkpoint(&checkpoint_dir, &config, true)?;
+        assert_eq!(info.last_mc_block_id, Some(block_id));
+
+        Ok(())
+    }
+}
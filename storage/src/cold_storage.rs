@@ -0,0 +1,57 @@
+use std::sync::{Arc, Weak};
+
+use anyhow::Result;
+use tycho_util::metrics::HistogramGuard;
+
+use crate::{ColdStorageConfig, Inner, Storage};
+
+impl Storage {
+    /// Moves block parts and states which are older than
+    /// [`ColdStorageConfig::hot_mc_blocks`] to the cold storage.
+    ///
+    /// Does nothing if the cold storage is disabled.
+    pub async fn move_to_cold_storage(&self) -> Result<()> {
+        let Some(config) = &self.inner.config.cold_storage else {
+            return Ok(());
+        };
+
+        let Some(last_mc_block_id) = self.node_state().load_last_mc_block_id() else {
+            return Ok(());
+        };
+
+        let until_mc_seqno = last_mc_block_id.seqno.saturating_sub(config.hot_mc_blocks);
+        if until_mc_seqno == 0 {
+            return Ok(());
+        }
+
+        let _hist = HistogramGuard::begin("tycho_storage_cold_migration_time");
+
+        self.block_storage()
+            .move_blocks_to_cold(until_mc_seqno, config.max_entries_per_batch)
+            .await?;
+        self.shard_state_storage()
+            .move_states_to_cold(until_mc_seqno, config.max_entries_per_batch)
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub(crate) fn spawn_cold_storage_loop(inner: &Arc<Inner>, config: ColdStorageConfig) {
+    let inner = Arc::downgrade(inner);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.interval);
+        loop {
+            interval.tick().await;
+
+            let Some(inner) = Weak::upgrade(&inner) else {
+                break;
+            };
+
+            let storage = Storage { inner };
+            if let Err(e) = storage.move_to_cold_storage().await {
+                tracing::error!("failed to move data to cold storage: {e:?}");
+            }
+        }
+    });
+}
@@ -55,6 +55,25 @@ pub struct StorageConfig {
 
     /// Blocks cache config.
     pub blocks_cache: BlocksCacheConfig,
+
+    /// Archive node mode.
+    ///
+    /// Blocks and archives GC is disabled and states of masterchain
+    /// key blocks are never removed. Masterchain blocks are also indexed
+    /// by generation time and logical time.
+    ///
+    /// Default: false.
+    pub archive_node: bool,
+
+    /// Cold storage config.
+    ///
+    /// Cold storage is disabled if this field is `None`.
+    pub cold_storage: Option<ColdStorageConfig>,
+
+    /// Scheduled manual compaction of all databases.
+    ///
+    /// Scheduled compaction is disabled if this field is `None`.
+    pub compaction_window: Option<CompactionWindowConfig>,
 }
 
 impl StorageConfig {
@@ -71,8 +90,20 @@ impl StorageConfig {
             states_gc: None,
             blocks_gc: None,
             blocks_cache: BlocksCacheConfig::default(),
+            archive_node: false,
+            cold_storage: None,
+            compaction_window: None,
         }
     }
+
+    /// Returns the path to the cold storage DB (if enabled).
+    pub fn cold_storage_dir(&self) -> Option<PathBuf> {
+        let config = self.cold_storage.as_ref()?;
+        Some(match &config.root_dir {
+            Some(root_dir) => self.root_dir.join(root_dir),
+            None => self.root_dir.join(crate::COLD_DB_SUBDIR),
+        })
+    }
 }
 
 impl Default for StorageConfig {
@@ -125,22 +156,94 @@ impl Default for StorageConfig {
             states_gc: Some(StatesGcConfig::default()),
             blocks_gc: Some(BlocksGcConfig::default()),
             blocks_cache: BlocksCacheConfig::default(),
+            archive_node: false,
+            cold_storage: None,
+            compaction_window: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColdStorageConfig {
+    /// Path to the cold storage DB. Relative paths are resolved
+    /// against the storage root directory.
+    ///
+    /// Default: `cold` subdirectory of the storage root.
+    pub root_dir: Option<PathBuf>,
+
+    /// Number of the most recent masterchain blocks whose data
+    /// and states are kept in the hot storage.
+    ///
+    /// NOTE: Older states are still available but they are slower to load,
+    /// so it should be greater than the distance to the latest persistent state.
+    ///
+    /// Default: 100000.
+    pub hot_mc_blocks: u32,
+
+    /// Interval between migrations.
+    ///
+    /// Default: `10 min`.
+    #[serde(with = "serde_helpers::humantime")]
+    pub interval: Duration,
+
+    /// Max number of entries written at once.
+    ///
+    /// Default: 100000.
+    pub max_entries_per_batch: usize,
+}
+
+impl Default for ColdStorageConfig {
+    fn default() -> Self {
+        Self {
+            root_dir: None,
+            hot_mc_blocks: 100_000,
+            interval: Duration::from_secs(600),
+            max_entries_per_batch: 100_000,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompactionWindowConfig {
+    /// Hour of the day (UTC) when the compaction of
+    /// all column families of all databases starts.
+    ///
+    /// NOTE: Manual compaction can't be interrupted, so it can take
+    /// much longer than an hour on large databases.
+    ///
+    /// Must be in the range `0..24`.
+    ///
+    /// Default: 3.
+    pub start_hour: u8,
+}
+
+impl Default for CompactionWindowConfig {
+    fn default() -> Self {
+        Self { start_hour: 3 }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct ArchivesGcConfig {
     /// Remove archives after this interval after the new persistent state
     #[serde(with = "serde_helpers::humantime")]
     pub persistent_state_offset: Duration,
+    /// Keep local archives until they are uploaded to the external storage.
+    ///
+    /// NOTE: Requires the archive uploader to be configured.
+    ///
+    /// Default: false.
+    pub keep_until_uploaded: bool,
 }
 
 impl Default for ArchivesGcConfig {
     fn default() -> Self {
         Self {
             persistent_state_offset: Duration::from_secs(300),
+            keep_until_uploaded: false,
         }
     }
 }
@@ -1,9 +1,11 @@
 use std::future::Future;
 use std::path::Path;
 
+use anyhow::Context;
 use tycho_util::sync::CancellationFlag;
 use weedb::{
-    Caches, MigrationError, Semver, Tables, VersionProvider, WeeDb, WeeDbBuilder, WeeDbRaw,
+    rocksdb, Caches, ColumnFamily, ColumnFamilyOptions, MigrationError, Semver, Tables,
+    VersionProvider, WeeDb, WeeDbBuilder, WeeDbRaw,
 };
 
 pub mod refcount;
@@ -70,6 +72,9 @@ pub type BaseDb = WeeDb<BaseTables>;
 
 pub trait BaseDbExt {
     fn normalize_version(&self) -> anyhow::Result<()>;
+
+    /// Fails if the DB requires migrations.
+    fn ensure_latest_version(&self) -> anyhow::Result<()>;
 }
 
 impl BaseDbExt for BaseDb {
@@ -99,6 +104,61 @@ impl BaseDbExt for BaseDb {
         provider.set_version(self.raw(), [0, 0, 1])?;
         Ok(())
     }
+
+    fn ensure_latest_version(&self) -> anyhow::Result<()> {
+        ensure_latest_base_db_version(self.rocksdb())
+    }
+}
+
+/// Same as [`BaseDbExt::ensure_latest_version`] but for the DB
+/// which was opened directly (e.g. in read-only mode).
+pub fn ensure_latest_base_db_version(db: &rocksdb::DB) -> anyhow::Result<()> {
+    let state = db
+        .cf_handle(tables::State::NAME)
+        .context("state column family not found")?;
+
+    if let Some(db_name) = db.get_pinned_cf(state, StateVersionProvider::DB_NAME_KEY)? {
+        anyhow::ensure!(
+            db_name.as_ref() == BaseDb::NAME.as_bytes(),
+            "expected db name: {}, got: {}",
+            BaseDb::NAME,
+            String::from_utf8_lossy(db_name.as_ref())
+        );
+    }
+
+    let version = match db.get_pinned_cf(state, StateVersionProvider::DB_VERSION_KEY)? {
+        Some(version) => Some(
+            Semver::try_from(version.as_ref()).map_err(|_e| MigrationError::InvalidDbVersion)?,
+        ),
+        None => None,
+    };
+    anyhow::ensure!(
+        version == Some(BaseDb::VERSION),
+        "unexpected DB version: {version:?}, expected: {:?}",
+        BaseDb::VERSION
+    );
+    Ok(())
+}
+
+/// Opens only the listed column families of an existing DB in read-only mode.
+pub(crate) fn open_read_only<I>(path: &Path, descriptors: I) -> anyhow::Result<rocksdb::DB>
+where
+    I: IntoIterator<Item = rocksdb::ColumnFamilyDescriptor>,
+{
+    let mut opts = rocksdb::Options::default();
+    opts.set_log_level(rocksdb::LogLevel::Error);
+
+    rocksdb::DB::open_cf_descriptors_read_only(&opts, path, descriptors, false)
+        .with_context(|| format!("failed to open {} in read-only mode", path.display()))
+}
+
+pub(crate) fn cf_descriptor<T>(caches: &Caches) -> rocksdb::ColumnFamilyDescriptor
+where
+    T: ColumnFamily + ColumnFamilyOptions<Caches>,
+{
+    let mut opts = rocksdb::Options::default();
+    T::options(&mut opts, &mut caches.clone());
+    rocksdb::ColumnFamilyDescriptor::new(T::NAME, opts)
 }
 
 impl WithMigrations for BaseDb {
@@ -125,6 +185,8 @@ weedb::tables! {
         pub archive_block_ids: tables::ArchiveBlockIds,
         pub block_handles: tables::BlockHandles,
         pub key_blocks: tables::KeyBlocks,
+        pub mc_blocks_by_utime: tables::McBlocksByUtime,
+        pub mc_blocks_by_lt: tables::McBlocksByLt,
         pub full_block_ids: tables::FullBlockIds,
         pub package_entries: tables::PackageEntries,
         pub block_data_entries: tables::BlockDataEntries,
@@ -224,15 +286,33 @@ pub type RpcDb = WeeDb<RpcTables>;
 
 impl WithMigrations for RpcDb {
     const NAME: &'static str = "rpc";
-    const VERSION: Semver = [0, 0, 1];
+    const VERSION: Semver = [0, 0, 2];
 
     fn register_migrations(
-        _migrations: &mut Migrations<Self>,
+        migrations: &mut Migrations<Self>,
         _cancelled: CancellationFlag,
     ) -> Result<(), MigrationError> {
-        // TODO: register migrations here
+        migrations.register([0, 0, 1], [0, 0, 2], rpc_migrations::v0_0_1_to_0_0_2)?;
+        Ok(())
+    }
+}
+
+mod rpc_migrations {
+    use super::*;
+
+    /// Account indices (by balance, by code hash and lt) were added.
+    ///
+    /// They can only be built from the full shard states, so the stored
+    /// instance id is removed here. A new one will be generated on start,
+    /// which forces the RPC state to reset all accounts and fill the indices.
+    pub fn v0_0_1_to_0_0_2(db: &RpcDb) -> Result<(), MigrationError> {
+        tracing::info!("resetting instance id to rebuild account indices");
+        db.state.remove(INSTANCE_ID)?;
         Ok(())
     }
+
+    // NOTE: Must be the same as in the RPC storage.
+    const INSTANCE_ID: &[u8] = b"instance_id";
 }
 
 weedb::tables! {
@@ -243,6 +323,9 @@ weedb::tables! {
         pub transactions_by_in_msg: tables::TransactionsByInMsg,
         pub code_hashes: tables::CodeHashes,
         pub code_hashes_by_address: tables::CodeHashesByAddress,
+        pub accounts_by_balance: tables::AccountsByBalance,
+        pub code_hashes_by_lt: tables::CodeHashesByLt,
+        pub account_indices_by_address: tables::AccountIndicesByAddress,
     }
 }
 
@@ -275,6 +358,33 @@ weedb::tables! {
     }
 }
 
+// === Cold DB ===
+
+pub type ColdDb = WeeDb<ColdTables>;
+
+impl WithMigrations for ColdDb {
+    const NAME: &'static str = "cold";
+    const VERSION: Semver = [0, 0, 1];
+
+    fn register_migrations(
+        _migrations: &mut Migrations<Self>,
+        _cancelled: CancellationFlag,
+    ) -> Result<(), MigrationError> {
+        // TODO: register migrations here
+        Ok(())
+    }
+}
+
+weedb::tables! {
+    /// Rarely accessed data which was moved out of the [`BaseDb`].
+    pub struct ColdTables<Caches> {
+        pub state: tables::State,
+        pub package_entries: tables::PackageEntries,
+        pub block_data_entries: tables::BlockDataEntries,
+        pub cells: tables::ColdCells,
+    }
+}
+
 // === Migrations stuff ===
 
 trait WithMigrations: Sized {
@@ -122,6 +122,44 @@ impl ColumnFamily for KeyBlocks {
 
 impl ColumnFamilyOptions<Caches> for KeyBlocks {}
 
+/// Maps masterchain block generation time to block info (archive node only)
+/// - Key: `gen_utime: u32 (BE), seqno: u32 (BE)`
+/// - Value: `McBlockIndexEntry`
+pub struct McBlocksByUtime;
+
+impl ColumnFamily for McBlocksByUtime {
+    const NAME: &'static str = "mc_blocks_by_utime";
+
+    fn read_options(opts: &mut ReadOptions) {
+        opts.set_verify_checksums(false);
+    }
+}
+
+impl ColumnFamilyOptions<Caches> for McBlocksByUtime {
+    fn options(opts: &mut rocksdb::Options, caches: &mut Caches) {
+        default_block_based_table_factory(opts, caches);
+    }
+}
+
+/// Maps masterchain block end lt to block info (archive node only)
+/// - Key: `end_lt: u64 (BE), seqno: u32 (BE)`
+/// - Value: `McBlockIndexEntry`
+pub struct McBlocksByLt;
+
+impl ColumnFamily for McBlocksByLt {
+    const NAME: &'static str = "mc_blocks_by_lt";
+
+    fn read_options(opts: &mut ReadOptions) {
+        opts.set_verify_checksums(false);
+    }
+}
+
+impl ColumnFamilyOptions<Caches> for McBlocksByLt {
+    fn options(opts: &mut rocksdb::Options, caches: &mut Caches) {
+        default_block_based_table_factory(opts, caches);
+    }
+}
+
 /// Maps block id (partial) to file hash
 pub struct FullBlockIds;
 
@@ -333,6 +371,32 @@ impl ColumnFamilyOptions<Caches> for Cells {
     }
 }
 
+/// Stores cells data of the old states
+/// - Key: `[u8; 32]` (cell repr hash)
+/// - Value: `StorageCell`
+pub struct ColdCells;
+
+impl ColumnFamily for ColdCells {
+    const NAME: &'static str = "cells";
+}
+
+impl ColumnFamilyOptions<Caches> for ColdCells {
+    fn options(opts: &mut Options, caches: &mut Caches) {
+        opts.set_level_compaction_dynamic_level_bytes(true);
+        optimize_for_level_compaction(opts, ByteSize::mib(512u64));
+        optimize_for_point_lookup(opts, caches);
+
+        opts.set_merge_operator_associative("cell_merge", refcount::merge_operator);
+        opts.set_compaction_filter("cell_compaction", refcount::compaction_filter);
+
+        // cells are rarely accessed, so it is better to save some space
+        opts.set_compression_type(DBCompressionType::Zstd);
+        opts.set_bottommost_compression_type(DBCompressionType::Zstd);
+
+        opts.set_optimize_filters_for_hits(true);
+    }
+}
+
 /// Stores temp cells data
 /// - Key: `ton_types::UInt256` (cell repr hash)
 /// - Value: `StorageCell`
@@ -620,9 +684,13 @@ impl ColumnFamilyOptions<Caches> for Transactions {
 
 /// Transaction hash to full key
 /// - Key: `tx_hash: [u8; 32]`
-/// - Value: `workchain: i8, account: [u8; 32], lt: u64`
+/// - Value: `workchain: i8, account: [u8; 32], lt: u64, block_id: BlockIdShort (optional)`
 pub struct TransactionsByHash;
 
+impl TransactionsByHash {
+    pub const VALUE_LEN: usize = Transactions::KEY_LEN + 16;
+}
+
 impl ColumnFamily for TransactionsByHash {
     const NAME: &'static str = "transactions_by_hash";
 }
@@ -686,6 +754,63 @@ impl ColumnFamilyOptions<Caches> for CodeHashesByAddress {
     }
 }
 
+/// Accounts sorted by balance (descending)
+/// - Key: `workchain: i8, !balance: u128 (BE), account: [u8; 32]`
+/// - Value: empty
+pub struct AccountsByBalance;
+
+impl AccountsByBalance {
+    pub const KEY_LEN: usize = 1 + 16 + 32;
+}
+
+impl ColumnFamily for AccountsByBalance {
+    const NAME: &'static str = "accounts_by_balance";
+}
+
+impl ColumnFamilyOptions<Caches> for AccountsByBalance {
+    fn options(opts: &mut Options, caches: &mut Caches) {
+        zstd_block_based_table_factory(opts, caches);
+    }
+}
+
+/// Code hash with account address sorted by the last transaction lt (descending)
+/// - Key: `code_hash: [u8; 32], !last_trans_lt: u64 (BE), workchain: i8, account: [u8; 32]`
+/// - Value: empty
+pub struct CodeHashesByLt;
+
+impl CodeHashesByLt {
+    pub const KEY_LEN: usize = 32 + 8 + 1 + 32;
+}
+
+impl ColumnFamily for CodeHashesByLt {
+    const NAME: &'static str = "code_hashes_by_lt";
+}
+
+impl ColumnFamilyOptions<Caches> for CodeHashesByLt {
+    fn options(opts: &mut Options, caches: &mut Caches) {
+        zstd_block_based_table_factory(opts, caches);
+    }
+}
+
+/// Account address to the indexed account info
+/// - Key: `workchain: i8, account: [u8; 32]`
+/// - Value: `balance: u128 (BE), last_trans_lt: u64 (BE), code_hash: Option<[u8; 32]>`
+pub struct AccountIndicesByAddress;
+
+impl AccountIndicesByAddress {
+    pub const KEY_LEN: usize = 1 + 32;
+}
+
+impl ColumnFamily for AccountIndicesByAddress {
+    const NAME: &'static str = "account_indices_by_address";
+}
+
+impl ColumnFamilyOptions<Caches> for AccountIndicesByAddress {
+    fn options(opts: &mut Options, caches: &mut Caches) {
+        zstd_block_based_table_factory(opts, caches);
+    }
+}
+
 /// Stores mempool point data
 /// - Key: `round: u32, digest: [u8; 32]`
 /// - Value: `Point`
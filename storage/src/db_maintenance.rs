@@ -0,0 +1,284 @@
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use tycho_util::time::now_sec;
+use weedb::rocksdb;
+
+use crate::{
+    CompactionWindowConfig, Inner, Storage, BASE_DB_SUBDIR, COLD_DB_SUBDIR, MEMPOOL_SUBDIR,
+    RPC_DB_SUBDIR,
+};
+
+impl Storage {
+    /// Returns names of all opened key-value databases.
+    pub fn kv_db_names(&self) -> Vec<&'static str> {
+        self.kv_dbs().into_iter().map(|(name, _)| name).collect()
+    }
+
+    /// Collects column family stats of all opened key-value databases.
+    pub async fn kv_db_stats(&self) -> Result<Vec<DbStats>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || {
+            this.kv_dbs()
+                .into_iter()
+                .map(|(name, db)| collect_db_stats(name, db))
+                .collect()
+        })
+        .await?
+    }
+
+    /// Triggers a manual compaction of the whole key range of the
+    /// specified column family (or all column families if `cf` is `None`).
+    /// Returns the names of the compacted column families.
+    pub async fn compact_kv_db(&self, db: &str, cf: Option<&str>) -> Result<Vec<String>> {
+        let db_name = db.to_owned();
+        let cf = cf.map(str::to_owned);
+
+        let this = self.clone();
+        let span = tracing::Span::current();
+        tokio::task::spawn_blocking(move || {
+            let _span = span.enter();
+
+            let (name, db) = this.find_kv_db(&db_name)?;
+            let cf_names = resolve_column_families(db, cf.as_deref())?;
+            compact_db(name, db, &cf_names)?;
+            Ok(cf_names)
+        })
+        .await?
+    }
+
+    /// Same as [`compact_kv_db`] but doesn't wait for the compaction to finish.
+    /// Returns the names of the column families which will be compacted.
+    ///
+    /// [`compact_kv_db`]: Self::compact_kv_db
+    pub async fn start_kv_db_compaction(&self, db: &str, cf: Option<&str>) -> Result<Vec<String>> {
+        let db_name = db.to_owned();
+        let cf = cf.map(str::to_owned);
+
+        let this = self.clone();
+        let cf_names = tokio::task::spawn_blocking(move || {
+            let (_, db) = this.find_kv_db(&db_name)?;
+            resolve_column_families(db, cf.as_deref())
+        })
+        .await??;
+
+        let db_name = db.to_owned();
+        let this = self.clone();
+        let span = tracing::Span::current();
+        let to_compact = cf_names.clone();
+        tokio::task::spawn_blocking(move || {
+            let _span = span.enter();
+
+            let res = this
+                .find_kv_db(&db_name)
+                .and_then(|(name, db)| compact_db(name, db, &to_compact));
+            if let Err(e) = res {
+                tracing::error!(db = db_name, "failed to compact database: {e:?}");
+            }
+        });
+
+        Ok(cf_names)
+    }
+
+    fn find_kv_db(&self, db_name: &str) -> Result<(&'static str, &rocksdb::DB)> {
+        self.kv_dbs()
+            .into_iter()
+            .find(|(name, _)| *name == db_name)
+            .with_context(|| format!("unknown database: {db_name}"))
+    }
+
+    fn kv_dbs(&self) -> Vec<(&'static str, &rocksdb::DB)> {
+        let mut dbs = Vec::with_capacity(4);
+        dbs.push((BASE_DB_SUBDIR, self.base_db().rocksdb().as_ref()));
+        if let Some(rpc_storage) = self.rpc_storage() {
+            dbs.push((RPC_DB_SUBDIR, rpc_storage.db().rocksdb().as_ref()));
+        }
+        dbs.push((MEMPOOL_SUBDIR, self.mempool_storage().db.rocksdb().as_ref()));
+        if let Some(cold_db) = self.cold_db() {
+            dbs.push((COLD_DB_SUBDIR, cold_db.rocksdb().as_ref()));
+        }
+        dbs
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DbStats {
+    pub name: String,
+    /// Memory used by the block cache.
+    ///
+    /// NOTE: The cache is shared between all databases.
+    pub block_cache_usage: u64,
+    /// Memory used by the pinned entries of the block cache.
+    pub block_cache_pinned_usage: u64,
+    pub column_families: Vec<ColumnFamilyStats>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnFamilyStats {
+    pub name: String,
+    /// Estimated size of the live data in bytes.
+    pub estimated_live_data_size: u64,
+    /// Total size of all SST files in bytes.
+    pub total_sst_files_size: u64,
+    /// Number of SST files on all levels.
+    pub sst_files: u64,
+    pub estimated_keys: u64,
+    /// Estimated number of bytes to rewrite to get all levels
+    /// below the target size.
+    pub pending_compaction_bytes: u64,
+    /// Size of active and unflushed memtables in bytes.
+    pub memtables_size: u64,
+}
+
+fn collect_db_stats(name: &str, db: &rocksdb::DB) -> Result<DbStats> {
+    use rocksdb::properties;
+
+    const MAX_LEVELS: usize = 7;
+
+    let get_int = |cf: &rocksdb::ColumnFamily, property: &rocksdb::properties::PropName| {
+        db.property_int_value_cf(cf, property)
+            .map(Option::unwrap_or_default)
+    };
+
+    let mut column_families = Vec::new();
+    for cf_name in list_column_families(db)? {
+        let Some(cf) = db.cf_handle(&cf_name) else {
+            continue;
+        };
+
+        let mut sst_files = 0;
+        for level in 0..MAX_LEVELS {
+            sst_files += get_int(cf, &properties::num_files_at_level(level))?;
+        }
+
+        column_families.push(ColumnFamilyStats {
+            estimated_live_data_size: get_int(cf, properties::ESTIMATE_LIVE_DATA_SIZE)?,
+            total_sst_files_size: get_int(cf, properties::TOTAL_SST_FILES_SIZE)?,
+            sst_files,
+            estimated_keys: get_int(cf, properties::ESTIMATE_NUM_KEYS)?,
+            pending_compaction_bytes: get_int(cf, properties::ESTIMATE_PENDING_COMPACTION_BYTES)?,
+            memtables_size: get_int(cf, properties::CUR_SIZE_ALL_MEM_TABLES)?,
+            name: cf_name,
+        });
+    }
+
+    Ok(DbStats {
+        name: name.to_owned(),
+        block_cache_usage: db
+            .property_int_value(properties::BLOCK_CACHE_USAGE)?
+            .unwrap_or_default(),
+        block_cache_pinned_usage: db
+            .property_int_value(properties::BLOCK_CACHE_PINNED_USAGE)?
+            .unwrap_or_default(),
+        column_families,
+    })
+}
+
+fn resolve_column_families(db: &rocksdb::DB, cf: Option<&str>) -> Result<Vec<String>> {
+    match cf {
+        Some(cf) => {
+            anyhow::ensure!(db.cf_handle(cf).is_some(), "unknown column family: {cf}");
+            Ok(vec![cf.to_owned()])
+        }
+        None => list_column_families(db),
+    }
+}
+
+fn compact_db(db_name: &str, db: &rocksdb::DB, cf_names: &[String]) -> Result<()> {
+    let mut opts = rocksdb::CompactOptions::default();
+    opts.set_exclusive_manual_compaction(false);
+    let null = Option::<&[u8]>::None;
+
+    for cf_name in cf_names {
+        let cf = db
+            .cf_handle(cf_name)
+            .with_context(|| format!("unknown column family: {cf_name}"))?;
+
+        let started_at = Instant::now();
+        tracing::info!(db = db_name, cf = cf_name, "started manual compaction");
+        db.compact_range_cf_opt(cf, null, null, &opts);
+        tracing::info!(
+            db = db_name,
+            cf = cf_name,
+            elapsed = %humantime::format_duration(started_at.elapsed()),
+            "finished manual compaction"
+        );
+    }
+
+    Ok(())
+}
+
+fn list_column_families(db: &rocksdb::DB) -> Result<Vec<String>> {
+    rocksdb::DB::list_cf(&rocksdb::Options::default(), db.path())
+        .context("failed to list column families")
+}
+
+pub(crate) fn spawn_compaction_window_loop(inner: &Arc<Inner>, config: CompactionWindowConfig) {
+    let inner = Arc::downgrade(inner);
+    tokio::spawn(async move {
+        loop {
+            let delay = compaction_window_delay(config.start_hour, now_sec());
+            tokio::time::sleep(Duration::from_secs(delay as u64)).await;
+
+            let Some(inner) = Weak::upgrade(&inner) else {
+                break;
+            };
+
+            let storage = Storage { inner };
+            for name in storage.kv_db_names() {
+                if let Err(e) = storage.compact_kv_db(name, None).await {
+                    tracing::error!(db = name, "failed to compact database: {e:?}");
+                }
+            }
+        }
+    });
+}
+
+/// Returns the number of seconds until the next compaction window start.
+///
+/// NOTE: Waits for the next day if the window starts right now.
+fn compaction_window_delay(start_hour: u8, now: u32) -> u32 {
+    const DAY: u32 = 86400;
+
+    let start_at = start_hour as u32 * 3600;
+    match (start_at + DAY - now % DAY) % DAY {
+        0 => DAY,
+        delay => delay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compaction_window_delay_works() {
+        const HOUR: u32 = 3600;
+        const DAY: u32 = 24 * HOUR;
+
+        // Some midnight
+        let midnight = 1_700_006_400;
+        assert_eq!(midnight % DAY, 0);
+
+        // Window later this day
+        assert_eq!(compaction_window_delay(3, midnight), 3 * HOUR);
+        assert_eq!(
+            compaction_window_delay(3, midnight + 2 * HOUR + 1),
+            HOUR - 1
+        );
+
+        // Window starts right now
+        assert_eq!(compaction_window_delay(3, midnight + 3 * HOUR), DAY);
+        assert_eq!(compaction_window_delay(0, midnight), DAY);
+
+        // Window already passed this day
+        assert_eq!(compaction_window_delay(3, midnight + 3 * HOUR + 1), DAY - 1);
+        assert_eq!(compaction_window_delay(3, midnight + 23 * HOUR), 4 * HOUR);
+        assert_eq!(compaction_window_delay(0, midnight + DAY - 1), 1);
+        assert_eq!(
+            compaction_window_delay(23, midnight + 23 * HOUR + 1),
+            DAY - 1
+        );
+    }
+}
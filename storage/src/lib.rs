@@ -5,12 +5,17 @@ use anyhow::Result;
 use tycho_util::metrics::spawn_metrics_loop;
 use weedb::rocksdb;
 
+pub use self::checkpoint::*;
 pub use self::config::*;
 pub use self::db::*;
+pub use self::db_maintenance::{ColumnFamilyStats, DbStats};
 pub use self::store::*;
 
+mod checkpoint;
+mod cold_storage;
 mod config;
 mod db;
+mod db_maintenance;
 mod store;
 
 mod util {
@@ -27,6 +32,7 @@ const BASE_DB_SUBDIR: &str = "base";
 const RPC_DB_SUBDIR: &str = "rpc";
 const FILES_SUBDIR: &str = "files";
 const MEMPOOL_SUBDIR: &str = "mempool";
+const COLD_DB_SUBDIR: &str = "cold";
 
 pub struct StorageBuilder {
     config: StorageConfig,
@@ -35,6 +41,14 @@ pub struct StorageBuilder {
 
 impl StorageBuilder {
     pub async fn build(self) -> Result<Storage> {
+        if let Some(config) = &self.config.compaction_window {
+            anyhow::ensure!(
+                config.start_hour < 24,
+                "invalid compaction window start hour: {}",
+                config.start_hour
+            );
+        }
+
         let root = FileDb::new(&self.config.root_dir)?;
 
         let file_db = root.create_subdir(FILES_SUBDIR)?;
@@ -118,17 +132,38 @@ impl StorageBuilder {
         base_db.normalize_version()?; // TODO: Remove on testnet reset
         base_db.apply_migrations().await?;
 
+        let cold_db = match self.config.cold_storage_dir() {
+            Some(path) => {
+                tracing::debug!(threads, fdlimit, path = %path.display(), "opening cold RocksDB");
+                let cold_db = ColdDb::builder_prepared(path, caches.clone())
+                    .with_metrics_enabled(self.config.rocksdb_enable_metrics)
+                    .with_options(|opts, _| update_options(opts, threads, fdlimit))
+                    .build()?;
+
+                cold_db.apply_migrations().await?;
+
+                Some(cold_db)
+            }
+            None => None,
+        };
+
         let temp_file_storage = TempFileStorage::new(&file_db)?;
 
         let blocks_storage_config = BlockStorageConfig {
             archive_chunk_size: self.config.archive_chunk_size,
             blocks_cache: self.config.blocks_cache,
             split_block_tasks: self.config.split_block_tasks,
+            keep_archives_until_uploaded: self
+                .config
+                .archives_gc
+                .is_some_and(|config| config.keep_until_uploaded),
+            archive_node: self.config.archive_node,
         };
         let block_handle_storage = Arc::new(BlockHandleStorage::new(base_db.clone()));
         let block_connection_storage = Arc::new(BlockConnectionStorage::new(base_db.clone()));
         let block_storage = Arc::new(BlockStorage::new(
             base_db.clone(),
+            cold_db.clone(),
             blocks_storage_config,
             block_handle_storage.clone(),
             block_connection_storage.clone(),
@@ -136,10 +171,12 @@ impl StorageBuilder {
         ));
         let shard_state_storage = ShardStateStorage::new(
             base_db.clone(),
+            cold_db.clone(),
             block_handle_storage.clone(),
             block_storage.clone(),
             temp_file_storage.clone(),
             self.config.cells_cache_size,
+            self.config.archive_node,
         )?;
         let persistent_state_storage = PersistentStateStorage::new(
             base_db.clone(),
@@ -173,6 +210,7 @@ impl StorageBuilder {
         let inner = Arc::new(Inner {
             root,
             base_db,
+            cold_db,
             config: self.config,
             block_handle_storage,
             block_storage,
@@ -184,6 +222,7 @@ impl StorageBuilder {
             internal_queue_storage,
             temp_file_storage,
             mempool_storage,
+            checkpoint_lock: Default::default(),
         });
 
         if inner.config.rocksdb_enable_metrics {
@@ -193,9 +232,20 @@ impl StorageBuilder {
                     rpc_state.db().refresh_metrics();
                 }
                 this.mempool_storage.db.refresh_metrics();
+                if let Some(cold_db) = this.cold_db.as_ref() {
+                    cold_db.refresh_metrics();
+                }
             });
         }
 
+        if let Some(config) = inner.config.cold_storage.clone() {
+            cold_storage::spawn_cold_storage_loop(&inner, config);
+        }
+
+        if let Some(config) = inner.config.compaction_window {
+            db_maintenance::spawn_compaction_window_loop(&inner, config);
+        }
+
         Ok(Storage { inner })
     }
 
@@ -246,6 +296,10 @@ impl Storage {
         &self.inner.base_db
     }
 
+    pub fn cold_db(&self) -> Option<&ColdDb> {
+        self.inner.cold_db.as_ref()
+    }
+
     pub fn config(&self) -> &StorageConfig {
         &self.inner.config
     }
@@ -294,6 +348,7 @@ impl Storage {
 struct Inner {
     root: FileDb,
     base_db: BaseDb,
+    cold_db: Option<ColdDb>,
     config: StorageConfig,
 
     block_handle_storage: Arc<BlockHandleStorage>,
@@ -306,4 +361,5 @@ struct Inner {
     internal_queue_storage: InternalQueueStorage,
     temp_file_storage: TempFileStorage,
     mempool_storage: MempoolStorage,
+    checkpoint_lock: Arc<tokio::sync::RwLock<()>>,
 }
@@ -0,0 +1,65 @@
+use bytes::Buf;
+use everscale_types::models::*;
+
+use super::{StoredValue, StoredValueBuffer};
+
+/// Info about the masterchain block stored in the archive node indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct McBlockIndexEntry {
+    pub block_id: BlockId,
+    pub gen_utime: u32,
+    pub start_lt: u64,
+    pub end_lt: u64,
+}
+
+impl McBlockIndexEntry {
+    pub(crate) fn utime_key(gen_utime: u32, seqno: u32) -> [u8; 8] {
+        let mut key = [0; 8];
+        key[..4].copy_from_slice(&gen_utime.to_be_bytes());
+        key[4..].copy_from_slice(&seqno.to_be_bytes());
+        key
+    }
+
+    pub(crate) fn lt_key(end_lt: u64, seqno: u32) -> [u8; 12] {
+        let mut key = [0; 12];
+        key[..8].copy_from_slice(&end_lt.to_be_bytes());
+        key[8..].copy_from_slice(&seqno.to_be_bytes());
+        key
+    }
+}
+
+impl StoredValue for McBlockIndexEntry {
+    /// 4 bytes `gen_utime`
+    /// 8 bytes `start_lt`
+    /// 8 bytes `end_lt`
+    /// 80 bytes `BlockId`
+    const SIZE_HINT: usize = 4 + 8 + 8 + BlockId::SIZE_HINT;
+
+    type OnStackSlice = [u8; Self::SIZE_HINT];
+
+    fn serialize<T: StoredValueBuffer>(&self, buffer: &mut T) {
+        buffer.write_raw_slice(&self.gen_utime.to_le_bytes());
+        buffer.write_raw_slice(&self.start_lt.to_le_bytes());
+        buffer.write_raw_slice(&self.end_lt.to_le_bytes());
+        self.block_id.serialize(buffer);
+    }
+
+    fn deserialize(reader: &mut &[u8]) -> Self
+    where
+        Self: Sized,
+    {
+        debug_assert!(reader.remaining() >= Self::SIZE_HINT);
+
+        let gen_utime = reader.get_u32_le();
+        let start_lt = reader.get_u64_le();
+        let end_lt = reader.get_u64_le();
+        let block_id = BlockId::deserialize(reader);
+
+        Self {
+            block_id,
+            gen_utime,
+            start_lt,
+            end_lt,
+        }
+    }
+}
@@ -26,6 +26,7 @@ use tycho_util::sync::{rayon_run, CancellationFlag};
 use tycho_util::{FastHashSet, FastHasherState};
 use weedb::{rocksdb, ColumnFamily, OwnedPinnableSlice};
 
+pub use self::mc_block_index::McBlockIndexEntry;
 pub use self::package_entry::{BlockDataEntryKey, PackageEntryKey, PartialBlockId};
 use crate::db::*;
 use crate::util::*;
@@ -34,6 +35,7 @@ use crate::{
     BlocksCacheConfig, HandleCreationStatus, NewBlockMeta,
 };
 
+mod mc_block_index;
 mod package_entry;
 
 const METRIC_LOAD_BLOCK_TOTAL: &str = "tycho_storage_load_block_total";
@@ -41,6 +43,7 @@ const METRIC_BLOCK_CACHE_HIT_TOTAL: &str = "tycho_storage_block_cache_hit_total"
 
 pub struct BlockStorage {
     db: BaseDb,
+    cold_db: Option<ColdDb>,
     blocks_cache: BlocksCache,
     block_handle_storage: Arc<BlockHandleStorage>,
     block_connection_storage: Arc<BlockConnectionStorage>,
@@ -51,6 +54,10 @@ pub struct BlockStorage {
     archive_ids_tx: ArchiveIdsTx,
     archive_chunk_size: NonZeroU32,
     split_block_semaphore: Arc<Semaphore>,
+    keep_archives_until_uploaded: bool,
+    archive_node: bool,
+    /// Prevents blocks GC while block parts are moved to the cold storage.
+    cold_lock: tokio::sync::Mutex<()>,
 }
 
 impl BlockStorage {
@@ -58,6 +65,7 @@ impl BlockStorage {
 
     pub fn new(
         db: BaseDb,
+        cold_db: Option<ColdDb>,
         config: BlockStorageConfig,
         block_handle_storage: Arc<BlockHandleStorage>,
         block_connection_storage: Arc<BlockConnectionStorage>,
@@ -86,16 +94,20 @@ impl BlockStorage {
 
         Self {
             db,
+            cold_db,
             blocks_cache,
             block_handle_storage,
             block_connection_storage,
             archive_ids_tx,
             archive_chunk_size,
             split_block_semaphore,
+            keep_archives_until_uploaded: config.keep_archives_until_uploaded,
+            archive_node: config.archive_node,
             archive_ids: Default::default(),
             block_subscriptions: Default::default(),
             store_block_data: Default::default(),
             prev_archive_commit: Default::default(),
+            cold_lock: Default::default(),
         }
     }
 
@@ -155,6 +167,23 @@ impl BlockStorage {
         drop(iter);
 
         for block_id in blocks_to_finish {
+            // NOTE: Older versions moved finished block data to the cold storage
+            // without its "started" marker, so it must just be removed.
+            if let Some(cold_db) = &self.cold_db {
+                let size_key = BlockDataEntryKey {
+                    block_id,
+                    chunk_index: BLOCK_DATA_SIZE_MAGIC,
+                };
+                if cold_db.block_data_entries.get(size_key.to_vec())?.is_some() {
+                    let started_key = BlockDataEntryKey {
+                        block_id,
+                        chunk_index: BLOCK_DATA_STARTED_MAGIC,
+                    };
+                    self.db.block_data_entries.remove(started_key.to_vec())?;
+                    continue;
+                }
+            }
+
             tracing::info!(?block_id, "found unfinished block");
 
             let key = PackageEntryKey {
@@ -162,8 +191,8 @@ impl BlockStorage {
                 ty: ArchiveEntryType::Block,
             };
 
-            let data = match self.db.package_entries.get(key.to_vec())? {
-                Some(data) => data,
+            let data = match self.get_package_entry(&key.to_vec())? {
+                Some((_, data)) => data,
                 None => return Err(BlockStorageError::BlockDataNotFound.into()),
             };
 
@@ -361,6 +390,16 @@ impl BlockStorage {
 
             let _lock = handle.block_data_lock().write().await;
             if !handle.has_data() {
+                if self.archive_node && block_id.is_masterchain() {
+                    let info = block.load_info()?;
+                    self.index_mc_block(&McBlockIndexEntry {
+                        block_id: *block_id,
+                        gen_utime: info.gen_utime,
+                        start_lt: info.start_lt,
+                        end_lt: info.end_lt,
+                    })?;
+                }
+
                 self.add_block_data_and_split(&archive_id, data).await?;
                 if handle.meta().add_flags(BlockFlags::HAS_DATA) {
                     self.block_handle_storage.store_handle(&handle, false);
@@ -401,7 +440,7 @@ impl BlockStorage {
             return Ok(block.clone());
         }
 
-        let FullBlockDataGuard { _lock, data } = self
+        let FullBlockDataGuard { _lock, db, data } = self
             .get_data_ref(handle, &PackageEntryKey::block(handle.id()))
             .await?;
 
@@ -410,13 +449,51 @@ impl BlockStorage {
         } else {
             let handle = handle.clone();
 
-            // SAFETY: `data` was created by the `self.db` RocksDB instance.
-            let owned_data =
-                unsafe { weedb::OwnedPinnableSlice::new(self.db.rocksdb().clone(), data) };
+            // SAFETY: `data` was created by the `db` RocksDB instance.
+            let owned_data = unsafe { weedb::OwnedPinnableSlice::new(db.clone(), data) };
             rayon_run(move || BlockStuff::deserialize(handle.id(), owned_data.as_ref())).await
         }
     }
 
+    /// Whether masterchain blocks are indexed by time and lt.
+    pub fn is_archive_node(&self) -> bool {
+        self.archive_node
+    }
+
+    /// Returns the latest masterchain block with `gen_utime <= utime`.
+    ///
+    /// NOTE: Only blocks stored in the archive node mode are indexed.
+    pub fn find_mc_block_by_utime(&self, utime: u32) -> Result<Option<McBlockIndexEntry>> {
+        let table = &self.db.mc_blocks_by_utime;
+        let mut iter = table.raw_iterator();
+        iter.seek_for_prev(McBlockIndexEntry::utime_key(utime, u32::MAX));
+
+        match iter.value() {
+            Some(value) => Ok(Some(McBlockIndexEntry::from_slice(value))),
+            None => {
+                iter.status()?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns the first masterchain block with `end_lt >= lt`.
+    ///
+    /// NOTE: Only blocks stored in the archive node mode are indexed.
+    pub fn find_mc_block_by_lt(&self, lt: u64) -> Result<Option<McBlockIndexEntry>> {
+        let table = &self.db.mc_blocks_by_lt;
+        let mut iter = table.raw_iterator();
+        iter.seek(McBlockIndexEntry::lt_key(lt, 0));
+
+        match iter.value() {
+            Some(value) => Ok(Some(McBlockIndexEntry::from_slice(value))),
+            None => {
+                iter.status()?;
+                Ok(None)
+            }
+        }
+    }
+
     pub async fn load_block_data_raw(&self, handle: &BlockHandle) -> Result<OwnedPinnableSlice> {
         if !handle.has_data() {
             return Err(BlockStorageError::BlockDataNotFound.into());
@@ -510,39 +587,17 @@ impl BlockStorage {
     }
 
     pub fn find_mc_block_data(&self, mc_seqno: u32) -> Result<Option<Block>> {
-        let package_entries = &self.db.package_entries;
-
-        let bound = BlockId {
-            shard: ShardIdent::MASTERCHAIN,
-            seqno: mc_seqno,
-            root_hash: HashBytes::ZERO,
-            file_hash: HashBytes::ZERO,
-        };
-
-        let mut bound = PackageEntryKey::block(&bound);
-
-        let mut readopts = package_entries.new_read_config();
-        readopts.set_iterate_lower_bound(bound.to_vec().into_vec());
-        bound.block_id.seqno += 1;
-        readopts.set_iterate_upper_bound(bound.to_vec().into_vec());
-
-        let mut iter = self
-            .db
-            .rocksdb()
-            .raw_iterator_cf_opt(&package_entries.cf(), readopts);
-
-        iter.seek_to_first();
-        loop {
-            let Some((key, value)) = iter.item() else {
-                iter.status()?;
-                return Ok(None);
-            };
-
-            let Some(ArchiveEntryType::Block) = extract_entry_type(key) else {
-                continue;
-            };
+        if let Some(block) =
+            find_mc_block_data(self.db.rocksdb(), &self.db.package_entries, mc_seqno)?
+        {
+            return Ok(Some(block));
+        }
 
-            return Ok(Some(BocRepr::decode::<Block, _>(value)?));
+        match &self.cold_db {
+            Some(cold_db) => {
+                find_mc_block_data(cold_db.rocksdb(), &cold_db.package_entries, mc_seqno)
+            }
+            None => Ok(None),
         }
     }
 
@@ -825,10 +880,8 @@ impl BlockStorage {
             chunk_index: BLOCK_DATA_SIZE_MAGIC,
         };
         let size = self
-            .db
-            .block_data_entries
-            .get(key.to_vec())?
-            .map(|slice| u32::from_le_bytes(slice.as_ref().try_into().unwrap()));
+            .get_block_data_entry(&key)?
+            .map(|(_, slice)| u32::from_le_bytes(slice.as_ref().try_into().unwrap()));
 
         Ok(size)
     }
@@ -848,9 +901,9 @@ impl BlockStorage {
             chunk_index: offset / chunk_size,
         };
 
-        Ok(self.db.block_data_entries.get(key.to_vec())?.map(|value| {
+        Ok(self.get_block_data_entry(&key)?.map(|(db, value)| {
             // SAFETY: A value was received from the same RocksDB instance.
-            unsafe { OwnedPinnableSlice::new(self.db.rocksdb().clone(), value) }
+            unsafe { OwnedPinnableSlice::new(db.clone(), value) }
         }))
     }
 
@@ -920,6 +973,9 @@ impl BlockStorage {
         let span = tracing::Span::current();
         let cancelled = cancelled.clone();
         let db = self.db.clone();
+        let cold_db = self.cold_db.clone();
+
+        let _cold_lock = self.cold_lock.lock().await;
 
         let BlockGcStats {
             mc_blocks_removed,
@@ -933,6 +989,7 @@ impl BlockStorage {
 
             let stats = remove_blocks(
                 db,
+                cold_db,
                 max_blocks_per_batch,
                 mc_seqno,
                 shard_heights,
@@ -953,10 +1010,79 @@ impl BlockStorage {
         Ok(())
     }
 
+    /// Moves block parts of the blocks, which are referenced by masterchain blocks
+    /// older than `until_mc_seqno`, to the cold storage.
+    ///
+    /// Returns the number of moved entries.
+    #[tracing::instrument(skip(self, max_entries_per_batch))]
+    pub async fn move_blocks_to_cold(
+        &self,
+        until_mc_seqno: u32,
+        max_entries_per_batch: usize,
+    ) -> Result<usize> {
+        let Some(cold_db) = self.cold_db.clone() else {
+            return Ok(0);
+        };
+
+        let _cold_lock = self.cold_lock.lock().await;
+
+        let span = tracing::Span::current();
+        let db = self.db.clone();
+
+        // NOTE: `spawn_blocking` is used here instead of `rayon_run` as it is IO-bound task.
+        let moved_entries = tokio::task::spawn_blocking(move || {
+            let _span = span.enter();
+            move_blocks_to_cold(&db, &cold_db, until_mc_seqno, max_entries_per_batch)
+        })
+        .await??;
+
+        if moved_entries > 0 {
+            tracing::info!(moved_entries, "moved block parts to cold storage");
+        }
+        Ok(moved_entries)
+    }
+
+    /// Returns the id of the last archive which was uploaded to the external storage.
+    pub fn load_last_uploaded_archive_id(&self) -> Result<Option<u32>> {
+        Ok(match self.db.state.get(LAST_UPLOADED_ARCHIVE)? {
+            Some(value) => {
+                let value: [u8; 4] = value
+                    .as_ref()
+                    .try_into()
+                    .map_err(|_e| BlockStorageError::InvalidUploadedArchiveId)?;
+                Some(u32::from_le_bytes(value))
+            }
+            None => None,
+        })
+    }
+
+    /// Marks all archives up to the specified id (including) as uploaded
+    /// to the external storage.
+    pub fn store_last_uploaded_archive_id(&self, id: u32) -> Result<()> {
+        if matches!(self.load_last_uploaded_archive_id()?, Some(last) if last >= id) {
+            return Ok(());
+        }
+        self.db
+            .state
+            .insert(LAST_UPLOADED_ARCHIVE, id.to_le_bytes())?;
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self))]
-    pub fn remove_outdated_archives(&self, until_id: u32) -> Result<()> {
+    pub fn remove_outdated_archives(&self, mut until_id: u32) -> Result<()> {
         tracing::trace!("started archives GC");
 
+        if self.keep_archives_until_uploaded {
+            // NOTE: Only archives which were uploaded can be removed.
+            match self.load_last_uploaded_archive_id()? {
+                Some(last_uploaded) => until_id = until_id.min(last_uploaded.saturating_add(1)),
+                None => {
+                    tracing::info!("no archives were uploaded yet");
+                    return Ok(());
+                }
+            }
+        }
+
         let mut archive_ids = self.archive_ids.write();
 
         let retained_ids = match archive_ids
@@ -1013,6 +1139,25 @@ impl BlockStorage {
 
     // === Internal ===
 
+    fn index_mc_block(&self, entry: &McBlockIndexEntry) -> Result<()> {
+        let value = entry.to_vec();
+        let seqno = entry.block_id.seqno;
+
+        let mut batch = rocksdb::WriteBatch::default();
+        batch.put_cf(
+            &self.db.mc_blocks_by_utime.cf(),
+            McBlockIndexEntry::utime_key(entry.gen_utime, seqno),
+            value.as_slice(),
+        );
+        batch.put_cf(
+            &self.db.mc_blocks_by_lt.cf(),
+            McBlockIndexEntry::lt_key(entry.end_lt, seqno),
+            value.as_slice(),
+        );
+        self.db.rocksdb().write(batch)?;
+        Ok(())
+    }
+
     fn add_data(&self, id: &PackageEntryKey, data: &[u8]) -> Result<(), rocksdb::Error> {
         self.db.package_entries.insert(id.to_vec(), data)
     }
@@ -1051,9 +1196,9 @@ impl BlockStorage {
         .read()
         .await;
 
-        match self.db.package_entries.get(id.to_vec())? {
+        match self.get_package_entry(&id.to_vec())? {
             // SAFETY: A value was received from the same RocksDB instance.
-            Some(value) => Ok(unsafe { OwnedPinnableSlice::new(self.db.rocksdb().clone(), value) }),
+            Some((db, value)) => Ok(unsafe { OwnedPinnableSlice::new(db.clone(), value) }),
             None => Err(BlockStorageError::PackageEntryNotFound.into()),
         }
     }
@@ -1071,12 +1216,43 @@ impl BlockStorage {
         .read()
         .await;
 
-        match self.db.package_entries.get(id.to_vec())? {
-            Some(data) => Ok(FullBlockDataGuard { _lock: lock, data }),
+        match self.get_package_entry(&id.to_vec())? {
+            Some((db, data)) => Ok(FullBlockDataGuard {
+                _lock: lock,
+                db,
+                data,
+            }),
             None => Err(BlockStorageError::PackageEntryNotFound.into()),
         }
     }
 
+    /// Reads the package entry from the base DB or from the cold DB.
+    fn get_package_entry(
+        &self,
+        key: &[u8],
+    ) -> Result<Option<(&Arc<rocksdb::DB>, rocksdb::DBPinnableSlice<'_>)>, rocksdb::Error> {
+        get_package_entry(&self.db, self.cold_db.as_ref(), key)
+    }
+
+    /// Reads the block data entry from the base DB or from the cold DB.
+    fn get_block_data_entry(
+        &self,
+        key: &BlockDataEntryKey,
+    ) -> Result<Option<(&Arc<rocksdb::DB>, rocksdb::DBPinnableSlice<'_>)>, rocksdb::Error> {
+        let key = key.to_vec();
+        if let Some(value) = self.db.block_data_entries.get(&key)? {
+            return Ok(Some((self.db.rocksdb(), value)));
+        }
+
+        let Some(cold_db) = &self.cold_db else {
+            return Ok(None);
+        };
+        Ok(cold_db
+            .block_data_entries
+            .get(&key)?
+            .map(|value| (cold_db.rocksdb(), value)))
+    }
+
     fn prepare_archive_id(&self, mc_seqno: u32, force_split_archive: bool) -> PreparedArchiveId {
         let mut archive_ids = self.archive_ids.write();
 
@@ -1148,6 +1324,7 @@ impl BlockStorage {
     #[tracing::instrument(skip(self))]
     fn spawn_commit_archive(&self, archive_id: u32) -> CommitArchiveTask {
         let db = self.db.clone();
+        let cold_db = self.cold_db.clone();
         let block_handle_storage = self.block_handle_storage.clone();
         let chunk_size = self.archive_chunk_size().get() as u64;
 
@@ -1212,8 +1389,11 @@ impl BlockStorage {
                             );
                         }
 
+                        // NOTE: Block parts might have been already moved to the cold storage.
                         let key = PackageEntryKey::from((block_id, ty));
-                        let Some(data) = db.package_entries.get(key.to_vec()).unwrap() else {
+                        let Some((_, data)) =
+                            get_package_entry(&db, cold_db.as_ref(), &key.to_vec())?
+                        else {
                             return Err(BlockStorageError::BlockDataNotFound.into());
                         };
 
@@ -1495,6 +1675,7 @@ struct ArchiveIds {
 
 fn remove_blocks(
     db: BaseDb,
+    cold_db: Option<ColdDb>,
     max_blocks_per_batch: Option<usize>,
     mc_seqno: u32,
     shard_heights: ShardHeights,
@@ -1513,6 +1694,13 @@ fn remove_blocks(
     let mut batch = rocksdb::WriteBatch::default();
     let mut batch_len = 0;
 
+    // NOTE: Cold batch is always written after the main batch
+    // so that the cold entries of the removed blocks are not visible.
+    let cold_cfs = cold_db
+        .as_ref()
+        .map(|db| (db.package_entries.cf(), db.block_data_entries.cf()));
+    let mut cold_batch = rocksdb::WriteBatch::default();
+
     // Iterate all entries and find expired items
     let mut blocks_iter =
         raw.raw_iterator_cf_opt(&full_block_ids_cf, db.full_block_ids.new_read_config());
@@ -1532,32 +1720,39 @@ fn remove_blocks(
     };
 
     let mut key_buffer = [0u8; tables::PackageEntries::KEY_LEN];
-    let mut delete_range =
-        |batch: &mut rocksdb::WriteBatch, from: &BlockIdShort, to: &BlockIdShort| {
-            debug_assert_eq!(from.shard, to.shard);
-            debug_assert!(from.seqno <= to.seqno);
-
-            let range_from = &mut key_buffer;
-            range_from[..4].copy_from_slice(&from.shard.workchain().to_be_bytes());
-            range_from[4..12].copy_from_slice(&from.shard.prefix().to_be_bytes());
-            range_from[12..16].copy_from_slice(&from.seqno.to_be_bytes());
-
-            let mut range_to = *range_from;
-            range_to[12..16].copy_from_slice(&to.seqno.saturating_add(1).to_be_bytes());
-
-            // At this point we have two keys:
-            // [workchain, shard, from_seqno, 0...]
-            // [workchain, shard, to_seqno + 1, 0...]
-            //
-            // It will delete all entries in range [from_seqno, to_seqno) for this shard.
-            // Note that package entry keys are the same as block connection keys.
-            batch.delete_range_cf(&full_block_ids_cf, &*range_from, &range_to);
-            batch.delete_range_cf(&package_entries_cf, &*range_from, &range_to);
-            batch.delete_range_cf(&block_data_entries_cf, &*range_from, &range_to);
-            batch.delete_range_cf(&block_connections_cf, &*range_from, &range_to);
-
-            tracing::debug!(%from, %to, "delete_range");
-        };
+    let mut delete_range = |batch: &mut rocksdb::WriteBatch,
+                            cold_batch: &mut rocksdb::WriteBatch,
+                            from: &BlockIdShort,
+                            to: &BlockIdShort| {
+        debug_assert_eq!(from.shard, to.shard);
+        debug_assert!(from.seqno <= to.seqno);
+
+        let range_from = &mut key_buffer;
+        range_from[..4].copy_from_slice(&from.shard.workchain().to_be_bytes());
+        range_from[4..12].copy_from_slice(&from.shard.prefix().to_be_bytes());
+        range_from[12..16].copy_from_slice(&from.seqno.to_be_bytes());
+
+        let mut range_to = *range_from;
+        range_to[12..16].copy_from_slice(&to.seqno.saturating_add(1).to_be_bytes());
+
+        // At this point we have two keys:
+        // [workchain, shard, from_seqno, 0...]
+        // [workchain, shard, to_seqno + 1, 0...]
+        //
+        // It will delete all entries in range [from_seqno, to_seqno) for this shard.
+        // Note that package entry keys are the same as block connection keys.
+        batch.delete_range_cf(&full_block_ids_cf, &*range_from, &range_to);
+        batch.delete_range_cf(&package_entries_cf, &*range_from, &range_to);
+        batch.delete_range_cf(&block_data_entries_cf, &*range_from, &range_to);
+        batch.delete_range_cf(&block_connections_cf, &*range_from, &range_to);
+
+        if let Some((package_entries_cf, block_data_entries_cf)) = &cold_cfs {
+            cold_batch.delete_range_cf(package_entries_cf, &*range_from, &range_to);
+            cold_batch.delete_range_cf(block_data_entries_cf, &*range_from, &range_to);
+        }
+
+        tracing::debug!(%from, %to, "delete_range");
+    };
 
     let mut cancelled = cancelled.map(|c| c.debounce(100));
     let mut current_range = None::<(BlockIdShort, BlockIdShort)>;
@@ -1592,7 +1787,7 @@ fn remove_blocks(
         {
             // Remove the current range
             if let Some((from, to)) = current_range.take() {
-                delete_range(&mut batch, &from, &to);
+                delete_range(&mut batch, &mut cold_batch, &from, &to);
                 batch_len += 1; // Ensure that we flush the batch
             }
             blocks_iter.next();
@@ -1602,7 +1797,7 @@ fn remove_blocks(
         match &mut current_range {
             // Delete the previous range and start a new one
             Some((from, to)) if from.shard != block_id.shard => {
-                delete_range(&mut batch, from, to);
+                delete_range(&mut batch, &mut cold_batch, from, to);
                 *from = block_id;
                 *to = block_id;
             }
@@ -1631,6 +1826,7 @@ fn remove_blocks(
             );
             let batch = std::mem::take(&mut batch);
             raw.write(batch)?;
+            write_cold_batch(cold_db.as_ref(), &mut cold_batch)?;
             batch_len = 0;
         }
 
@@ -1638,23 +1834,202 @@ fn remove_blocks(
     }
 
     if let Some((from, to)) = current_range.take() {
-        delete_range(&mut batch, &from, &to);
+        delete_range(&mut batch, &mut cold_batch, &from, &to);
         batch_len += 1; // Ensure that we flush the batch
     }
 
     if batch_len > 0 {
         tracing::info!("applying final batch");
         raw.write(batch)?;
+        write_cold_batch(cold_db.as_ref(), &mut cold_batch)?;
     }
 
     // Done
     Ok(stats)
 }
 
+/// Reads the package entry from the base DB or from the cold DB.
+fn get_package_entry<'a>(
+    db: &'a BaseDb,
+    cold_db: Option<&'a ColdDb>,
+    key: &[u8],
+) -> Result<Option<(&'a Arc<rocksdb::DB>, rocksdb::DBPinnableSlice<'a>)>, rocksdb::Error> {
+    if let Some(value) = db.package_entries.get(key)? {
+        return Ok(Some((db.rocksdb(), value)));
+    }
+
+    let Some(cold_db) = cold_db else {
+        return Ok(None);
+    };
+    Ok(cold_db
+        .package_entries
+        .get(key)?
+        .map(|value| (cold_db.rocksdb(), value)))
+}
+
+fn write_cold_batch(
+    cold_db: Option<&ColdDb>,
+    batch: &mut rocksdb::WriteBatch,
+) -> Result<(), rocksdb::Error> {
+    match cold_db {
+        Some(cold_db) if !batch.is_empty() => cold_db.rocksdb().write(std::mem::take(batch)),
+        _ => Ok(()),
+    }
+}
+
+fn move_blocks_to_cold(
+    db: &BaseDb,
+    cold_db: &ColdDb,
+    until_mc_seqno: u32,
+    max_entries_per_batch: usize,
+) -> Result<usize> {
+    let mut ctx = MoveToColdContext {
+        db,
+        cold_db,
+        until_mc_seqno,
+        max_entries_per_batch: std::cmp::max(max_entries_per_batch, 1),
+        last_checked: None,
+        moved_entries: 0,
+    };
+
+    ctx.move_entries(&db.package_entries, &cold_db.package_entries, |_| {
+        MoveAction::Move
+    })?;
+
+    // NOTE: Unfinished block data is split on startup, so its "started"
+    // marker is kept in the base DB and is removed only together with
+    // the "size" marker (which is written last).
+    ctx.move_entries(&db.block_data_entries, &cold_db.block_data_entries, |key| {
+        let mut key = BlockDataEntryKey::from_slice(key);
+        match key.chunk_index {
+            BLOCK_DATA_STARTED_MAGIC => MoveAction::Skip,
+            BLOCK_DATA_SIZE_MAGIC => {
+                key.chunk_index = BLOCK_DATA_STARTED_MAGIC;
+                MoveAction::MoveAndRemove(key.to_vec())
+            }
+            _ => MoveAction::Move,
+        }
+    })?;
+
+    Ok(ctx.moved_entries)
+}
+
+enum MoveAction {
+    Skip,
+    Move,
+    /// Move the entry and remove one more key from the base DB.
+    MoveAndRemove(Vec<u8>),
+}
+
+struct MoveToColdContext<'a> {
+    db: &'a BaseDb,
+    cold_db: &'a ColdDb,
+    until_mc_seqno: u32,
+    max_entries_per_batch: usize,
+    last_checked: Option<([u8; 32], bool)>,
+    moved_entries: usize,
+}
+
+impl MoveToColdContext<'_> {
+    /// Block parts are moved when the block is referenced
+    /// by an old enough masterchain block.
+    fn is_cold(&mut self, root_hash: &[u8; 32]) -> Result<bool, rocksdb::Error> {
+        if let Some((hash, is_cold)) = &self.last_checked {
+            if hash == root_hash {
+                return Ok(*is_cold);
+            }
+        }
+
+        let is_cold = match self.db.block_handles.get(root_hash)? {
+            // NOTE: Lower 32 bits of the block meta flags is `mc_ref_seqno`.
+            Some(value) => (value.as_ref().get_u64_le() as u32) < self.until_mc_seqno,
+            None => false,
+        };
+        self.last_checked = Some((*root_hash, is_cold));
+        Ok(is_cold)
+    }
+
+    fn move_entries<T: ColumnFamily>(
+        &mut self,
+        hot: &weedb::Table<T>,
+        cold: &weedb::Table<T>,
+        action: impl Fn(&[u8]) -> MoveAction,
+    ) -> Result<()> {
+        let hot_cf = hot.cf();
+        let cold_cf = cold.cf();
+
+        let mut batch = rocksdb::WriteBatch::default();
+        let mut cold_batch = rocksdb::WriteBatch::default();
+
+        // Key structure:
+        // [workchain id, 4 bytes]  |
+        // [shard id, 8 bytes]      | BlockIdShort
+        // [seqno, 4 bytes]         |
+        // [root hash, 32 bytes] <-
+        // ..
+        let mut iter = hot.raw_iterator();
+        iter.seek_to_first();
+        loop {
+            let Some((key, value)) = iter.item() else {
+                iter.status()?;
+                break;
+            };
+
+            let block_id = BlockIdShort::from_slice(key);
+            let root_hash: &[u8; 32] = key[16..48].try_into().unwrap();
+            let action = action(key);
+            if block_id.seqno == 0
+                || matches!(action, MoveAction::Skip)
+                || !self.is_cold(root_hash)?
+            {
+                iter.next();
+                continue;
+            }
+
+            cold_batch.put_cf(&cold_cf, key, value);
+            batch.delete_cf(&hot_cf, key);
+            if let MoveAction::MoveAndRemove(extra_key) = action {
+                batch.delete_cf(&hot_cf, extra_key);
+            }
+
+            if batch.len() >= self.max_entries_per_batch {
+                self.flush(&mut batch, &mut cold_batch)?;
+            }
+
+            iter.next();
+        }
+
+        self.flush(&mut batch, &mut cold_batch)
+    }
+
+    fn flush(
+        &mut self,
+        batch: &mut rocksdb::WriteBatch,
+        cold_batch: &mut rocksdb::WriteBatch,
+    ) -> Result<()> {
+        // NOTE: Only moved entries are counted
+        let len = cold_batch.len();
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        // NOTE: Entries are written to the cold DB first
+        // so that they are always readable from at least one DB.
+        self.cold_db.rocksdb().write(std::mem::take(cold_batch))?;
+        self.db.rocksdb().write(std::mem::take(batch))?;
+
+        self.moved_entries += len;
+        metrics::counter!("tycho_storage_cold_moved_block_entries_total").increment(len as u64);
+        Ok(())
+    }
+}
+
 pub struct BlockStorageConfig {
     pub archive_chunk_size: ByteSize,
     pub blocks_cache: BlocksCacheConfig,
     pub split_block_tasks: usize,
+    pub keep_archives_until_uploaded: bool,
+    pub archive_node: bool,
 }
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
@@ -1665,6 +2040,7 @@ pub struct BlockGcStats {
 
 struct FullBlockDataGuard<'a> {
     _lock: BlockDataGuard<'a>,
+    db: &'a Arc<rocksdb::DB>,
     data: rocksdb::DBPinnableSlice<'a>,
 }
 
@@ -1674,26 +2050,67 @@ impl AsRef<[u8]> for FullBlockDataGuard<'_> {
     }
 }
 
+fn find_mc_block_data(
+    db: &rocksdb::DB,
+    package_entries: &weedb::Table<tables::PackageEntries>,
+    mc_seqno: u32,
+) -> Result<Option<Block>> {
+    let bound = BlockId {
+        shard: ShardIdent::MASTERCHAIN,
+        seqno: mc_seqno,
+        root_hash: HashBytes::ZERO,
+        file_hash: HashBytes::ZERO,
+    };
+
+    let mut bound = PackageEntryKey::block(&bound);
+
+    let mut readopts = package_entries.new_read_config();
+    readopts.set_iterate_lower_bound(bound.to_vec().into_vec());
+    bound.block_id.seqno += 1;
+    readopts.set_iterate_upper_bound(bound.to_vec().into_vec());
+
+    let mut iter = db.raw_iterator_cf_opt(&package_entries.cf(), readopts);
+
+    iter.seek_to_first();
+    loop {
+        let Some((key, value)) = iter.item() else {
+            iter.status()?;
+            return Ok(None);
+        };
+
+        let Some(ArchiveEntryType::Block) = extract_entry_type(key) else {
+            iter.next();
+            continue;
+        };
+
+        return Ok(Some(BocRepr::decode::<Block, _>(value)?));
+    }
+}
+
 fn extract_entry_type(key: &[u8]) -> Option<ArchiveEntryType> {
     key.get(48).copied().and_then(ArchiveEntryType::from_byte)
 }
 
-const ARCHIVE_PACKAGE_SIZE: u32 = 100;
+/// Max number of masterchain blocks in a single archive.
+pub const ARCHIVE_PACKAGE_SIZE: u32 = 100;
 // Reserved key in which the archive size is stored
-const ARCHIVE_SIZE_MAGIC: u64 = u64::MAX;
+pub(crate) const ARCHIVE_SIZE_MAGIC: u64 = u64::MAX;
 // Reserved key in which we store the fact that the archive must be committed
 const ARCHIVE_TO_COMMIT_MAGIC: u64 = u64::MAX - 1;
 // Reserved key in which we store the next archive id to override.
 const ARCHIVE_OVERRIDE_NEXT_MAGIC: u64 = u64::MAX - 2;
 // Reserved key in which we store the fact that archive was started
-const ARCHIVE_STARTED_MAGIC: u64 = u64::MAX - 3;
+pub(crate) const ARCHIVE_STARTED_MAGIC: u64 = u64::MAX - 3;
+
+pub(crate) const ARCHIVE_MAGIC_MIN: u64 = u64::MAX & !0xff;
 
-const ARCHIVE_MAGIC_MIN: u64 = u64::MAX & !0xff;
+// Key in the state table with the id of the last uploaded archive
+const LAST_UPLOADED_ARCHIVE: &[u8] = b"last_uploaded_archive";
 
 const BLOCK_DATA_CHUNK_SIZE: u32 = 1024 * 1024; // 1MB
 
 // Reserved key in which the compressed block size is stored
-const BLOCK_DATA_SIZE_MAGIC: u32 = u32::MAX;
+pub(crate) const BLOCK_DATA_SIZE_MAGIC: u32 = u32::MAX;
 // Reserved key in which we store the fact that compressed block was started
 const BLOCK_DATA_STARTED_MAGIC: u32 = u32::MAX - 2;
 
@@ -1726,6 +2143,8 @@ enum BlockStorageError {
     PackageEntryNotFound,
     #[error("Offset is outside of the archive slice")]
     InvalidOffset,
+    #[error("Invalid uploaded archive id")]
+    InvalidUploadedArchiveId,
 }
 
 #[cfg(test)]
@@ -1892,6 +2311,7 @@ mod tests {
         // Remove some blocks
         let stats = remove_blocks(
             blocks.db.clone(),
+            blocks.cold_db.clone(),
             None,
             70,
             [(ShardIdent::BASECHAIN, 50)].into(),
@@ -1938,6 +2358,7 @@ mod tests {
         // Remove single block
         let stats = remove_blocks(
             blocks.db.clone(),
+            blocks.cold_db.clone(),
             None,
             71,
             [(ShardIdent::BASECHAIN, 51)].into(),
@@ -1951,6 +2372,7 @@ mod tests {
         // Remove no blocks
         let stats = remove_blocks(
             blocks.db.clone(),
+            blocks.cold_db.clone(),
             None,
             71,
             [(ShardIdent::BASECHAIN, 51)].into(),
@@ -1963,4 +2385,207 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn last_uploaded_archive_id() -> Result<()> {
+        let (storage, _tmp_dir) = Storage::new_temp().await?;
+        let blocks = storage.block_storage();
+
+        assert_eq!(blocks.load_last_uploaded_archive_id()?, None);
+
+        blocks.store_last_uploaded_archive_id(200)?;
+        assert_eq!(blocks.load_last_uploaded_archive_id()?, Some(200));
+
+        // Watermark never goes back
+        blocks.store_last_uploaded_archive_id(100)?;
+        assert_eq!(blocks.load_last_uploaded_archive_id()?, Some(200));
+
+        blocks.store_last_uploaded_archive_id(300)?;
+        assert_eq!(blocks.load_last_uploaded_archive_id()?, Some(300));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn mc_block_index() -> Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let storage = Storage::builder()
+            .with_config(crate::StorageConfig {
+                archive_node: true,
+                ..crate::StorageConfig::new_potato(tmp_dir.path())
+            })
+            .build()
+            .await?;
+
+        let blocks = storage.block_storage();
+        assert!(blocks.is_archive_node());
+        assert_eq!(blocks.find_mc_block_by_utime(u32::MAX)?, None);
+        assert_eq!(blocks.find_mc_block_by_lt(0)?, None);
+
+        // Blocks with 10 seconds and 1000 lt intervals
+        let mut entries = Vec::new();
+        for seqno in 1..=10u32 {
+            let entry = McBlockIndexEntry {
+                block_id: BlockId {
+                    shard: ShardIdent::MASTERCHAIN,
+                    seqno,
+                    root_hash: HashBytes(rand::random()),
+                    file_hash: HashBytes(rand::random()),
+                },
+                gen_utime: 1000 + seqno * 10,
+                start_lt: seqno as u64 * 1000,
+                end_lt: seqno as u64 * 1000 + 10,
+            };
+            blocks.index_mc_block(&entry)?;
+            entries.push(entry);
+        }
+
+        // By utime
+        assert_eq!(blocks.find_mc_block_by_utime(1000)?, None);
+        assert_eq!(blocks.find_mc_block_by_utime(1010)?, Some(entries[0]));
+        assert_eq!(blocks.find_mc_block_by_utime(1015)?, Some(entries[0]));
+        assert_eq!(blocks.find_mc_block_by_utime(1050)?, Some(entries[4]));
+        assert_eq!(blocks.find_mc_block_by_utime(u32::MAX)?, Some(entries[9]));
+
+        // By lt
+        assert_eq!(blocks.find_mc_block_by_lt(0)?, Some(entries[0]));
+        assert_eq!(blocks.find_mc_block_by_lt(1010)?, Some(entries[0]));
+        assert_eq!(blocks.find_mc_block_by_lt(1011)?, Some(entries[1]));
+        assert_eq!(blocks.find_mc_block_by_lt(5005)?, Some(entries[4]));
+        assert_eq!(blocks.find_mc_block_by_lt(10011)?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cold_block_parts() -> Result<()> {
+        const DATA: &[u8] = b"block data";
+        // Block whose data was not split before the migration.
+        const UNFINISHED_SEQNO: u32 = 3;
+
+        let tmp_dir = tempfile::tempdir()?;
+        let config = crate::StorageConfig {
+            cold_storage: Some(Default::default()),
+            ..crate::StorageConfig::new_potato(tmp_dir.path())
+        };
+        let storage = Storage::builder()
+            .with_config(config.clone())
+            .build()
+            .await?;
+
+        let blocks = storage.block_storage();
+        let block_handles = storage.block_handle_storage();
+
+        let mut block_ids = Vec::new();
+        for seqno in 0..=10 {
+            let block_id = BlockId {
+                shard: ShardIdent::MASTERCHAIN,
+                seqno,
+                root_hash: HashBytes(rand::random()),
+                file_hash: HashBytes(rand::random()),
+            };
+            block_ids.push(block_id);
+
+            let (handle, _) = block_handles.create_or_load_handle(&block_id, NewBlockMeta {
+                is_key_block: seqno == 0,
+                gen_utime: 0,
+                ref_by_mc_seqno: seqno,
+            });
+            blocks.add_data(&PackageEntryKey::block(&block_id), DATA)?;
+            blocks.add_data(&PackageEntryKey::proof(&block_id), DATA)?;
+            blocks.add_data(&PackageEntryKey::queue_diff(&block_id), DATA)?;
+
+            let started_key = BlockDataEntryKey {
+                block_id: (&block_id).into(),
+                chunk_index: BLOCK_DATA_STARTED_MAGIC,
+            };
+            blocks
+                .db
+                .block_data_entries
+                .insert(started_key.to_vec(), [])?;
+            if seqno != UNFINISHED_SEQNO {
+                let permit = blocks.split_block_semaphore.clone().acquire_owned().await?;
+                blocks
+                    .spawn_split_block_data(&(&block_id).into(), DATA, permit)
+                    .await??;
+            }
+
+            handle.meta().add_flags(BlockFlags::HAS_ALL_BLOCK_PARTS);
+            block_handles.store_handle(&handle, false);
+
+            // NOTE: The last block is added to the archive after the restart.
+            if seqno > 0 && seqno < 10 {
+                // Force the next archive to start with the last block.
+                blocks.move_into_archive(&handle, seqno == 9).await?;
+            }
+        }
+
+        // 3 package entries for each block and 2 block data entries
+        // (chunk and size) for each finished block.
+        let moved = blocks.move_blocks_to_cold(5, 2).await?;
+        assert_eq!(moved, 4 * 3 + 3 * 2);
+
+        let cold_db = blocks.cold_db.as_ref().unwrap();
+        for block_id in &block_ids {
+            let key = PackageEntryKey::proof(block_id).to_vec();
+            let is_cold = block_id.seqno > 0 && block_id.seqno < 5;
+            assert_eq!(blocks.db.package_entries.get(&key)?.is_none(), is_cold);
+            assert_eq!(cold_db.package_entries.get(&key)?.is_some(), is_cold);
+
+            // "Started" marker is removed only for the finished blocks
+            let started_key = BlockDataEntryKey {
+                block_id: block_id.into(),
+                chunk_index: BLOCK_DATA_STARTED_MAGIC,
+            };
+            let has_started = blocks
+                .db
+                .block_data_entries
+                .get(started_key.to_vec())?
+                .is_some();
+            assert_eq!(has_started, !is_cold || block_id.seqno == UNFINISHED_SEQNO);
+
+            // Block parts are still readable
+            let handle = block_handles.load_handle(block_id).unwrap();
+            let data = blocks
+                .get_data(&handle, &PackageEntryKey::proof(block_id))
+                .await?;
+            assert_eq!(data.as_ref(), DATA);
+        }
+
+        // Storage must be reopened after the migration
+        drop(storage);
+        let storage = Storage::builder().with_config(config).build().await?;
+
+        let blocks = storage.block_storage();
+        let block_handles = storage.block_handle_storage();
+
+        // Unfinished block data is split using the cold package entry
+        let size = blocks.get_block_data_size(&block_ids[UNFINISHED_SEQNO as usize])?;
+        assert!(size.is_some());
+
+        // Archive with cold block parts can be committed
+        let handle = block_handles.load_handle(&block_ids[10]).unwrap();
+        blocks.move_into_archive(&handle, false).await?;
+        blocks.wait_for_archive_commit().await?;
+        assert!(blocks.get_archive_size(1)?.is_some());
+
+        // GC also removes cold entries
+        let cold_db = blocks.cold_db.as_ref().unwrap();
+        remove_blocks(
+            blocks.db.clone(),
+            blocks.cold_db.clone(),
+            None,
+            3,
+            Default::default(),
+            None,
+        )?;
+        for block_id in &block_ids[1..3] {
+            let key = PackageEntryKey::proof(block_id).to_vec();
+            assert!(cold_db.package_entries.get(&key)?.is_none());
+        }
+        let key = PackageEntryKey::proof(&block_ids[3]).to_vec();
+        assert!(cold_db.package_entries.get(&key)?.is_some());
+
+        Ok(())
+    }
 }
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
-use everscale_types::models::BlockId;
+use everscale_types::models::{BlockId, BlockIdShort};
+use everscale_types::prelude::HashBytes;
 use tycho_block_util::block::{BlockStuff, ShardHeights};
 use tycho_util::FastDashMap;
 
@@ -145,6 +146,30 @@ impl BlockHandleStorage {
         Some(self.fill_cache(block_id, meta))
     }
 
+    /// Finds the full block id by its short id.
+    pub fn find_block_id(&self, short_id: &BlockIdShort) -> Option<BlockId> {
+        let full_block_ids = &self.db.full_block_ids;
+
+        let prefix = short_id.to_vec();
+
+        let mut iter = self
+            .db
+            .rocksdb()
+            .raw_iterator_cf_opt(&full_block_ids.cf(), full_block_ids.new_read_config());
+        iter.seek(prefix.as_slice());
+
+        let Some((key, file_hash)) = iter.item() else {
+            iter.status().unwrap();
+            return None;
+        };
+        if !key.starts_with(prefix.as_slice()) {
+            return None;
+        }
+
+        let partial_id = PartialBlockId::from_slice(key);
+        Some(partial_id.make_full(HashBytes::from_slice(file_hash)))
+    }
+
     pub fn store_handle(&self, handle: &BlockHandle, is_new: bool) {
         let id = handle.id();
 
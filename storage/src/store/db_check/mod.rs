@@ -0,0 +1,1129 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use everscale_types::cell::HashBytes;
+use everscale_types::models::BlockId;
+use serde::Serialize;
+use tycho_block_util::archive::ArchiveEntryType;
+use tycho_util::FastHashMap;
+use weedb::{rocksdb, Caches, ColumnFamily};
+
+use crate::db::*;
+use crate::store::{
+    is_cold_state_value, BlockDataEntryKey, BlockFlags, BlockMeta, PackageEntryKey, PartialBlockId,
+    StorageCell, ARCHIVE_MAGIC_MIN, ARCHIVE_SIZE_MAGIC, ARCHIVE_STARTED_MAGIC,
+    BLOCK_DATA_SIZE_MAGIC,
+};
+use crate::util::*;
+use crate::{StorageConfig, BASE_DB_SUBDIR};
+
+/// Offline integrity checker for the base DB.
+///
+/// NOTE: The node must be stopped while the checker is running.
+pub struct StorageChecker {
+    db: RawDb,
+    /// Cold DB is used to find block parts and cells of the cold states.
+    cold_db: Option<RawDb>,
+    read_only: bool,
+    /// Directory for the temporary refcounts DB. System temp dir is used if not set.
+    temp_root: Option<PathBuf>,
+}
+
+impl StorageChecker {
+    /// Opens an existing base DB without applying migrations.
+    ///
+    /// The DB is opened in read-only mode unless `read_only` is `false`,
+    /// which is only required to repair refcounts.
+    pub fn open(config: &StorageConfig, read_only: bool) -> Result<Self> {
+        let path = config.root_dir.join(BASE_DB_SUBDIR);
+        anyhow::ensure!(path.exists(), "base DB not found at {}", path.display());
+
+        let update_options = |opts: &mut rocksdb::Options| {
+            opts.create_if_missing(false);
+            opts.set_log_level(rocksdb::LogLevel::Error);
+        };
+
+        let caches = Caches::with_capacity(config.rocksdb_lru_capacity.as_u64() as _);
+        let db = if read_only {
+            Arc::new(open_read_only(&path, [
+                cf_descriptor::<tables::State>(&caches),
+                cf_descriptor::<tables::ArchiveBlockIds>(&caches),
+                cf_descriptor::<tables::Archives>(&caches),
+                cf_descriptor::<tables::BlockHandles>(&caches),
+                cf_descriptor::<tables::FullBlockIds>(&caches),
+                cf_descriptor::<tables::PackageEntries>(&caches),
+                cf_descriptor::<tables::BlockDataEntries>(&caches),
+                cf_descriptor::<tables::ShardStates>(&caches),
+                cf_descriptor::<tables::Cells>(&caches),
+            ])?)
+        } else {
+            BaseDb::builder_prepared(path, caches.clone())
+                .with_metrics_enabled(false)
+                .with_options(|opts, _| update_options(opts))
+                .build()?
+                .rocksdb()
+                .clone()
+        };
+
+        ensure_latest_base_db_version(&db)?;
+
+        let cold_db = match config.cold_storage_dir() {
+            Some(path) if path.exists() => Some(Arc::new(open_read_only(&path, [
+                cf_descriptor::<tables::State>(&caches),
+                cf_descriptor::<tables::PackageEntries>(&caches),
+                cf_descriptor::<tables::BlockDataEntries>(&caches),
+                cf_descriptor::<tables::ColdCells>(&caches),
+            ])?)),
+            _ => None,
+        };
+
+        Ok(Self {
+            db: RawDb(db),
+            cold_db: cold_db.map(RawDb),
+            read_only,
+            temp_root: Some(config.root_dir.clone()),
+        })
+    }
+
+    pub fn new(db: BaseDb, cold_db: Option<ColdDb>) -> Self {
+        Self {
+            db: RawDb(db.rocksdb().clone()),
+            cold_db: cold_db.map(|db| RawDb(db.rocksdb().clone())),
+            read_only: false,
+            temp_root: None,
+        }
+    }
+
+    pub fn check(&self, options: &CheckOptions) -> Result<CheckReport> {
+        anyhow::ensure!(
+            !(options.repair_refcounts && self.read_only),
+            "storage is opened in read-only mode, repair is not possible"
+        );
+
+        let mut report = CheckReport::default();
+
+        if options.blocks {
+            tracing::info!("checking blocks");
+            report.blocks = Some(self.check_blocks(&mut report)?);
+        }
+        if options.archives {
+            tracing::info!("checking archives");
+            report.archives = Some(self.check_archives(&mut report)?);
+        }
+        if options.states {
+            tracing::info!("checking states");
+            report.states = Some(self.check_states(options.repair_refcounts, &mut report)?);
+        }
+
+        Ok(report)
+    }
+
+    /// Compares block handle flags with the stored block parts.
+    fn check_blocks(&self, report: &mut CheckReport) -> Result<BlocksCheckStats> {
+        const ENTRIES: [(ArchiveEntryType, BlockFlags); 3] = [
+            (ArchiveEntryType::Block, BlockFlags::HAS_DATA),
+            (ArchiveEntryType::Proof, BlockFlags::HAS_PROOF),
+            (ArchiveEntryType::QueueDiff, BlockFlags::HAS_QUEUE_DIFF),
+        ];
+
+        let mut stats = BlocksCheckStats::default();
+
+        // Collect all stored block parts
+        let mut package_entries = FastHashMap::<PartialBlockId, BlockFlags>::default();
+        for db in std::iter::once(&self.db).chain(self.cold_db.as_ref()) {
+            let mut iter = db.raw_iterator::<tables::PackageEntries>()?;
+            iter.seek_to_first();
+            loop {
+                let Some(key) = iter.key() else {
+                    iter.status()?;
+                    break;
+                };
+
+                match parse_package_entry_key(key) {
+                    Some(key) => {
+                        let flag = entry_flag(key.ty);
+                        *package_entries
+                            .entry(key.block_id)
+                            .or_insert(BlockFlags::empty()) |= flag;
+                    }
+                    None => report.push(CheckIssue::InvalidKey {
+                        table: "package_entries",
+                        key: hex::encode(key),
+                    }),
+                }
+
+                iter.next();
+            }
+        }
+
+        let mut iter = self.db.raw_iterator::<tables::FullBlockIds>()?;
+        iter.seek_to_first();
+        loop {
+            let Some((key, value)) = iter.item() else {
+                iter.status()?;
+                break;
+            };
+
+            if key.len() != PartialBlockId::SIZE_HINT || value.len() != 32 {
+                report.push(CheckIssue::InvalidKey {
+                    table: "full_block_ids",
+                    key: hex::encode(key),
+                });
+                iter.next();
+                continue;
+            }
+
+            let partial_id = PartialBlockId::from_slice(key);
+            let block_id = partial_id.make_full(HashBytes::from_slice(value));
+            let entries = package_entries
+                .remove(&partial_id)
+                .unwrap_or(BlockFlags::empty());
+
+            stats.total += 1;
+
+            let Some(meta) = self
+                .db
+                .get::<tables::BlockHandles>(partial_id.root_hash.as_slice())?
+            else {
+                report.push(CheckIssue::MissingBlockHandle {
+                    block_id: block_id.to_string(),
+                });
+                iter.next();
+                continue;
+            };
+
+            let flags = BlockMeta::from_slice(meta.as_ref()).flags();
+            if flags.contains(BlockFlags::IS_REMOVED) {
+                iter.next();
+                continue;
+            }
+
+            for (ty, flag) in ENTRIES {
+                let has_flag = flags.contains(flag);
+                let has_entry = entries.contains(flag);
+                if has_flag != has_entry {
+                    report.push(CheckIssue::BlockEntryMismatch {
+                        block_id: block_id.to_string(),
+                        entry: entry_name(ty),
+                        has_flag,
+                        has_entry,
+                    });
+                }
+            }
+
+            if flags.contains(BlockFlags::HAS_DATA) {
+                let key = BlockDataEntryKey {
+                    block_id: partial_id,
+                    chunk_index: BLOCK_DATA_SIZE_MAGIC,
+                };
+                let key = key.to_vec();
+                let has_size = self.db.get::<tables::BlockDataEntries>(&key)?.is_some()
+                    || match &self.cold_db {
+                        Some(cold_db) => cold_db.get::<tables::BlockDataEntries>(&key)?.is_some(),
+                        None => false,
+                    };
+                if !has_size {
+                    report.push(CheckIssue::UnfinishedBlockData {
+                        block_id: block_id.to_string(),
+                    });
+                }
+            }
+
+            // NOTE: Zerostate blocks don't have any parts
+            let missing = BlockFlags::HAS_ALL_BLOCK_PARTS.difference(flags | entries);
+            if block_id.seqno != 0 && !missing.is_empty() {
+                stats.incomplete += 1;
+                report.push(CheckIssue::IncompleteBlock {
+                    block_id: block_id.to_string(),
+                    missing: ENTRIES
+                        .iter()
+                        .filter(|(_, flag)| missing.contains(*flag))
+                        .map(|(ty, _)| entry_name(*ty))
+                        .collect(),
+                });
+            }
+
+            iter.next();
+        }
+
+        // All remaining entries don't have a full block id
+        for (partial_id, entries) in package_entries {
+            stats.orphaned += 1;
+            report.push(CheckIssue::OrphanedBlockEntries {
+                block_id: partial_id.as_short_id().to_string(),
+                root_hash: partial_id.root_hash.to_string(),
+                entries: ENTRIES
+                    .iter()
+                    .filter(|(_, flag)| entries.contains(*flag))
+                    .map(|(ty, _)| entry_name(*ty))
+                    .collect(),
+            });
+        }
+
+        Ok(stats)
+    }
+
+    /// Compares archive chunks with the list of archive block ids.
+    fn check_archives(&self, report: &mut CheckReport) -> Result<ArchivesCheckStats> {
+        #[derive(Default)]
+        struct ArchiveInfo {
+            started: bool,
+            size: Option<u64>,
+            chunk_count: u64,
+            chunks_len: u64,
+            next_chunk: u64,
+            has_gaps: bool,
+        }
+
+        let mut stats = ArchivesCheckStats::default();
+
+        let mut archives = BTreeMap::<u32, ArchiveInfo>::new();
+        {
+            let mut iter = self.db.raw_iterator::<tables::Archives>()?;
+            iter.seek_to_first();
+            loop {
+                let Some((key, value)) = iter.item() else {
+                    iter.status()?;
+                    break;
+                };
+
+                if key.len() != tables::Archives::KEY_LEN {
+                    report.push(CheckIssue::InvalidKey {
+                        table: "archives",
+                        key: hex::encode(key),
+                    });
+                    iter.next();
+                    continue;
+                }
+
+                let archive_id = u32::from_be_bytes(key[..4].try_into().unwrap());
+                let chunk_index = u64::from_be_bytes(key[4..].try_into().unwrap());
+
+                let info = archives.entry(archive_id).or_default();
+                match chunk_index {
+                    ARCHIVE_STARTED_MAGIC => info.started = true,
+                    ARCHIVE_SIZE_MAGIC => match value.try_into() {
+                        Ok(size) => info.size = Some(u64::from_le_bytes(size)),
+                        Err(_) => report.push(CheckIssue::InvalidArchive {
+                            archive_id,
+                            reason: "invalid size entry".to_owned(),
+                        }),
+                    },
+                    ARCHIVE_MAGIC_MIN.. => {}
+                    _ => {
+                        info.has_gaps |= chunk_index != info.next_chunk;
+                        info.next_chunk = chunk_index + 1;
+                        info.chunk_count += 1;
+                        info.chunks_len += value.len() as u64;
+                    }
+                }
+
+                iter.next();
+            }
+        }
+
+        // Collect archives which are not committed yet
+        let mut archive_block_ids = FastHashMap::<u32, usize>::default();
+        {
+            let mut iter = self.db.raw_iterator::<tables::ArchiveBlockIds>()?;
+            iter.seek_to_first();
+            loop {
+                let Some((key, value)) = iter.item() else {
+                    iter.status()?;
+                    break;
+                };
+
+                let Ok(archive_id) = key.try_into().map(u32::from_be_bytes) else {
+                    report.push(CheckIssue::InvalidKey {
+                        table: "archive_block_ids",
+                        key: hex::encode(key),
+                    });
+                    iter.next();
+                    continue;
+                };
+
+                if value.len() % BlockId::SIZE_HINT != 0 {
+                    report.push(CheckIssue::InvalidArchive {
+                        archive_id,
+                        reason: format!("invalid block ids length: {}", value.len()),
+                    });
+                } else {
+                    for raw_block_id in value.chunks_exact(BlockId::SIZE_HINT) {
+                        let block_id = BlockId::from_slice(raw_block_id);
+                        if self
+                            .db
+                            .get::<tables::BlockHandles>(block_id.root_hash.as_slice())?
+                            .is_none()
+                        {
+                            report.push(CheckIssue::MissingArchiveBlock {
+                                archive_id,
+                                block_id: block_id.to_string(),
+                            });
+                        }
+                    }
+                }
+
+                archive_block_ids.insert(archive_id, value.len() / BlockId::SIZE_HINT);
+                iter.next();
+            }
+        }
+
+        for (archive_id, info) in archives {
+            stats.total += 1;
+
+            let has_block_ids = archive_block_ids.remove(&archive_id).is_some();
+
+            let mut invalid = |reason: &str| {
+                report.push(CheckIssue::InvalidArchive {
+                    archive_id,
+                    reason: reason.to_owned(),
+                });
+            };
+
+            if !info.started {
+                invalid("no STARTED entry");
+            }
+
+            let Some(size) = info.size else {
+                stats.uncommitted += 1;
+                if !has_block_ids {
+                    invalid("uncommitted archive without block ids");
+                }
+                continue;
+            };
+
+            stats.committed += 1;
+            stats.total_size += size;
+
+            if has_block_ids {
+                invalid("committed archive still has block ids");
+            }
+            if info.chunk_count == 0 {
+                invalid("committed archive has no chunks");
+            }
+            if info.has_gaps {
+                invalid("archive chunks are not contiguous");
+            }
+            if info.chunks_len != size {
+                invalid(&format!(
+                    "chunks length mismatch: expected {size}, got {}",
+                    info.chunks_len
+                ));
+            }
+        }
+
+        // All remaining block ids don't have a related archive
+        for (archive_id, _) in archive_block_ids {
+            report.push(CheckIssue::InvalidArchive {
+                archive_id,
+                reason: "block ids without archive".to_owned(),
+            });
+        }
+
+        Ok(stats)
+    }
+
+    /// Traverses all stored states and compares cell refcounts.
+    ///
+    /// NOTE: Cells of the cold states are only checked for presence.
+    /// Their refcounts are not compared since an interrupted copy
+    /// to the cold storage leaves some extra references.
+    fn check_states(&self, repair: bool, report: &mut CheckReport) -> Result<StatesCheckStats> {
+        const MAX_BATCH_LEN: usize = 10000;
+
+        let mut stats = StatesCheckStats::default();
+
+        // NOTE: Each stored state adds a reference to its root cell
+        // and each stored cell adds a reference to its children.
+        // Expected refcounts are stored on disk since the number
+        // of reachable cells can be too large to fit into memory.
+        let expected_rc = RefcountsDb::new(self.temp_root.as_deref())?;
+        let mut stack = Vec::new();
+        let mut cold_roots = Vec::new();
+        {
+            let mut iter = self.db.raw_iterator::<tables::ShardStates>()?;
+            iter.seek_to_first();
+            loop {
+                let Some((key, value)) = iter.item() else {
+                    iter.status()?;
+                    break;
+                };
+
+                if key.len() != BlockId::SIZE_HINT || !matches!(value.len(), 32 | 33) {
+                    report.push(CheckIssue::InvalidKey {
+                        table: "shard_states",
+                        key: hex::encode(key),
+                    });
+                    iter.next();
+                    continue;
+                }
+
+                stats.states += 1;
+
+                let root_hash = HashBytes::from_slice(&value[..32]);
+
+                // NOTE: Cells of the cold states are not in the base DB
+                if is_cold_state_value(value) {
+                    stats.cold_states += 1;
+                    cold_roots.push(root_hash);
+                    iter.next();
+                    continue;
+                }
+
+                if expected_rc.increment(&root_hash)? {
+                    stack.push(root_hash);
+                }
+
+                iter.next();
+            }
+        }
+
+        stats.reachable_cells =
+            traverse_cells::<tables::Cells>(&self.db, &expected_rc, stack, false, report)?;
+
+        // Compare actual refcounts
+        let cells_cf = self.db.cf::<tables::Cells>()?;
+        let mut batch = rocksdb::WriteBatch::default();
+        let mut buffer = Vec::new();
+
+        let mut iter = self.db.raw_iterator::<tables::Cells>()?;
+        iter.seek_to_first();
+        loop {
+            let Some((key, value)) = iter.item() else {
+                iter.status()?;
+                break;
+            };
+
+            if value.is_empty() {
+                // Skip removed cells which are not compacted yet
+                iter.next();
+                continue;
+            }
+            let (rc, data) = refcount::decode_value_with_rc(value);
+
+            stats.stored_cells += 1;
+
+            let hash = HashBytes::from_slice(key);
+            match expected_rc.get(&hash)? {
+                expected if rc < 0 => {
+                    report.push(CheckIssue::NegativeRefcount {
+                        hash: hash.to_string(),
+                        rc,
+                    });
+                    // NOTE: Reachable cells without payload are reported as missing
+                    if repair && expected == 0 {
+                        batch.delete_cf(cells_cf, key);
+                    }
+                }
+                0 => {
+                    stats.orphaned_cells += 1;
+                    report.push(CheckIssue::OrphanedCell {
+                        hash: hash.to_string(),
+                        rc,
+                    });
+                    if repair {
+                        batch.delete_cf(cells_cf, key);
+                    }
+                }
+                expected if expected != rc as u64 => {
+                    report.push(CheckIssue::RefcountMismatch {
+                        hash: hash.to_string(),
+                        expected,
+                        actual: rc,
+                    });
+                    if let (true, Some(data)) = (repair, data) {
+                        buffer.clear();
+                        buffer.extend_from_slice(&(expected as i64).to_le_bytes());
+                        buffer.extend_from_slice(data);
+                        batch.put_cf(cells_cf, key, buffer.as_slice());
+                    }
+                }
+                _ => {}
+            }
+
+            if batch.len() >= MAX_BATCH_LEN {
+                stats.repaired_cells += batch.len() as u64;
+                self.db.0.write(std::mem::take(&mut batch))?;
+            }
+
+            iter.next();
+        }
+
+        if !batch.is_empty() {
+            stats.repaired_cells += batch.len() as u64;
+            self.db.0.write(batch)?;
+        }
+
+        if !cold_roots.is_empty() {
+            match &self.cold_db {
+                Some(cold_db) => {
+                    let visited = RefcountsDb::new(self.temp_root.as_deref())?;
+                    let mut stack = Vec::new();
+                    for root_hash in cold_roots {
+                        if visited.increment(&root_hash)? {
+                            stack.push(root_hash);
+                        }
+                    }
+
+                    stats.reachable_cold_cells = traverse_cells::<tables::ColdCells>(
+                        cold_db, &visited, stack, true, report,
+                    )?;
+                }
+                None => report.push(CheckIssue::MissingColdDb {
+                    states: stats.cold_states,
+                }),
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+/// Visits all cells reachable from the `stack` and increments their refcounts.
+/// Returns the number of visited cells.
+fn traverse_cells<T: ColumnFamily>(
+    db: &RawDb,
+    refcounts: &RefcountsDb,
+    mut stack: Vec<HashBytes>,
+    cold: bool,
+    report: &mut CheckReport,
+) -> Result<u64> {
+    let mut visited = 0;
+    let mut references = Vec::with_capacity(4);
+    while let Some(hash) = stack.pop() {
+        visited += 1;
+
+        let value = db.get::<T>(hash.as_slice())?;
+        let Some(data) = value
+            .as_ref()
+            .and_then(|v| refcount::decode_value_with_rc(v).1)
+        else {
+            let hash = hash.to_string();
+            report.push(if cold {
+                CheckIssue::MissingColdCell { hash }
+            } else {
+                CheckIssue::MissingCell { hash }
+            });
+            continue;
+        };
+
+        references.clear();
+        if !StorageCell::deserialize_references(data, &mut references) {
+            let hash = hash.to_string();
+            report.push(if cold {
+                CheckIssue::InvalidColdCell { hash }
+            } else {
+                CheckIssue::InvalidCell { hash }
+            });
+            continue;
+        }
+
+        for child in &references {
+            if refcounts.increment(child)? {
+                stack.push(*child);
+            }
+        }
+    }
+
+    Ok(visited)
+}
+
+/// Raw DB handle which can be opened either by [`weedb`] or in read-only mode.
+struct RawDb(Arc<rocksdb::DB>);
+
+impl RawDb {
+    fn cf<T: ColumnFamily>(&self) -> Result<&rocksdb::ColumnFamily> {
+        self.0
+            .cf_handle(T::NAME)
+            .with_context(|| format!("column family not found: {}", T::NAME))
+    }
+
+    fn raw_iterator<T: ColumnFamily>(&self) -> Result<rocksdb::DBRawIterator<'_>> {
+        let cf = self.cf::<T>()?;
+        Ok(self.0.raw_iterator_cf_opt(cf, read_options::<T>()))
+    }
+
+    fn get<T: ColumnFamily>(&self, key: &[u8]) -> Result<Option<rocksdb::DBPinnableSlice<'_>>> {
+        let cf = self.cf::<T>()?;
+        Ok(self.0.get_pinned_cf_opt(cf, key, &read_options::<T>())?)
+    }
+}
+
+/// Temporary on-disk storage for the expected cell refcounts.
+struct RefcountsDb {
+    db: rocksdb::DB,
+    write_options: rocksdb::WriteOptions,
+    // NOTE: Must be dropped after the DB
+    _dir: tempfile::TempDir,
+}
+
+impl RefcountsDb {
+    fn new(temp_root: Option<&Path>) -> Result<Self> {
+        let dir = match temp_root {
+            Some(root) => tempfile::tempdir_in(root)?,
+            None => tempfile::tempdir()?,
+        };
+
+        let mut opts = rocksdb::Options::default();
+        opts.create_if_missing(true);
+        opts.set_log_level(rocksdb::LogLevel::Error);
+        let db = rocksdb::DB::open(&opts, dir.path()).context("failed to open refcounts DB")?;
+
+        // NOTE: The DB is removed after the check so there is no need in WAL
+        let mut write_options = rocksdb::WriteOptions::default();
+        write_options.disable_wal(true);
+
+        Ok(Self {
+            db,
+            write_options,
+            _dir: dir,
+        })
+    }
+
+    /// Returns the expected refcount or zero if the cell is not reachable.
+    fn get(&self, hash: &HashBytes) -> Result<u64> {
+        Ok(match self.db.get_pinned(hash.as_slice())? {
+            Some(value) => u64::from_le_bytes(
+                value
+                    .as_ref()
+                    .try_into()
+                    .context("invalid refcounts DB entry")?,
+            ),
+            None => 0,
+        })
+    }
+
+    /// Returns `true` if the cell is referenced for the first time.
+    fn increment(&self, hash: &HashBytes) -> Result<bool> {
+        let rc = self.get(hash)?;
+        self.db
+            .put_opt(hash.as_slice(), (rc + 1).to_le_bytes(), &self.write_options)?;
+        Ok(rc == 0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CheckOptions {
+    pub blocks: bool,
+    pub archives: bool,
+    pub states: bool,
+    /// Overwrite invalid refcounts and remove orphaned cells.
+    pub repair_refcounts: bool,
+}
+
+impl Default for CheckOptions {
+    fn default() -> Self {
+        Self {
+            blocks: true,
+            archives: true,
+            states: true,
+            repair_refcounts: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct CheckReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<BlocksCheckStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archives: Option<ArchivesCheckStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub states: Option<StatesCheckStats>,
+    pub total_issues: u64,
+    /// First [`CheckReport::MAX_ISSUES`] issues.
+    pub issues: Vec<CheckIssue>,
+}
+
+impl CheckReport {
+    pub const MAX_ISSUES: usize = 1000;
+
+    pub fn is_ok(&self) -> bool {
+        self.total_issues == 0
+    }
+
+    fn push(&mut self, issue: CheckIssue) {
+        self.total_issues += 1;
+        if self.issues.len() < Self::MAX_ISSUES {
+            self.issues.push(issue);
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct BlocksCheckStats {
+    pub total: u64,
+    pub incomplete: u64,
+    pub orphaned: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct ArchivesCheckStats {
+    pub total: u64,
+    pub committed: u64,
+    pub uncommitted: u64,
+    pub total_size: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct StatesCheckStats {
+    pub states: u64,
+    /// Number of states which cells are in the cold storage.
+    pub cold_states: u64,
+    pub stored_cells: u64,
+    pub reachable_cells: u64,
+    pub reachable_cold_cells: u64,
+    pub orphaned_cells: u64,
+    pub repaired_cells: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CheckIssue {
+    InvalidKey {
+        table: &'static str,
+        key: String,
+    },
+    MissingBlockHandle {
+        block_id: String,
+    },
+    BlockEntryMismatch {
+        block_id: String,
+        entry: &'static str,
+        has_flag: bool,
+        has_entry: bool,
+    },
+    UnfinishedBlockData {
+        block_id: String,
+    },
+    IncompleteBlock {
+        block_id: String,
+        missing: Vec<&'static str>,
+    },
+    OrphanedBlockEntries {
+        block_id: String,
+        root_hash: String,
+        entries: Vec<&'static str>,
+    },
+    InvalidArchive {
+        archive_id: u32,
+        reason: String,
+    },
+    MissingArchiveBlock {
+        archive_id: u32,
+        block_id: String,
+    },
+    MissingCell {
+        hash: String,
+    },
+    InvalidCell {
+        hash: String,
+    },
+    MissingColdCell {
+        hash: String,
+    },
+    InvalidColdCell {
+        hash: String,
+    },
+    /// Cold states were found but the cold storage is not available,
+    /// so their cells were not checked.
+    MissingColdDb {
+        states: u64,
+    },
+    NegativeRefcount {
+        hash: String,
+        rc: i64,
+    },
+    OrphanedCell {
+        hash: String,
+        rc: i64,
+    },
+    RefcountMismatch {
+        hash: String,
+        expected: u64,
+        actual: i64,
+    },
+}
+
+fn read_options<T: ColumnFamily>() -> rocksdb::ReadOptions {
+    let mut opts = rocksdb::ReadOptions::default();
+    T::read_options(&mut opts);
+    opts
+}
+
+fn parse_package_entry_key(key: &[u8]) -> Option<PackageEntryKey> {
+    if key.len() != PackageEntryKey::SIZE_HINT {
+        return None;
+    }
+    ArchiveEntryType::from_byte(key[PartialBlockId::SIZE_HINT])?;
+    Some(PackageEntryKey::from_slice(key))
+}
+
+fn entry_flag(ty: ArchiveEntryType) -> BlockFlags {
+    match ty {
+        ArchiveEntryType::Block => BlockFlags::HAS_DATA,
+        ArchiveEntryType::Proof => BlockFlags::HAS_PROOF,
+        ArchiveEntryType::QueueDiff => BlockFlags::HAS_QUEUE_DIFF,
+    }
+}
+
+fn entry_name(ty: ArchiveEntryType) -> &'static str {
+    match ty {
+        ArchiveEntryType::Block => "block",
+        ArchiveEntryType::Proof => "proof",
+        ArchiveEntryType::QueueDiff => "queue_diff",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use everscale_types::cell::{Cell, CellBuilder};
+    use everscale_types::models::ShardIdent;
+
+    use super::*;
+    use crate::store::NewBlockMeta;
+    use crate::{ColdStorageConfig, Storage};
+
+    fn make_block_id(seqno: u32) -> BlockId {
+        BlockId {
+            shard: ShardIdent::MASTERCHAIN,
+            seqno,
+            root_hash: HashBytes([seqno as u8; 32]),
+            file_hash: HashBytes([0xff; 32]),
+        }
+    }
+
+    fn make_cells() -> Result<(Cell, Cell)> {
+        let child = CellBuilder::build_from(1u32)?;
+        let mut builder = CellBuilder::new();
+        builder.store_u32(2)?;
+        builder.store_reference(child.clone())?;
+        Ok((builder.build()?, child))
+    }
+
+    fn cell_value(cell: &Cell, rc: i64) -> Result<Vec<u8>> {
+        let mut value = rc.to_le_bytes().to_vec();
+        StorageCell::serialize_to(cell.as_ref(), &mut value)?;
+        Ok(value)
+    }
+
+    #[tokio::test]
+    async fn empty_storage_is_consistent() -> Result<()> {
+        let (storage, _tmp_dir) = Storage::new_temp().await?;
+
+        let checker = StorageChecker::new(storage.base_db().clone(), storage.cold_db().cloned());
+        let report = checker.check(&CheckOptions::default())?;
+        assert!(report.is_ok(), "{report:?}");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_only_check() -> Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let config = StorageConfig::new_potato(tmp_dir.path());
+
+        let storage = Storage::builder()
+            .with_config(config.clone())
+            .build()
+            .await?;
+        drop(storage);
+
+        let checker = StorageChecker::open(&config, true)?;
+        let report = checker.check(&CheckOptions::default())?;
+        assert!(report.is_ok(), "{report:?}");
+
+        // Refcounts can't be repaired without write access
+        let res = checker.check(&CheckOptions {
+            repair_refcounts: true,
+            ..Default::default()
+        });
+        assert!(res.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn missing_package_entry() -> Result<()> {
+        let (storage, _tmp_dir) = Storage::new_temp().await?;
+        let db = storage.base_db();
+
+        // Block with all parts in flags but without the block data entry
+        let block_id = make_block_id(1);
+        let partial_id = PartialBlockId::from(&block_id);
+
+        let meta = BlockMeta::with_data(NewBlockMeta {
+            is_key_block: false,
+            gen_utime: 0,
+            ref_by_mc_seqno: 1,
+        });
+        meta.add_flags(BlockFlags::HAS_ALL_BLOCK_PARTS);
+
+        db.full_block_ids
+            .insert(partial_id.to_vec(), block_id.file_hash.as_slice())?;
+        db.block_handles
+            .insert(block_id.root_hash.as_slice(), meta.to_vec())?;
+        db.package_entries
+            .insert(PackageEntryKey::proof(&block_id).to_vec(), [])?;
+        db.package_entries
+            .insert(PackageEntryKey::queue_diff(&block_id).to_vec(), [])?;
+        db.block_data_entries.insert(
+            BlockDataEntryKey {
+                block_id: partial_id,
+                chunk_index: BLOCK_DATA_SIZE_MAGIC,
+            }
+            .to_vec(),
+            [],
+        )?;
+
+        // Package entry of an unknown block
+        let orphaned_id = make_block_id(2);
+        db.package_entries
+            .insert(PackageEntryKey::block(&orphaned_id).to_vec(), [])?;
+
+        let checker = StorageChecker::new(db.clone(), None);
+        let report = checker.check(&CheckOptions::default())?;
+
+        let stats = report.blocks.unwrap();
+        assert_eq!(stats.total, 1);
+        assert_eq!(stats.incomplete, 0);
+        assert_eq!(stats.orphaned, 1);
+
+        assert_eq!(report.total_issues, 2, "{report:?}");
+        assert!(report.issues.iter().any(|issue| matches!(
+            issue,
+            CheckIssue::BlockEntryMismatch {
+                block_id: id,
+                entry: "block",
+                has_flag: true,
+                has_entry: false,
+            } if *id == block_id.to_string()
+        )));
+        assert!(report.issues.iter().any(|issue| matches!(
+            issue,
+            CheckIssue::OrphanedBlockEntries { root_hash, entries, .. }
+                if *root_hash == orphaned_id.root_hash.to_string() && entries == &["block"]
+        )));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn repair_refcounts() -> Result<()> {
+        let (storage, _tmp_dir) = Storage::new_temp().await?;
+        let db = storage.base_db();
+
+        let (root, child) = make_cells()?;
+        let orphaned = CellBuilder::build_from(3u32)?;
+
+        db.shard_states
+            .insert(make_block_id(1).to_vec(), root.repr_hash().as_slice())?;
+        db.cells
+            .insert(root.repr_hash().as_slice(), cell_value(&root, 1)?)?;
+        // Child is referenced only by the root
+        db.cells
+            .insert(child.repr_hash().as_slice(), cell_value(&child, 3)?)?;
+        db.cells
+            .insert(orphaned.repr_hash().as_slice(), cell_value(&orphaned, 1)?)?;
+
+        let checker = StorageChecker::new(db.clone(), None);
+        let options = CheckOptions {
+            repair_refcounts: true,
+            ..Default::default()
+        };
+
+        let report = checker.check(&options)?;
+        let stats = report.states.unwrap();
+        assert_eq!(stats.states, 1);
+        assert_eq!(stats.stored_cells, 3);
+        assert_eq!(stats.reachable_cells, 2);
+        assert_eq!(stats.orphaned_cells, 1);
+        assert_eq!(stats.repaired_cells, 2);
+
+        assert_eq!(report.total_issues, 2, "{report:?}");
+        assert!(report.issues.iter().any(|issue| matches!(
+            issue,
+            CheckIssue::OrphanedCell { hash, rc: 1 } if *hash == orphaned.repr_hash().to_string()
+        )));
+        assert!(report.issues.iter().any(|issue| matches!(
+            issue,
+            CheckIssue::RefcountMismatch { hash, expected: 1, actual: 3 }
+                if *hash == child.repr_hash().to_string()
+        )));
+
+        // Repaired storage must be consistent
+        let report = checker.check(&CheckOptions::default())?;
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!(report.states.unwrap().stored_cells, 2);
+
+        // Missing cell is reported
+        db.cells.remove(child.repr_hash().as_slice())?;
+        let report = checker.check(&CheckOptions::default())?;
+        assert_eq!(report.total_issues, 1, "{report:?}");
+        assert!(matches!(
+            &report.issues[0],
+            CheckIssue::MissingCell { hash } if *hash == child.repr_hash().to_string()
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cold_states() -> Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let mut config = StorageConfig::new_potato(tmp_dir.path());
+        config.cold_storage = Some(ColdStorageConfig::default());
+
+        let storage = Storage::builder()
+            .with_config(config.clone())
+            .build()
+            .await?;
+        let db = storage.base_db();
+        let cold_db = storage.cold_db().unwrap();
+
+        let (root, child) = make_cells()?;
+
+        let mut value = root.repr_hash().0.to_vec();
+        value.push(1);
+        assert!(is_cold_state_value(&value));
+        db.shard_states.insert(make_block_id(1).to_vec(), value)?;
+
+        // Child cell is missing
+        cold_db
+            .cells
+            .insert(root.repr_hash().as_slice(), cell_value(&root, 1)?)?;
+
+        let checker = StorageChecker::new(db.clone(), Some(cold_db.clone()));
+        let report = checker.check(&CheckOptions::default())?;
+
+        let stats = report.states.unwrap();
+        assert_eq!(stats.states, 1);
+        assert_eq!(stats.cold_states, 1);
+        assert_eq!(stats.stored_cells, 0);
+        assert_eq!(stats.reachable_cold_cells, 2);
+
+        assert_eq!(report.total_issues, 1, "{report:?}");
+        assert!(matches!(
+            &report.issues[0],
+            CheckIssue::MissingColdCell { hash } if *hash == child.repr_hash().to_string()
+        ));
+
+        cold_db
+            .cells
+            .insert(child.repr_hash().as_slice(), cell_value(&child, 1)?)?;
+        let report = checker.check(&CheckOptions::default())?;
+        assert!(report.is_ok(), "{report:?}");
+
+        // Cold states can't be checked without the cold storage
+        let checker = StorageChecker::new(db.clone(), None);
+        let report = checker.check(&CheckOptions::default())?;
+        assert_eq!(report.total_issues, 1, "{report:?}");
+        assert!(matches!(report.issues[0], CheckIssue::MissingColdDb {
+            states: 1
+        }));
+
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 pub use self::block::*;
 pub use self::block_connection::*;
 pub use self::block_handle::*;
+pub use self::db_check::*;
 pub use self::internal_queue::*;
 pub use self::mempool::*;
 pub use self::node_state::*;
@@ -12,6 +13,7 @@ pub use self::temp_file::*;
 mod block;
 mod block_connection;
 mod block_handle;
+mod db_check;
 mod internal_queue;
 mod mempool;
 mod node_state;
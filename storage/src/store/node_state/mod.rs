@@ -1,7 +1,9 @@
 use std::cmp::Ordering;
 
+use anyhow::{Context, Result};
 use everscale_types::models::*;
 use parking_lot::Mutex;
+use weedb::{rocksdb, ColumnFamily};
 
 use crate::db::*;
 use crate::util::*;
@@ -82,6 +84,27 @@ impl NodeStateStorage {
         Some(value)
     }
 
+    /// Loads the last and the init masterchain block ids from the DB
+    /// which was opened directly (e.g. a checkpoint in read-only mode).
+    pub(crate) fn load_mc_block_ids_raw(
+        db: &rocksdb::DB,
+    ) -> Result<(Option<BlockId>, Option<BlockId>)> {
+        let state = db
+            .cf_handle(tables::State::NAME)
+            .context("state column family not found")?;
+
+        let load = |key: &[u8]| -> Result<Option<BlockId>> {
+            Ok(db
+                .get_pinned_cf(state, key)?
+                .map(|value| read_block_id_le(&value)))
+        };
+        Ok((load(LAST_MC_BLOCK_ID)?, load(INIT_MC_BLOCK_ID)?))
+    }
+
+    pub fn store_instance_id(&self, id: InstanceId) {
+        self.db.state.insert(INSTANCE_ID, id).unwrap();
+    }
+
     pub fn load_instance_id(&self) -> InstanceId {
         let id = self.db.state.get(INSTANCE_ID).unwrap().unwrap();
         InstanceId::from_slice(id.as_ref())
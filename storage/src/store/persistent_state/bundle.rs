@@ -0,0 +1,229 @@
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use everscale_types::cell::HashBytes;
+use everscale_types::models::BlockId;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tycho_util::compression::ZstdDecompressStream;
+
+use super::PersistentStateKind;
+
+/// A directory with persistent states of a single key block
+/// which can be used to boot a node without downloading them.
+///
+/// Layout:
+/// - `manifest.json` - [`StateBundleManifest`];
+/// - compressed persistent state files as they are stored by the node.
+#[derive(Clone)]
+pub struct StateBundle {
+    dir: PathBuf,
+    manifest: StateBundleManifest,
+}
+
+impl StateBundle {
+    pub const MANIFEST_FILE_NAME: &'static str = "manifest.json";
+
+    pub const VERSION: u32 = 1;
+
+    /// Opens an existing bundle and checks its manifest.
+    ///
+    /// NOTE: File checksums are verified only when the file is unpacked.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+
+        let manifest = std::fs::read(dir.join(Self::MANIFEST_FILE_NAME))
+            .with_context(|| format!("failed to read bundle manifest in {}", dir.display()))?;
+        let manifest = serde_json::from_slice::<StateBundleManifest>(&manifest)
+            .context("failed to parse bundle manifest")?;
+
+        anyhow::ensure!(
+            manifest.version == Self::VERSION,
+            "unsupported bundle version: {}",
+            manifest.version
+        );
+        anyhow::ensure!(
+            manifest.mc_block_id.is_masterchain(),
+            "bundle block must be in the masterchain"
+        );
+        anyhow::ensure!(
+            manifest
+                .entry(&manifest.mc_block_id, PersistentStateKind::Shard)
+                .is_some(),
+            "bundle has no masterchain state"
+        );
+
+        for entry in &manifest.entries {
+            anyhow::ensure!(
+                Path::new(&entry.file_name).file_name() == Some(OsStr::new(&entry.file_name)),
+                "invalid bundle file name: {}",
+                entry.file_name
+            );
+        }
+
+        Ok(Self { dir, manifest })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn manifest(&self) -> &StateBundleManifest {
+        &self.manifest
+    }
+
+    pub fn mc_block_id(&self) -> &BlockId {
+        &self.manifest.mc_block_id
+    }
+
+    pub fn contains(&self, block_id: &BlockId, kind: PersistentStateKind) -> bool {
+        self.manifest.entry(block_id, kind).is_some()
+    }
+
+    /// Verifies the checksum of the state file and writes
+    /// the decompressed state into the `output`.
+    pub fn unpack<W: Write>(
+        &self,
+        block_id: &BlockId,
+        kind: PersistentStateKind,
+        mut output: W,
+    ) -> Result<W> {
+        const CHUNK_SIZE: usize = 1 << 20; // 1 MB
+
+        let entry = self
+            .manifest
+            .entry(block_id, kind)
+            .with_context(|| format!("{kind:?} state for {block_id} not found in bundle"))?;
+
+        let mut file = File::open(self.dir.join(&entry.file_name))
+            .with_context(|| format!("failed to open bundle file {}", entry.file_name))?;
+
+        let mut hasher = Sha256::new();
+        let mut decoder = ZstdDecompressStream::new(CHUNK_SIZE)?;
+
+        let mut size = 0u64;
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let mut decompressed = Vec::new();
+        loop {
+            let n = file.read(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            size += n as u64;
+            hasher.update(&chunk[..n]);
+
+            decompressed.clear();
+            decoder.write(&chunk[..n], &mut decompressed)?;
+            output.write_all(&decompressed)?;
+        }
+
+        anyhow::ensure!(
+            size == entry.size,
+            "bundle file size mismatch for {}",
+            entry.file_name
+        );
+        anyhow::ensure!(
+            HashBytes(hasher.finalize().into()) == entry.sha256,
+            "bundle file checksum mismatch for {}",
+            entry.file_name
+        );
+
+        output.flush()?;
+        Ok(output)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateBundleManifest {
+    pub version: u32,
+    /// Key block of the persistent states.
+    pub mc_block_id: BlockId,
+    pub entries: Vec<StateBundleEntry>,
+}
+
+impl StateBundleManifest {
+    pub fn entry(
+        &self,
+        block_id: &BlockId,
+        kind: PersistentStateKind,
+    ) -> Option<&StateBundleEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.block_id == *block_id && entry.kind == kind)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateBundleEntry {
+    pub block_id: BlockId,
+    pub kind: PersistentStateKind,
+    /// File name relative to the bundle directory.
+    pub file_name: String,
+    /// Size of the compressed file in bytes.
+    pub size: u64,
+    /// SHA256 of the compressed file.
+    pub sha256: HashBytes,
+}
+
+pub(super) struct StateBundleWriter {
+    dir: PathBuf,
+    entries: Vec<StateBundleEntry>,
+}
+
+impl StateBundleWriter {
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        anyhow::ensure!(
+            !dir.exists(),
+            "bundle directory already exists: {}",
+            dir.display()
+        );
+        std::fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            dir,
+            entries: Vec::new(),
+        })
+    }
+
+    pub fn add_file(
+        &mut self,
+        block_id: &BlockId,
+        kind: PersistentStateKind,
+        data: &[u8],
+    ) -> Result<()> {
+        let file_name = kind.make_file_name(block_id);
+        let file_name = file_name.to_str().context("invalid file name")?.to_owned();
+
+        std::fs::write(self.dir.join(&file_name), data)?;
+
+        self.entries.push(StateBundleEntry {
+            block_id: *block_id,
+            kind,
+            file_name,
+            size: data.len() as u64,
+            sha256: HashBytes(Sha256::digest(data).into()),
+        });
+        Ok(())
+    }
+
+    /// Writes the manifest. The bundle is incomplete until this is called.
+    pub fn finish(self, mc_block_id: &BlockId) -> Result<StateBundleManifest> {
+        let manifest = StateBundleManifest {
+            version: StateBundle::VERSION,
+            mc_block_id: *mc_block_id,
+            entries: self.entries,
+        };
+
+        let temp_path = self
+            .dir
+            .join(StateBundle::MANIFEST_FILE_NAME)
+            .with_extension("temp");
+        std::fs::write(&temp_path, serde_json::to_vec_pretty(&manifest)?)?;
+        std::fs::rename(&temp_path, self.dir.join(StateBundle::MANIFEST_FILE_NAME))?;
+
+        Ok(manifest)
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::{BTreeMap, VecDeque};
 use std::fs::File;
 use std::io::{Seek, Write};
 use std::num::{NonZeroU32, NonZeroU64};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
@@ -10,6 +10,7 @@ use arc_swap::ArcSwapAny;
 use dashmap::DashMap;
 use everscale_types::models::{BlockId, PrevBlockRef};
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use tokio::sync::{Notify, Semaphore};
 use tokio::time::Instant;
 use tycho_block_util::block::BlockStuff;
@@ -18,6 +19,8 @@ use tycho_block_util::state::RefMcStateHandle;
 use tycho_util::sync::CancellationFlag;
 use tycho_util::FastHashSet;
 
+use self::bundle::StateBundleWriter;
+pub use self::bundle::{StateBundle, StateBundleEntry, StateBundleManifest};
 pub use self::queue_state::reader::{QueueDiffReader, QueueStateReader};
 pub use self::queue_state::writer::QueueStateWriter;
 pub use self::shard_state::reader::{BriefBocHeader, ShardStateReader};
@@ -26,6 +29,7 @@ use super::{KeyBlocksDirection, ShardStateStorage};
 use crate::db::{BaseDb, FileDb, MappedFile};
 use crate::store::{BlockHandle, BlockHandleStorage, BlockStorage};
 
+mod bundle;
 mod queue_state {
     pub mod reader;
     pub mod writer;
@@ -40,7 +44,8 @@ mod tests;
 
 const BASE_DIR: &str = "states";
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PersistentStateKind {
     Shard,
     Queue,
@@ -298,6 +303,70 @@ impl PersistentStateStorage {
         .ok()
     }
 
+    /// Copies all persistent states of the specified key block
+    /// into a new [`StateBundle`] directory.
+    #[tracing::instrument(skip_all, fields(mc_block_id = %mc_block_id))]
+    pub async fn export_bundle<P: AsRef<Path>>(
+        &self,
+        mc_block_id: &BlockId,
+        path: P,
+    ) -> Result<StateBundleManifest> {
+        anyhow::ensure!(
+            mc_block_id.is_masterchain(),
+            "bundle block must be in the masterchain"
+        );
+        anyhow::ensure!(
+            self.state_exists(mc_block_id, PersistentStateKind::Shard),
+            "persistent state not found for {mc_block_id}"
+        );
+
+        let mut block_ids = vec![*mc_block_id];
+
+        // Collect the top shard blocks of the key block
+        if mc_block_id.seqno != 0 {
+            let handle = self
+                .inner
+                .block_handles
+                .load_handle(mc_block_id)
+                .context("key block handle not found")?;
+            let mc_block = self.inner.blocks.load_block_data(&handle).await?;
+
+            let mut shard_block_ids = mc_block.shard_blocks()?.into_values().collect::<Vec<_>>();
+            shard_block_ids.sort_unstable_by_key(|block_id| {
+                (block_id.shard.workchain(), block_id.shard.prefix())
+            });
+            block_ids.extend(shard_block_ids);
+        }
+
+        let mc_block_id = *mc_block_id;
+        let path = path.as_ref().to_path_buf();
+        let this = self.inner.clone();
+        let span = tracing::Span::current();
+
+        tokio::task::spawn_blocking(move || {
+            let _span = span.enter();
+
+            let mut writer = StateBundleWriter::new(path)?;
+            for block_id in block_ids {
+                for kind in [PersistentStateKind::Shard, PersistentStateKind::Queue] {
+                    let Some(cached) = this
+                        .descriptor_cache
+                        .get(&CacheKey { block_id, kind })
+                        .map(|r| r.clone())
+                    else {
+                        continue;
+                    };
+
+                    writer.add_file(&block_id, kind, cached.file.as_slice())?;
+                    tracing::info!(%block_id, ?kind, "exported persistent state");
+                }
+            }
+
+            writer.finish(&mc_block_id)
+        })
+        .await?
+    }
+
     #[tracing::instrument(skip_all, fields(mc_seqno, block_id = %handle.id()))]
     pub async fn store_shard_state(
         &self,
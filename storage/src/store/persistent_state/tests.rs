@@ -393,3 +393,72 @@ async fn persistent_queue_state_read_write() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn persistent_state_bundle() -> Result<()> {
+    let (storage, tmp_dir) = Storage::new_temp().await?;
+
+    let shard_states = storage.shard_state_storage();
+    let persistent_states = storage.persistent_state_storage();
+
+    static ZEROSTATE_BOC: &[u8] = include_bytes!("../../../../core/tests/data/zerostate.boc");
+    let zerostate_root = Boc::decode(ZEROSTATE_BOC)?;
+    let zerostate_id = BlockId {
+        shard: ShardIdent::MASTERCHAIN,
+        seqno: 0,
+        root_hash: *zerostate_root.repr_hash(),
+        file_hash: Boc::file_hash_blake(ZEROSTATE_BOC),
+    };
+
+    let zerostate = ShardStateStuff::from_root(
+        &zerostate_id,
+        zerostate_root,
+        shard_states.min_ref_mc_state(),
+    )?;
+
+    let (handle, _) = storage.block_handle_storage().create_or_load_handle(
+        &zerostate_id,
+        NewBlockMeta::zero_state(zerostate.as_ref().gen_utime, true),
+    );
+    shard_states
+        .store_state(&handle, &zerostate, Default::default())
+        .await?;
+    persistent_states
+        .store_shard_state(0, &handle, zerostate.ref_mc_state_handle().clone())
+        .await?;
+
+    // Export
+    let bundle_dir = tmp_dir.path().join("bundle");
+    let manifest = persistent_states
+        .export_bundle(&zerostate_id, &bundle_dir)
+        .await?;
+    assert_eq!(manifest.mc_block_id, zerostate_id);
+    assert_eq!(manifest.entries.len(), 1);
+
+    // The directory must not be overwritten
+    assert!(persistent_states
+        .export_bundle(&zerostate_id, &bundle_dir)
+        .await
+        .is_err());
+
+    // Import
+    let bundle = StateBundle::open(&bundle_dir)?;
+    assert_eq!(bundle.mc_block_id(), &zerostate_id);
+    assert!(bundle.contains(&zerostate_id, PersistentStateKind::Shard));
+    assert!(!bundle.contains(&zerostate_id, PersistentStateKind::Queue));
+
+    let boc = bundle.unpack(&zerostate_id, PersistentStateKind::Shard, Vec::new())?;
+    assert_eq!(&Boc::decode(&boc)?, zerostate.root_cell());
+
+    // Corrupted files are rejected
+    let file_path = bundle_dir.join(&manifest.entries[0].file_name);
+    let mut data = std::fs::read(&file_path)?;
+    *data.last_mut().unwrap() ^= 0xff;
+    std::fs::write(&file_path, data)?;
+
+    assert!(bundle
+        .unpack(&zerostate_id, PersistentStateKind::Shard, Vec::new())
+        .is_err());
+
+    Ok(())
+}
@@ -6,12 +6,13 @@ use anyhow::{Context, Result};
 use arc_swap::ArcSwapOption;
 use everscale_types::models::*;
 use everscale_types::prelude::*;
+use parking_lot::RwLock;
 use tycho_block_util::block::BlockStuff;
 use tycho_block_util::state::ShardStateStuff;
 use tycho_util::metrics::HistogramGuard;
-use tycho_util::sync::CancellationFlag;
-use tycho_util::FastHashMap;
-use weedb::{rocksdb, OwnedSnapshot};
+use tycho_util::sync::{CancellationFlag, DebounceCancellationFlag};
+use tycho_util::{FastHashMap, FastHashSet};
+use weedb::{rocksdb, ColumnFamily, OwnedSnapshot, Table};
 
 use crate::db::*;
 use crate::util::*;
@@ -21,6 +22,7 @@ pub struct RpcStorage {
     min_tx_lt: AtomicU64,
     min_tx_lt_guard: tokio::sync::Mutex<()>,
     snapshot: ArcSwapOption<OwnedSnapshot>,
+    tx_watch_list: RwLock<FastHashSet<AccountKey>>,
 }
 
 impl RpcStorage {
@@ -30,6 +32,7 @@ impl RpcStorage {
             min_tx_lt: AtomicU64::new(u64::MAX),
             min_tx_lt_guard: Default::default(),
             snapshot: Default::default(),
+            tx_watch_list: Default::default(),
         };
 
         let state = &this.db.state;
@@ -48,6 +51,13 @@ impl RpcStorage {
         this.min_tx_lt
             .store(min_lt.unwrap_or(u64::MAX), Ordering::Release);
 
+        if let Some(value) = state.get(TX_WATCH_LIST).unwrap() {
+            let mut watch_list = this.tx_watch_list.write();
+            for item in value.chunks_exact(ACCOUNT_KEY_LEN) {
+                watch_list.insert(item.try_into().unwrap());
+            }
+        }
+
         tracing::debug!(?min_lt, "rpc storage initialized");
 
         this
@@ -57,10 +67,55 @@ impl RpcStorage {
         &self.db
     }
 
+    /// Returns the lt since which transactions of all accounts are stored.
+    ///
+    /// NOTE: Transactions older than this may still be kept for accounts
+    /// from the retention rules or the watch list.
     pub fn min_tx_lt(&self) -> u64 {
         self.min_tx_lt.load(Ordering::Acquire)
     }
 
+    /// Returns accounts which always keep the full transactions history.
+    pub fn tx_watch_list(&self) -> Vec<StdAddr> {
+        let mut items = self
+            .tx_watch_list
+            .read()
+            .iter()
+            .map(account_from_key)
+            .collect::<Vec<_>>();
+        items.sort_unstable_by(|a, b| (a.workchain, &a.address).cmp(&(b.workchain, &b.address)));
+        items
+    }
+
+    /// Updates and saves the transactions watch list.
+    /// Returns the updated list.
+    ///
+    /// NOTE: Transactions of removed accounts will be
+    /// removed during the next transactions GC.
+    pub fn update_tx_watch_list(
+        &self,
+        add: &[StdAddr],
+        remove: &[StdAddr],
+    ) -> Result<Vec<StdAddr>> {
+        {
+            let mut watch_list = self.tx_watch_list.write();
+            for address in remove {
+                watch_list.remove(&account_key(address));
+            }
+            for address in add {
+                watch_list.insert(account_key(address));
+            }
+
+            let mut value = Vec::with_capacity(watch_list.len() * ACCOUNT_KEY_LEN);
+            for item in watch_list.iter() {
+                value.extend_from_slice(item);
+            }
+            self.db.state.insert(TX_WATCH_LIST, value)?;
+        }
+
+        Ok(self.tx_watch_list())
+    }
+
     pub fn update_snapshot(&self) {
         let snapshot = Arc::new(self.db.owned_snapshot());
         self.snapshot.store(Some(snapshot));
@@ -112,6 +167,77 @@ impl RpcStorage {
         Ok(CodeHashesIter { inner: iter })
     }
 
+    pub fn get_accounts_by_balance(
+        &self,
+        workchain: i8,
+        continuation: Option<(&StdAddr, Tokens)>,
+    ) -> Result<AccountsByBalanceIter<'_>> {
+        let mut key = [0u8; tables::AccountsByBalance::KEY_LEN];
+        key[0] = workchain as u8;
+        if let Some((address, balance)) = continuation {
+            key[1..17].copy_from_slice(&(!balance.into_inner()).to_be_bytes());
+            key[17..49].copy_from_slice(address.address.as_ref());
+        }
+
+        let mut upper_bound = Vec::with_capacity(tables::AccountsByBalance::KEY_LEN);
+        upper_bound.push(key[0]);
+        upper_bound.extend_from_slice(&[0xff; 48]);
+
+        let mut readopts = self.db.accounts_by_balance.new_read_config();
+        readopts.set_iterate_upper_bound(upper_bound);
+        if let Some(snapshot) = &*self.snapshot.load() {
+            readopts.set_snapshot(snapshot);
+        }
+
+        let rocksdb = self.db.rocksdb();
+        let accounts_by_balance_cf = self.db.accounts_by_balance.cf();
+        let mut iter = rocksdb.raw_iterator_cf_opt(&accounts_by_balance_cf, readopts);
+
+        iter.seek(key);
+        if continuation.is_some() && iter.key() == Some(key.as_slice()) {
+            iter.next();
+        }
+
+        Ok(AccountsByBalanceIter { inner: iter })
+    }
+
+    pub fn get_active_accounts_by_code_hash(
+        &self,
+        code_hash: &HashBytes,
+        since_lt: u64,
+        continuation: Option<(&StdAddr, u64)>,
+    ) -> Result<ActiveAccountsIter<'_>> {
+        let mut key = [0u8; tables::CodeHashesByLt::KEY_LEN];
+        key[0..32].copy_from_slice(code_hash.as_ref());
+        if let Some((address, last_trans_lt)) = continuation {
+            key[32..40].copy_from_slice(&(!last_trans_lt).to_be_bytes());
+            key[40] = address.workchain as u8;
+            key[41..73].copy_from_slice(address.address.as_ref());
+        }
+
+        let mut upper_bound = Vec::with_capacity(tables::CodeHashesByLt::KEY_LEN);
+        upper_bound.extend_from_slice(&key[..32]);
+        upper_bound.extend_from_slice(&(!since_lt).to_be_bytes());
+        upper_bound.extend_from_slice(&[0xff; 33]);
+
+        let mut readopts = self.db.code_hashes_by_lt.new_read_config();
+        readopts.set_iterate_upper_bound(upper_bound);
+        if let Some(snapshot) = &*self.snapshot.load() {
+            readopts.set_snapshot(snapshot);
+        }
+
+        let rocksdb = self.db.rocksdb();
+        let code_hashes_by_lt_cf = self.db.code_hashes_by_lt.cf();
+        let mut iter = rocksdb.raw_iterator_cf_opt(&code_hashes_by_lt_cf, readopts);
+
+        iter.seek(key);
+        if continuation.is_some() && iter.key() == Some(key.as_slice()) {
+            iter.next();
+        }
+
+        Ok(ActiveAccountsIter { inner: iter })
+    }
+
     pub fn get_transactions(
         &self,
         account: &StdAddr,
@@ -144,12 +270,33 @@ impl RpcStorage {
         &self,
         hash: &HashBytes,
     ) -> Result<Option<rocksdb::DBPinnableSlice<'_>>> {
-        let Some(key) = self.db.transactions_by_hash.get(hash)? else {
+        let Some(value) = self.db.transactions_by_hash.get(hash)? else {
             return Ok(None);
         };
+        let key = &value[..tables::Transactions::KEY_LEN];
         self.db.transactions.get(key).map_err(Into::into)
     }
 
+    pub fn get_transaction_info(&self, hash: &HashBytes) -> Result<Option<TransactionInfo>> {
+        let Some(value) = self.db.transactions_by_hash.get(hash)? else {
+            return Ok(None);
+        };
+
+        let value = value.as_ref();
+        let account = StdAddr::new(value[0] as i8, HashBytes::from_slice(&value[1..33]));
+        let lt = u64::from_be_bytes(value[33..41].try_into().unwrap());
+
+        // NOTE: Block id is not stored for transactions saved by older versions.
+        let block_id = (value.len() == tables::TransactionsByHash::VALUE_LEN)
+            .then(|| BlockIdShort::from_slice(&value[tables::Transactions::KEY_LEN..]));
+
+        Ok(Some(TransactionInfo {
+            account,
+            lt,
+            block_id,
+        }))
+    }
+
     pub fn get_dst_transaction(
         &self,
         in_msg_hash: &HashBytes,
@@ -176,12 +323,12 @@ impl RpcStorage {
             return Ok(());
         };
 
-        tracing::info!("clearing old code hash indices");
+        tracing::info!("clearing old account indices");
         let started_at = Instant::now();
-        self.remove_code_hashes(&shard_ident).await?;
+        self.remove_account_indices(&shard_ident).await?;
         tracing::info!(
             elapsed = %humantime::format_duration(started_at.elapsed()),
-            "cleared old code hash indices"
+            "cleared old account indices"
         );
 
         // Split on virtual shards
@@ -220,12 +367,15 @@ impl RpcStorage {
                 tracing::warn!("cancelled");
             });
 
-            tracing::info!(split_depth, "started building new code hash indices");
+            tracing::info!(split_depth, "started building new account indices");
             let started_at = Instant::now();
 
             let raw = db.rocksdb().as_ref();
             let code_hashes_cf = &db.code_hashes.cf();
             let code_hashes_by_address_cf = &db.code_hashes_by_address.cf();
+            let accounts_by_balance_cf = &db.accounts_by_balance.cf();
+            let code_hashes_by_lt_cf = &db.code_hashes_by_lt.cf();
+            let account_indices_by_address_cf = &db.account_indices_by_address.cf();
 
             let mut non_empty_batch = false;
             let mut write_batch = rocksdb::WriteBatch::default();
@@ -237,9 +387,11 @@ impl RpcStorage {
             let mut code_hashes_by_address_key = [0u8; { tables::CodeHashesByAddress::KEY_LEN }];
             code_hashes_by_address_key[0] = workchain as u8;
 
+            let mut index_buffer = Vec::with_capacity(AccountIndexValue::MAX_LEN);
+
             // Iterate all accounts
             for (virtual_shard, accounts) in virtual_shards {
-                tracing::info!(%virtual_shard, "started collecting code hashes");
+                tracing::info!(%virtual_shard, "started collecting account indices");
                 let started_at = Instant::now();
 
                 for entry in accounts.iter() {
@@ -249,20 +401,38 @@ impl RpcStorage {
 
                     let (id, (_, account)) = entry?;
 
-                    let code_hash = match extract_code_hash(&account)? {
-                        ExtractedCodeHash::Exact(Some(code_hash)) => code_hash,
-                        ExtractedCodeHash::Exact(None) => continue,
-                        ExtractedCodeHash::Skip => anyhow::bail!("code in account state is pruned"),
+                    let value = match extract_account_index(&account)? {
+                        ExtractedAccountIndex::Exact(Some(value)) => value,
+                        ExtractedAccountIndex::Exact(None) => continue,
+                        ExtractedAccountIndex::Skip => anyhow::bail!("account state is pruned"),
                     };
 
                     non_empty_batch |= true;
 
+                    // Write balance and activity indices
+                    let key = value.balance_key(workchain, &id);
+                    write_batch.put_cf(accounts_by_balance_cf, key.as_slice(), []);
+
+                    if let Some(key) = value.code_hash_key(workchain, &id) {
+                        write_batch.put_cf(code_hashes_by_lt_cf, key.as_slice(), []);
+                    }
+
+                    value.write_to(&mut index_buffer);
+                    code_hashes_by_address_key[1..33].copy_from_slice(id.as_slice());
+                    write_batch.put_cf(
+                        account_indices_by_address_cf,
+                        code_hashes_by_address_key.as_slice(),
+                        &index_buffer,
+                    );
+
+                    let Some(code_hash) = value.code_hash else {
+                        continue;
+                    };
+
                     // Fill account address in the key buffer
                     code_hashes_key[..32].copy_from_slice(code_hash.as_slice());
                     code_hashes_key[33..65].copy_from_slice(id.as_slice());
 
-                    code_hashes_by_address_key[1..33].copy_from_slice(id.as_slice());
-
                     // Write tx data and indices
                     write_batch.put_cf(code_hashes_cf, code_hashes_key.as_slice(), []);
                     write_batch.put_cf(
@@ -275,7 +445,7 @@ impl RpcStorage {
                 tracing::info!(
                     %virtual_shard,
                     elapsed = %humantime::format_duration(started_at.elapsed()),
-                    "finished collecting code hashes",
+                    "finished collecting account indices",
                 );
             }
 
@@ -285,30 +455,43 @@ impl RpcStorage {
 
             tracing::info!(
                 elapsed = %humantime::format_duration(started_at.elapsed()),
-                "finished building new code hash indices"
+                "finished building new account indices"
             );
 
             // Flush indices after delete/insert
-            tracing::info!("started flushing code hash indices");
+            tracing::info!("started flushing account indices");
             let started_at = Instant::now();
 
             let bound = Option::<[u8; 0]>::None;
             raw.compact_range_cf(code_hashes_cf, bound, bound);
             raw.compact_range_cf(code_hashes_by_address_cf, bound, bound);
+            raw.compact_range_cf(accounts_by_balance_cf, bound, bound);
+            raw.compact_range_cf(code_hashes_by_lt_cf, bound, bound);
+            raw.compact_range_cf(account_indices_by_address_cf, bound, bound);
 
             // Done
             scopeguard::ScopeGuard::into_inner(guard);
             tracing::info!(
                 elapsed = %humantime::format_duration(started_at.elapsed()),
-                "finished flushing code hash indices"
+                "finished flushing account indices"
             );
             Ok(())
         })
         .await?
     }
 
-    #[tracing::instrument(level = "info", name = "remove_old_transactions", skip(self))]
-    pub async fn remove_old_transactions(&self, min_lt: u64) -> Result<()> {
+    /// Removes transactions with `lt < min_lt` except for the accounts
+    /// from the watch list and the accounts with custom `retention`.
+    #[tracing::instrument(
+        level = "info",
+        name = "remove_old_transactions",
+        skip(self, retention)
+    )]
+    pub async fn remove_old_transactions(
+        &self,
+        min_lt: u64,
+        retention: &TransactionsRetention,
+    ) -> Result<()> {
         const ITEMS_PER_BATCH: usize = 100000;
 
         type TxKey = [u8; tables::Transactions::KEY_LEN];
@@ -428,6 +611,11 @@ impl RpcStorage {
         let mut cancelled = cancelled.debounce(10000);
         let span = tracing::Span::current();
 
+        let mut retention = retention.clone();
+        for account in self.tx_watch_list.read().iter() {
+            retention.by_address.insert(*account, 0);
+        }
+
         // NOTE: `spawn_blocking` is used here instead of `rayon_run` as it is IO-bound task.
         tokio::task::spawn_blocking(move || {
             let _span = span.enter();
@@ -436,6 +624,23 @@ impl RpcStorage {
                 tracing::warn!("cancelled");
             });
 
+            // Returns the min lt of kept transactions for the account.
+            let get_account_min_lt = |account: &[u8]| -> Result<u64> {
+                let mut account_min_lt = min_lt;
+                if let Some(lt) = retention.by_address.get(account) {
+                    account_min_lt = std::cmp::min(account_min_lt, *lt);
+                }
+                if !retention.by_code_hash.is_empty() {
+                    if let Some(code_hash) = db.code_hashes_by_address.get(account)? {
+                        let code_hash = HashBytes::from_slice(&code_hash[..32]);
+                        if let Some(lt) = retention.by_code_hash.get(&code_hash) {
+                            account_min_lt = std::cmp::min(account_min_lt, *lt);
+                        }
+                    }
+                }
+                Ok(account_min_lt)
+            };
+
             let raw = db.rocksdb().as_ref();
 
             tracing::info!("started removing old transactions");
@@ -460,6 +665,8 @@ impl RpcStorage {
             let mut last_account: TxKey = [0u8; tables::Transactions::KEY_LEN];
             last_account[33..41].copy_from_slice(&u64::MAX.to_be_bytes());
 
+            let mut account_min_lt = min_lt;
+
             let mut items = 0usize;
             let mut total_invalid = 0usize;
             let mut iteration = 0usize;
@@ -490,12 +697,14 @@ impl RpcStorage {
 
                     // Add pending delete into batch
                     gc.end_account();
+
+                    account_min_lt = get_account_min_lt(item_account)?;
                 }
 
                 // Get lt from the key
                 let lt = u64::from_be_bytes(key[33..41].try_into().unwrap());
 
-                if lt < min_lt {
+                if lt < account_min_lt {
                     // Add tx and its secondary indices into the batch
                     items += 1;
                     gc.delete_tx(key, value);
@@ -564,8 +773,9 @@ impl RpcStorage {
             let extra = block.load_extra()?;
             let account_blocks = extra.account_blocks.load()?;
 
+            // NOTE: `None` means that account states were not changed in this block.
             let accounts = if account_blocks.is_empty() {
-                Dict::new()
+                None
             } else {
                 let merkle_update = block.as_ref().state_update.load()?;
 
@@ -580,11 +790,11 @@ impl RpcStorage {
                 let new_accounts = get_accounts(merkle_update.new)?;
 
                 if old_accounts.repr_hash() == new_accounts.repr_hash() {
-                    Dict::new()
+                    None
                 } else {
                     let accounts = Lazy::<ShardAccounts>::from_raw(new_accounts).load()?;
                     let (accounts, _) = accounts.into_parts();
-                    accounts
+                    Some(accounts)
                 }
             };
 
@@ -597,7 +807,13 @@ impl RpcStorage {
             let mut tx_key = [0u8; tables::Transactions::KEY_LEN];
             tx_key[0] = workchain as u8;
 
+            // Prepare buffer for full tx id with block id
+            let mut tx_by_hash_value = [0u8; tables::TransactionsByHash::VALUE_LEN];
+            tx_by_hash_value[tables::Transactions::KEY_LEN..]
+                .copy_from_slice(&block.id().as_short_id().to_vec());
+
             let mut tx_buffer = Vec::with_capacity(1024);
+            let mut index_buffer = Vec::with_capacity(AccountIndexValue::MAX_LEN);
 
             // Iterate through all changed accounts in the block
             let mut non_empty_batch = false;
@@ -658,7 +874,9 @@ impl RpcStorage {
                     .encode(&mut tx_buffer);
 
                     write_batch.put_cf(tx_cf, tx_key.as_slice(), &tx_buffer);
-                    write_batch.put_cf(tx_by_hash_cf, tx_hash.as_slice(), tx_key.as_slice());
+                    tx_by_hash_value[..tables::Transactions::KEY_LEN].copy_from_slice(&tx_key);
+
+                    write_batch.put_cf(tx_by_hash_cf, tx_hash.as_slice(), tx_by_hash_value);
                     if let Some(in_msg) = &tx.in_msg {
                         write_batch.put_cf(tx_by_in_msg_cf, in_msg.repr_hash(), tx_key.as_slice());
                     }
@@ -679,17 +897,33 @@ impl RpcStorage {
                     None
                 };
 
+                // Account indices must not be touched if states are the same,
+                // otherwise all entries would be treated as removed.
+                let Some(accounts) = &accounts else {
+                    continue;
+                };
+
                 // Apply the update if any
                 if let Some(remove) = update {
                     Self::update_code_hash(
                         &db,
                         workchain,
                         &account,
-                        &accounts,
+                        accounts,
                         remove,
                         &mut write_batch,
                     )?;
                 }
+
+                // Update balance and activity indices
+                Self::update_account_indices(
+                    &db,
+                    workchain,
+                    &account,
+                    accounts,
+                    &mut index_buffer,
+                    &mut write_batch,
+                )?;
             }
 
             drop(prepare_batch_histogram);
@@ -812,10 +1046,83 @@ impl RpcStorage {
         Ok(())
     }
 
-    async fn remove_code_hashes(&self, shard: &ShardIdent) -> Result<()> {
+    fn update_account_indices(
+        db: &RpcDb,
+        workchain: i8,
+        account: &HashBytes,
+        accounts: &ShardAccountsDict,
+        buffer: &mut Vec<u8>,
+        write_batch: &mut rocksdb::WriteBatch,
+    ) -> Result<()> {
+        // Find the new account info
+        let new_value = match accounts.get(account)? {
+            Some((_, account)) => match extract_account_index(&account)? {
+                ExtractedAccountIndex::Exact(value) => value,
+                ExtractedAccountIndex::Skip => return Ok(()),
+            },
+            None => None,
+        };
+
+        // Prepare column families
+        let accounts_by_balance_cf = &db.accounts_by_balance.cf();
+        let code_hashes_by_lt_cf = &db.code_hashes_by_lt.cf();
+        let account_indices_by_address_cf = &db.account_indices_by_address.cf();
+
+        let mut address_key = [0u8; tables::AccountIndicesByAddress::KEY_LEN];
+        address_key[0] = workchain as u8;
+        address_key[1..33].copy_from_slice(account.as_slice());
+
+        // Find the old account info
+        let old_value = db
+            .account_indices_by_address
+            .get(address_key.as_slice())?
+            .and_then(|value| AccountIndexValue::from_slice(&value));
+
+        if old_value == new_value {
+            return Ok(());
+        }
+
+        // Remove entries from the primary indices
+        if let Some(old_value) = &old_value {
+            let key = old_value.balance_key(workchain, account);
+            write_batch.delete_cf(accounts_by_balance_cf, key.as_slice());
+
+            if let Some(key) = old_value.code_hash_key(workchain, account) {
+                write_batch.delete_cf(code_hashes_by_lt_cf, key.as_slice());
+            }
+        }
+
+        match &new_value {
+            Some(new_value) => {
+                // Update primary indices
+                let key = new_value.balance_key(workchain, account);
+                write_batch.put_cf(accounts_by_balance_cf, key.as_slice(), []);
+
+                if let Some(key) = new_value.code_hash_key(workchain, account) {
+                    write_batch.put_cf(code_hashes_by_lt_cf, key.as_slice(), []);
+                }
+
+                // Update secondary index
+                new_value.write_to(buffer);
+                write_batch.put_cf(
+                    account_indices_by_address_cf,
+                    address_key.as_slice(),
+                    buffer.as_slice(),
+                );
+            }
+            None => {
+                // Remove entry from the secondary index
+                write_batch.delete_cf(account_indices_by_address_cf, address_key.as_slice());
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn remove_account_indices(&self, shard: &ShardIdent) -> Result<()> {
         let workchain = shard.workchain() as u8;
 
-        // Remove from the secondary index first
+        // Remove from the secondary indices first
         {
             let mut from = [0u8; { tables::CodeHashesByAddress::KEY_LEN }];
             from[0] = workchain;
@@ -832,13 +1139,21 @@ impl RpcStorage {
             }
 
             let raw = self.db.rocksdb();
-            let cf = &self.db.code_hashes_by_address.cf();
-            let writeopts = self.db.code_hashes_by_address.write_config();
-
-            // Remove `[from; to)`
-            raw.delete_range_cf_opt(cf, &from, &to, writeopts)?;
-            // Remove `to`, (-1:ffff..ffff might be a valid existing address)
-            raw.delete_cf_opt(cf, to, writeopts)?;
+            for (cf, writeopts) in [
+                (
+                    self.db.code_hashes_by_address.cf(),
+                    self.db.code_hashes_by_address.write_config(),
+                ),
+                (
+                    self.db.account_indices_by_address.cf(),
+                    self.db.account_indices_by_address.write_config(),
+                ),
+            ] {
+                // Remove `[from; to)`
+                raw.delete_range_cf_opt(&cf, &from, &to, writeopts)?;
+                // Remove `to`, (-1:ffff..ffff might be a valid existing address)
+                raw.delete_cf_opt(&cf, to, writeopts)?;
+            }
         }
 
         let cancelled = CancellationFlag::new();
@@ -846,7 +1161,7 @@ impl RpcStorage {
             cancelled.cancel();
         }
 
-        // Full scan the main code hashes index and remove all entires for the shard
+        // Full scan the main indices and remove all entires for the shard
         let db = self.db.clone();
         let mut cancelled = cancelled.debounce(1000);
         let shard = *shard;
@@ -860,54 +1175,107 @@ impl RpcStorage {
                 tracing::warn!("cancelled");
             });
 
-            let cf = &db.code_hashes.cf();
-
             let raw = db.rocksdb().as_ref();
-            let snapshot = raw.snapshot();
-            let mut readopts = db.code_hashes.new_read_config();
-            readopts.set_snapshot(&snapshot);
 
-            let writeopts = db.code_hashes.write_config();
+            // NOTE: Offsets point to the `workchain: i8` and `account: [u8; 32]` parts of the key.
+            remove_shard_entries(
+                raw,
+                &db.code_hashes,
+                tables::CodeHashes::KEY_LEN,
+                32,
+                33,
+                &shard,
+                &mut cancelled,
+            )?;
+            remove_shard_entries(
+                raw,
+                &db.accounts_by_balance,
+                tables::AccountsByBalance::KEY_LEN,
+                0,
+                17,
+                &shard,
+                &mut cancelled,
+            )?;
+            remove_shard_entries(
+                raw,
+                &db.code_hashes_by_lt,
+                tables::CodeHashesByLt::KEY_LEN,
+                40,
+                41,
+                &shard,
+                &mut cancelled,
+            )?;
 
-            let mut iter = raw.raw_iterator_cf_opt(cf, readopts);
-            iter.seek_to_first();
+            scopeguard::ScopeGuard::into_inner(guard);
+            Ok(())
+        })
+        .await?
+    }
+}
 
-            let mut prefix = shard.prefix();
-            let tag = extract_tag(&shard);
-            prefix -= tag; // Remove tag from the prefix
+fn remove_shard_entries<T: ColumnFamily>(
+    raw: &rocksdb::DB,
+    table: &Table<T>,
+    key_len: usize,
+    workchain_offset: usize,
+    account_offset: usize,
+    shard: &ShardIdent,
+    cancelled: &mut DebounceCancellationFlag,
+) -> Result<()> {
+    let workchain = shard.workchain() as u8;
+    let cf = &table.cf();
 
-            // For the prefix 1010000 the mask is 1100000
-            let prefix_mask = !(tag | (tag - 1));
+    let snapshot = raw.snapshot();
+    let mut readopts = table.new_read_config();
+    readopts.set_snapshot(&snapshot);
 
-            loop {
-                let key = match iter.key() {
-                    Some(key) => key,
-                    None => break iter.status()?,
-                };
+    let writeopts = table.write_config();
 
-                if cancelled.check() {
-                    anyhow::bail!("remove_code_hashes cancelled");
-                }
+    let mut iter = raw.raw_iterator_cf_opt(cf, readopts);
+    iter.seek_to_first();
 
-                if key.len() != tables::CodeHashes::KEY_LEN
-                    || key[32] == workchain
-                        && (shard.is_full() || {
-                            // Filter only the keys with the same prefix
-                            let key = u64::from_be_bytes(key[33..41].try_into().unwrap());
-                            (key ^ prefix) & prefix_mask == 0
-                        })
-                {
-                    raw.delete_cf_opt(cf, key, writeopts)?;
-                }
+    let mut prefix = shard.prefix();
+    let tag = extract_tag(shard);
+    prefix -= tag; // Remove tag from the prefix
 
-                iter.next();
-            }
+    // For the prefix 1010000 the mask is 1100000
+    let prefix_mask = !(tag | (tag - 1));
 
-            scopeguard::ScopeGuard::into_inner(guard);
-            Ok(())
-        })
-        .await?
+    loop {
+        let key = match iter.key() {
+            Some(key) => key,
+            None => break iter.status()?,
+        };
+
+        if cancelled.check() {
+            anyhow::bail!("remove_account_indices cancelled");
+        }
+
+        if key.len() != key_len
+            || key[workchain_offset] == workchain
+                && (shard.is_full() || {
+                    // Filter only the keys with the same prefix
+                    let key = u64::from_be_bytes(
+                        key[account_offset..account_offset + 8].try_into().unwrap(),
+                    );
+                    (key ^ prefix) & prefix_mask == 0
+                })
+        {
+            raw.delete_cf_opt(cf, key, writeopts)?;
+        }
+
+        iter.next();
     }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct TransactionInfo {
+    pub account: StdAddr,
+    pub lt: u64,
+    /// Block which contains the transaction.
+    pub block_id: Option<BlockIdShort>,
 }
 
 pub struct CodeHashesIter<'a> {
@@ -954,6 +1322,56 @@ impl Iterator for RawCodeHashesIter<'_> {
     }
 }
 
+pub struct AccountsByBalanceIter<'a> {
+    inner: rocksdb::DBRawIterator<'a>,
+}
+
+impl Iterator for AccountsByBalanceIter<'_> {
+    type Item = (StdAddr, Tokens);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.inner.key()?;
+        debug_assert!(value.len() == tables::AccountsByBalance::KEY_LEN);
+
+        let balance = !u128::from_be_bytes(value[1..17].try_into().unwrap());
+        let result = Some((
+            StdAddr {
+                anycast: None,
+                workchain: value[0] as i8,
+                address: HashBytes(value[17..49].try_into().unwrap()),
+            },
+            Tokens::new(balance),
+        ));
+        self.inner.next();
+        result
+    }
+}
+
+pub struct ActiveAccountsIter<'a> {
+    inner: rocksdb::DBRawIterator<'a>,
+}
+
+impl Iterator for ActiveAccountsIter<'_> {
+    type Item = (StdAddr, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.inner.key()?;
+        debug_assert!(value.len() == tables::CodeHashesByLt::KEY_LEN);
+
+        let last_trans_lt = !u64::from_be_bytes(value[32..40].try_into().unwrap());
+        let result = Some((
+            StdAddr {
+                anycast: None,
+                workchain: value[40] as i8,
+                address: HashBytes(value[41..73].try_into().unwrap()),
+            },
+            last_trans_lt,
+        ));
+        self.inner.next();
+        result
+    }
+}
+
 pub struct TransactionsIterBuilder<'a> {
     inner: rocksdb::DBRawIterator<'a>,
 }
@@ -1010,6 +1428,92 @@ fn extract_code_hash(account: &ShardAccount) -> Result<ExtractedCodeHash> {
     Ok(ExtractedCodeHash::Exact(None))
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AccountIndexValue {
+    balance: u128,
+    last_trans_lt: u64,
+    code_hash: Option<HashBytes>,
+}
+
+impl AccountIndexValue {
+    const MIN_LEN: usize = 16 + 8;
+    const MAX_LEN: usize = Self::MIN_LEN + 32;
+
+    fn from_slice(value: &[u8]) -> Option<Self> {
+        if value.len() != Self::MIN_LEN && value.len() != Self::MAX_LEN {
+            return None;
+        }
+
+        Some(Self {
+            balance: u128::from_be_bytes(value[0..16].try_into().unwrap()),
+            last_trans_lt: u64::from_be_bytes(value[16..24].try_into().unwrap()),
+            code_hash: (value.len() == Self::MAX_LEN)
+                .then(|| HashBytes(value[24..56].try_into().unwrap())),
+        })
+    }
+
+    fn write_to(&self, buffer: &mut Vec<u8>) {
+        buffer.clear();
+        buffer.extend_from_slice(&self.balance.to_be_bytes());
+        buffer.extend_from_slice(&self.last_trans_lt.to_be_bytes());
+        if let Some(code_hash) = &self.code_hash {
+            buffer.extend_from_slice(code_hash.as_slice());
+        }
+    }
+
+    fn balance_key(
+        &self,
+        workchain: i8,
+        account: &HashBytes,
+    ) -> [u8; tables::AccountsByBalance::KEY_LEN] {
+        let mut key = [0u8; tables::AccountsByBalance::KEY_LEN];
+        key[0] = workchain as u8;
+        key[1..17].copy_from_slice(&(!self.balance).to_be_bytes());
+        key[17..49].copy_from_slice(account.as_slice());
+        key
+    }
+
+    fn code_hash_key(
+        &self,
+        workchain: i8,
+        account: &HashBytes,
+    ) -> Option<[u8; tables::CodeHashesByLt::KEY_LEN]> {
+        let code_hash = self.code_hash.as_ref()?;
+        let mut key = [0u8; tables::CodeHashesByLt::KEY_LEN];
+        key[0..32].copy_from_slice(code_hash.as_slice());
+        key[32..40].copy_from_slice(&(!self.last_trans_lt).to_be_bytes());
+        key[40] = workchain as u8;
+        key[41..73].copy_from_slice(account.as_slice());
+        Some(key)
+    }
+}
+
+enum ExtractedAccountIndex {
+    Exact(Option<AccountIndexValue>),
+    Skip,
+}
+
+fn extract_account_index(account: &ShardAccount) -> Result<ExtractedAccountIndex> {
+    if account.account.inner().descriptor().is_pruned_branch() {
+        return Ok(ExtractedAccountIndex::Skip);
+    }
+
+    let Some(state) = account.load_account()? else {
+        return Ok(ExtractedAccountIndex::Exact(None));
+    };
+
+    let code_hash = match &state.state {
+        AccountState::Active(state_init) => state_init.code.as_ref().map(|code| *code.repr_hash()),
+        _ => None,
+    };
+
+    Ok(ExtractedAccountIndex::Exact(Some(AccountIndexValue {
+        balance: state.balance.tokens.into_inner(),
+        last_trans_lt: account.last_trans_lt,
+        code_hash,
+    })))
+}
+
 fn split_shard(
     shard: &ShardIdent,
     accounts: &ShardAccountsDict,
@@ -1086,6 +1590,123 @@ const fn extract_tag(shard: &ShardIdent) -> u64 {
     prefix & (!prefix).wrapping_add(1)
 }
 
+/// Transactions retention rules for specific accounts.
+///
+/// Rules can only extend the retention: transactions of an account
+/// are kept if they are newer than any matching threshold.
+#[derive(Debug, Default, Clone)]
+pub struct TransactionsRetention {
+    by_address: FastHashMap<AccountKey, u64>,
+    by_code_hash: FastHashMap<HashBytes, u64>,
+}
+
+impl TransactionsRetention {
+    pub fn is_empty(&self) -> bool {
+        self.by_address.is_empty() && self.by_code_hash.is_empty()
+    }
+
+    /// Keep transactions of the account with `lt >= min_lt`.
+    pub fn add_address(&mut self, address: &StdAddr, min_lt: u64) {
+        let lt = self
+            .by_address
+            .entry(account_key(address))
+            .or_insert(min_lt);
+        *lt = std::cmp::min(*lt, min_lt);
+    }
+
+    /// Keep transactions of accounts with the specified code hash with `lt >= min_lt`.
+    ///
+    /// NOTE: Only the current code hash of an account is checked.
+    pub fn add_code_hash(&mut self, code_hash: &HashBytes, min_lt: u64) {
+        let lt = self.by_code_hash.entry(*code_hash).or_insert(min_lt);
+        *lt = std::cmp::min(*lt, min_lt);
+    }
+}
+
+/// `workchain: i8, account: [u8; 32]`
+type AccountKey = [u8; ACCOUNT_KEY_LEN];
+
+const ACCOUNT_KEY_LEN: usize = 33;
+
+fn account_key(address: &StdAddr) -> AccountKey {
+    let mut key = [0u8; ACCOUNT_KEY_LEN];
+    key[0] = address.workchain as u8;
+    key[1..33].copy_from_slice(address.address.as_ref());
+    key
+}
+
+fn account_from_key(key: &AccountKey) -> StdAddr {
+    StdAddr::new(key[0] as i8, HashBytes::from_slice(&key[1..33]))
+}
+
 const TX_MIN_LT: &[u8] = b"tx_min_lt";
+const TX_WATCH_LIST: &[u8] = b"tx_watch_list";
 const TX_GC_RUNNING: &[u8] = b"tx_gc_running";
 const INSTANCE_ID: &[u8] = b"instance_id";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Storage, StorageConfig};
+
+    #[tokio::test]
+    async fn balance_index_after_shard_reset() -> Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let storage = Storage::builder()
+            .with_config(StorageConfig::new_potato(tmp_dir.path()))
+            .with_rpc_storage(true)
+            .build()
+            .await?;
+        let rpc = storage.rpc_storage().unwrap();
+
+        let (left, _) = ShardIdent::BASECHAIN.split().unwrap();
+
+        // NOTE: Balances are chosen so that the first byte of the inverted
+        // balance doesn't match the shard prefix of the account.
+        let make_account = |first_byte: u8, balance: u128| {
+            let mut address = HashBytes(rand::random());
+            address.0[0] = first_byte;
+            let value = AccountIndexValue {
+                balance,
+                last_trans_lt: 0,
+                code_hash: None,
+            };
+            (address, value)
+        };
+        let left_accounts = [make_account(0x00, 100), make_account(0x7f, u128::MAX)];
+        let right_accounts = [make_account(0x80, 100), make_account(0xff, u128::MAX)];
+
+        for (address, value) in left_accounts.iter().chain(&right_accounts) {
+            let key = value.balance_key(0, address);
+            rpc.db.accounts_by_balance.insert(key.as_slice(), [])?;
+        }
+
+        // Masterchain entries must not be touched
+        let (mc_address, mc_value) = make_account(0x00, 100);
+        let key = mc_value.balance_key(-1, &mc_address);
+        rpc.db.accounts_by_balance.insert(key.as_slice(), [])?;
+
+        rpc.remove_account_indices(&left).await?;
+
+        let mut remaining = rpc
+            .get_accounts_by_balance(0, None)?
+            .map(|(address, _)| address.address)
+            .collect::<Vec<_>>();
+        remaining.sort();
+
+        let mut expected = right_accounts
+            .iter()
+            .map(|(address, _)| *address)
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(remaining, expected);
+
+        let mc = rpc
+            .get_accounts_by_balance(-1, None)?
+            .map(|(address, _)| address.address)
+            .collect::<Vec<_>>();
+        assert_eq!(mc, vec![mc_address]);
+
+        Ok(())
+    }
+}
@@ -20,6 +20,7 @@ use crate::db::*;
 
 pub struct CellStorage {
     db: BaseDb,
+    cold_db: Option<ColdDb>,
     cells_cache: Arc<CellsIndex>,
     raw_cells_cache: Arc<RawCellsCache>,
 }
@@ -27,7 +28,7 @@ pub struct CellStorage {
 type CellsIndex = FastDashMap<HashBytes, Weak<StorageCell>>;
 
 impl CellStorage {
-    pub fn new(db: BaseDb, cache_size_bytes: ByteSize) -> Arc<Self> {
+    pub fn new(db: BaseDb, cold_db: Option<ColdDb>, cache_size_bytes: ByteSize) -> Arc<Self> {
         let cells_cache = Default::default();
         let raw_cells_cache = Arc::new(RawCellsCache::new(cache_size_bytes.as_u64()));
 
@@ -39,6 +40,7 @@ impl CellStorage {
 
         Arc::new(Self {
             db,
+            cold_db,
             cells_cache,
             raw_cells_cache,
         })
@@ -389,7 +391,7 @@ impl CellStorage {
                 Some(cell) => Arc::new(cell),
                 None => return Err(CellStorageError::InvalidCell),
             },
-            Ok(None) => return Err(CellStorageError::CellNotFound),
+            Ok(None) => self.load_cold_cell(&hash)?,
             Err(e) => return Err(CellStorageError::Internal(e)),
         };
 
@@ -404,6 +406,33 @@ impl CellStorage {
         Ok(cell)
     }
 
+    /// Loads the cell which was moved to the cold storage.
+    ///
+    /// NOTE: Cold cells are not added to the raw cells cache
+    /// since they are rarely accessed.
+    fn load_cold_cell(
+        self: &Arc<Self>,
+        hash: &HashBytes,
+    ) -> Result<Arc<StorageCell>, CellStorageError> {
+        let Some(cold_db) = &self.cold_db else {
+            return Err(CellStorageError::CellNotFound);
+        };
+
+        let Some(value) = cold_db.cells.get(hash.as_slice())? else {
+            return Err(CellStorageError::CellNotFound);
+        };
+        let Some(data) = refcount::decode_value_with_rc(value.as_ref()).1 else {
+            return Err(CellStorageError::CellNotFound);
+        };
+
+        metrics::counter!("tycho_storage_cold_cell_loads_total").increment(1);
+
+        match StorageCell::deserialize(self.clone(), data) {
+            Some(cell) => Ok(Arc::new(cell)),
+            None => Err(CellStorageError::InvalidCell),
+        }
+    }
+
     pub fn remove_cell(
         &self,
         alloc: &Bump,
@@ -0,0 +1,249 @@
+use std::collections::hash_map;
+
+use bumpalo::Bump;
+use everscale_types::cell::HashBytes;
+use tycho_util::FastHashMap;
+use weedb::rocksdb::WriteBatch;
+
+use super::cell_storage::{CellStorageError, StorageCell};
+use crate::db::*;
+
+/// Copies the cells tree from the base DB into the cold DB.
+/// Returns the number of new cells in the cold DB.
+///
+/// NOTE: Cells are written in post-order in batches of `max_batch_len`
+/// entries, so each cell in the cold DB always has all its children.
+/// An interrupted copy only leaves some extra references.
+pub fn store_cold_cells(
+    db: &BaseDb,
+    cold_db: &ColdDb,
+    root_hash: &HashBytes,
+    max_batch_len: usize,
+) -> Result<usize, CellStorageError> {
+    struct AddedCell {
+        additions: u32,
+        data: Option<Box<[u8]>>,
+    }
+
+    struct NewCell {
+        hash: HashBytes,
+        data: Box<[u8]>,
+        refs: Vec<HashBytes>,
+        next_ref: usize,
+    }
+
+    struct Context<'a> {
+        db: &'a BaseDb,
+        cold_db: &'a ColdDb,
+        transaction: FastHashMap<HashBytes, AddedCell>,
+        max_batch_len: usize,
+        new_cells: usize,
+        buffer: Vec<u8>,
+    }
+
+    impl Context<'_> {
+        /// Returns the cell data if it is not in the cold DB yet.
+        fn visit(&mut self, hash: &HashBytes) -> Result<Option<NewCell>, CellStorageError> {
+            if let Some(item) = self.transaction.get_mut(hash) {
+                item.additions += 1;
+                return Ok(None);
+            }
+
+            let is_stored = match self.cold_db.cells.get(hash.as_slice())? {
+                Some(value) => refcount::decode_value_with_rc(value.as_ref()).0 > 0,
+                None => false,
+            };
+            if is_stored {
+                self.transaction.insert(*hash, AddedCell {
+                    additions: 1,
+                    data: None,
+                });
+                return Ok(None);
+            }
+
+            let Some(value) = self.db.cells.get(hash.as_slice())? else {
+                return Err(CellStorageError::CellNotFound);
+            };
+            let Some(data) = refcount::decode_value_with_rc(value.as_ref()).1 else {
+                return Err(CellStorageError::CellNotFound);
+            };
+
+            let mut refs = Vec::with_capacity(4);
+            if !StorageCell::deserialize_references(data, &mut refs) {
+                return Err(CellStorageError::InvalidCell);
+            }
+
+            Ok(Some(NewCell {
+                hash: *hash,
+                data: Box::from(data),
+                refs,
+                next_ref: 0,
+            }))
+        }
+
+        fn add_new_cell(&mut self, cell: NewCell) -> Result<(), CellStorageError> {
+            self.transaction.insert(cell.hash, AddedCell {
+                additions: 1,
+                data: Some(cell.data),
+            });
+            self.new_cells += 1;
+
+            if self.transaction.len() >= self.max_batch_len {
+                self.flush()?;
+            }
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), CellStorageError> {
+            if self.transaction.is_empty() {
+                return Ok(());
+            }
+
+            let cells_cf = &self.cold_db.cells.cf();
+            let mut batch = WriteBatch::default();
+            for (key, item) in self.transaction.drain() {
+                self.buffer.clear();
+                refcount::add_positive_refount(
+                    item.additions,
+                    item.data.as_deref(),
+                    &mut self.buffer,
+                );
+                batch.merge_cf(cells_cf, key.as_slice(), &self.buffer);
+            }
+
+            self.cold_db
+                .rocksdb()
+                .write_opt(batch, self.cold_db.cells.write_config())?;
+            Ok(())
+        }
+    }
+
+    let mut ctx = Context {
+        db,
+        cold_db,
+        transaction: Default::default(),
+        max_batch_len: std::cmp::max(max_batch_len, 1),
+        new_cells: 0,
+        buffer: Vec::with_capacity(512),
+    };
+
+    let mut stack = Vec::with_capacity(16);
+    if let Some(root) = ctx.visit(root_hash)? {
+        stack.push(root);
+    }
+
+    while let Some(cell) = stack.last_mut() {
+        match cell.refs.get(cell.next_ref).copied() {
+            // Visit the next child
+            Some(child) => {
+                cell.next_ref += 1;
+                if let Some(child) = ctx.visit(&child)? {
+                    stack.push(child);
+                }
+            }
+            // Add the cell when all of its children were processed
+            None => {
+                if let Some(cell) = stack.pop() {
+                    ctx.add_new_cell(cell)?;
+                }
+            }
+        }
+    }
+
+    ctx.flush()?;
+    Ok(ctx.new_cells)
+}
+
+/// Removes the cells tree from the cold DB.
+pub fn remove_cold_cells(
+    cold_db: &ColdDb,
+    alloc: &Bump,
+    hash: &HashBytes,
+) -> Result<(usize, WriteBatch), CellStorageError> {
+    #[derive(Clone, Copy)]
+    struct RemovedCell<'a> {
+        old_rc: i64,
+        removes: u32,
+        refs: &'a [HashBytes],
+    }
+
+    impl<'a> RemovedCell<'a> {
+        fn remove(&mut self) -> Result<Option<&'a [HashBytes]>, CellStorageError> {
+            self.removes += 1;
+            if self.removes as i64 <= self.old_rc {
+                Ok(self.next_refs())
+            } else {
+                Err(CellStorageError::CounterMismatch)
+            }
+        }
+
+        fn next_refs(&self) -> Option<&'a [HashBytes]> {
+            if self.old_rc > self.removes as i64 {
+                None
+            } else {
+                Some(self.refs)
+            }
+        }
+    }
+
+    let load_rc = |key: &HashBytes, refs: &mut Vec<HashBytes>| {
+        refs.clear();
+        if let Some(value) = cold_db.cells.get(key.as_slice())? {
+            if let (rc, Some(value)) = refcount::decode_value_with_rc(value.as_ref()) {
+                return StorageCell::deserialize_references(value, refs)
+                    .then_some(rc)
+                    .ok_or(CellStorageError::InvalidCell);
+            }
+        }
+        Err(CellStorageError::CellNotFound)
+    };
+
+    let mut transaction: FastHashMap<&HashBytes, RemovedCell<'_>> =
+        FastHashMap::with_capacity_and_hasher(128, Default::default());
+    let mut buffer = Vec::with_capacity(4);
+
+    let mut stack = Vec::with_capacity(16);
+    stack.push(std::slice::from_ref(hash).iter());
+
+    'outer: loop {
+        let Some(iter) = stack.last_mut() else {
+            break;
+        };
+
+        for cell_id in iter.by_ref() {
+            let refs = match transaction.entry(cell_id) {
+                hash_map::Entry::Occupied(mut v) => v.get_mut().remove()?,
+                hash_map::Entry::Vacant(v) => {
+                    let old_rc = load_rc(cell_id, &mut buffer)?;
+                    v.insert(RemovedCell {
+                        old_rc,
+                        removes: 1,
+                        refs: alloc.alloc_slice_copy(buffer.as_slice()),
+                    })
+                    .next_refs()
+                }
+            };
+
+            if let Some(refs) = refs {
+                stack.push(refs.iter());
+                continue 'outer;
+            }
+        }
+
+        stack.pop();
+    }
+
+    let total = transaction.len();
+    let cells_cf = &cold_db.cells.cf();
+
+    let mut batch = WriteBatch::with_capacity_bytes(total * (32 + 8 + 8));
+    for (key, item) in transaction {
+        batch.merge_cf(
+            cells_cf,
+            key.as_slice(),
+            refcount::encode_negative_refcount(item.removes),
+        );
+    }
+
+    Ok((total, batch))
+}
@@ -12,18 +12,24 @@ use tycho_block_util::state::*;
 use tycho_util::metrics::HistogramGuard;
 use weedb::rocksdb;
 
+pub(crate) use self::cell_storage::StorageCell;
 use self::cell_storage::*;
+use self::cold_cells::{remove_cold_cells, store_cold_cells};
+pub use self::state_stats::{compute_shard_state_stats, AccountStats, CellsUsage, ShardStateStats};
 use self::store_state_raw::StoreStateContext;
 use crate::db::*;
 use crate::store::{BlockFlags, BlockHandle, BlockHandleStorage, BlockStorage, TempFileStorage};
 use crate::util::*;
 
 mod cell_storage;
+mod cold_cells;
 mod entries_buffer;
+mod state_stats;
 mod store_state_raw;
 
 pub struct ShardStateStorage {
     db: BaseDb,
+    cold_db: Option<ColdDb>,
 
     block_handle_storage: Arc<BlockHandleStorage>,
     block_storage: Arc<BlockStorage>,
@@ -31,6 +37,7 @@ pub struct ShardStateStorage {
     temp_file_storage: TempFileStorage,
 
     gc_lock: tokio::sync::Mutex<()>,
+    keep_key_block_states: bool,
     min_ref_mc_state: MinRefMcStateTracker,
     max_new_mc_cell_count: AtomicUsize,
     max_new_sc_cell_count: AtomicUsize,
@@ -39,20 +46,24 @@ pub struct ShardStateStorage {
 impl ShardStateStorage {
     pub fn new(
         db: BaseDb,
+        cold_db: Option<ColdDb>,
         block_handle_storage: Arc<BlockHandleStorage>,
         block_storage: Arc<BlockStorage>,
         temp_file_storage: TempFileStorage,
         cache_size_bytes: ByteSize,
+        keep_key_block_states: bool,
     ) -> Result<Arc<Self>> {
-        let cell_storage = CellStorage::new(db.clone(), cache_size_bytes);
+        let cell_storage = CellStorage::new(db.clone(), cold_db.clone(), cache_size_bytes);
 
         Ok(Arc::new(Self {
             db,
+            cold_db,
             block_handle_storage,
             block_storage,
             temp_file_storage,
             cell_storage,
             gc_lock: Default::default(),
+            keep_key_block_states,
             min_ref_mc_state: MinRefMcStateTracker::new(),
             max_new_mc_cell_count: AtomicUsize::new(0),
             max_new_sc_cell_count: AtomicUsize::new(0),
@@ -75,6 +86,11 @@ impl ShardStateStorage {
         &self.min_ref_mc_state
     }
 
+    /// Prevents states from being stored or removed while the guard is alive.
+    pub(crate) async fn lock_gc(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.gc_lock.lock().await
+    }
+
     pub async fn store_state(
         &self,
         handle: &BlockHandle,
@@ -187,6 +203,33 @@ impl ShardStateStorage {
         ShardStateStuff::from_root(block_id, Cell::from(cell as Arc<_>), &self.min_ref_mc_state)
     }
 
+    /// Computes the space usage of the stored state with
+    /// the `top_n` heaviest accounts.
+    pub async fn compute_state_stats(
+        &self,
+        block_id: &BlockId,
+        top_n: usize,
+    ) -> Result<ShardStateStats> {
+        let state = self.load_state(block_id).await?;
+
+        let span = tracing::Span::current();
+        tokio::task::spawn_blocking(move || {
+            let _span = span.enter();
+
+            let started_at = Instant::now();
+            let stats = compute_shard_state_stats(&state, top_n)?;
+            tracing::info!(
+                block_id = %stats.block_id,
+                cells = stats.total.cells,
+                elapsed = %humantime::format_duration(started_at.elapsed()),
+                "computed state stats",
+            );
+
+            Ok(stats)
+        })
+        .await?
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn remove_outdated_states(&self, mc_seqno: u32) -> Result<()> {
         // Compute recent block ids for the specified masterchain seqno
@@ -229,7 +272,6 @@ impl ShardStateStorage {
             };
 
             let block_id = BlockId::from_slice(key);
-            let root_hash = HashBytes::from_slice(value);
 
             // Skip blocks from zero state and top blocks
             if block_id.seqno == 0
@@ -239,24 +281,64 @@ impl ShardStateStorage {
                 continue;
             }
 
+            // Skip key block states in the archive node mode
+            if self.keep_key_block_states && block_id.is_masterchain() {
+                let is_key_block = self
+                    .block_handle_storage
+                    .load_handle(&block_id)
+                    .is_some_and(|handle| handle.is_key_block());
+                if is_key_block {
+                    iter.next();
+                    continue;
+                }
+            }
+
             alloc.reset();
 
             {
                 let _guard = self.gc_lock.lock().await;
 
                 let db = self.db.clone();
+                let cold_db = self.cold_db.clone();
                 let cell_storage = self.cell_storage.clone();
                 let key = key.to_vec();
 
                 let (total, inner_alloc) = tokio::task::spawn_blocking(move || {
-                    let (stats, mut batch) = cell_storage.remove_cell(&alloc, &root_hash)?;
-
-                    batch.delete_cf(&db.shard_states.get_unbounded_cf().bound(), key);
-                    db.raw()
+                    // NOTE: The state could have been moved to the cold storage
+                    // after the snapshot was taken, so the actual value is used.
+                    let Some(value) = db.shard_states.get(&key)? else {
+                        return Ok::<_, anyhow::Error>((0, alloc));
+                    };
+                    let root_hash = HashBytes::from_slice(&value[..32]);
+                    let is_cold = is_cold_state_value(&value);
+                    drop(value);
+
+                    if !is_cold {
+                        let (stats, mut batch) = cell_storage.remove_cell(&alloc, &root_hash)?;
+
+                        batch.delete_cf(&db.shard_states.get_unbounded_cf().bound(), key);
+                        db.raw()
+                            .rocksdb()
+                            .write_opt(batch, db.cells.write_config())?;
+
+                        return Ok((stats, alloc));
+                    }
+
+                    // NOTE: The state entry is removed first so that
+                    // an interrupted GC only leaks some cold cells.
+                    db.shard_states.remove(&key)?;
+
+                    let Some(cold_db) = cold_db else {
+                        tracing::warn!(%block_id, "cold storage is disabled, state cells are kept");
+                        return Ok((0, alloc));
+                    };
+
+                    let (stats, batch) = remove_cold_cells(&cold_db, &alloc, &root_hash)?;
+                    cold_db
                         .rocksdb()
-                        .write_opt(batch, db.cells.write_config())?;
+                        .write_opt(batch, cold_db.cells.write_config())?;
 
-                    Ok::<_, anyhow::Error>((stats, alloc))
+                    Ok((stats, alloc))
                 })
                 .await??;
 
@@ -288,6 +370,98 @@ impl ShardStateStorage {
         Ok(())
     }
 
+    /// Moves cells of the states, which are referenced by masterchain blocks
+    /// older than `until_mc_seqno`, to the cold storage.
+    ///
+    /// Returns the number of moved states.
+    #[tracing::instrument(skip(self))]
+    pub async fn move_states_to_cold(
+        &self,
+        until_mc_seqno: u32,
+        max_batch_len: usize,
+    ) -> Result<usize> {
+        let Some(cold_db) = &self.cold_db else {
+            return Ok(0);
+        };
+
+        // Collect hot states
+        let block_ids = {
+            let raw = self.db.rocksdb();
+            let snapshot = raw.snapshot();
+            let mut readopts = self.db.shard_states.new_read_config();
+            readopts.set_snapshot(&snapshot);
+
+            let mut block_ids = Vec::new();
+            let mut iter = raw.raw_iterator_cf_opt(&self.db.shard_states.cf(), readopts);
+            iter.seek_to_first();
+            while let Some((key, value)) = iter.item() {
+                let block_id = BlockId::from_slice(key);
+
+                // NOTE: Zerostates are always kept in the base DB
+                if block_id.seqno != 0 && !is_cold_state_value(value) {
+                    block_ids.push(block_id);
+                }
+                iter.next();
+            }
+            iter.status()?;
+
+            block_ids
+        };
+
+        let mut moved_states = 0usize;
+        let mut new_cold_cells = 0usize;
+        for block_id in block_ids {
+            match self.block_handle_storage.load_handle(&block_id) {
+                Some(handle) if handle.ref_by_mc_seqno() < until_mc_seqno => {}
+                _ => continue,
+            }
+
+            let _guard = self.gc_lock.lock().await;
+
+            let db = self.db.clone();
+            let cold_db = cold_db.clone();
+            let cell_storage = self.cell_storage.clone();
+
+            let new_cells = tokio::task::spawn_blocking(move || {
+                let key = block_id.to_vec();
+
+                // NOTE: The state could have been removed by GC
+                let Some(value) = db.shard_states.get(&key)? else {
+                    return Ok::<_, anyhow::Error>(None);
+                };
+                if is_cold_state_value(&value) {
+                    return Ok(None);
+                }
+                let root_hash = HashBytes::from_slice(&value[..32]);
+                drop(value);
+
+                let new_cells = store_cold_cells(&db, &cold_db, &root_hash, max_batch_len)?;
+
+                let alloc = bumpalo::Bump::new();
+                let (_, mut batch) = cell_storage.remove_cell(&alloc, &root_hash)?;
+                batch.put_cf(&db.shard_states.cf(), key, cold_state_value(&root_hash));
+                db.rocksdb().write_opt(batch, db.cells.write_config())?;
+
+                Ok(Some(new_cells))
+            })
+            .await??;
+
+            if let Some(new_cells) = new_cells {
+                moved_states += 1;
+                new_cold_cells += new_cells;
+
+                metrics::counter!("tycho_storage_cold_moved_states_total").increment(1);
+                metrics::counter!("tycho_storage_cold_new_cells_total").increment(new_cells as u64);
+                tracing::debug!(%block_id, new_cells, "moved state to cold storage");
+            }
+        }
+
+        if moved_states > 0 {
+            tracing::info!(moved_states, new_cold_cells, "moved states to cold storage");
+        }
+        Ok(moved_states)
+    }
+
     /// Searches for an edge with the least referenced masterchain block
     ///
     /// Returns `None` if all states are recent enough
@@ -348,6 +522,11 @@ impl ShardStateStorage {
             .map(Some)
     }
 
+    pub fn contains_state(&self, block_id: &BlockId) -> Result<bool> {
+        let shard_states = &self.db.shard_states;
+        Ok(shard_states.get(block_id.to_vec())?.is_some())
+    }
+
     pub fn load_state_root(&self, block_id: &BlockId) -> Result<HashBytes> {
         let shard_states = &self.db.shard_states;
         let shard_state = shard_states.get(block_id.to_vec())?;
@@ -357,6 +536,14 @@ impl ShardStateStorage {
         }
     }
 
+    /// Returns the id of the stored masterchain state with the specified seqno.
+    ///
+    /// Returns `None` if there is no such state (e.g. it was removed by GC).
+    pub fn find_mc_state_id(&self, mc_seqno: u32) -> Result<Option<BlockId>> {
+        let snapshot = self.db.rocksdb().snapshot();
+        self.find_mc_block_id(mc_seqno, &snapshot)
+    }
+
     fn find_mc_block_id(
         &self,
         mc_seqno: u32,
@@ -387,6 +574,20 @@ impl ShardStateStorage {
     }
 }
 
+/// Marks the `shard_states` entry of the state which cells are in the cold storage.
+const STATE_IN_COLD_STORAGE: u8 = 1;
+
+pub(crate) fn is_cold_state_value(value: &[u8]) -> bool {
+    value.get(32) == Some(&STATE_IN_COLD_STORAGE)
+}
+
+fn cold_state_value(root_hash: &HashBytes) -> [u8; 33] {
+    let mut value = [0; 33];
+    value[..32].copy_from_slice(root_hash.as_slice());
+    value[32] = STATE_IN_COLD_STORAGE;
+    value
+}
+
 #[derive(Default, Debug, Clone, Copy)]
 pub struct StoreStateHint {
     pub block_data_size: Option<usize>,
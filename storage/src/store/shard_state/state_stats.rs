@@ -0,0 +1,222 @@
+use std::cmp::Reverse;
+use std::collections::hash_map;
+
+use anyhow::Result;
+use everscale_types::cell::{Cell, DynCell, HashBytes};
+use everscale_types::models::BlockId;
+use tycho_block_util::state::ShardStateStuff;
+use tycho_util::FastHashMap;
+
+/// Space usage of a single shard state.
+#[derive(Debug, Clone)]
+pub struct ShardStateStats {
+    pub block_id: BlockId,
+    /// All unique cells of the state.
+    pub total: CellsUsage,
+    /// Cells which are reachable only from a single account.
+    pub accounts: CellsUsage,
+    /// Cells which are reachable from more than one account.
+    pub shared: CellsUsage,
+    pub account_count: u64,
+    /// Accounts with the largest amount of unique bytes.
+    pub top_accounts: Vec<AccountStats>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountStats {
+    pub address: HashBytes,
+    /// All cells of the account state.
+    pub total: CellsUsage,
+    /// Cells which are not shared with other accounts.
+    pub unique: CellsUsage,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CellsUsage {
+    pub cells: u64,
+    /// Size of the cells in the storage.
+    pub bytes: u64,
+}
+
+impl CellsUsage {
+    fn add(&mut self, bytes: u32) {
+        self.cells += 1;
+        self.bytes += bytes as u64;
+    }
+}
+
+/// Walks the whole state tree and computes its space usage.
+///
+/// NOTE: This is a heavy blocking operation which keeps
+/// an entry for each cell of the state in memory.
+pub fn compute_shard_state_stats(state: &ShardStateStuff, top_n: usize) -> Result<ShardStateStats> {
+    const SHARED: u32 = u32::MAX;
+    const NO_ACCOUNT: u32 = u32::MAX - 1;
+
+    struct CellInfo {
+        /// Account index, `SHARED` or `NO_ACCOUNT`.
+        owner: u32,
+        /// Index of the last account which counted this cell.
+        last_visitor: u32,
+        bytes: u32,
+    }
+
+    let mut cells = FastHashMap::<HashBytes, CellInfo>::default();
+    let mut accounts = Vec::new();
+    let mut stack = Vec::<Cell>::new();
+
+    // Walk all accounts
+    for entry in state.as_ref().load_accounts()?.iter() {
+        let (address, (_, shard_account)) = entry?;
+
+        let idx = accounts.len() as u32;
+        anyhow::ensure!(idx < NO_ACCOUNT, "too many accounts");
+
+        let mut total = CellsUsage::default();
+
+        stack.push(shard_account.account.inner().clone());
+        while let Some(cell) = stack.pop() {
+            let bytes = stored_cell_size(cell.as_ref());
+            match cells.entry(*cell.repr_hash()) {
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(CellInfo {
+                        owner: idx,
+                        last_visitor: idx,
+                        bytes,
+                    });
+                }
+                hash_map::Entry::Occupied(mut entry) => {
+                    let info = entry.get_mut();
+                    if info.last_visitor == idx {
+                        continue;
+                    }
+                    info.last_visitor = idx;
+                    info.owner = SHARED;
+                }
+            }
+
+            total.add(bytes);
+            stack.extend(cell.references().cloned());
+        }
+
+        accounts.push(AccountStats {
+            address,
+            total,
+            unique: CellsUsage::default(),
+        });
+    }
+
+    // Walk the remaining cells of the state
+    stack.push(state.root_cell().clone());
+    while let Some(cell) = stack.pop() {
+        if let hash_map::Entry::Vacant(entry) = cells.entry(*cell.repr_hash()) {
+            entry.insert(CellInfo {
+                owner: NO_ACCOUNT,
+                last_visitor: NO_ACCOUNT,
+                bytes: stored_cell_size(cell.as_ref()),
+            });
+            stack.extend(cell.references().cloned());
+        }
+    }
+
+    let mut total = CellsUsage::default();
+    let mut accounts_usage = CellsUsage::default();
+    let mut shared = CellsUsage::default();
+    for info in cells.values() {
+        total.add(info.bytes);
+        match info.owner {
+            NO_ACCOUNT => {}
+            SHARED => shared.add(info.bytes),
+            idx => {
+                accounts_usage.add(info.bytes);
+                accounts[idx as usize].unique.add(info.bytes);
+            }
+        }
+    }
+
+    let account_count = accounts.len() as u64;
+
+    accounts.sort_unstable_by_key(|item| Reverse(item.unique.bytes));
+    accounts.truncate(top_n);
+
+    Ok(ShardStateStats {
+        block_id: *state.block_id(),
+        total,
+        accounts: accounts_usage,
+        shared,
+        account_count,
+        top_accounts: accounts,
+    })
+}
+
+/// Size of the cell in the cells table (without refcount).
+fn stored_cell_size(cell: &DynCell) -> u32 {
+    let descriptor = cell.descriptor();
+    4 + descriptor.byte_len() as u32
+        + (32 + 2) * descriptor.hash_count() as u32
+        + 32 * descriptor.reference_count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use everscale_types::models::{
+        DepthBalanceInfo, ShardAccount, ShardAccounts, ShardIdent, ShardStateUnsplit,
+    };
+    use everscale_types::prelude::*;
+    use tycho_block_util::state::MinRefMcStateTracker;
+
+    use super::*;
+
+    #[test]
+    fn shared_code_is_not_unique() -> Result<()> {
+        let code = CellBuilder::build_from(0xdeadbeefu32)?;
+
+        let mut accounts = ShardAccounts::new();
+        for i in 0..3u8 {
+            // NOTE: Account cells are not parsed, so any cell tree is fine here
+            let data = CellBuilder::build_from(i as u32)?;
+            let mut account = CellBuilder::new();
+            account.store_reference(code.clone())?;
+            account.store_reference(data)?;
+
+            accounts.set(
+                HashBytes([i; 32]),
+                DepthBalanceInfo::default(),
+                ShardAccount {
+                    account: Lazy::from_raw(account.build()?),
+                    last_trans_hash: HashBytes::ZERO,
+                    last_trans_lt: 0,
+                },
+            )?;
+        }
+
+        let state = ShardStateUnsplit {
+            shard_ident: ShardIdent::BASECHAIN,
+            accounts: Lazy::new(&accounts)?,
+            ..Default::default()
+        };
+
+        let block_id = BlockId {
+            shard: ShardIdent::BASECHAIN,
+            ..Default::default()
+        };
+        let root = CellBuilder::build_from(&state)?;
+        let state = ShardStateStuff::from_root(&block_id, root, &MinRefMcStateTracker::new())?;
+
+        let stats = compute_shard_state_stats(&state, 2)?;
+        assert_eq!(stats.account_count, 3);
+        assert_eq!(stats.top_accounts.len(), 2);
+
+        // Only the code cell is shared
+        assert_eq!(stats.shared.cells, 1);
+        assert_eq!(stats.accounts.cells, 3 * 2);
+        for account in &stats.top_accounts {
+            assert_eq!(account.total.cells, 3);
+            assert_eq!(account.unique.cells, 2);
+        }
+
+        assert!(stats.total.cells > stats.accounts.cells + stats.shared.cells);
+
+        Ok(())
+    }
+}
@@ -182,6 +182,15 @@ impl ZstdCompressStream<'_> {
         Ok(())
     }
 
+    /// Loads a dictionary which will be used for all subsequent frames.
+    pub fn load_dictionary(&mut self, dictionary: &[u8]) -> Result<()> {
+        self.cctx
+            .load_dictionary(dictionary)
+            .map_err(ZstdError::from_raw)?;
+
+        Ok(())
+    }
+
     pub fn write(&mut self, uncompressed: &[u8], compress_buffer: &mut Vec<u8>) -> Result<()> {
         const MODE: zstd_sys::ZSTD_EndDirective = zstd_sys::ZSTD_EndDirective::ZSTD_e_continue;
         if self.finished {
@@ -286,7 +295,34 @@ impl ZstdDecompressStream<'_> {
         })
     }
 
+    /// Loads a dictionary which will be used for all subsequent frames.
+    pub fn load_dictionary(&mut self, dictionary: &[u8]) -> Result<()> {
+        self.dctx
+            .load_dictionary(dictionary)
+            .map_err(ZstdError::from_raw)?;
+
+        Ok(())
+    }
+
+    /// Returns `true` if the frame was completely decoded and fully flushed.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
     pub fn write(&mut self, compressed: &[u8], decompress_buffer: &mut Vec<u8>) -> Result<()> {
+        self.write_bounded(compressed, decompress_buffer, usize::MAX)
+    }
+
+    /// Same as [`write`], but fails as soon as the decompress buffer
+    /// contains more than `max_len` bytes.
+    ///
+    /// [`write`]: Self::write
+    pub fn write_bounded(
+        &mut self,
+        compressed: &[u8],
+        decompress_buffer: &mut Vec<u8>,
+        max_len: usize,
+    ) -> Result<()> {
         if self.finished {
             return Err(ZstdError::StreamAlreadyFinished);
         }
@@ -302,22 +338,30 @@ impl ZstdDecompressStream<'_> {
                 decompress_buffer.reserve(self.resize_by);
             }
 
-            // all input was read, chunky boy wants more
-            if input.pos() == input.src.len() {
-                break Ok(());
-            }
+            let read = {
+                let mut output = OutBuffer::around_pos(decompress_buffer, start);
+                self.dctx
+                    .decompress_stream(&mut output, &mut input)
+                    .map_err(ZstdError::from_raw)?
+            };
 
-            let mut output = OutBuffer::around_pos(decompress_buffer, start);
-            let read = self
-                .dctx
-                .decompress_stream(&mut output, &mut input)
-                .map_err(ZstdError::from_raw)?;
+            if decompress_buffer.len() > max_len {
+                return Err(ZstdError::OutputTooLarge { max_len });
+            }
 
             // when a frame is completely decoded and fully flushed,
             if read == 0 {
                 self.finished = true;
                 break Ok(());
             }
+
+            // all input was read, chunky boy wants more
+            // (unless the output was full and some data might still be buffered)
+            if input.pos() == input.src.len()
+                && decompress_buffer.len() < decompress_buffer.capacity()
+            {
+                break Ok(());
+            }
         }
     }
 
@@ -352,6 +396,9 @@ pub enum ZstdError {
 
     #[error("Stream already finished")]
     StreamAlreadyFinished,
+
+    #[error("Decompressed size exceeds the limit of {max_len} bytes")]
+    OutputTooLarge { max_len: usize },
 }
 
 impl From<ZstdError> for std::io::Error {
@@ -582,6 +629,46 @@ mod tests {
         assert_eq!(data, decompressed);
     }
 
+    #[test]
+    fn test_decode_full_output_buffer() {
+        // Highly compressible data is consumed at once but
+        // doesn't fit into the output buffer
+        let data = vec![0u8; 1 << 20];
+        let compressed = encode_all(&data, 3).unwrap();
+        assert!(compressed.len() < 1024);
+
+        let mut decompressed = Vec::new();
+        let mut decompressor = ZstdDecompressStream::new(1024).unwrap();
+        decompressor.write(&compressed, &mut decompressed).unwrap();
+
+        assert!(decompressor.is_finished());
+        assert_eq!(data, decompressed);
+    }
+
+    #[test]
+    fn test_decode_bounded() {
+        const RESIZE_BY: usize = 1024;
+
+        let data = vec![0u8; 1 << 20];
+        let compressed = encode_all(&data, 3).unwrap();
+
+        let mut decompressed = Vec::new();
+        let mut decompressor = ZstdDecompressStream::new(RESIZE_BY).unwrap();
+        let res = decompressor.write_bounded(&compressed, &mut decompressed, 4 * RESIZE_BY);
+        assert!(matches!(res, Err(ZstdError::OutputTooLarge { .. })));
+
+        // Decompression stops right after the limit is reached
+        assert!(decompressed.len() <= 8 * RESIZE_BY);
+
+        let mut decompressed = Vec::new();
+        let mut decompressor = ZstdDecompressStream::new(RESIZE_BY).unwrap();
+        decompressor
+            .write_bounded(&compressed, &mut decompressed, data.len())
+            .unwrap();
+        assert!(decompressor.is_finished());
+        assert_eq!(data, decompressed);
+    }
+
     #[test]
     fn buffered_compress_decompress() {
         const BUFFER_LEN: usize = 64 << 20; // 64 MB